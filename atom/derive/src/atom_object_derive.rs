@@ -0,0 +1,361 @@
+use proc_macro::TokenStream;
+use syn::export::Span;
+use syn::{parse_macro_input, Data, DataStruct, GenericArgument, GenericParam, Ident, Lifetime};
+use syn::{DeriveInput, Error, Field, PathArguments, Result, Type, Visibility};
+
+/// Return the inner type if the given type is an `Option`.
+fn option_inner_type(field_type: &Type) -> Option<&Type> {
+    let path = match field_type {
+        Type::Path(path) if path.qself.is_none() => &path.path,
+        _ => return None,
+    };
+    let segment = path.segments.last()?;
+    if segment.ident != "Option" {
+        return None;
+    }
+    match &segment.arguments {
+        PathArguments::AngleBracketed(arguments) if arguments.args.len() == 1 => {
+            match arguments.args.first() {
+                Some(GenericArgument::Type(inner)) => Some(inner),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// A field in the struct we implement `AtomObject` for.
+struct AtomObjectField<'a> {
+    identifier: &'a Ident,
+    key: Type,
+    value_type: &'a Type,
+    optional: bool,
+}
+
+impl<'a> AtomObjectField<'a> {
+    /// Create a `Self` instance from a field object.
+    ///
+    /// This parses the `#[property(...)]` attribute of the field.
+    fn from_input_field(input: &'a Field) -> Result<Self> {
+        let identifier = input.ident.as_ref().unwrap();
+        if identifier == "atom" || identifier == "class" {
+            return Err(Error::new_spanned(
+                identifier,
+                "The field names `atom` and `class` are reserved for the URID collection",
+            ));
+        }
+
+        let mut key: Option<Type> = None;
+        for attribute in input
+            .attrs
+            .iter()
+            .filter(|attr| attr.path.is_ident("property"))
+        {
+            if key.is_some() {
+                return Err(Error::new_spanned(
+                    attribute,
+                    "A field may only have one `#[property(...)]` attribute",
+                ));
+            }
+            key = Some(attribute.parse_args()?);
+        }
+        let key = key.ok_or_else(|| {
+            Error::new_spanned(
+                identifier,
+                "The field has no `#[property(...)]` attribute with the key of the property",
+            )
+        })?;
+
+        let (value_type, optional) = match option_inner_type(&input.ty) {
+            Some(inner) => (inner, true),
+            None => (&input.ty, false),
+        };
+
+        Ok(AtomObjectField {
+            identifier,
+            key,
+            value_type,
+            optional,
+        })
+    }
+
+    /// Create the name of the local variable that holds the value while reading.
+    fn local_name(index: usize) -> Ident {
+        Ident::new(&format!("value_{}", index), Span::call_site())
+    }
+
+    /// Create the field declaration line for the URID collection.
+    fn make_urid_declaration(&self) -> impl ::quote::ToTokens {
+        let identifier = self.identifier;
+        let key = &self.key;
+        quote! {
+            pub #identifier: ::lv2_urid::URID<#key>,
+        }
+    }
+
+    /// Create the field initialization line for the URID collection.
+    fn make_urid_initialization(&self) -> impl ::quote::ToTokens {
+        let identifier = self.identifier;
+        quote! {
+            #identifier: map.populate_collection()?,
+        }
+    }
+
+    /// Create the matching arm that reads the value of the property.
+    ///
+    /// Like `ObjectReader::get_many`, only the first property with the key is read.
+    fn make_reader(&self, index: usize, lifetime: &Lifetime) -> impl ::quote::ToTokens {
+        let identifier = self.identifier;
+        let value_type = self.value_type;
+        let local = Self::local_name(index);
+        quote! {
+            if header.key == urids.#identifier {
+                if #local.is_none() {
+                    #local = Some(<#value_type as ::lv2_atom::object::PropertyValue<#lifetime>>::read_value(value, &urids.atom)?);
+                }
+                continue;
+            }
+        }
+    }
+
+    /// Create the field initialization line for the read struct.
+    fn make_field_initialization(&self, index: usize) -> impl ::quote::ToTokens {
+        let identifier = self.identifier;
+        let local = Self::local_name(index);
+        if self.optional {
+            quote! {
+                #identifier: #local,
+            }
+        } else {
+            quote! {
+                #identifier: #local.ok_or_else(|| ::lv2_atom::AtomError::MissingProperty(urids.#identifier.into_general()))?,
+            }
+        }
+    }
+
+    /// Create the line that writes the property.
+    fn make_writer(&self) -> impl ::quote::ToTokens {
+        let identifier = self.identifier;
+        if self.optional {
+            quote! {
+                if let Some(value) = &self.#identifier {
+                    ::lv2_atom::object::PropertyValue::write_value(value, &mut writer, urids.#identifier, &urids.atom)?;
+                }
+            }
+        } else {
+            quote! {
+                ::lv2_atom::object::PropertyValue::write_value(&self.#identifier, &mut writer, urids.#identifier, &urids.atom)?;
+            }
+        }
+    }
+}
+
+/// Representation of a struct we implement `AtomObject` for.
+struct AtomObjectStruct<'a> {
+    struct_name: &'a Ident,
+    visibility: &'a Visibility,
+    lifetime: Option<&'a Lifetime>,
+    class: Type,
+    fields: Vec<AtomObjectField<'a>>,
+}
+
+impl<'a> AtomObjectStruct<'a> {
+    /// Return an `Ident` for the generated URID collection.
+    fn urid_collection_name(&self) -> Ident {
+        Ident::new(
+            &format!("{}URIDCollection", self.struct_name),
+            Span::call_site(),
+        )
+    }
+
+    /// Construct a `Self` instance from a `DeriveInput`.
+    fn from_derive_input(input: &'a DeriveInput) -> Result<Self> {
+        let struct_name = &input.ident;
+
+        let mut lifetime: Option<&Lifetime> = None;
+        for parameter in input.generics.params.iter() {
+            match parameter {
+                GenericParam::Lifetime(definition) if lifetime.is_none() => {
+                    lifetime = Some(&definition.lifetime)
+                }
+                _ => {
+                    return Err(Error::new_spanned(
+                        parameter,
+                        "An `AtomObject` may only have a single lifetime parameter",
+                    ))
+                }
+            }
+        }
+
+        let mut class: Option<Type> = None;
+        for attribute in input
+            .attrs
+            .iter()
+            .filter(|attr| attr.path.is_ident("object"))
+        {
+            if class.is_some() {
+                return Err(Error::new_spanned(
+                    attribute,
+                    "A struct may only have one `#[object(...)]` attribute",
+                ));
+            }
+            class = Some(attribute.parse_args()?);
+        }
+        let class = class.ok_or_else(|| {
+            Error::new_spanned(
+                struct_name,
+                "The struct has no `#[object(...)]` attribute with the class of the object",
+            )
+        })?;
+
+        let fields = match &input.data {
+            Data::Struct(DataStruct { fields, .. }) => fields
+                .iter()
+                .map(AtomObjectField::from_input_field)
+                .collect::<Result<Vec<_>>>()?,
+            _ => {
+                return Err(Error::new_spanned(
+                    struct_name,
+                    "Only structs can implement `AtomObject`",
+                ))
+            }
+        };
+
+        Ok(AtomObjectStruct {
+            struct_name,
+            visibility: &input.vis,
+            lifetime,
+            class,
+            fields,
+        })
+    }
+
+    /// Implement `AtomObject` for the struct and create the URID collection.
+    fn make_derived_contents(&self) -> TokenStream {
+        let struct_name = self.struct_name;
+        let visibility = self.visibility;
+        let class = &self.class;
+        let urid_collection_name = self.urid_collection_name();
+        let urid_collection_doc = format!(
+            "Collection with the URIDs of the [`{}`](struct.{}.html) object.",
+            struct_name, struct_name
+        );
+
+        let default_lifetime = Lifetime::new("'lv2_atom", Span::call_site());
+        let (impl_lifetime, struct_type) = match self.lifetime {
+            Some(lifetime) => (lifetime, quote! {#struct_name<#lifetime>}),
+            None => (&default_lifetime, quote! {#struct_name}),
+        };
+
+        let urid_declarations = self
+            .fields
+            .iter()
+            .map(AtomObjectField::make_urid_declaration);
+        let urid_initializations = self
+            .fields
+            .iter()
+            .map(AtomObjectField::make_urid_initialization);
+        let locals = (0..self.fields.len()).map(AtomObjectField::local_name);
+        let readers = self
+            .fields
+            .iter()
+            .enumerate()
+            .map(|(i, f)| f.make_reader(i, impl_lifetime));
+        let field_initializations = self
+            .fields
+            .iter()
+            .enumerate()
+            .map(|(i, f)| f.make_field_initialization(i));
+        let writers = self.fields.iter().map(AtomObjectField::make_writer);
+
+        let read_loop = if self.fields.is_empty() {
+            quote! {}
+        } else {
+            quote! {
+                for (header, value) in reader {
+                    #(#readers)*
+                }
+            }
+        };
+        let write_properties = if self.fields.is_empty() {
+            quote! {.map(|_| ())}
+        } else {
+            quote! {
+                .and_then(|mut writer| {
+                    #(#writers)*
+                    Ok(())
+                })
+            }
+        };
+
+        (quote! {
+            #[doc = #urid_collection_doc]
+            #[derive(Clone)]
+            #visibility struct #urid_collection_name {
+                pub atom: ::lv2_atom::AtomURIDCollection,
+                pub class: ::lv2_urid::URID<#class>,
+                #(#urid_declarations)*
+            }
+
+            impl ::lv2_urid::URIDCollection for #urid_collection_name {
+                fn from_map(map: &::lv2_urid::Map) -> Option<Self> {
+                    Some(Self {
+                        atom: map.populate_collection()?,
+                        class: map.populate_collection()?,
+                        #(#urid_initializations)*
+                    })
+                }
+            }
+
+            impl<#impl_lifetime> ::lv2_atom::object::AtomObject<#impl_lifetime> for #struct_type {
+                type URIDCollection = #urid_collection_name;
+
+                fn read(
+                    atom: ::lv2_atom::UnidentifiedAtom<#impl_lifetime>,
+                    urids: &#urid_collection_name,
+                ) -> Result<Self, ::lv2_atom::AtomError> {
+                    let (header, reader) = atom.read(urids.atom.object, ())?;
+                    if header.otype != urids.class {
+                        return Err(::lv2_atom::AtomError::TypeMismatch);
+                    }
+
+                    #(let mut #locals = None;)*
+                    #read_loop
+
+                    Ok(Self {
+                        #(#field_initializations)*
+                    })
+                }
+
+                fn write<'lv2_space>(
+                    &self,
+                    space: &mut dyn ::lv2_atom::space::MutSpace<'lv2_space>,
+                    urids: &#urid_collection_name,
+                ) -> Result<(), ::lv2_atom::AtomError> {
+                    let checkpoint = space.checkpoint();
+                    let header = ::lv2_atom::object::ObjectHeader {
+                        id: None,
+                        otype: urids.class.into_general(),
+                    };
+                    let result = space.init(urids.atom.object, header)#write_properties;
+                    if result.is_err() {
+                        // The object writer has been dropped, see `MutSpace::rollback`.
+                        unsafe { space.rollback(checkpoint) };
+                    }
+                    result
+                }
+            }
+        })
+        .into()
+    }
+}
+
+/// Implement `AtomObject` for a struct.
+#[inline]
+pub fn atom_object_derive_impl(input: TokenStream) -> TokenStream {
+    let input: DeriveInput = parse_macro_input!(input);
+    match AtomObjectStruct::from_derive_input(&input) {
+        Ok(object) => object.make_derived_contents(),
+        Err(error) => error.to_compile_error().into(),
+    }
+}
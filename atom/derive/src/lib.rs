@@ -0,0 +1,19 @@
+//! Procedural macros for `lv2-atom`.
+#![recursion_limit = "128"]
+
+extern crate proc_macro;
+extern crate syn;
+#[macro_use]
+extern crate quote;
+
+mod atom_object_derive;
+
+use proc_macro::TokenStream;
+
+/// Implement the `AtomObject` trait for a struct.
+///
+/// The class of the object is set with an `#[object(Class)]` attribute on the struct and the key of every property with a `#[property(Key)]` attribute on its field, where `Class` and `Key` are `UriBound`s. Fields with an `Option` type are optional properties. The macro also creates a URID collection named after the struct, for example `NoteURIDCollection` for a struct named `Note`.
+#[proc_macro_derive(AtomObject, attributes(object, property))]
+pub fn atom_object_derive(input: TokenStream) -> TokenStream {
+    atom_object_derive::atom_object_derive_impl(input)
+}
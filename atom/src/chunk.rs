@@ -26,7 +26,7 @@
 //!
 //! [http://lv2plug.in/ns/ext/atom/atom.html#Chunk](http://lv2plug.in/ns/ext/atom/atom.html#Chunk)
 use crate::space::*;
-use crate::Atom;
+use crate::{Atom, AtomError};
 use core::UriBound;
 
 /// An atom containing memory of undefined type.
@@ -47,12 +47,12 @@ where
     type WriteParameter = ();
     type WriteHandle = FramedMutSpace<'a, 'b>;
 
-    fn read(space: Space<'a>, _: ()) -> Option<&'a [u8]> {
-        space.data()
+    fn read(space: Space<'a>, _: ()) -> Result<&'a [u8], AtomError> {
+        space.data().ok_or(AtomError::Malformed)
     }
 
-    fn init(frame: FramedMutSpace<'a, 'b>, _: ()) -> Option<FramedMutSpace<'a, 'b>> {
-        Some(frame)
+    fn init(frame: FramedMutSpace<'a, 'b>, _: ()) -> Result<FramedMutSpace<'a, 'b>, AtomError> {
+        Ok(frame)
     }
 }
 
@@ -74,7 +74,7 @@ mod tests {
         let map = Map::new(&interface);
         let urids = crate::AtomURIDCollection::from_map(&map).unwrap();
 
-        let mut raw_space: Box<[u8]> = Box::new([0; 256]);
+        let mut raw_space = AlignedBuffer::new(256);
 
         // writing
         {
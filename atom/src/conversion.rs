@@ -0,0 +1,195 @@
+//! Items shared by the conversions of atoms to JSON and Turtle.
+
+/// Return a null-terminated URI constant of the `sys` crate as a string.
+pub(crate) fn as_str(uri: &'static [u8]) -> &'static str {
+    std::str::from_utf8(&uri[..uri.len() - 1]).unwrap()
+}
+
+/// Macro to define the error type of a conversion.
+///
+/// Every conversion may fail to read or write an atom and to map or unmap URIDs. The macro adds the variants for these errors to the given ones and implements `From<AtomError>`, `Display` and `std::error::Error`. The messages of the given variants are written by the given match arms, using the given name for the formatter.
+macro_rules! make_conversion_error {
+    (
+        $(#[$attribute:meta])*
+        pub enum $error:ident { $($variants:tt)* }
+
+        fn fmt($formatter:ident) { $($arms:tt)* }
+    ) => {
+        $(#[$attribute])*
+        #[derive(Clone, Debug, PartialEq, Eq)]
+        pub enum $error {
+            /// Reading or writing an atom failed.
+            Atom($crate::AtomError),
+            /// A URID couldn't be unmapped to a URI.
+            UnknownURID(::urid::URID),
+            /// A URI couldn't be mapped to a URID.
+            UnmappableURI(std::string::String),
+            $($variants)*
+        }
+
+        impl From<$crate::AtomError> for $error {
+            fn from(error: $crate::AtomError) -> Self {
+                $error::Atom(error)
+            }
+        }
+
+        impl std::fmt::Display for $error {
+            fn fmt(&self, $formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+                match self {
+                    $error::Atom(error) => write!($formatter, "atom error: {:?}", error),
+                    $error::UnknownURID(urid) => {
+                        write!($formatter, "URID {} can not be unmapped", urid.get())
+                    }
+                    $error::UnmappableURI(uri) => {
+                        write!($formatter, "URI \"{}\" can not be mapped", uri)
+                    }
+                    $($arms)*
+                }
+            }
+        }
+
+        impl std::error::Error for $error {}
+    };
+}
+
+/// Fixtures shared by the tests of the conversions.
+#[cfg(test)]
+#[cfg(feature = "host")]
+pub(crate) mod test_support {
+    use crate::prelude::*;
+    use crate::space::*;
+    use crate::tuple::TupleWriter;
+    use core::prelude::*;
+    use std::mem::size_of;
+    use units::prelude::*;
+    use urid::prelude::*;
+
+    pub struct Class;
+    unsafe impl UriBound for Class {
+        const URI: &'static [u8] = b"urn:test#class\0";
+    }
+
+    pub struct Key;
+    unsafe impl UriBound for Key {
+        const URI: &'static [u8] = b"urn:test#key\0";
+    }
+
+    pub struct Custom;
+    unsafe impl UriBound for Custom {
+        const URI: &'static [u8] = b"urn:test#custom\0";
+    }
+
+    pub struct English;
+    unsafe impl UriBound for English {
+        const URI: &'static [u8] = b"http://lexvo.org/id/iso639-3/eng\0";
+    }
+
+    /// Return the bytes of the atom at the start of the buffer.
+    pub fn atom_bytes(buffer: &AlignedBuffer) -> &[u8] {
+        let size = unsafe { (*(buffer.as_ptr() as *const sys::LV2_Atom)).size } as usize;
+        &buffer[..size_of::<sys::LV2_Atom>() + size]
+    }
+
+    /// The number of atoms written by [`write_atoms`](fn.write_atoms.html).
+    pub const ATOM_COUNT: usize = 18;
+
+    /// Write atoms of every type that has to survive a round trip to a tuple.
+    pub fn write_atoms(tuple: &mut TupleWriter, urids: &AtomURIDCollection, map: &Map) {
+        let units = UnitURIDCollection::from_map(map).unwrap();
+        let class = map.map_type::<Class>().unwrap();
+        let key = map.map_type::<Key>().unwrap();
+        let custom = map.map_type::<Custom>().unwrap();
+        let english = map.map_type::<English>().unwrap();
+
+        let mut custom_buffer = AlignedBuffer::new(16);
+        FramedMutSpace::new(&mut custom_buffer.mut_space(), custom)
+            .unwrap()
+            .write_raw(&[1, 2, 3], false)
+            .unwrap();
+        let (custom_atom, _) = custom_buffer.space().split_atom().unwrap();
+        let custom_atom = UnidentifiedAtom::new(custom_atom);
+
+        tuple.init(urids.int, -42).unwrap();
+        tuple.init(urids.long, 1 << 40).unwrap();
+        tuple.init(urids.float, 0.1).unwrap();
+        tuple.init(urids.double, f64::NEG_INFINITY).unwrap();
+        tuple.init(urids.bool, 1).unwrap();
+        tuple.init(urids.urid, class.into_general()).unwrap();
+        tuple
+            .init(urids.string, ())
+            .unwrap()
+            .append("Hello \"World\"\n")
+            .unwrap();
+        tuple
+            .init(urids.uri, ())
+            .unwrap()
+            .append("urn:uri")
+            .unwrap();
+        tuple.init(urids.path, ()).unwrap().append("/tmp").unwrap();
+        tuple
+            .init(urids.literal, LiteralInfo::Language(english.into_general()))
+            .unwrap()
+            .append("Hello")
+            .unwrap();
+        tuple
+            .init(urids.literal, LiteralInfo::Datatype(key.into_general()))
+            .unwrap()
+            .append("value")
+            .unwrap();
+        tuple
+            .init(urids.literal, LiteralInfo::Plain)
+            .unwrap()
+            .append("Hi")
+            .unwrap();
+        tuple
+            .init(urids.chunk, ())
+            .unwrap()
+            .write_raw(&[0, 1, 254, 255], false)
+            .unwrap();
+        tuple
+            .init(urids.vector(), urids.float)
+            .unwrap()
+            .append(&[0.5, f32::INFINITY])
+            .unwrap();
+        {
+            let mut object = tuple
+                .init(
+                    urids.object,
+                    ObjectHeader {
+                        id: Some(key.into_general()),
+                        otype: class.into_general(),
+                    },
+                )
+                .unwrap();
+            object.init(key, None, urids.int, 1).unwrap();
+            object.forward(key, None, custom_atom).unwrap();
+            let mut inner = object
+                .init(
+                    key,
+                    None,
+                    urids.object,
+                    ObjectHeader {
+                        id: None,
+                        otype: class.into_general(),
+                    },
+                )
+                .unwrap();
+            inner.init(key, None, urids.string, ()).unwrap();
+        }
+        {
+            let mut sequence = tuple
+                .init(urids.sequence, TimeStampURID::BeatsPerMinute(units.beat))
+                .unwrap();
+            sequence
+                .init(TimeStamp::BeatsPerMinute(0.25), urids.int, 3)
+                .unwrap();
+        }
+        {
+            let mut sequence = tuple
+                .init(urids.sequence, TimeStampURID::Frames(units.frame))
+                .unwrap();
+            sequence.init(TimeStamp::Frames(7), urids.bool, 0).unwrap();
+        }
+        tuple.init(urids.tuple, ()).unwrap();
+    }
+}
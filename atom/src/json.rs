@@ -0,0 +1,659 @@
+//! JSON conversion of atoms.
+//!
+//! With the `json` feature enabled, any atom can be converted to a [`serde_json::Value`](https://docs.rs/serde_json/1/serde_json/enum.Value.html) and a value can be written back to a [`MutSpace`](../space/trait.MutSpace.html). This is useful to inspect atoms while debugging or to control a plugin from another process.
+//!
+//! Every atom is represented by a JSON object with the URI of it's type in the `type` field. URIDs are always represented by their URIs, which is why converting an atom to JSON requires the [`Unmap`](../../lv2_urid/struct.Unmap.html) feature and converting JSON to an atom requires the [`Map`](../../lv2_urid/struct.Map.html) feature. The other fields depend on the type of the atom:
+//!
+//! | Atom type | Fields |
+//! |-----------|--------|
+//! | `Int`, `Long`, `Float`, `Double` | `value`: The number. Infinite numbers and NaN are written as the strings `"inf"`, `"-inf"` and `"NaN"`. |
+//! | `Bool` | `value`: The boolean. |
+//! | `URID` | `value`: The URI. |
+//! | `String`, `URI`, `Path` | `value`: The string. |
+//! | `Literal` | `value`: The string, `language` or `datatype`: The URI of the language or the datatype. Both are omitted for plain literals. |
+//! | `Chunk` | `value`: The data as a hex string. |
+//! | `Tuple` | `value`: An array with the atoms. |
+//! | `Vector` | `childType`: The URI of the child type, `value`: An array with the children. |
+//! | `Object`, `Blank` | `id`: The optional URI of the object, `otype`: The URI of the class, `properties`: An array of objects with the fields `key`, `value` and the optional `context`. |
+//! | `Sequence` | `unit`: The optional URI of the time stamp unit, `events`: An array of objects with either a `frames` or a `beats` field and the atom in the `value` field. |
+//! | Other types | `body`: The body of the atom as a hex string. |
+//!
+//! The null atom, which has a type URID of zero, is represented by `null`.
+//!
+//! The conversion is loss-free: Converting an atom to JSON and back results in the same atom, except for the payloads of NaNs and for booleans, which are always written as `0` and `1`. Mapping and unmapping URIDs is generally not real-time-safe, which is why atoms should not be converted in the audio thread.
+//!
+//! # Example
+//!
+//! ```
+//! # use lv2_core::prelude::*;
+//! # use lv2_urid::prelude::*;
+//! # use lv2_urid::mapper::*;
+//! use lv2_atom::json::*;
+//! use lv2_atom::prelude::*;
+//! use lv2_atom::space::*;
+//!
+//! # let mut mapper = Box::pin(HashURIDMapper::new());
+//! # let map_interface = mapper.as_mut().make_map_interface();
+//! # let unmap_interface = mapper.as_mut().make_unmap_interface();
+//! # let map = Map::new(&map_interface);
+//! # let unmap = Unmap::new(&unmap_interface);
+//! let urids: AtomURIDCollection = map.populate_collection().unwrap();
+//!
+//! let value = serde_json::json!({
+//!     "type": "http://lv2plug.in/ns/ext/atom#Tuple",
+//!     "value": [
+//!         {"type": "http://lv2plug.in/ns/ext/atom#Int", "value": 42},
+//!         {"type": "http://lv2plug.in/ns/ext/atom#String", "value": "Hello"},
+//!     ],
+//! });
+//!
+//! let mut buffer = AlignedBuffer::new(256);
+//! from_json(&value, &mut buffer.mut_space(), &urids, &map).unwrap();
+//!
+//! let atom = UnidentifiedAtom::new(buffer.space());
+//! assert_eq!(value, to_json(atom, &urids, &unmap).unwrap());
+//! ```
+use crate::conversion::as_str;
+use crate::object::{Object, Property};
+use crate::scalar::*;
+use crate::space::*;
+use crate::string::LiteralInfo;
+use crate::{Atom, AtomError, AtomURIDCollection, UnidentifiedAtom};
+use serde_json::{Map as JsonMap, Number, Value};
+use std::convert::TryFrom;
+use std::ffi::CString;
+use urid::{Map, Unmap, URID};
+
+make_conversion_error! {
+    /// Errors that may occur while converting atoms to JSON or back.
+    pub enum Error {
+        /// The JSON value doesn't describe an atom. The string describes what was expected instead.
+        UnexpectedValue(&'static str),
+    }
+
+    fn fmt(f) {
+        Error::UnexpectedValue(expected) => write!(f, "expected {}", expected),
+    }
+}
+
+/// Convert an atom to JSON.
+pub fn to_json(
+    atom: UnidentifiedAtom,
+    urids: &AtomURIDCollection,
+    unmap: &Unmap,
+) -> Result<Value, Error> {
+    Reader { urids, unmap }.read(atom)
+}
+
+/// Convert JSON to an atom and write it to a space.
+///
+/// If the conversion fails, everything that has been written is rolled back.
+pub fn from_json(
+    value: &Value,
+    space: &mut dyn MutSpace,
+    urids: &AtomURIDCollection,
+    map: &Map,
+) -> Result<(), Error> {
+    let checkpoint = space.checkpoint();
+    let result = Writer { urids, map }.write(value, space);
+    if result.is_err() {
+        // The writer has returned an error, see `MutSpace::rollback`.
+        unsafe { space.rollback(checkpoint) };
+    }
+    result
+}
+
+/// Convert a floating-point number to JSON.
+fn float_to_json(value: f64) -> Value {
+    match Number::from_f64(value) {
+        Some(number) => Value::Number(number),
+        None if value.is_nan() => Value::String("NaN".to_owned()),
+        None if value > 0.0 => Value::String("inf".to_owned()),
+        None => Value::String("-inf".to_owned()),
+    }
+}
+
+/// Convert JSON to a floating-point number.
+fn float_from_json(value: Option<&Value>) -> Result<f64, Error> {
+    match value {
+        Some(Value::Number(number)) => number.as_f64(),
+        Some(Value::String(string)) => match string.as_str() {
+            "NaN" => Some(f64::NAN),
+            "inf" => Some(f64::INFINITY),
+            "-inf" => Some(f64::NEG_INFINITY),
+            _ => None,
+        },
+        _ => None,
+    }
+    .ok_or(Error::UnexpectedValue("a number"))
+}
+
+/// Encode bytes as a hex string.
+fn encode_hex(data: &[u8]) -> String {
+    data.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// Decode a hex string.
+fn decode_hex(value: Option<&Value>) -> Result<Vec<u8>, Error> {
+    let error = Error::UnexpectedValue("a hex string");
+    let string = value.and_then(Value::as_str).ok_or_else(|| error.clone())?;
+    if string.len() & 1 != 0 {
+        return Err(error);
+    }
+    (0..string.len())
+        .step_by(2)
+        .map(|i| {
+            string
+                .get(i..i + 2)
+                .and_then(|byte| u8::from_str_radix(byte, 16).ok())
+                .ok_or_else(|| error.clone())
+        })
+        .collect()
+}
+
+/// Converter from atoms to JSON.
+struct Reader<'c> {
+    urids: &'c AtomURIDCollection,
+    unmap: &'c Unmap<'c>,
+}
+
+impl<'c> Reader<'c> {
+    /// Unmap a URID to a JSON string.
+    fn uri(&self, urid: URID) -> Result<Value, Error> {
+        self.unmap
+            .unmap(urid)
+            .and_then(|uri| uri.to_str().ok())
+            .map(|uri| Value::String(uri.to_owned()))
+            .ok_or(Error::UnknownURID(urid))
+    }
+
+    /// Unmap a raw URID that may be zero.
+    fn optional_uri(&self, urid: u32) -> Result<Value, Error> {
+        match URID::new(urid) {
+            Some(urid) => self.uri(urid),
+            None => Ok(Value::Null),
+        }
+    }
+
+    fn read(&self, atom: UnidentifiedAtom) -> Result<Value, Error> {
+        let (space, _) = atom.space().split_atom()?;
+        let (header, body) = space.split_type::<sys::LV2_Atom>()?;
+        let type_ = match URID::new(header.type_) {
+            Some(type_) => type_,
+            None => return Ok(Value::Null),
+        };
+        let urids = self.urids;
+
+        let mut object = JsonMap::new();
+        let type_uri = self.uri(type_)?;
+        object.insert("type".to_owned(), type_uri.clone());
+
+        if type_ == urids.int {
+            object.insert("value".to_owned(), atom.read(urids.int, ())?.into());
+        } else if type_ == urids.long {
+            object.insert("value".to_owned(), atom.read(urids.long, ())?.into());
+        } else if type_ == urids.float {
+            let value = atom.read(urids.float, ())?;
+            object.insert("value".to_owned(), float_to_json(value.into()));
+        } else if type_ == urids.double {
+            let value = atom.read(urids.double, ())?;
+            object.insert("value".to_owned(), float_to_json(value));
+        } else if type_ == urids.bool {
+            object.insert("value".to_owned(), (atom.read(urids.bool, ())? != 0).into());
+        } else if type_ == urids.urid {
+            let value = atom.read(urids.urid, ())?;
+            object.insert("value".to_owned(), self.uri(value)?);
+        } else if type_ == urids.string {
+            object.insert("value".to_owned(), atom.read(urids.string, ())?.into());
+        } else if type_ == urids.uri {
+            object.insert("value".to_owned(), atom.read(urids.uri, ())?.into());
+        } else if type_ == urids.path {
+            let value = atom.read(urids.path, ())?;
+            let value = value.to_str().ok_or(AtomError::Malformed)?;
+            object.insert("value".to_owned(), value.into());
+        } else if type_ == urids.literal {
+            let (info, value) = atom.read(urids.literal, ())?;
+            object.insert("value".to_owned(), value.into());
+            match info {
+                LiteralInfo::Plain => None,
+                LiteralInfo::Language(language) => {
+                    object.insert("language".to_owned(), self.uri(language)?)
+                }
+                LiteralInfo::Datatype(datatype) => {
+                    object.insert("datatype".to_owned(), self.uri(datatype)?)
+                }
+            };
+        } else if type_ == urids.chunk {
+            let value = atom.read(urids.chunk, ())?;
+            object.insert("value".to_owned(), encode_hex(value).into());
+        } else if type_ == urids.tuple {
+            let value = atom
+                .read(urids.tuple, ())?
+                .map(|atom| self.read(atom))
+                .collect::<Result<Vec<Value>, Error>>()?;
+            object.insert("value".to_owned(), value.into());
+        } else if type_ == urids.vector::<Int>() {
+            let (vector_header, _) = body.split_type::<sys::LV2_Atom_Vector_Body>()?;
+            let child_type = URID::new(vector_header.child_type).ok_or(AtomError::Malformed)?;
+            let children: Vec<Value> = if child_type == urids.int {
+                let children = atom.read(urids.vector(), urids.int)?;
+                children.iter().map(|child| (*child).into()).collect()
+            } else if child_type == urids.long {
+                let children = atom.read(urids.vector(), urids.long)?;
+                children.iter().map(|child| (*child).into()).collect()
+            } else if child_type == urids.float {
+                let children = atom.read(urids.vector(), urids.float)?;
+                children
+                    .iter()
+                    .map(|child| float_to_json((*child).into()))
+                    .collect()
+            } else if child_type == urids.double {
+                let children = atom.read(urids.vector(), urids.double)?;
+                children.iter().map(|child| float_to_json(*child)).collect()
+            } else if child_type == urids.bool {
+                let children = atom.read(urids.vector(), urids.bool)?;
+                children.iter().map(|child| (*child != 0).into()).collect()
+            } else if child_type == urids.urid {
+                let children = atom.read(urids.vector(), urids.urid)?;
+                children
+                    .iter()
+                    .map(|child| self.uri(*child))
+                    .collect::<Result<_, Error>>()?
+            } else {
+                return self.read_body(object, body);
+            };
+            object.insert("childType".to_owned(), self.uri(child_type)?);
+            object.insert("value".to_owned(), children.into());
+        } else if type_ == urids.object || type_uri == as_str(sys::LV2_ATOM__Blank) {
+            let (object_header, _) = body.split_type::<sys::LV2_Atom_Object_Body>()?;
+            let (_, reader) = Object::read(body, ())?;
+            let properties = reader
+                .map(|(header, value)| {
+                    let mut property = JsonMap::new();
+                    property.insert("key".to_owned(), self.uri(header.key)?);
+                    if let Some(context) = header.context {
+                        property.insert("context".to_owned(), self.uri(context)?);
+                    }
+                    property.insert("value".to_owned(), self.read(value)?);
+                    Ok(Value::Object(property))
+                })
+                .collect::<Result<Vec<Value>, Error>>()?;
+            object.insert("id".to_owned(), self.optional_uri(object_header.id)?);
+            object.insert("otype".to_owned(), self.optional_uri(object_header.otype)?);
+            object.insert("properties".to_owned(), properties.into());
+        } else if type_ == urids.sequence {
+            let (sequence_header, mut events) = body.split_type::<sys::LV2_Atom_Sequence_Body>()?;
+            let unit = self.optional_uri(sequence_header.unit)?;
+            let beats = unit == as_str(sys::LV2_UNITS__beat);
+            let mut values = Vec::new();
+            while !events.data().map(<[u8]>::is_empty).unwrap_or(true) {
+                let mut event = JsonMap::new();
+                let (stamp, space) = if beats {
+                    let (stamp, space) = events.split_type::<f64>()?;
+                    (float_to_json(*stamp), space)
+                } else {
+                    let (stamp, space) = events.split_type::<i64>()?;
+                    ((*stamp).into(), space)
+                };
+                let (atom, space) = space.split_atom()?;
+                event.insert(if beats { "beats" } else { "frames" }.to_owned(), stamp);
+                event.insert("value".to_owned(), self.read(UnidentifiedAtom::new(atom))?);
+                values.push(Value::Object(event));
+                events = space;
+            }
+            object.insert("unit".to_owned(), unit);
+            object.insert("events".to_owned(), values.into());
+        } else {
+            return self.read_body(object, body);
+        }
+
+        Ok(Value::Object(object))
+    }
+
+    /// Add the raw body of an atom to it's JSON object.
+    fn read_body(&self, mut object: JsonMap<String, Value>, body: Space) -> Result<Value, Error> {
+        let data = body.data().unwrap_or(&[]);
+        object.insert("body".to_owned(), encode_hex(data).into());
+        Ok(Value::Object(object))
+    }
+}
+
+/// Converter from JSON to atoms.
+struct Writer<'c> {
+    urids: &'c AtomURIDCollection,
+    map: &'c Map<'c>,
+}
+
+impl<'c> Writer<'c> {
+    /// Map a JSON string to a URID.
+    fn urid(&self, value: Option<&Value>) -> Result<URID, Error> {
+        let uri = value
+            .and_then(Value::as_str)
+            .ok_or(Error::UnexpectedValue("a URI"))?;
+        CString::new(uri)
+            .ok()
+            .and_then(|cstring| self.map.map_uri(&cstring))
+            .ok_or_else(|| Error::UnmappableURI(uri.to_owned()))
+    }
+
+    /// Map an optional JSON string to a raw URID, which is zero if the value is missing or `null`.
+    fn optional_urid(&self, value: Option<&Value>) -> Result<u32, Error> {
+        match value {
+            None | Some(Value::Null) => Ok(0),
+            value => self.urid(value).map(|urid| urid.get()),
+        }
+    }
+
+    /// Retrieve an integer from a JSON value.
+    fn integer(value: Option<&Value>) -> Result<i64, Error> {
+        value
+            .and_then(Value::as_i64)
+            .ok_or(Error::UnexpectedValue("an integer"))
+    }
+
+    /// Retrieve a string from a JSON value.
+    fn string(value: Option<&Value>) -> Result<&str, Error> {
+        value
+            .and_then(Value::as_str)
+            .ok_or(Error::UnexpectedValue("a string"))
+    }
+
+    /// Retrieve an array from a JSON value.
+    fn array(value: Option<&Value>) -> Result<&Vec<Value>, Error> {
+        value
+            .and_then(Value::as_array)
+            .ok_or(Error::UnexpectedValue("an array"))
+    }
+
+    fn write(&self, value: &Value, space: &mut dyn MutSpace) -> Result<(), Error> {
+        let object = match value {
+            Value::Null => {
+                space.write(&sys::LV2_Atom { size: 0, type_: 0 }, true)?;
+                return Ok(());
+            }
+            Value::Object(object) => object,
+            _ => return Err(Error::UnexpectedValue("an object or null")),
+        };
+        let type_ = self.urid(object.get("type"))?;
+        let value = object.get("value");
+        let urids = self.urids;
+
+        if object.contains_key("body") {
+            let data = decode_hex(object.get("body"))?;
+            let mut frame = FramedMutSpace::new(space, type_)?;
+            frame.write_raw(&data, false)?;
+        } else if type_ == urids.int {
+            let value = i32::try_from(Self::integer(value)?)
+                .map_err(|_| Error::UnexpectedValue("a 32-bit integer"))?;
+            space.init(urids.int, value)?;
+        } else if type_ == urids.long {
+            space.init(urids.long, Self::integer(value)?)?;
+        } else if type_ == urids.float {
+            space.init(urids.float, float_from_json(value)? as f32)?;
+        } else if type_ == urids.double {
+            space.init(urids.double, float_from_json(value)?)?;
+        } else if type_ == urids.bool {
+            let value = value
+                .and_then(Value::as_bool)
+                .ok_or(Error::UnexpectedValue("a boolean"))?;
+            space.init(urids.bool, value as i32)?;
+        } else if type_ == urids.urid {
+            space.init(urids.urid, self.urid(value)?)?;
+        } else if type_ == urids.string {
+            space.init(urids.string, ())?.append(Self::string(value)?)?;
+        } else if type_ == urids.uri {
+            space.init(urids.uri, ())?.append(Self::string(value)?)?;
+        } else if type_ == urids.path {
+            space.init(urids.path, ())?.append(Self::string(value)?)?;
+        } else if type_ == urids.literal {
+            let info = if object.contains_key("language") {
+                LiteralInfo::Language(self.urid(object.get("language"))?)
+            } else if object.contains_key("datatype") {
+                LiteralInfo::Datatype(self.urid(object.get("datatype"))?)
+            } else {
+                LiteralInfo::Plain
+            };
+            space
+                .init(urids.literal, info)?
+                .append(Self::string(value)?)?;
+        } else if type_ == urids.chunk {
+            let data = decode_hex(value)?;
+            space.init(urids.chunk, ())?.write_raw(&data, false)?;
+        } else if type_ == urids.tuple {
+            let mut frame = FramedMutSpace::new(space, type_)?;
+            for item in Self::array(value)? {
+                self.write(item, &mut frame)?;
+            }
+        } else if type_ == urids.vector::<Int>() {
+            let children = Self::array(value)?;
+            let child_type = self.urid(object.get("childType"))?;
+            if child_type == urids.int {
+                let mut writer = space.init(urids.vector(), urids.int)?;
+                for child in children {
+                    let child = i32::try_from(Self::integer(Some(child))?)
+                        .map_err(|_| Error::UnexpectedValue("a 32-bit integer"))?;
+                    writer.push(child)?;
+                }
+            } else if child_type == urids.long {
+                let mut writer = space.init(urids.vector(), urids.long)?;
+                for child in children {
+                    writer.push(Self::integer(Some(child))?)?;
+                }
+            } else if child_type == urids.float {
+                let mut writer = space.init(urids.vector(), urids.float)?;
+                for child in children {
+                    writer.push(float_from_json(Some(child))? as f32)?;
+                }
+            } else if child_type == urids.double {
+                let mut writer = space.init(urids.vector(), urids.double)?;
+                for child in children {
+                    writer.push(float_from_json(Some(child))?)?;
+                }
+            } else if child_type == urids.bool {
+                let mut writer = space.init(urids.vector(), urids.bool)?;
+                for child in children {
+                    let child = child.as_bool().ok_or(Error::UnexpectedValue("a boolean"))?;
+                    writer.push(child as i32)?;
+                }
+            } else if child_type == urids.urid {
+                let mut writer = space.init(urids.vector(), urids.urid)?;
+                for child in children {
+                    writer.push(self.urid(Some(child))?)?;
+                }
+            } else {
+                return Err(Error::UnexpectedValue(
+                    "a vector of scalars or the body of the vector",
+                ));
+            }
+        } else if type_ == urids.object
+            || object.get("type").and_then(Value::as_str) == Some(as_str(sys::LV2_ATOM__Blank))
+        {
+            let mut frame = FramedMutSpace::new(space, type_)?;
+            let frame = &mut frame as &mut dyn MutSpace;
+            frame.write(
+                &sys::LV2_Atom_Object_Body {
+                    id: self.optional_urid(object.get("id"))?,
+                    otype: self.optional_urid(object.get("otype"))?,
+                },
+                true,
+            )?;
+            for property in Self::array(object.get("properties"))? {
+                let key = self.urid(property.get("key"))?;
+                let context = URID::new(self.optional_urid(property.get("context"))?);
+                Property::write_header(frame, key, context)?;
+                self.write(
+                    property
+                        .get("value")
+                        .ok_or(Error::UnexpectedValue("the value of the property"))?,
+                    frame,
+                )?;
+            }
+        } else if type_ == urids.sequence {
+            let mut frame = FramedMutSpace::new(space, type_)?;
+            let frame = &mut frame as &mut dyn MutSpace;
+            frame.write(
+                &sys::LV2_Atom_Sequence_Body {
+                    unit: self.optional_urid(object.get("unit"))?,
+                    pad: 0,
+                },
+                true,
+            )?;
+            for event in Self::array(object.get("events"))? {
+                if let Some(beats) = event.get("beats") {
+                    frame.write(&float_from_json(Some(beats))?, true)?;
+                } else {
+                    frame.write(&Self::integer(event.get("frames"))?, true)?;
+                }
+                self.write(
+                    event
+                        .get("value")
+                        .ok_or(Error::UnexpectedValue("the value of the event"))?,
+                    frame,
+                )?;
+            }
+        } else {
+            return Err(Error::UnexpectedValue("the body of the atom"));
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "host")]
+mod tests {
+    use crate::conversion::test_support::*;
+    use crate::json::*;
+    use crate::prelude::*;
+    use serde_json::json;
+    use urid::mapper::*;
+    use urid::prelude::*;
+
+    #[test]
+    fn test_round_trip() {
+        let mut mapper = Box::pin(HashURIDMapper::new());
+        let map_interface = mapper.as_mut().make_map_interface();
+        let unmap_interface = mapper.as_mut().make_unmap_interface();
+        let map = Map::new(&map_interface);
+        let unmap = Unmap::new(&unmap_interface);
+        let urids = AtomURIDCollection::from_map(&map).unwrap();
+        let class = map.map_type::<Class>().unwrap();
+        let key = map.map_type::<Key>().unwrap();
+
+        // writing
+        let mut buffer = AlignedBuffer::new(1024);
+        {
+            let mut space = buffer.mut_space();
+            let mut tuple = (&mut space as &mut dyn MutSpace)
+                .init(urids.tuple, ())
+                .unwrap();
+            write_atoms(&mut tuple, &urids, &map);
+            tuple
+                .init(urids.vector(), urids.double)
+                .unwrap()
+                .append(&[0.5, f64::NAN])
+                .unwrap();
+            tuple
+                .init(
+                    urids.object,
+                    ObjectHeader {
+                        id: None,
+                        otype: class.into_general(),
+                    },
+                )
+                .unwrap()
+                .init(key, Some(class.into_general()), urids.int, 2)
+                .unwrap();
+        }
+
+        // converting to JSON
+        let value = to_json(UnidentifiedAtom::new(buffer.space()), &urids, &unmap).unwrap();
+        let children = value["value"].as_array().unwrap();
+        assert_eq!(value["type"], "http://lv2plug.in/ns/ext/atom#Tuple");
+        assert_eq!(children.len(), ATOM_COUNT + 2);
+        assert_eq!(children[0]["value"], -42);
+        assert_eq!(children[2]["value"], 0.1f32 as f64);
+        assert_eq!(children[3]["value"], "-inf");
+        assert_eq!(children[4]["value"], true);
+        assert_eq!(children[5]["value"], "urn:test#class");
+        assert_eq!(children[9]["language"], "http://lexvo.org/id/iso639-3/eng");
+        assert_eq!(
+            children[11],
+            json!({"type": "http://lv2plug.in/ns/ext/atom#Literal", "value": "Hi"})
+        );
+        assert_eq!(children[12]["value"], "0001feff");
+        assert_eq!(children[14]["id"], "urn:test#key");
+        assert_eq!(
+            children[14]["properties"][1]["value"],
+            json!({"type": "urn:test#custom", "body": "010203"})
+        );
+        assert_eq!(children[15]["events"][0]["beats"], 0.25);
+        assert_eq!(children[16]["events"][0]["frames"], 7);
+        assert_eq!(children[ATOM_COUNT]["value"], json!([0.5, "NaN"]));
+        assert_eq!(
+            children[ATOM_COUNT + 1]["properties"][0]["context"],
+            "urn:test#class"
+        );
+
+        // converting back
+        let text = value.to_string();
+        let parsed: Value = serde_json::from_str(&text).unwrap();
+        let mut copy = AlignedBuffer::new(1024);
+        from_json(&parsed, &mut copy.mut_space(), &urids, &map).unwrap();
+        assert_eq!(atom_bytes(&buffer), atom_bytes(&copy));
+
+        // the null atom
+        let mut copy = AlignedBuffer::new(16);
+        from_json(&Value::Null, &mut copy.mut_space(), &urids, &map).unwrap();
+        assert_eq!(atom_bytes(&copy), &[0; 8]);
+        assert_eq!(
+            to_json(UnidentifiedAtom::new(copy.space()), &urids, &unmap).unwrap(),
+            Value::Null
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        let mut mapper = Box::pin(HashURIDMapper::new());
+        let map_interface = mapper.as_mut().make_map_interface();
+        let map = Map::new(&map_interface);
+        let urids = AtomURIDCollection::from_map(&map).unwrap();
+
+        let mut buffer = AlignedBuffer::new(256);
+        let invalid = [
+            (json!(42), Error::UnexpectedValue("an object or null")),
+            (
+                json!({"type": "http://lv2plug.in/ns/ext/atom#Int", "value": 1i64 << 40}),
+                Error::UnexpectedValue("a 32-bit integer"),
+            ),
+            (
+                json!({"type": "http://lv2plug.in/ns/ext/atom#Chunk", "value": "0g"}),
+                Error::UnexpectedValue("a hex string"),
+            ),
+            (
+                json!({"type": "http://lv2plug.in/ns/ext/atom#Tuple", "value": [
+                    {"type": "http://lv2plug.in/ns/ext/atom#Int", "value": 1},
+                    {"type": "http://lv2plug.in/ns/ext/atom#Int", "value": "one"},
+                ]}),
+                Error::UnexpectedValue("an integer"),
+            ),
+            (
+                json!({"type": "http://lv2plug.in/ns/ext/atom#String", "value": "x".repeat(512)}),
+                Error::Atom(AtomError::OutOfSpace),
+            ),
+        ];
+        {
+            let mut space = buffer.mut_space();
+            for (value, error) in invalid.iter() {
+                assert_eq!(
+                    from_json(value, &mut space, &urids, &map),
+                    Err(error.clone())
+                );
+            }
+            from_json(&Value::Null, &mut space, &urids, &map).unwrap();
+        }
+
+        // Nothing but the null atom has been written.
+        assert_eq!(&buffer[..8], &[0; 8]);
+    }
+}
@@ -47,7 +47,7 @@
 //!         // An event contains a timestamp and an atom.
 //!         let (timestamp, atom) = event;
 //!         // If the read atom is a 32-bit integer...
-//!         if let Some(integer) = atom.read(urids.atom.int, ()) {
+//!         if let Ok(integer) = atom.read(urids.atom.int, ()) {
 //!             // Multiply it by two and write it to the sequence.
 //!             output_sequence.init(timestamp, urids.atom.int, integer * 2).unwrap();
 //!         } else {
@@ -72,33 +72,46 @@ extern crate lv2_units as units;
 extern crate lv2_urid as urid;
 
 pub mod chunk;
+#[macro_use]
+mod conversion;
+#[cfg(feature = "json")]
+pub mod json;
 pub mod object;
 pub mod port;
 pub mod scalar;
 pub mod sequence;
+#[cfg(feature = "serde")]
+pub mod serde;
 pub mod space;
 pub mod string;
+#[cfg(feature = "host")]
+pub mod test;
 pub mod tuple;
+pub mod turtle;
 pub mod vector;
 
+pub use lv2_atom_derive::*;
+
 /// Prelude of `lv2_atom` for wildcard usage.
 pub mod prelude {
     use crate::*;
 
-    pub use crate::{Atom, AtomURIDCollection, UnidentifiedAtom};
+    pub use crate::{Atom, AtomBuf, AtomError, AtomURIDCollection, UnidentifiedAtom};
     pub use chunk::Chunk;
-    pub use object::{Object, ObjectHeader, PropertyHeader};
+    pub use lv2_atom_derive::*;
+    pub use object::{AtomObject, Object, ObjectHeader, PropertyHeader, PropertyQuery};
     pub use port::AtomPort;
-    pub use scalar::{AtomURID, Bool, Double, Float, Int, Long};
-    pub use sequence::{Sequence, TimeStamp, TimeStampURID};
+    pub use scalar::{AtomURID, Bool, Double, Float, Int, Long, Number, NumberValue};
+    pub use sequence::{Event, Sequence, TimeStamp, TimeStampURID};
     pub use space::{FramedMutSpace, MutSpace, Space};
-    pub use string::{Literal, LiteralInfo, String};
+    pub use string::{Literal, LiteralInfo, Path, String, URI};
     pub use tuple::Tuple;
-    pub use vector::Vector;
+    pub use vector::{Sound, Vector};
 }
 
 use core::UriBound;
 use space::*;
+use std::mem::size_of;
 use urid::{URIDCollection, URID};
 
 #[derive(Clone, URIDCollection)]
@@ -110,14 +123,19 @@ pub struct AtomURIDCollection {
     pub long: URID<scalar::Long>,
     pub urid: URID<scalar::AtomURID>,
     pub bool: URID<scalar::Bool>,
+    pub number: URID<scalar::Number>,
     vector: URID<vector::Vector<scalar::Int>>,
+    pub sound: URID<vector::Sound>,
     pub chunk: URID<chunk::Chunk>,
     pub literal: URID<string::Literal>,
     pub object: URID<object::Object>,
     pub property: URID<object::Property>,
     pub string: URID<string::String>,
+    pub uri: URID<string::URI>,
+    pub path: URID<string::Path>,
     pub tuple: URID<tuple::Tuple>,
     pub sequence: URID<sequence::Sequence>,
+    pub event: URID<sequence::Event>,
 }
 
 impl AtomURIDCollection {
@@ -126,6 +144,29 @@ impl AtomURIDCollection {
     }
 }
 
+/// Errors that may occur when an atom is read or written.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AtomError {
+    /// The space is too small to contain the requested data, or there is not enough space left to write it.
+    OutOfSpace,
+    /// The data isn't aligned as required by its type.
+    Misaligned,
+    /// The type URID of the atom doesn't match the requested type.
+    TypeMismatch,
+    /// The header or body of the atom is invalid.
+    Malformed,
+    /// A time stamp is measured in a different unit than the sequence.
+    TimeStampUnitMismatch,
+    /// A time stamp is older than the time stamp of the previous event.
+    NonMonotonicTimeStamp,
+    /// A time stamp in beats is not a number.
+    InvalidTimeStamp,
+    /// An atom has already been written to the port.
+    AlreadyWritten,
+    /// A required property with the given key is missing in an object.
+    MissingProperty(URID),
+}
+
 /// Atom type.
 ///
 /// This is the foundation of this crate: Types that implement `Atom` define the reading and writing functions for an atom type. However, these types will never be constructed; They are only names to be used for generic type arguments.
@@ -159,8 +200,9 @@ where
     ///
     /// The passed space exactly covers the body of the atom, excluding the header. You may assume that the body is actually of your atom type, since the URID of the atom was checked beforehand.
     ///
-    /// If the atom is malformed, you may not panic and return `None` instead.
-    fn read(body: Space<'a>, parameter: Self::ReadParameter) -> Option<Self::ReadHandle>;
+    /// If the atom is malformed, you may not panic and return an error instead.
+    fn read(body: Space<'a>, parameter: Self::ReadParameter)
+        -> Result<Self::ReadHandle, AtomError>;
 
     /// Initialize the body of the atom.
     ///
@@ -169,11 +211,11 @@ where
     ///
     /// The frame of the atom was already initialized, containing the URID.
     ///
-    /// If space is insufficient, you may not panic and return `None` instead. The written results are assumed to be malformed.
+    /// If space is insufficient, you may not panic and return `AtomError::OutOfSpace` instead. The written results are assumed to be malformed.
     fn init(
         frame: FramedMutSpace<'a, 'b>,
         parameter: Self::WriteParameter,
-    ) -> Option<Self::WriteHandle>;
+    ) -> Result<Self::WriteHandle, AtomError>;
 }
 
 /// An atom of yet unknown type.
@@ -199,10 +241,71 @@ impl<'a> UnidentifiedAtom<'a> {
         self,
         urid: URID<A>,
         parameter: A::ReadParameter,
-    ) -> Option<A::ReadHandle> {
+    ) -> Result<A::ReadHandle, AtomError> {
         self.space
             .split_atom_body(urid)
-            .map(|(body, _)| body)
-            .and_then(|body| A::read(body, parameter))
+            .and_then(|(body, _)| A::read(body, parameter))
+    }
+
+    /// Return the space of the atom, containing the header and the body.
+    pub fn space(&self) -> Space<'a> {
+        self.space
+    }
+}
+
+/// An owned atom of yet unknown type.
+///
+/// Unlike [`UnidentifiedAtom`](struct.UnidentifiedAtom.html), which only borrows the atom, this struct contains a copy of the atom's header and body. Therefore, it can be kept after the `run` call, for example to store the last received message or to queue a message for the UI. Two buffers are equal if they contain the same bytes.
+///
+/// To write the atom back, for example into a sequence, create an `UnidentifiedAtom` with [`as_atom`](#method.as_atom) and forward it.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct AtomBuf {
+    data: AlignedBuffer,
+}
+
+impl AtomBuf {
+    /// Copy an atom into a new buffer.
+    ///
+    /// If the atom is malformed or it's type URID is zero, `AtomError::Malformed` is returned.
+    pub fn new(atom: UnidentifiedAtom) -> Result<Self, AtomError> {
+        let (space, _) = atom.space().split_atom()?;
+        let data = space.data().ok_or(AtomError::Malformed)?;
+        let (header, _) = space.split_type::<sys::LV2_Atom>()?;
+        if header.type_ == 0 {
+            return Err(AtomError::Malformed);
+        }
+        Ok(Self {
+            data: AlignedBuffer::from_bytes(data),
+        })
+    }
+
+    fn header(&self) -> &sys::LV2_Atom {
+        unsafe { &*(self.data.as_ptr() as *const sys::LV2_Atom) }
+    }
+
+    /// Return the type URID of the atom.
+    pub fn type_urid(&self) -> URID {
+        unsafe { URID::new_unchecked(self.header().type_) }
+    }
+
+    /// Return the space of the atom's body.
+    pub fn body(&self) -> Space<'_> {
+        Space::from_slice(&self.data[size_of::<sys::LV2_Atom>()..])
+    }
+
+    /// Return a borrowed view of the atom.
+    pub fn as_atom(&self) -> UnidentifiedAtom<'_> {
+        UnidentifiedAtom::new(self.data.space())
+    }
+
+    /// Try to read the atom.
+    ///
+    /// This works just like [`UnidentifiedAtom::read`](struct.UnidentifiedAtom.html#method.read).
+    pub fn read<'a, 'b, A: Atom<'a, 'b>>(
+        &'a self,
+        urid: URID<A>,
+        parameter: A::ReadParameter,
+    ) -> Result<A::ReadHandle, AtomError> {
+        self.as_atom().read(urid, parameter)
     }
 }
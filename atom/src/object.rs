@@ -39,7 +39,7 @@
 //!     /// Iterate through all properties of the object.
 //!     for (property_header, atom) in object_reader {
 //!         // If the property is an integer...
-//!         if let Some(integer) = atom.read(urids.atom.int, ()) {
+//!         if let Ok(integer) = atom.read(urids.atom.int, ()) {
 //!             // Print it!
 //!             println!(
 //!                 "Property No. {} has integer value {}",
@@ -76,7 +76,7 @@ use crate::*;
 use core::UriBound;
 use std::convert::TryFrom;
 use std::iter::Iterator;
-use urid::URID;
+use urid::{URIDCollection, URID};
 
 /// An atom containing multiple key-value pairs.
 ///
@@ -106,22 +106,22 @@ where
     type WriteParameter = ObjectHeader;
     type WriteHandle = ObjectWriter<'a, 'b>;
 
-    fn read(body: Space<'a>, _: ()) -> Option<(ObjectHeader, ObjectReader<'a>)> {
+    fn read(body: Space<'a>, _: ()) -> Result<(ObjectHeader, ObjectReader<'a>), AtomError> {
         let (header, body) = body.split_type::<sys::LV2_Atom_Object_Body>()?;
         let header = ObjectHeader {
             id: URID::try_from(header.id).ok(),
-            otype: URID::try_from(header.otype).ok()?,
+            otype: URID::try_from(header.otype).map_err(|_| AtomError::Malformed)?,
         };
 
         let reader = ObjectReader { space: body };
 
-        Some((header, reader))
+        Ok((header, reader))
     }
 
     fn init(
         mut frame: FramedMutSpace<'a, 'b>,
         header: ObjectHeader,
-    ) -> Option<ObjectWriter<'a, 'b>> {
+    ) -> Result<ObjectWriter<'a, 'b>, AtomError> {
         {
             let frame = &mut frame as &mut dyn MutSpace;
             frame.write(
@@ -130,9 +130,9 @@ where
                     otype: header.otype.get(),
                 },
                 true,
-            );
+            )?;
         }
-        Some(ObjectWriter { frame })
+        Ok(ObjectWriter { frame })
     }
 }
 
@@ -158,14 +158,17 @@ where
     type WriteHandle = <Object as Atom<'a, 'b>>::WriteHandle;
 
     #[allow(clippy::unit_arg)]
-    fn read(body: Space<'a>, parameter: Self::ReadParameter) -> Option<Self::ReadHandle> {
+    fn read(
+        body: Space<'a>,
+        parameter: Self::ReadParameter,
+    ) -> Result<Self::ReadHandle, AtomError> {
         Object::read(body, parameter)
     }
 
     fn init(
         frame: FramedMutSpace<'a, 'b>,
         parameter: Self::WriteParameter,
-    ) -> Option<Self::WriteHandle> {
+    ) -> Result<Self::WriteHandle, AtomError> {
         Object::init(frame, parameter)
     }
 }
@@ -181,12 +184,152 @@ impl<'a> Iterator for ObjectReader<'a> {
     type Item = (PropertyHeader, UnidentifiedAtom<'a>);
 
     fn next(&mut self) -> Option<(PropertyHeader, UnidentifiedAtom<'a>)> {
-        let (header, value, space) = Property::read_body(self.space)?;
+        let (header, value, space) = Property::read_body(self.space).ok()?;
         self.space = space;
         Some((header, UnidentifiedAtom::new(value)))
     }
 }
 
+impl<'a> ObjectReader<'a> {
+    /// Look up multiple properties in a single pass.
+    ///
+    /// Every query has a slot of the type `Option<T>`, where `T` is a [`PropertyValue`](trait.PropertyValue.html). All slots are cleared and then, the value of the first property with the key of a query is read into its slot. Nothing is allocated and the iteration stops as soon as every slot is filled.
+    ///
+    /// If a value can't be read as the type of its slot, the error of the `read_value` method is returned. If a required property is missing, `AtomError::MissingProperty` is returned with its key.
+    ///
+    /// ```
+    /// # use lv2_atom::prelude::*;
+    /// # use lv2_atom::object::ObjectReader;
+    /// # use lv2_urid::prelude::*;
+    /// fn read_note<'a>(
+    ///     reader: ObjectReader<'a>,
+    ///     pitch_key: URID,
+    ///     label_key: URID,
+    ///     urids: &AtomURIDCollection,
+    /// ) -> Result<(i32, Option<&'a str>), AtomError> {
+    ///     let mut pitch: Option<i32> = None;
+    ///     let mut label: Option<&str> = None;
+    ///     reader.get_many(
+    ///         urids,
+    ///         &mut [
+    ///             PropertyQuery::required(pitch_key, &mut pitch),
+    ///             PropertyQuery::optional(label_key, &mut label),
+    ///         ],
+    ///     )?;
+    ///     Ok((pitch.unwrap(), label))
+    /// }
+    /// ```
+    pub fn get_many(
+        self,
+        urids: &AtomURIDCollection,
+        queries: &mut [PropertyQuery<'a, '_>],
+    ) -> Result<(), AtomError> {
+        for query in queries.iter_mut() {
+            query.slot.clear();
+        }
+
+        let mut remaining = queries.len();
+        for (header, value) in self {
+            if remaining == 0 {
+                break;
+            }
+            let query = queries
+                .iter_mut()
+                .find(|query| !query.slot.is_filled() && query.matches(&header));
+            if let Some(query) = query {
+                query.slot.fill(value, urids)?;
+                remaining -= 1;
+            }
+        }
+
+        match queries
+            .iter()
+            .find(|query| query.required && !query.slot.is_filled())
+        {
+            Some(query) => Err(AtomError::MissingProperty(query.key)),
+            None => Ok(()),
+        }
+    }
+}
+
+/// A slot that is filled by `ObjectReader::get_many`.
+trait PropertySlot<'a> {
+    fn clear(&mut self);
+
+    fn is_filled(&self) -> bool;
+
+    fn fill(
+        &mut self,
+        atom: UnidentifiedAtom<'a>,
+        urids: &AtomURIDCollection,
+    ) -> Result<(), AtomError>;
+}
+
+impl<'a, T: PropertyValue<'a>> PropertySlot<'a> for Option<T> {
+    fn clear(&mut self) {
+        *self = None;
+    }
+
+    fn is_filled(&self) -> bool {
+        self.is_some()
+    }
+
+    fn fill(
+        &mut self,
+        atom: UnidentifiedAtom<'a>,
+        urids: &AtomURIDCollection,
+    ) -> Result<(), AtomError> {
+        *self = Some(T::read_value(atom, urids)?);
+        Ok(())
+    }
+}
+
+/// A property that is looked up by [`ObjectReader::get_many`](struct.ObjectReader.html#method.get_many).
+///
+/// The query contains the key of the property, a mutable reference to the slot for its value and whether the property is required. By default, properties with any context match the query, but the context can be set with [`with_context`](#method.with_context).
+pub struct PropertyQuery<'a, 'q> {
+    key: URID,
+    context: Option<URID>,
+    required: bool,
+    slot: &'q mut dyn PropertySlot<'a>,
+}
+
+impl<'a, 'q> PropertyQuery<'a, 'q> {
+    /// Create a query for a property that has to be present.
+    pub fn required<G: ?Sized, T: PropertyValue<'a>>(
+        key: URID<G>,
+        slot: &'q mut Option<T>,
+    ) -> Self {
+        Self {
+            key: key.into_general(),
+            context: None,
+            required: true,
+            slot,
+        }
+    }
+
+    /// Create a query for a property that may be missing.
+    pub fn optional<G: ?Sized, T: PropertyValue<'a>>(
+        key: URID<G>,
+        slot: &'q mut Option<T>,
+    ) -> Self {
+        Self {
+            required: false,
+            ..Self::required(key, slot)
+        }
+    }
+
+    /// Only match properties with the given context.
+    pub fn with_context<C: ?Sized>(mut self, context: URID<C>) -> Self {
+        self.context = Some(context.into_general());
+        self
+    }
+
+    fn matches(&self, header: &PropertyHeader) -> bool {
+        header.key == self.key && (self.context.is_none() || header.context == self.context)
+    }
+}
+
 /// Writing handle for object properties.
 ///
 /// This handle is a safeguard to assure that a object is always a series of properties.
@@ -197,16 +340,46 @@ pub struct ObjectWriter<'a, 'b> {
 impl<'a, 'b> ObjectWriter<'a, 'b> {
     /// Initialize a new property.
     ///
-    /// This method writes out the header of a property and returns a reference to the space, so the property values can be written.
+    /// This method writes out the header of a property and returns a reference to the space, so the property values can be written. If writing fails, the property is removed again and the object stays valid.
     pub fn init<'c, G: ?Sized, A: Atom<'a, 'c>>(
         &'c mut self,
         key: URID<G>,
         context: Option<URID>,
         child_urid: URID<A>,
         parameter: A::WriteParameter,
-    ) -> Option<A::WriteHandle> {
-        Property::write_header(&mut self.frame, key.into_general(), context)?;
-        (&mut self.frame as &mut dyn MutSpace).init(child_urid, parameter)
+    ) -> Result<A::WriteHandle, AtomError> {
+        let checkpoint = self.frame.checkpoint();
+        if let Err(error) = Property::write_header(&mut self.frame, key.into_general(), context) {
+            unsafe { self.frame.rollback(checkpoint) };
+            return Err(error);
+        }
+        // Borrowing the writer through a pointer, like in `MutSpace::init`.
+        let this: *mut Self = self;
+        let result =
+            (unsafe { &mut (*this).frame } as &mut dyn MutSpace).init(child_urid, parameter);
+        if result.is_err() {
+            unsafe { (*this).frame.rollback(checkpoint) };
+        }
+        result
+    }
+
+    /// Forward an unidentified atom as the value of a new property.
+    ///
+    /// If your cannot identify the type of the atom but have to write it, you can simply forward it.
+    pub fn forward<G: ?Sized>(
+        &mut self,
+        key: URID<G>,
+        context: Option<URID>,
+        atom: UnidentifiedAtom,
+    ) -> Result<(), AtomError> {
+        let data = atom.space().data().ok_or(AtomError::Malformed)?;
+        let checkpoint = self.frame.checkpoint();
+        let result = Property::write_header(&mut self.frame, key.into_general(), context)
+            .and_then(|_| self.frame.write_raw(data, true).map(|_| ()));
+        if result.is_err() {
+            unsafe { self.frame.rollback(checkpoint) };
+        }
+        result
     }
 }
 
@@ -234,7 +407,7 @@ impl Property {
     /// Read the body of a property atom from a space.
     ///
     /// This method assumes that the space actually contains the body of a property atom, without the header. It returns the property header, containing the key and optional context of the property, the body of the actual atom, and the space behind the atom.
-    fn read_body(space: Space) -> Option<(PropertyHeader, Space, Space)> {
+    fn read_body(space: Space) -> Result<(PropertyHeader, Space, Space), AtomError> {
         #[repr(C)]
         #[derive(Clone, Copy)]
         /// A custom version of the property body that does not include the value atom header.
@@ -248,24 +421,191 @@ impl Property {
         let (header, space) = space.split_type::<StrippedPropertyBody>()?;
 
         let header = PropertyHeader {
-            key: URID::try_from(header.key).ok()?,
+            key: URID::try_from(header.key).map_err(|_| AtomError::Malformed)?,
             context: URID::try_from(header.context).ok(),
         };
 
         let (atom, space) = space.split_atom()?;
-        Some((header, atom, space))
+        Ok((header, atom, space))
     }
 
     /// Write out the header of a property atom.
     ///
-    /// This method simply writes out the content of the header to the space and returns `Some(())` if it's successful.
-    fn write_header(space: &mut dyn MutSpace, key: URID, context: Option<URID>) -> Option<()> {
+    /// This method simply writes out the content of the header to the space and returns `Ok(())` if it's successful.
+    pub(crate) fn write_header(
+        space: &mut dyn MutSpace,
+        key: URID,
+        context: Option<URID>,
+    ) -> Result<(), AtomError> {
         space.write(&key.get(), true)?;
         space.write(&context.map(|urid| urid.get()).unwrap_or(0), false)?;
-        Some(())
+        Ok(())
     }
 }
 
+/// A Rust type that can be read from and written to the value of a property.
+///
+/// This trait is used by the code generated by `#[derive(AtomObject)]` and it is implemented for the scalar types, for string slices and for unidentified atoms, which are simply forwarded.
+pub trait PropertyValue<'a>: Sized {
+    /// Read the value from the atom of a property.
+    ///
+    /// If the atom doesn't have the expected type, `AtomError::TypeMismatch` is returned.
+    fn read_value(
+        atom: UnidentifiedAtom<'a>,
+        urids: &AtomURIDCollection,
+    ) -> Result<Self, AtomError>;
+
+    /// Write the value as a new property of an object.
+    fn write_value<G: ?Sized>(
+        &self,
+        writer: &mut ObjectWriter,
+        key: URID<G>,
+        urids: &AtomURIDCollection,
+    ) -> Result<(), AtomError>;
+}
+
+macro_rules! make_scalar_property_value {
+    ($value:ty, $urid:ident) => {
+        impl<'a> PropertyValue<'a> for $value {
+            fn read_value(
+                atom: UnidentifiedAtom<'a>,
+                urids: &AtomURIDCollection,
+            ) -> Result<Self, AtomError> {
+                atom.read(urids.$urid, ())
+            }
+
+            fn write_value<G: ?Sized>(
+                &self,
+                writer: &mut ObjectWriter,
+                key: URID<G>,
+                urids: &AtomURIDCollection,
+            ) -> Result<(), AtomError> {
+                writer.init(key, None, urids.$urid, *self).map(|_| ())
+            }
+        }
+    };
+}
+
+make_scalar_property_value!(f64, double);
+make_scalar_property_value!(f32, float);
+make_scalar_property_value!(i64, long);
+make_scalar_property_value!(i32, int);
+make_scalar_property_value!(URID, urid);
+
+impl<'a> PropertyValue<'a> for bool {
+    fn read_value(
+        atom: UnidentifiedAtom<'a>,
+        urids: &AtomURIDCollection,
+    ) -> Result<Self, AtomError> {
+        atom.read(urids.bool, ()).map(|value| value != 0)
+    }
+
+    fn write_value<G: ?Sized>(
+        &self,
+        writer: &mut ObjectWriter,
+        key: URID<G>,
+        urids: &AtomURIDCollection,
+    ) -> Result<(), AtomError> {
+        writer.init(key, None, urids.bool, *self as i32).map(|_| ())
+    }
+}
+
+impl<'a> PropertyValue<'a> for &'a str {
+    fn read_value(
+        atom: UnidentifiedAtom<'a>,
+        urids: &AtomURIDCollection,
+    ) -> Result<Self, AtomError> {
+        atom.read(urids.string, ())
+    }
+
+    fn write_value<G: ?Sized>(
+        &self,
+        writer: &mut ObjectWriter,
+        key: URID<G>,
+        urids: &AtomURIDCollection,
+    ) -> Result<(), AtomError> {
+        writer
+            .init(key, None, urids.string, ())?
+            .append(self)
+            .map(|_| ())
+    }
+}
+
+impl<'a> PropertyValue<'a> for UnidentifiedAtom<'a> {
+    fn read_value(atom: UnidentifiedAtom<'a>, _: &AtomURIDCollection) -> Result<Self, AtomError> {
+        Ok(atom)
+    }
+
+    fn write_value<G: ?Sized>(
+        &self,
+        writer: &mut ObjectWriter,
+        key: URID<G>,
+        _: &AtomURIDCollection,
+    ) -> Result<(), AtomError> {
+        writer.forward(key, None, *self)
+    }
+}
+
+/// A Rust struct that represents an object of a certain class.
+///
+/// Instead of iterating over the properties of an object and matching their keys, you can read the whole object into a struct and write it from one. This trait should be implemented with `#[derive(AtomObject)]`, which also creates the URID collection with the class, the keys of the properties and the URIDs of this crate:
+///
+/// ```
+/// use lv2_atom::prelude::*;
+/// use lv2_core::prelude::*;
+/// use lv2_urid::prelude::*;
+///
+/// struct Note;
+/// unsafe impl UriBound for Note {
+///     const URI: &'static [u8] = b"urn:note\0";
+/// }
+///
+/// struct Pitch;
+/// unsafe impl UriBound for Pitch {
+///     const URI: &'static [u8] = b"urn:pitch\0";
+/// }
+///
+/// struct Label;
+/// unsafe impl UriBound for Label {
+///     const URI: &'static [u8] = b"urn:label\0";
+/// }
+///
+/// #[derive(AtomObject)]
+/// #[object(Note)]
+/// struct NoteObject<'a> {
+///     #[property(Pitch)]
+///     pitch: i32,
+///     #[property(Label)]
+///     label: Option<&'a str>,
+/// }
+///
+/// fn run(input: UnidentifiedAtom, output: &mut dyn MutSpace, urids: &NoteObjectURIDCollection) {
+///     let mut note = NoteObject::read(input, urids).unwrap();
+///     note.pitch += 12;
+///     note.write(output, urids).unwrap();
+/// }
+/// ```
+pub trait AtomObject<'a>: Sized {
+    /// The URIDs that are needed to read and write the object.
+    type URIDCollection: URIDCollection;
+
+    /// Read the object from an atom.
+    ///
+    /// The returned struct references the strings and atoms in the object, nothing is copied. Properties with unknown keys are ignored and if a key appears multiple times, the first property is used, like in [`get_many`](struct.ObjectReader.html#method.get_many).
+    ///
+    /// If the atom is not an object of the expected class, `AtomError::TypeMismatch` is returned. If a required property is missing, `AtomError::MissingProperty` is returned with its key.
+    fn read(atom: UnidentifiedAtom<'a>, urids: &Self::URIDCollection) -> Result<Self, AtomError>;
+
+    /// Write the object to a space.
+    ///
+    /// The object is written with an `ObjectWriter` and optional properties with a value of `None` are left out. If writing fails, the object is removed again.
+    fn write<'b>(
+        &self,
+        space: &mut dyn MutSpace<'b>,
+        urids: &Self::URIDCollection,
+    ) -> Result<(), AtomError>;
+}
+
 #[cfg(test)]
 #[cfg(feature = "host")]
 mod tests {
@@ -297,7 +637,7 @@ mod tests {
             .unwrap();
         let second_value: f32 = 42.0;
 
-        let mut raw_space: Box<[u8]> = Box::new([0; 256]);
+        let mut raw_space = AlignedBuffer::new(256);
 
         // writing
         {
@@ -388,4 +728,92 @@ mod tests {
             assert_eq!(atom.read::<Float>(urids.float, ()).unwrap(), second_value);
         }
     }
+
+    #[test]
+    fn test_get_many() {
+        let mut mapper = Box::pin(HashURIDMapper::new());
+        let interface = mapper.as_mut().make_map_interface();
+        let map = Map::new(&interface);
+        let urids = AtomURIDCollection::from_map(&map).unwrap();
+
+        let object_type = map
+            .map_uri(Uri::from_bytes_with_nul(b"urn:my-type\0").unwrap())
+            .unwrap();
+        let first_key = map
+            .map_uri(Uri::from_bytes_with_nul(b"urn:value-a\0").unwrap())
+            .unwrap();
+        let second_key = map
+            .map_uri(Uri::from_bytes_with_nul(b"urn:value-b\0").unwrap())
+            .unwrap();
+        let third_key = map
+            .map_uri(Uri::from_bytes_with_nul(b"urn:value-c\0").unwrap())
+            .unwrap();
+        let context = map
+            .map_uri(Uri::from_bytes_with_nul(b"urn:context\0").unwrap())
+            .unwrap();
+
+        let mut raw_space = AlignedBuffer::new(256);
+        {
+            let mut space = raw_space.mut_space();
+            let mut writer = (&mut space as &mut dyn MutSpace)
+                .init(
+                    urids.object,
+                    ObjectHeader {
+                        id: None,
+                        otype: object_type,
+                    },
+                )
+                .unwrap();
+            writer.init(first_key, None, urids.int, 17).unwrap();
+            writer.init(second_key, None, urids.float, 1.0).unwrap();
+            writer
+                .init(second_key, Some(context), urids.float, 2.0)
+                .unwrap();
+            writer.init(first_key, None, urids.int, 42).unwrap();
+        }
+        let atom = UnidentifiedAtom::new(raw_space.space());
+
+        // the first matching property is used
+        let mut first: Option<i32> = Some(0);
+        let mut second: Option<f32> = None;
+        let mut third: Option<i64> = None;
+        let (_, reader) = atom.read(urids.object, ()).unwrap();
+        reader
+            .get_many(
+                &urids,
+                &mut [
+                    PropertyQuery::required(first_key, &mut first),
+                    PropertyQuery::required(second_key, &mut second).with_context(context),
+                    PropertyQuery::optional(third_key, &mut third),
+                ],
+            )
+            .unwrap();
+        assert_eq!(first, Some(17));
+        assert_eq!(second, Some(2.0));
+        assert_eq!(third, None);
+
+        // a missing required property
+        let (_, reader) = atom.read(urids.object, ()).unwrap();
+        assert_eq!(
+            reader.get_many(
+                &urids,
+                &mut [
+                    PropertyQuery::required(first_key, &mut first),
+                    PropertyQuery::required(third_key, &mut third),
+                ],
+            ),
+            Err(AtomError::MissingProperty(third_key))
+        );
+
+        // a value of the wrong type
+        let mut wrong: Option<f64> = None;
+        let (_, reader) = atom.read(urids.object, ()).unwrap();
+        assert_eq!(
+            reader.get_many(
+                &urids,
+                &mut [PropertyQuery::optional(first_key, &mut wrong)]
+            ),
+            Err(AtomError::TypeMismatch)
+        );
+    }
 }
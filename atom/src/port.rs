@@ -25,7 +25,9 @@
 //! }
 //! ```
 use crate::space::*;
+use crate::AtomError;
 use core::port::PortType;
+use core::UriBound;
 use std::ffi::c_void;
 use std::ptr::NonNull;
 use urid::URID;
@@ -47,12 +49,12 @@ impl<'a> PortReader<'a> {
     ///
     /// In order to identify the atom, the reader needs to know it's URID. Also, some atoms require a parameter. However, you can simply pass `()` in most cases.
     ///
-    /// This method returns `None` if the atom is malformed or simply isn't of the specified type.
+    /// This method returns an error if the atom is malformed or simply isn't of the specified type.
     pub fn read<'b, A: crate::Atom<'a, 'b>>(
         &'b self,
         urid: URID<A>,
         parameter: A::ReadParameter,
-    ) -> Option<A::ReadHandle> {
+    ) -> Result<A::ReadHandle, AtomError> {
         A::read(self.space.split_atom_body(urid)?.0, parameter)
     }
 }
@@ -80,18 +82,21 @@ impl<'a> PortWriter<'a> {
     ///
     /// Please note that you can call this method once only, because any atoms written behind the first one will not be identified.
     ///
-    /// This method returns `None` if the space of the port isn't big enough or if the method was called multiple times.
+    /// This method returns `AtomError::OutOfSpace` if the space of the port isn't big enough and `AtomError::AlreadyWritten` if an atom has already been written. If writing fails, nothing is written and the method may be called again.
     pub fn init<'b, A: crate::Atom<'a, 'b>>(
         &'b mut self,
         urid: URID<A>,
         parameter: A::WriteParameter,
-    ) -> Option<A::WriteHandle> {
-        if !self.has_been_written {
+    ) -> Result<A::WriteHandle, AtomError> {
+        if self.has_been_written {
+            return Err(AtomError::AlreadyWritten);
+        }
+        let result = (&mut self.space as &mut dyn MutSpace).init(urid, parameter);
+        // A failed write is rolled back, so the port may be written again.
+        if result.is_ok() {
             self.has_been_written = true;
-            (&mut self.space as &mut dyn MutSpace).init(urid, parameter)
-        } else {
-            None
         }
+        result
     }
 }
 
@@ -102,6 +107,10 @@ impl<'a> PortWriter<'a> {
 /// [See also the module documentation.](index.html)
 pub struct AtomPort;
 
+unsafe impl UriBound for AtomPort {
+    const URI: &'static [u8] = sys::LV2_ATOM__AtomPort;
+}
+
 impl PortType for AtomPort {
     type InputPortType = PortReader<'static>;
     type OutputPortType = PortWriter<'static>;
@@ -134,7 +143,7 @@ mod tests {
         let map = Map::new(&interface);
         let urids = AtomURIDCollection::from_map(&map).unwrap();
 
-        let mut raw_space: Box<[u8]> = Box::new([0; 256]);
+        let mut raw_space = AlignedBuffer::new(256);
 
         // writing a chunk to indicate the size of the space.
         {
@@ -152,6 +161,10 @@ mod tests {
             let mut writer =
                 unsafe { AtomPort::output_from_raw(NonNull::from(raw_space.as_mut()).cast(), 0) };
             writer.init::<Int>(urids.int, 42).unwrap();
+            assert_eq!(
+                Err(AtomError::AlreadyWritten),
+                writer.init::<Int>(urids.int, 17).map(|_| ())
+            );
         }
 
         // Reading
@@ -159,6 +172,47 @@ mod tests {
             let reader =
                 unsafe { AtomPort::input_from_raw(NonNull::from(raw_space.as_mut()).cast(), 0) };
             assert_eq!(reader.read::<Int>(urids.int, ()).unwrap(), 42);
+            assert_eq!(
+                Err(AtomError::TypeMismatch),
+                reader.read::<Long>(urids.long, ()).map(|_| ())
+            );
+        }
+    }
+
+    #[test]
+    fn test_failed_write() {
+        let mut mapper = Box::pin(HashURIDMapper::new());
+        let interface = mapper.as_mut().make_map_interface();
+        let map = Map::new(&interface);
+        let urids = AtomURIDCollection::from_map(&map).unwrap();
+
+        let mut raw_space = AlignedBuffer::new(256);
+
+        // The port has space for an `Int`, but not for a `Long`.
+        {
+            let mut space = RootMutSpace::new(raw_space.as_mut());
+            let mut writer = (&mut space as &mut dyn MutSpace)
+                .init(urids.chunk, ())
+                .unwrap();
+            writer.allocate(size_of::<i32>(), false).unwrap();
         }
+
+        {
+            let mut writer =
+                unsafe { AtomPort::output_from_raw(NonNull::from(raw_space.as_mut()).cast(), 0) };
+            assert_eq!(
+                Err(AtomError::OutOfSpace),
+                writer.init::<Long>(urids.long, 42).map(|_| ())
+            );
+            writer.init::<Int>(urids.int, 17).unwrap();
+            assert_eq!(
+                Err(AtomError::AlreadyWritten),
+                writer.init::<Int>(urids.int, 42).map(|_| ())
+            );
+        }
+
+        let reader =
+            unsafe { AtomPort::input_from_raw(NonNull::from(raw_space.as_mut()).cast(), 0) };
+        assert_eq!(reader.read::<Int>(urids.int, ()).unwrap(), 17);
     }
 }
@@ -4,6 +4,8 @@
 //!
 //! Unlike other atoms, scalars do not need to be written after the initialization. However, you still can modify the scalar after it was initialized.
 //!
+//! The numeric scalars `Int`, `Long`, `Float` and `Double` are all subclasses of the abstract [`Number`](struct.Number.html) class. If you don't care about the exact type of a number, you can read and write any of them as a [`NumberValue`](enum.NumberValue.html).
+//!
 //! # Example
 //!
 //! ```
@@ -48,19 +50,19 @@ pub trait ScalarAtom: UriBound {
 
     /// Try to read the atom from a space.
     ///
-    /// If the space does not contain the atom or is not big enough, return `None`. The second return value is the space behind the atom.
-    fn read_scalar(body: Space) -> Option<Self::InternalType> {
+    /// If the space does not contain the atom or is not big enough, return an error.
+    fn read_scalar(body: Space) -> Result<Self::InternalType, AtomError> {
         body.split_type::<Self::InternalType>()
             .map(|(value, _)| *value)
     }
 
     /// Try to write the atom into a space.
     ///
-    /// Write an atom with the value of `value` into the space and return a mutable reference to the written value. If the space is not big enough, return `None`.
+    /// Write an atom with the value of `value` into the space and return a mutable reference to the written value. If the space is not big enough, return `AtomError::OutOfSpace`.
     fn write_scalar<'a, 'b>(
         mut frame: FramedMutSpace<'a, 'b>,
         value: Self::InternalType,
-    ) -> Option<&'a mut Self::InternalType> {
+    ) -> Result<&'a mut Self::InternalType, AtomError> {
         (&mut frame as &mut dyn MutSpace).write(&value, true)
     }
 }
@@ -74,14 +76,14 @@ where
     type WriteParameter = A::InternalType;
     type WriteHandle = &'a mut A::InternalType;
 
-    fn read(body: Space<'a>, _: ()) -> Option<A::InternalType> {
+    fn read(body: Space<'a>, _: ()) -> Result<A::InternalType, AtomError> {
         <A as ScalarAtom>::read_scalar(body)
     }
 
     fn init(
         frame: FramedMutSpace<'a, 'b>,
         value: A::InternalType,
-    ) -> Option<&'a mut A::InternalType> {
+    ) -> Result<&'a mut A::InternalType, AtomError> {
         <A as ScalarAtom>::write_scalar(frame, value)
     }
 }
@@ -161,6 +163,77 @@ make_scalar_atom!(
     |urids: &AtomURIDCollection| urids.urid
 );
 
+/// The abstract class of all numeric atoms.
+///
+/// No atom has this type; It's the super class of `Int`, `Long`, `Float` and `Double`. Therefore, this type doesn't implement `Atom`. Numbers of any type are read and written with [`NumberValue`](enum.NumberValue.html) instead.
+pub struct Number;
+
+unsafe impl UriBound for Number {
+    const URI: &'static [u8] = sys::LV2_ATOM__Number;
+}
+
+/// The value of a numeric atom of any type.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum NumberValue {
+    Int(i32),
+    Long(i64),
+    Float(f32),
+    Double(f64),
+}
+
+impl NumberValue {
+    /// Try to read a numeric atom.
+    ///
+    /// If the atom is neither an `Int`, `Long`, `Float` nor `Double`, `AtomError::TypeMismatch` is returned.
+    pub fn read(atom: UnidentifiedAtom, urids: &AtomURIDCollection) -> Result<Self, AtomError> {
+        atom.read(urids.int, ())
+            .map(NumberValue::Int)
+            .or_else(|_| atom.read(urids.long, ()).map(NumberValue::Long))
+            .or_else(|_| atom.read(urids.float, ()).map(NumberValue::Float))
+            .or_else(|_| atom.read(urids.double, ()).map(NumberValue::Double))
+    }
+
+    /// Write the number as an atom of the fitting type.
+    ///
+    /// If space is insufficient, `AtomError::OutOfSpace` is returned.
+    pub fn write<'a>(
+        self,
+        space: &mut dyn MutSpace<'a>,
+        urids: &AtomURIDCollection,
+    ) -> Result<(), AtomError> {
+        match self {
+            NumberValue::Int(value) => space.init(urids.int, value).map(|_| ()),
+            NumberValue::Long(value) => space.init(urids.long, value).map(|_| ()),
+            NumberValue::Float(value) => space.init(urids.float, value).map(|_| ()),
+            NumberValue::Double(value) => space.init(urids.double, value).map(|_| ()),
+        }
+    }
+
+    /// Convert the number to a `f64`.
+    ///
+    /// Large `Long` values may lose precision.
+    pub fn as_f64(self) -> f64 {
+        match self {
+            NumberValue::Int(value) => value as f64,
+            NumberValue::Long(value) => value as f64,
+            NumberValue::Float(value) => value as f64,
+            NumberValue::Double(value) => value,
+        }
+    }
+
+    /// Convert the number to a `i64`.
+    ///
+    /// Floating-point values are rounded towards zero and saturate at the bounds of `i64`.
+    pub fn as_i64(self) -> i64 {
+        match self {
+            NumberValue::Int(value) => value as i64,
+            NumberValue::Long(value) => value,
+            NumberValue::Float(value) => value as i64,
+            NumberValue::Double(value) => value as i64,
+        }
+    }
+}
+
 #[cfg(test)]
 #[cfg(feature = "host")]
 mod tests {
@@ -182,7 +255,7 @@ mod tests {
         let map = Map::new(&interface);
         let urid: URID<A> = map.map_type().unwrap();
 
-        let mut raw_space: Box<[u8]> = Box::new([0; 256]);
+        let mut raw_space = AlignedBuffer::new(256);
 
         // writing
         {
@@ -224,4 +297,52 @@ mod tests {
         test_scalar::<Bool>(1);
         test_scalar::<AtomURID>(URID::try_from(1).unwrap());
     }
+
+    #[test]
+    fn test_number_value() {
+        let mut mapper = Box::pin(HashURIDMapper::new());
+        let interface = mapper.as_mut().make_map_interface();
+        let map = Map::new(&interface);
+        let urids = crate::AtomURIDCollection::from_map(&map).unwrap();
+
+        let mut raw_space = AlignedBuffer::new(256);
+        let values = [
+            NumberValue::Int(-3),
+            NumberValue::Long(1 << 40),
+            NumberValue::Float(0.5),
+            NumberValue::Double(-2.75),
+        ];
+
+        // writing
+        {
+            let mut space = RootMutSpace::new(raw_space.as_mut());
+            for value in values.iter() {
+                value.write(&mut space, &urids).unwrap();
+            }
+            (&mut space as &mut dyn MutSpace)
+                .init(urids.bool, 1)
+                .unwrap();
+        }
+
+        // reading
+        {
+            let mut space = Space::from_slice(raw_space.as_ref());
+            for value in values.iter() {
+                let (atom, tail) = space.split_atom().unwrap();
+                assert_eq!(
+                    *value,
+                    NumberValue::read(UnidentifiedAtom::new(atom), &urids).unwrap()
+                );
+                space = tail;
+            }
+            let (atom, _) = space.split_atom().unwrap();
+            assert_eq!(
+                Err(AtomError::TypeMismatch),
+                NumberValue::read(UnidentifiedAtom::new(atom), &urids)
+            );
+        }
+
+        assert_eq!(-3.0, NumberValue::Int(-3).as_f64());
+        assert_eq!(-2, NumberValue::Double(-2.75).as_i64());
+    }
 }
@@ -51,7 +51,7 @@
 //!         // An event contains a timestamp and an atom.
 //!         let (timestamp, atom): (TimeStamp, UnidentifiedAtom) = event;
 //!         // If the read atom is a 32-bit integer...
-//!         if let Some(integer) = atom.read(urids.atom.int, ()) {
+//!         if let Ok(integer) = atom.read(urids.atom.int, ()) {
 //!             // Multiply it by two and write it to the sequence.
 //!             output_sequence.init(timestamp, urids.atom.int, integer * 2).unwrap();
 //!         } else {
@@ -62,12 +62,20 @@
 //! }
 //! ```
 //!
+//! If a plugin only works with time stamps in one unit, the time stamps can be converted with a [`Tempo`](struct.Tempo.html): [`SequenceIterator::convert`](struct.SequenceIterator.html#method.convert) converts the time stamps of the read events and [`SequenceWriter::set_tempo`](struct.SequenceWriter.html#method.set_tempo) lets the writer convert the time stamps of new events. The `lv2-time` crate creates the tempo from the position of the host.
+//!
+//! A single time-stamped atom outside of a sequence can be stored in an [`Event`](struct.Event.html) atom. Since it isn't part of a sequence, the unit of its time stamp has to be known when it's read.
+//!
 //! # Specification
 //!
 //! [http://lv2plug.in/ns/ext/atom/atom.html#Sequence](http://lv2plug.in/ns/ext/atom/atom.html#Sequence)
+//! [http://lv2plug.in/ns/ext/atom/atom.html#Event](http://lv2plug.in/ns/ext/atom/atom.html#Event)
+use crate::scalar::ScalarAtom;
 use crate::space::*;
 use crate::*;
 use core::prelude::*;
+use std::cmp::Ordering;
+use std::ops::Range;
 use sys::LV2_Atom_Event__bindgen_ty_1 as RawTimeStamp;
 use units::prelude::*;
 use urid::prelude::*;
@@ -90,20 +98,24 @@ where
     type WriteParameter = TimeStampURID;
     type WriteHandle = SequenceWriter<'a, 'b>;
 
-    fn read(body: Space, bpm_urid: URID<Beat>) -> Option<SequenceIterator> {
+    fn read(body: Space, bpm_urid: URID<Beat>) -> Result<SequenceIterator, AtomError> {
         let (header, body) = body.split_type::<sys::LV2_Atom_Sequence_Body>()?;
         let unit = if header.unit == bpm_urid {
             TimeStampUnit::BeatsPerMinute
         } else {
             TimeStampUnit::Frames
         };
-        Some(SequenceIterator { space: body, unit })
+        Ok(SequenceIterator {
+            space: body,
+            unit,
+            conversion: None,
+        })
     }
 
     fn init(
         mut frame: FramedMutSpace<'a, 'b>,
         unit: TimeStampURID,
-    ) -> Option<SequenceWriter<'a, 'b>> {
+    ) -> Result<SequenceWriter<'a, 'b>, AtomError> {
         {
             let frame = &mut frame as &mut dyn MutSpace;
             let header = sys::LV2_Atom_Sequence_Body {
@@ -115,10 +127,11 @@ where
             };
             frame.write(&header, true)?;
         }
-        Some(SequenceWriter {
+        Ok(SequenceWriter {
             frame,
             unit: unit.into(),
             last_stamp: None,
+            tempo: None,
         })
     }
 }
@@ -167,17 +180,198 @@ impl TimeStamp {
             _ => None,
         }
     }
+
+    /// Return the unit of the time stamp.
+    pub fn unit(self) -> TimeStampUnit {
+        match self {
+            Self::Frames(_) => TimeStampUnit::Frames,
+            Self::BeatsPerMinute(_) => TimeStampUnit::BeatsPerMinute,
+        }
+    }
+
+    /// Compare the time stamp with another one of the same unit.
+    ///
+    /// Beats are compared with `f64::total_cmp`. Time stamps of different units can't be compared without a tempo, which returns `None`.
+    fn compare(self, other: Self) -> Option<Ordering> {
+        match (self, other) {
+            (Self::Frames(frames), Self::Frames(other)) => Some(frames.cmp(&other)),
+            (Self::BeatsPerMinute(beats), Self::BeatsPerMinute(other)) => {
+                Some(beats.total_cmp(&other))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// An atom containing a single time-stamped atom.
+///
+/// The body of this atom has the same layout as an event in a sequence: A time stamp, followed by the atom. Since the unit of the time stamp isn't stored in the atom, it's the reading parameter.
+///
+/// [See also the module documentation.](index.html)
+pub struct Event;
+
+unsafe impl UriBound for Event {
+    const URI: &'static [u8] = sys::LV2_ATOM__Event;
+}
+
+impl<'a, 'b> Atom<'a, 'b> for Event
+where
+    'a: 'b,
+{
+    type ReadParameter = TimeStampUnit;
+    type ReadHandle = (TimeStamp, UnidentifiedAtom<'a>);
+    type WriteParameter = TimeStamp;
+    type WriteHandle = EventWriter<'a, 'b>;
+
+    fn read(
+        body: Space<'a>,
+        unit: TimeStampUnit,
+    ) -> Result<(TimeStamp, UnidentifiedAtom<'a>), AtomError> {
+        let (raw_stamp, body) = body.split_type::<RawTimeStamp>()?;
+        let stamp = match unit {
+            TimeStampUnit::Frames => unsafe { TimeStamp::Frames(raw_stamp.frames) },
+            TimeStampUnit::BeatsPerMinute => unsafe { TimeStamp::BeatsPerMinute(raw_stamp.beats) },
+        };
+        let (atom, _) = body.split_atom()?;
+        Ok((stamp, UnidentifiedAtom::new(atom)))
+    }
+
+    fn init(
+        mut frame: FramedMutSpace<'a, 'b>,
+        stamp: TimeStamp,
+    ) -> Result<EventWriter<'a, 'b>, AtomError> {
+        let raw_stamp = match stamp {
+            TimeStamp::Frames(frames) => RawTimeStamp { frames },
+            TimeStamp::BeatsPerMinute(beats) => RawTimeStamp { beats },
+        };
+        (&mut frame as &mut dyn MutSpace).write(&raw_stamp, true)?;
+        Ok(EventWriter { frame })
+    }
+}
+
+/// The writing handle for events.
+///
+/// An event contains exactly one atom, which is written with either [`init`](#method.init) or [`forward`](#method.forward).
+pub struct EventWriter<'a, 'b> {
+    frame: FramedMutSpace<'a, 'b>,
+}
+
+impl<'a, 'b> EventWriter<'a, 'b> {
+    /// Initialize the atom of the event.
+    ///
+    /// If space is insufficient, this method returns `AtomError::OutOfSpace`.
+    pub fn init<'c, A: Atom<'a, 'c>>(
+        &'c mut self,
+        urid: URID<A>,
+        parameter: A::WriteParameter,
+    ) -> Result<A::WriteHandle, AtomError> {
+        (&mut self.frame as &mut dyn MutSpace).init(urid, parameter)
+    }
+
+    /// Forward an unidentified atom to the event.
+    ///
+    /// If space is insufficient, this method returns `AtomError::OutOfSpace`.
+    pub fn forward(&mut self, atom: UnidentifiedAtom) -> Result<(), AtomError> {
+        let data = atom.space.data().ok_or(AtomError::Malformed)?;
+        self.frame.write_raw(data, true).map(|_| ())
+    }
 }
 
 /// An iterator over all events in a sequence.
+#[derive(Clone)]
 pub struct SequenceIterator<'a> {
     space: Space<'a>,
     unit: TimeStampUnit,
+    conversion: Option<(TimeStampUnit, Tempo)>,
 }
 
 impl<'a> SequenceIterator<'a> {
+    /// Return the unit of the returned time stamps.
+    ///
+    /// This is the unit of the sequence, unless the time stamps are converted to another unit.
     pub fn unit(&self) -> TimeStampUnit {
-        self.unit
+        match self.conversion {
+            Some((unit, _)) => unit,
+            None => self.unit,
+        }
+    }
+
+    /// Convert all time stamps to the given unit.
+    ///
+    /// Whether a sequence is measured in frames or in beats depends on the host, but most plugins only work with one of them. With this method, the iterator returns all time stamps in the unit the plugin expects, for example in frames:
+    ///
+    /// ```
+    /// use lv2_atom::prelude::*;
+    /// use lv2_atom::sequence::*;
+    /// use lv2_units::prelude::*;
+    ///
+    /// fn run(input: UnidentifiedAtom, urids: &AtomURIDCollection, units: &UnitURIDCollection, tempo: Tempo) {
+    ///     let events = input
+    ///         .read(urids.sequence, units.beat)
+    ///         .unwrap()
+    ///         .convert(TimeStampUnit::Frames, tempo);
+    ///
+    ///     for (stamp, atom) in events {
+    ///         let frame: i64 = stamp.as_frames().unwrap();
+    ///     }
+    /// }
+    /// ```
+    pub fn convert(self, unit: TimeStampUnit, tempo: Tempo) -> Self {
+        Self {
+            conversion: Some((unit, tempo)),
+            ..self
+        }
+    }
+
+    /// Split a block of audio at the time stamps of the events.
+    ///
+    /// The returned iterator yields [`SubBlock`](struct.SubBlock.html)s that cover the whole block. Each sub-block starts with an optional event, which should be applied before the audio of the sub-block is rendered, and ends at the time stamp of the next event. If multiple events have the same time stamp, the sub-blocks between them are empty.
+    ///
+    /// Time stamps before the previous event or the start of the block are moved to the previous event and time stamps after the end of the block are moved to the end. Time stamps in beats are relative to the start of the block and are converted to the nearest frame using the tempo. If the time stamps are measured in beats and no tempo is given, `AtomError::TimeStampUnitMismatch` is returned.
+    ///
+    /// ```
+    /// use lv2_atom::prelude::*;
+    /// use lv2_atom::sequence::*;
+    ///
+    /// fn render(
+    ///     events: SequenceIterator,
+    ///     input: &[f32],
+    ///     output: &mut [f32],
+    ///     gain: &mut f32,
+    ///     urids: &AtomURIDCollection,
+    /// ) -> Result<(), AtomError> {
+    ///     for block in events.split_block((input, output), None)? {
+    ///         // Apply the event before rendering the audio.
+    ///         if let Some((_, atom)) = block.event {
+    ///             if let Ok(new_gain) = atom.read(urids.float, ()) {
+    ///                 *gain = new_gain;
+    ///             }
+    ///         }
+    ///
+    ///         let (input, output) = block.audio;
+    ///         for (input, output) in input.iter().zip(output.iter_mut()) {
+    ///             *output = *input * *gain;
+    ///         }
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn split_block<B: AudioBlock>(
+        self,
+        audio: B,
+        tempo: Option<Tempo>,
+    ) -> Result<SubBlockIterator<'a, B>, AtomError> {
+        if self.unit() == TimeStampUnit::BeatsPerMinute && tempo.is_none() {
+            return Err(AtomError::TimeStampUnitMismatch);
+        }
+        Ok(SubBlockIterator {
+            length: audio.frames(),
+            events: self,
+            audio: Some(audio),
+            position: 0,
+            tempo,
+            next_event: None,
+        })
     }
 }
 
@@ -185,80 +379,576 @@ impl<'a> Iterator for SequenceIterator<'a> {
     type Item = (TimeStamp, UnidentifiedAtom<'a>);
 
     fn next(&mut self) -> Option<(TimeStamp, UnidentifiedAtom<'a>)> {
-        let (raw_stamp, space) = self.space.split_type::<RawTimeStamp>()?;
+        let (raw_stamp, space) = self.space.split_type::<RawTimeStamp>().ok()?;
         let stamp = match self.unit {
             TimeStampUnit::Frames => unsafe { TimeStamp::Frames(raw_stamp.frames) },
             TimeStampUnit::BeatsPerMinute => unsafe { TimeStamp::BeatsPerMinute(raw_stamp.beats) },
         };
-        let (atom, space) = space.split_atom()?;
+        let stamp = match self.conversion {
+            Some((unit, tempo)) => tempo.convert(stamp, unit),
+            None => stamp,
+        };
+        let (atom, space) = space.split_atom().ok()?;
         self.space = space;
         Some((stamp, UnidentifiedAtom::new(atom)))
     }
 }
 
+/// Audio buffers that can be split into sub-blocks.
+///
+/// This trait is implemented for slices, mutable slices and tuples of them, which allows you to split all audio ports of a plugin at once.
+pub trait AudioBlock: Sized {
+    /// The number of frames in the buffers.
+    ///
+    /// If the buffers have different lengths, this is the length of the shortest one.
+    fn frames(&self) -> usize;
+
+    /// Split the buffers into the frames before and after the given frame.
+    fn split_at_frame(self, frame: usize) -> (Self, Self);
+}
+
+impl<T> AudioBlock for &[T] {
+    fn frames(&self) -> usize {
+        self.len()
+    }
+
+    fn split_at_frame(self, frame: usize) -> (Self, Self) {
+        self.split_at(frame.min(self.len()))
+    }
+}
+
+impl<T> AudioBlock for &mut [T] {
+    fn frames(&self) -> usize {
+        self.len()
+    }
+
+    fn split_at_frame(self, frame: usize) -> (Self, Self) {
+        let frame = frame.min(self.len());
+        self.split_at_mut(frame)
+    }
+}
+
+macro_rules! make_tuple_audio_block {
+    ($($buffer:ident),*) => {
+        #[allow(non_snake_case)]
+        impl<$($buffer: AudioBlock),*> AudioBlock for ($($buffer,)*) {
+            fn frames(&self) -> usize {
+                let ($($buffer,)*) = self;
+                let frames = usize::MAX;
+                $(let frames = frames.min($buffer.frames());)*
+                frames
+            }
+
+            fn split_at_frame(self, frame: usize) -> (Self, Self) {
+                let ($($buffer,)*) = self;
+                $(let $buffer = $buffer.split_at_frame(frame);)*
+                (($($buffer.0,)*), ($($buffer.1,)*))
+            }
+        }
+    };
+}
+
+make_tuple_audio_block!(A);
+make_tuple_audio_block!(A, B);
+make_tuple_audio_block!(A, B, C);
+make_tuple_audio_block!(A, B, C, D);
+make_tuple_audio_block!(A, B, C, D, E);
+make_tuple_audio_block!(A, B, C, D, E, F);
+
+/// The tempo that is used to convert time stamps between frames and beats.
+///
+/// Time stamps in beats are relative to the start of the current block, just like time stamps in frames. The tempo is usually taken from the `time:Position` objects sent by the host.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Tempo {
+    /// The tempo in beats per minute.
+    ///
+    /// It has to be positive and finite, otherwise the converted time stamps are meaningless.
+    pub beats_per_minute: f64,
+    /// The sample rate of the plugin in frames per second.
+    ///
+    /// It has to be positive and finite, like the beats per minute.
+    pub sample_rate: f64,
+}
+
+impl Tempo {
+    /// The number of frames in one beat.
+    pub fn frames_per_beat(&self) -> f64 {
+        self.sample_rate * 60.0 / self.beats_per_minute
+    }
+
+    /// Return the frame that is nearest to a time stamp in beats.
+    ///
+    /// This is the rounding rule for all conversions from beats to frames.
+    fn nearest_frame(&self, beats: f64) -> f64 {
+        (beats * self.frames_per_beat()).round()
+    }
+
+    /// Convert a time stamp to the given unit.
+    ///
+    /// Beats are converted to the nearest frame.
+    pub fn convert(&self, stamp: TimeStamp, unit: TimeStampUnit) -> TimeStamp {
+        match (stamp, unit) {
+            (TimeStamp::BeatsPerMinute(beats), TimeStampUnit::Frames) => {
+                TimeStamp::Frames(self.nearest_frame(beats) as i64)
+            }
+            (TimeStamp::Frames(frames), TimeStampUnit::BeatsPerMinute) => {
+                TimeStamp::BeatsPerMinute(frames as f64 / self.frames_per_beat())
+            }
+            (stamp, _) => stamp,
+        }
+    }
+}
+
+/// A part of an audio block, created by [`SequenceIterator::split_block`](struct.SequenceIterator.html#method.split_block).
+pub struct SubBlock<'a, B> {
+    /// The frames of the sub-block, relative to the start of the whole block.
+    pub range: Range<usize>,
+    /// The audio buffers of the sub-block.
+    pub audio: B,
+    /// The event that has to be applied before the sub-block is rendered.
+    pub event: Option<(TimeStamp, UnidentifiedAtom<'a>)>,
+}
+
+/// An iterator over the sub-blocks of an audio block.
+///
+/// [See also `SequenceIterator::split_block`.](struct.SequenceIterator.html#method.split_block)
+pub struct SubBlockIterator<'a, B> {
+    events: SequenceIterator<'a>,
+    audio: Option<B>,
+    position: usize,
+    length: usize,
+    tempo: Option<Tempo>,
+    next_event: Option<(TimeStamp, UnidentifiedAtom<'a>)>,
+}
+
+impl<'a, B> SubBlockIterator<'a, B> {
+    /// Convert a time stamp to a frame within the rest of the block.
+    fn frame(&self, stamp: TimeStamp) -> usize {
+        let frame = match stamp {
+            TimeStamp::Frames(frames) => frames as f64,
+            // `split_block` only accepts time stamps in beats together with a tempo.
+            TimeStamp::BeatsPerMinute(beats) => self
+                .tempo
+                .map_or(f64::NAN, |tempo| tempo.nearest_frame(beats)),
+        };
+        // `max` ignores NaN, which moves events with an invalid time stamp to the current position.
+        frame.max(self.position as f64).min(self.length as f64) as usize
+    }
+}
+
+impl<'a, B: AudioBlock> Iterator for SubBlockIterator<'a, B> {
+    type Item = SubBlock<'a, B>;
+
+    fn next(&mut self) -> Option<SubBlock<'a, B>> {
+        loop {
+            let audio = self.audio.take()?;
+            let event = self.next_event.take();
+            let end = match self.events.next() {
+                Some(next_event) => {
+                    let end = self.frame(next_event.0);
+                    self.next_event = Some(next_event);
+                    end
+                }
+                None => self.length,
+            };
+
+            let (audio, rest) = audio.split_at_frame(end - self.position);
+            let range = self.position..end;
+            self.position = end;
+            if self.next_event.is_some() {
+                self.audio = Some(rest);
+            }
+
+            // The block before the first event is skipped if the first event is at the start.
+            if event.is_some() || !range.is_empty() {
+                return Some(SubBlock {
+                    range,
+                    audio,
+                    event,
+                });
+            }
+        }
+    }
+}
+
+/// An iterator that merges multiple sequences in the order of their time stamps.
+///
+/// In every step, the events at the front of all sequences are compared and the earliest one is returned. If multiple events have the same time stamp, the event of the sequence that comes first in the slice is returned first. Nothing is allocated, but every step looks at all sequences, which is fine for the few inputs a plugin usually has.
+///
+/// The sequences should use the same time stamp unit. Time stamps of different units aren't compared, and if the front events of two sequences have different units, the event of the sequence that comes first in the slice is returned first. If the sequences themselves aren't ordered, the merged events aren't ordered either.
+pub struct MergedSequences<'a, 'i> {
+    sequences: &'i mut [SequenceIterator<'a>],
+}
+
+impl<'a, 'i> MergedSequences<'a, 'i> {
+    /// Create a new iterator that merges the given sequences.
+    pub fn new(sequences: &'i mut [SequenceIterator<'a>]) -> Self {
+        Self { sequences }
+    }
+}
+
+impl<'a, 'i> Iterator for MergedSequences<'a, 'i> {
+    type Item = (TimeStamp, UnidentifiedAtom<'a>);
+
+    fn next(&mut self) -> Option<(TimeStamp, UnidentifiedAtom<'a>)> {
+        let mut earliest: Option<(usize, TimeStamp)> = None;
+        for (index, sequence) in self.sequences.iter().enumerate() {
+            // Iterators over sequences are cheap to clone, which makes peeking free.
+            if let Some((stamp, _)) = sequence.clone().next() {
+                if earliest
+                    .map(|(_, earliest)| stamp.compare(earliest) == Some(Ordering::Less))
+                    .unwrap_or(true)
+                {
+                    earliest = Some((index, stamp));
+                }
+            }
+        }
+        let (index, _) = earliest?;
+        self.sequences[index].next()
+    }
+}
+
 /// The writing handle for sequences.
 pub struct SequenceWriter<'a, 'b> {
     frame: FramedMutSpace<'a, 'b>,
     unit: TimeStampUnit,
     last_stamp: Option<TimeStamp>,
+    tempo: Option<Tempo>,
 }
 
 impl<'a, 'b> SequenceWriter<'a, 'b> {
+    /// Set the tempo that is used to convert time stamps to the unit of the sequence.
+    ///
+    /// By default, time stamps that are measured in another unit than the sequence are rejected with `AtomError::TimeStampUnitMismatch`. Once a tempo is set, they are converted instead, which allows you to write a sequence in frames from logic that works in beats and vice versa.
+    pub fn set_tempo(&mut self, tempo: Tempo) {
+        self.tempo = Some(tempo);
+    }
+
+    /// Convert a time stamp to the unit of the sequence, if a tempo is set.
+    fn convert(&self, stamp: TimeStamp) -> TimeStamp {
+        match self.tempo {
+            Some(tempo) => tempo.convert(stamp, self.unit),
+            None => stamp,
+        }
+    }
+
     /// Write out the time stamp and update `last_stamp`.
     ///
-    /// This method returns `Ǹone` if:
+    /// This method returns an error if:
     /// * The time stamp is not measured in our unit.
+    /// * The time stamp is not a number.
     /// * The last time stamp is younger than the time stamp.
     /// * Space is insufficient.
-    fn write_time_stamp(&mut self, stamp: TimeStamp) -> Option<()> {
+    fn write_time_stamp(&mut self, stamp: TimeStamp) -> Result<(), AtomError> {
+        let stamp = self.convert(stamp);
         let raw_stamp = match self.unit {
             TimeStampUnit::Frames => {
-                let frames = stamp.as_frames()?;
+                let frames = stamp.as_frames().ok_or(AtomError::TimeStampUnitMismatch)?;
                 if let Some(last_stamp) = self.last_stamp {
                     if last_stamp.as_frames().unwrap() > frames {
-                        return None;
+                        return Err(AtomError::NonMonotonicTimeStamp);
                     }
                 }
                 RawTimeStamp { frames }
             }
             TimeStampUnit::BeatsPerMinute => {
-                let beats = stamp.as_bpm()?;
+                let beats = stamp.as_bpm().ok_or(AtomError::TimeStampUnitMismatch)?;
+                if beats.is_nan() {
+                    return Err(AtomError::InvalidTimeStamp);
+                }
                 if let Some(last_stamp) = self.last_stamp {
                     if last_stamp.as_bpm().unwrap() > beats {
-                        return None;
+                        return Err(AtomError::NonMonotonicTimeStamp);
                     }
                 }
                 RawTimeStamp { beats }
             }
         };
+        (&mut self.frame as &mut dyn MutSpace).write(&raw_stamp, true)?;
         self.last_stamp = Some(stamp);
-        (&mut self.frame as &mut dyn MutSpace)
-            .write(&raw_stamp, true)
-            .map(|_| ())
+        Ok(())
+    }
+
+    /// Roll back the event that has been started at the checkpoint.
+    ///
+    /// # Safety
+    ///
+    /// There may be no references to the event that is rolled back.
+    unsafe fn rollback(&mut self, checkpoint: Checkpoint, last_stamp: Option<TimeStamp>) {
+        self.frame.rollback(checkpoint);
+        self.last_stamp = last_stamp;
     }
 
     /// Initialize an event.
     ///
-    /// The time stamp has to be measured in the unit of the sequence. If the time stamp is measured in the wrong unit, is younger than the last written time stamp or space is insufficient, this method returns `None`.
+    /// The time stamp has to be measured in the unit of the sequence, unless a tempo has been set with [`set_tempo`](#method.set_tempo). If the time stamp is measured in the wrong unit, is younger than the last written time stamp or space is insufficient, this method returns the corresponding error. In this case, the event is removed again and the sequence stays valid.
     pub fn init<'c, A: Atom<'a, 'c>>(
         &'c mut self,
         stamp: TimeStamp,
         urid: URID<A>,
         parameter: A::WriteParameter,
-    ) -> Option<A::WriteHandle> {
-        self.write_time_stamp(stamp)?;
-        (&mut self.frame as &mut dyn MutSpace).init(urid, parameter)
+    ) -> Result<A::WriteHandle, AtomError> {
+        let checkpoint = self.frame.checkpoint();
+        let last_stamp = self.last_stamp;
+        if let Err(error) = self.write_time_stamp(stamp) {
+            unsafe { self.rollback(checkpoint, last_stamp) };
+            return Err(error);
+        }
+        // Borrowing the writer through a pointer, like in `MutSpace::init`.
+        let this: *mut Self = self;
+        let result = (unsafe { &mut (*this).frame } as &mut dyn MutSpace).init(urid, parameter);
+        if result.is_err() {
+            unsafe { (*this).rollback(checkpoint, last_stamp) };
+        }
+        result
     }
 
     /// Forward an unidentified atom to the sequence.
     ///
     /// If your cannot identify the type of the atom but have to write it, you can simply forward it.
     ///
-    /// The time stamp has to be measured in the unit of the sequence. If the time stamp is measured in the wrong unit, is younger than the last written time stamp or space is insufficient, this method returns `None`.
-    pub fn forward(&mut self, stamp: TimeStamp, atom: UnidentifiedAtom) -> Option<()> {
-        let data = atom.space.data()?;
-        self.write_time_stamp(stamp)?;
-        self.frame.write_raw(data, true).map(|_| ())
+    /// The time stamp has to be measured in the unit of the sequence, unless a tempo has been set with [`set_tempo`](#method.set_tempo). If the time stamp is measured in the wrong unit, is younger than the last written time stamp or space is insufficient, this method returns the corresponding error.
+    pub fn forward(&mut self, stamp: TimeStamp, atom: UnidentifiedAtom) -> Result<(), AtomError> {
+        let data = atom.space.data().ok_or(AtomError::Malformed)?;
+        let checkpoint = self.frame.checkpoint();
+        let last_stamp = self.last_stamp;
+        let result = self
+            .write_time_stamp(stamp)
+            .and_then(|_| self.frame.write_raw(data, true).map(|_| ()));
+        if result.is_err() {
+            unsafe { self.rollback(checkpoint, last_stamp) };
+        }
+        result
+    }
+
+    /// Write an event in several steps, or nothing at all.
+    ///
+    /// The `write` function may initialize events with this writer and write their contents, for example the properties of an object. If it returns an error, everything it has written is removed again and the sequence stays valid.
+    ///
+    /// Since the removed space is reused by the following events, `write` has to accept a writer for any lifetime of the space. This way, it can't keep references to the data it has written.
+    pub fn transaction<F, R>(&mut self, write: F) -> Result<R, AtomError>
+    where
+        F: for<'x, 'y> FnOnce(&mut SequenceWriter<'x, 'y>) -> Result<R, AtomError>,
+    {
+        let checkpoint = self.frame.checkpoint();
+        let last_stamp = self.last_stamp;
+        let result = write(self);
+        if result.is_err() {
+            // `write` has returned, see `MutSpace::rollback`.
+            unsafe { self.rollback(checkpoint, last_stamp) };
+        }
+        result
+    }
+
+    /// Merge multiple sequences and forward their events to this sequence.
+    ///
+    /// The events are written in the order of their time stamps, as described in [`MergedSequences`](struct.MergedSequences.html). Events that are older than the last written event are written with the time stamp of the last event, which keeps the sequence monotonic. Nothing is allocated.
+    ///
+    /// If a time stamp is measured in the wrong unit or space is insufficient, this method returns the corresponding error. The events written up to this point stay in the sequence.
+    pub fn merge_from(&mut self, sequences: &mut [SequenceIterator]) -> Result<(), AtomError> {
+        for (stamp, atom) in MergedSequences::new(sequences) {
+            self.forward(self.monotonic_stamp(stamp), atom)?;
+        }
+        Ok(())
+    }
+
+    /// Move a time stamp that is older than the last written one to the last one.
+    fn monotonic_stamp(&self, stamp: TimeStamp) -> TimeStamp {
+        match (self.convert(stamp), self.last_stamp) {
+            (TimeStamp::Frames(frames), Some(TimeStamp::Frames(last))) if frames < last => {
+                TimeStamp::Frames(last)
+            }
+            (TimeStamp::BeatsPerMinute(beats), Some(TimeStamp::BeatsPerMinute(last)))
+                if beats < last =>
+            {
+                TimeStamp::BeatsPerMinute(last)
+            }
+            (stamp, _) => stamp,
+        }
+    }
+}
+
+/// A staged event, stored in the data buffer of a `SequenceStage`.
+#[derive(Clone, Copy)]
+struct StagedEvent {
+    stamp: TimeStamp,
+    /// The number of events that were staged before this one, to keep the order of equal time stamps.
+    index: usize,
+    offset: usize,
+    size: usize,
+}
+
+/// A fixed-capacity buffer for events that are created out of order.
+///
+/// A [`SequenceWriter`](struct.SequenceWriter.html) only accepts events in the order of their time stamps, but generators like arpeggiators or delays often compute them in a different order. The stage accepts events in any order and [`flush`](#method.flush) writes them to a sequence, sorted by their time stamps. Events with equal time stamps keep the order in which they were staged.
+///
+/// All memory is allocated when the stage is created, which makes staging and flushing events realtime-safe. If the stage is full, either because it contains the maximum number of events or because the atom doesn't fit into the data buffer, the new event is rejected with `AtomError::OutOfSpace` and counted as [`dropped`](#method.dropped). Events that are already staged are never dropped.
+///
+/// All staged time stamps have to be measured in the unit of the first staged event, since time stamps of different units can't be sorted. Other time stamps are rejected with `AtomError::TimeStampUnitMismatch` and time stamps in beats that are not a number with `AtomError::InvalidTimeStamp`.
+///
+/// ```
+/// use lv2_atom::prelude::*;
+/// use lv2_atom::sequence::*;
+///
+/// struct Arpeggiator {
+///     stage: SequenceStage,
+/// }
+///
+/// impl Arpeggiator {
+///     fn new() -> Self {
+///         // Allocate the stage while the plugin is instantiated.
+///         Self {
+///             stage: SequenceStage::new(64, 4096),
+///         }
+///     }
+///
+///     fn run(&mut self, output: &mut SequenceWriter, urids: &AtomURIDCollection) {
+///         // The events are staged in any order...
+///         for (frame, note) in [(32, 64), (0, 60), (16, 62)].iter() {
+///             let _ = self.stage.init(TimeStamp::Frames(*frame), urids.int, *note);
+///         }
+///         // ... and written in the order of their time stamps.
+///         self.stage.flush(output).unwrap();
+///     }
+/// }
+/// ```
+pub struct SequenceStage {
+    data: AlignedBuffer,
+    allocated: usize,
+    events: Vec<StagedEvent>,
+    max_events: usize,
+    dropped: usize,
+}
+
+impl SequenceStage {
+    /// Create a new stage for up to `max_events` events with `data_size` bytes for their atoms.
+    ///
+    /// Every atom is padded to a multiple of 64 bits.
+    pub fn new(max_events: usize, data_size: usize) -> Self {
+        Self {
+            data: AlignedBuffer::new(data_size),
+            allocated: 0,
+            events: Vec::with_capacity(max_events),
+            max_events,
+            dropped: 0,
+        }
+    }
+
+    /// Return the number of staged events.
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    /// Return `true` if no events are staged.
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    /// Return the number of events that have been rejected since the stage was last flushed or cleared.
+    pub fn dropped(&self) -> usize {
+        self.dropped
+    }
+
+    /// Remove all staged events and reset the number of dropped events.
+    pub fn clear(&mut self) {
+        self.events.clear();
+        self.allocated = 0;
+        self.dropped = 0;
+    }
+
+    /// Stage an event with an atom that is written by the given function.
+    ///
+    /// The function receives the space for the atom and has to write exactly one atom to it. If the stage is full, the time stamp is invalid or the function returns an error, the event is rejected and counted as dropped.
+    pub fn write<F>(&mut self, stamp: TimeStamp, write: F) -> Result<(), AtomError>
+    where
+        F: FnOnce(&mut dyn MutSpace) -> Result<(), AtomError>,
+    {
+        let result = self.try_write(stamp, write);
+        if result.is_err() {
+            self.dropped += 1;
+        }
+        result
+    }
+
+    fn try_write<F>(&mut self, stamp: TimeStamp, write: F) -> Result<(), AtomError>
+    where
+        F: FnOnce(&mut dyn MutSpace) -> Result<(), AtomError>,
+    {
+        if let TimeStamp::BeatsPerMinute(beats) = stamp {
+            if beats.is_nan() {
+                return Err(AtomError::InvalidTimeStamp);
+            }
+        }
+        if let Some(first) = self.events.first() {
+            if first.stamp.unit() != stamp.unit() {
+                return Err(AtomError::TimeStampUnitMismatch);
+            }
+        }
+        if self.events.len() >= self.max_events {
+            return Err(AtomError::OutOfSpace);
+        }
+        let offset = (self.allocated + 7) & !7;
+        if offset > self.data.len() {
+            return Err(AtomError::OutOfSpace);
+        }
+
+        let mut space = RootMutSpace::new(&mut self.data[offset..]);
+        write(&mut space as &mut dyn MutSpace)?;
+        let written = space.checkpoint().allocated();
+        let (atom, _) = Space::from_slice(&self.data[offset..offset + written]).split_atom()?;
+        let size = atom.data().map(<[u8]>::len).unwrap_or(0);
+
+        self.events.push(StagedEvent {
+            stamp,
+            index: self.events.len(),
+            offset,
+            size,
+        });
+        self.allocated = offset + size;
+        Ok(())
+    }
+
+    /// Stage an event with a scalar atom.
+    ///
+    /// This is a shortcut for [`write`](#method.write), which has to be used for all other atom types.
+    pub fn init<A: ScalarAtom>(
+        &mut self,
+        stamp: TimeStamp,
+        urid: URID<A>,
+        value: A::InternalType,
+    ) -> Result<(), AtomError> {
+        self.write(stamp, |space| space.init(urid, value).map(|_| ()))
+    }
+
+    /// Stage an event with a copy of an unidentified atom.
+    pub fn forward(&mut self, stamp: TimeStamp, atom: UnidentifiedAtom) -> Result<(), AtomError> {
+        let data = atom.space.data().ok_or(AtomError::Malformed)?;
+        self.write(stamp, |space| space.write_raw(data, true).map(|_| ()))
+    }
+
+    /// Write all staged events to the sequence, sorted by their time stamps, and clear the stage.
+    ///
+    /// Events that are older than the last event of the sequence are written with the time stamp of the last event. If a time stamp is measured in the wrong unit or space is insufficient, the error is returned and the remaining events are discarded. The stage is cleared in any case.
+    pub fn flush(&mut self, writer: &mut SequenceWriter) -> Result<(), AtomError> {
+        self.events.sort_unstable_by(|a, b| {
+            // `try_write` only stages time stamps of a single unit, which can always be compared.
+            a.stamp
+                .compare(b.stamp)
+                .unwrap_or(Ordering::Equal)
+                .then(a.index.cmp(&b.index))
+        });
+
+        let mut result = Ok(());
+        for event in self.events.iter() {
+            let atom = Space::from_slice(&self.data[event.offset..event.offset + event.size]);
+            let stamp = writer.monotonic_stamp(event.stamp);
+            result = writer.forward(stamp, UnidentifiedAtom::new(atom));
+            if result.is_err() {
+                break;
+            }
+        }
+        self.clear();
+        result
     }
 }
 
@@ -283,7 +973,7 @@ mod tests {
         let map = Map::new(&interface);
         let urids = TestURIDCollection::from_map(&map).unwrap();
 
-        let mut raw_space: Box<[u8]> = Box::new([0; 256]);
+        let mut raw_space = AlignedBuffer::new(256);
 
         // writing
         {
@@ -365,4 +1055,637 @@ mod tests {
             assert!(reader.next().is_none());
         }
     }
+
+    #[test]
+    fn test_sequence_errors() {
+        let mut mapper = Box::pin(HashURIDMapper::new());
+        let interface = mapper.as_mut().make_map_interface();
+        let map = Map::new(&interface);
+        let urids = TestURIDCollection::from_map(&map).unwrap();
+
+        let mut raw_space = AlignedBuffer::new(64);
+        let mut space = RootMutSpace::new(raw_space.as_mut());
+        let mut writer = (&mut space as &mut dyn MutSpace)
+            .init(
+                urids.atom.sequence,
+                TimeStampURID::Frames(urids.units.frame),
+            )
+            .unwrap();
+
+        writer
+            .init(TimeStamp::Frames(1), urids.atom.int, 42)
+            .unwrap();
+        assert_eq!(
+            Err(AtomError::NonMonotonicTimeStamp),
+            writer
+                .init(TimeStamp::Frames(0), urids.atom.int, 42)
+                .map(|_| ())
+        );
+        assert_eq!(
+            Err(AtomError::TimeStampUnitMismatch),
+            writer
+                .init(TimeStamp::BeatsPerMinute(1.0), urids.atom.int, 42)
+                .map(|_| ())
+        );
+        assert_eq!(
+            Err(AtomError::OutOfSpace),
+            writer
+                .init(TimeStamp::Frames(2), urids.atom.vector(), urids.atom.int)
+                .and_then(|mut vector| vector.append(&[0; 8]).map(|_| ()))
+        );
+    }
+
+    #[test]
+    fn test_atom_buf() {
+        let mut mapper = Box::pin(HashURIDMapper::new());
+        let interface = mapper.as_mut().make_map_interface();
+        let map = Map::new(&interface);
+        let urids = TestURIDCollection::from_map(&map).unwrap();
+
+        let mut raw_space = AlignedBuffer::new(256);
+
+        // writing
+        {
+            let mut space = RootMutSpace::new(raw_space.as_mut());
+            let mut writer = (&mut space as &mut dyn MutSpace)
+                .init(
+                    urids.atom.sequence,
+                    TimeStampURID::Frames(urids.units.frame),
+                )
+                .unwrap();
+            writer
+                .init(TimeStamp::Frames(0), urids.atom.int, 42)
+                .unwrap();
+            writer
+                .init(TimeStamp::Frames(1), urids.atom.string, ())
+                .unwrap()
+                .append("Hello")
+                .unwrap();
+        }
+
+        // copying
+        let buffers: Vec<AtomBuf> = {
+            let (body, _) = raw_space
+                .space()
+                .split_atom_body(urids.atom.sequence)
+                .unwrap();
+            Sequence::read(body, urids.units.beat)
+                .unwrap()
+                .map(|(_, atom)| AtomBuf::new(atom).unwrap())
+                .collect()
+        };
+        assert_eq!(buffers.len(), 2);
+        assert_eq!(buffers[0].type_urid(), urids.atom.int.into_general());
+        assert_eq!(buffers[1].type_urid(), urids.atom.string.into_general());
+        assert_eq!(buffers[0].read(urids.atom.int, ()).unwrap(), 42);
+        assert_eq!(buffers[1].read(urids.atom.string, ()).unwrap(), "Hello");
+        assert_eq!(buffers[0].body().data().unwrap(), &42i32.to_ne_bytes()[..]);
+        assert!(buffers[0].read(urids.atom.long, ()).is_err());
+
+        let set: std::collections::HashSet<AtomBuf> = buffers.iter().cloned().collect();
+        assert!(set.contains(&buffers[0]));
+        assert_ne!(buffers[0], buffers[1]);
+
+        // forwarding
+        let mut raw_space = AlignedBuffer::new(256);
+        {
+            let mut space = RootMutSpace::new(raw_space.as_mut());
+            let mut writer = (&mut space as &mut dyn MutSpace)
+                .init(
+                    urids.atom.sequence,
+                    TimeStampURID::Frames(urids.units.frame),
+                )
+                .unwrap();
+            for (frame, buffer) in buffers.iter().enumerate() {
+                writer
+                    .forward(TimeStamp::Frames(frame as i64), buffer.as_atom())
+                    .unwrap();
+            }
+        }
+
+        // reading
+        {
+            let (body, _) = raw_space
+                .space()
+                .split_atom_body(urids.atom.sequence)
+                .unwrap();
+            let mut reader = Sequence::read(body, urids.units.beat).unwrap();
+            let (_, atom) = reader.next().unwrap();
+            assert_eq!(AtomBuf::new(atom).unwrap(), buffers[0]);
+            let (_, atom) = reader.next().unwrap();
+            assert_eq!(atom.read(urids.atom.string, ()).unwrap(), "Hello");
+            assert!(reader.next().is_none());
+        }
+    }
+
+    #[test]
+    fn test_sequence_rollback() {
+        let mut mapper = Box::pin(HashURIDMapper::new());
+        let interface = mapper.as_mut().make_map_interface();
+        let map = Map::new(&interface);
+        let urids = TestURIDCollection::from_map(&map).unwrap();
+
+        let mut raw_space = AlignedBuffer::new(48);
+
+        // writing
+        {
+            let mut space = RootMutSpace::new(raw_space.as_mut());
+            let mut writer = (&mut space as &mut dyn MutSpace)
+                .init(
+                    urids.atom.sequence,
+                    TimeStampURID::Frames(urids.units.frame),
+                )
+                .unwrap();
+            writer
+                .init(TimeStamp::Frames(1), urids.atom.int, 42)
+                .unwrap();
+            // The time stamp fits, but the atom header doesn't.
+            assert_eq!(
+                Err(AtomError::OutOfSpace),
+                writer
+                    .init(TimeStamp::Frames(5), urids.atom.int, 17)
+                    .map(|_| ())
+            );
+            assert_eq!(writer.last_stamp.unwrap().as_frames(), Some(1));
+        }
+
+        // verifying
+        {
+            let atom = unsafe { &*(raw_space.as_ptr() as *const sys::LV2_Atom) };
+            assert_eq!(
+                atom.size as usize,
+                size_of::<sys::LV2_Atom_Sequence_Body>()
+                    + size_of::<RawTimeStamp>()
+                    + size_of::<sys::LV2_Atom_Int>()
+            );
+        }
+
+        // reading
+        {
+            let (body, _) = Space::from_slice(raw_space.as_ref())
+                .split_atom_body(urids.atom.sequence)
+                .unwrap();
+            let mut reader = Sequence::read(body, urids.units.beat).unwrap();
+            let (stamp, atom) = reader.next().unwrap();
+            assert_eq!(stamp.as_frames(), Some(1));
+            assert_eq!(atom.read::<Int>(urids.atom.int, ()).unwrap(), 42);
+            assert!(reader.next().is_none());
+        }
+
+        // A failed transaction removes the events it has written.
+        let mut raw_space = AlignedBuffer::new(64);
+        let mut space = RootMutSpace::new(raw_space.as_mut());
+        let mut writer = (&mut space as &mut dyn MutSpace)
+            .init(
+                urids.atom.sequence,
+                TimeStampURID::Frames(urids.units.frame),
+            )
+            .unwrap();
+        assert_eq!(
+            Err(AtomError::Malformed),
+            writer.transaction(|writer| {
+                writer.init(TimeStamp::Frames(3), urids.atom.int, 17)?;
+                Err::<(), _>(AtomError::Malformed)
+            })
+        );
+        assert!(writer.last_stamp.is_none());
+        assert_eq!(
+            writer.frame.checkpoint().allocated(),
+            size_of::<sys::LV2_Atom_Sequence>()
+        );
+    }
+
+    #[test]
+    fn test_event() {
+        let mut mapper = Box::pin(HashURIDMapper::new());
+        let interface = mapper.as_mut().make_map_interface();
+        let map = Map::new(&interface);
+        let urids = TestURIDCollection::from_map(&map).unwrap();
+
+        let mut raw_space = AlignedBuffer::new(256);
+
+        // writing
+        {
+            let mut space = RootMutSpace::new(raw_space.as_mut());
+            let mut writer = (&mut space as &mut dyn MutSpace)
+                .init(urids.atom.event, TimeStamp::BeatsPerMinute(1.5))
+                .unwrap();
+            writer.init(urids.atom.int, 42).unwrap();
+        }
+
+        // reading
+        {
+            let space = Space::from_slice(raw_space.as_ref());
+            let (body, _) = space.split_atom_body(urids.atom.event).unwrap();
+            let (stamp, atom) = Event::read(body, TimeStampUnit::BeatsPerMinute).unwrap();
+            assert_eq!(Some(1.5), stamp.as_bpm());
+            assert_eq!(42, atom.read(urids.atom.int, ()).unwrap());
+        }
+    }
+
+    fn read_sequence<'a>(
+        raw_space: &'a AlignedBuffer,
+        urids: &TestURIDCollection,
+    ) -> SequenceIterator<'a> {
+        let (body, _) = raw_space
+            .space()
+            .split_atom_body(urids.atom.sequence)
+            .unwrap();
+        Sequence::read(body, urids.units.beat).unwrap()
+    }
+
+    #[test]
+    fn test_merge() {
+        let mut mapper = Box::pin(HashURIDMapper::new());
+        let interface = mapper.as_mut().make_map_interface();
+        let map = Map::new(&interface);
+        let urids = TestURIDCollection::from_map(&map).unwrap();
+
+        let mut first_space = AlignedBuffer::new(256);
+        let mut second_space = AlignedBuffer::new(256);
+        for (raw_space, events) in [
+            (&mut first_space, &[(0, 0), (2, 1), (5, 2)][..]),
+            (&mut second_space, &[(2, 3), (3, 4)][..]),
+        ]
+        .iter_mut()
+        {
+            let mut space = RootMutSpace::new(raw_space.as_mut());
+            let mut writer = (&mut space as &mut dyn MutSpace)
+                .init(
+                    urids.atom.sequence,
+                    TimeStampURID::Frames(urids.units.frame),
+                )
+                .unwrap();
+            for (frame, value) in events.iter() {
+                writer
+                    .init(TimeStamp::Frames(*frame), urids.atom.int, *value)
+                    .unwrap();
+            }
+        }
+        let events = |iterator: &mut dyn Iterator<Item = (TimeStamp, UnidentifiedAtom)>| {
+            iterator
+                .map(|(stamp, atom)| {
+                    (
+                        stamp.as_frames().unwrap(),
+                        atom.read(urids.atom.int, ()).unwrap(),
+                    )
+                })
+                .collect::<Vec<(i64, i32)>>()
+        };
+
+        // merging
+        let mut sequences = [
+            read_sequence(&first_space, &urids),
+            read_sequence(&second_space, &urids),
+        ];
+        assert_eq!(
+            events(&mut MergedSequences::new(&mut sequences)),
+            vec![(0, 0), (2, 1), (2, 3), (3, 4), (5, 2)]
+        );
+
+        // writing
+        let mut raw_space = AlignedBuffer::new(256);
+        {
+            let mut space = RootMutSpace::new(raw_space.as_mut());
+            let mut writer = (&mut space as &mut dyn MutSpace)
+                .init(
+                    urids.atom.sequence,
+                    TimeStampURID::Frames(urids.units.frame),
+                )
+                .unwrap();
+            writer
+                .init(TimeStamp::Frames(3), urids.atom.int, 5)
+                .unwrap();
+            writer
+                .merge_from(&mut [
+                    read_sequence(&second_space, &urids),
+                    read_sequence(&first_space, &urids),
+                ])
+                .unwrap();
+        }
+        assert_eq!(
+            events(&mut read_sequence(&raw_space, &urids)),
+            vec![(3, 5), (3, 0), (3, 3), (3, 1), (3, 4), (5, 2)]
+        );
+
+        // Time stamps of different units aren't compared.
+        let mut beat_space = AlignedBuffer::new(256);
+        {
+            let mut space = RootMutSpace::new(beat_space.as_mut());
+            let mut writer = (&mut space as &mut dyn MutSpace)
+                .init(
+                    urids.atom.sequence,
+                    TimeStampURID::BeatsPerMinute(urids.units.beat),
+                )
+                .unwrap();
+            writer
+                .init(TimeStamp::BeatsPerMinute(1.0), urids.atom.int, 6)
+                .unwrap();
+        }
+        let mut sequences = [
+            read_sequence(&second_space, &urids),
+            read_sequence(&beat_space, &urids),
+        ];
+        let values: Vec<i32> = MergedSequences::new(&mut sequences)
+            .map(|(_, atom)| atom.read(urids.atom.int, ()).unwrap())
+            .collect();
+        assert_eq!(values, vec![3, 4, 6]);
+    }
+
+    #[test]
+    fn test_stage() {
+        let mut mapper = Box::pin(HashURIDMapper::new());
+        let interface = mapper.as_mut().make_map_interface();
+        let map = Map::new(&interface);
+        let urids = TestURIDCollection::from_map(&map).unwrap();
+
+        let mut long_space = AlignedBuffer::new(16);
+        (&mut long_space.mut_space() as &mut dyn MutSpace)
+            .init(urids.atom.long, 17)
+            .unwrap();
+
+        // staging
+        let mut stage = SequenceStage::new(4, 64);
+        stage.init(TimeStamp::Frames(5), urids.atom.int, 0).unwrap();
+        stage.init(TimeStamp::Frames(1), urids.atom.int, 1).unwrap();
+        stage
+            .forward(
+                TimeStamp::Frames(3),
+                UnidentifiedAtom::new(long_space.space()),
+            )
+            .unwrap();
+        assert_eq!(
+            stage.write(TimeStamp::Frames(1), |space| {
+                space
+                    .init(urids.atom.vector(), urids.atom.int)?
+                    .append(&[0; 16])?;
+                Ok(())
+            }),
+            Err(AtomError::OutOfSpace)
+        );
+        stage
+            .write(TimeStamp::Frames(1), |space| {
+                space.init(urids.atom.int, 2).map(|_| ())
+            })
+            .unwrap();
+        assert_eq!(
+            stage.init(TimeStamp::Frames(0), urids.atom.int, 3),
+            Err(AtomError::OutOfSpace)
+        );
+        assert_eq!(
+            stage.init(TimeStamp::BeatsPerMinute(0.5), urids.atom.int, 3),
+            Err(AtomError::TimeStampUnitMismatch)
+        );
+        assert_eq!(stage.len(), 4);
+        assert_eq!(stage.dropped(), 3);
+
+        // flushing
+        let mut raw_space = AlignedBuffer::new(256);
+        {
+            let mut space = RootMutSpace::new(raw_space.as_mut());
+            let mut writer = (&mut space as &mut dyn MutSpace)
+                .init(
+                    urids.atom.sequence,
+                    TimeStampURID::Frames(urids.units.frame),
+                )
+                .unwrap();
+            writer
+                .init(TimeStamp::Frames(2), urids.atom.int, 4)
+                .unwrap();
+            stage.flush(&mut writer).unwrap();
+        }
+        assert!(stage.is_empty());
+        assert_eq!(stage.dropped(), 0);
+
+        let events: Vec<(i64, i64)> = read_sequence(&raw_space, &urids)
+            .map(|(stamp, atom)| {
+                let value = atom
+                    .read(urids.atom.int, ())
+                    .map(i64::from)
+                    .or_else(|_| atom.read(urids.atom.long, ()))
+                    .unwrap();
+                (stamp.as_frames().unwrap(), value)
+            })
+            .collect();
+        assert_eq!(events, vec![(2, 4), (2, 1), (2, 2), (3, 17), (5, 0)]);
+
+        // Beats are sorted too, but they have to be numbers.
+        let mut stage = SequenceStage::new(4, 64);
+        for (beats, value) in [(1.5, 0), (-0.0, 1), (0.0, 2)].iter() {
+            stage
+                .init(TimeStamp::BeatsPerMinute(*beats), urids.atom.int, *value)
+                .unwrap();
+        }
+        assert_eq!(
+            stage.init(TimeStamp::BeatsPerMinute(f64::NAN), urids.atom.int, 3),
+            Err(AtomError::InvalidTimeStamp)
+        );
+        assert_eq!(
+            stage.init(TimeStamp::Frames(0), urids.atom.int, 3),
+            Err(AtomError::TimeStampUnitMismatch)
+        );
+        assert_eq!(stage.dropped(), 2);
+
+        let mut raw_space = AlignedBuffer::new(256);
+        {
+            let mut space = RootMutSpace::new(raw_space.as_mut());
+            let mut writer = (&mut space as &mut dyn MutSpace)
+                .init(
+                    urids.atom.sequence,
+                    TimeStampURID::BeatsPerMinute(urids.units.beat),
+                )
+                .unwrap();
+            stage.flush(&mut writer).unwrap();
+        }
+        let values: Vec<i32> = read_sequence(&raw_space, &urids)
+            .map(|(_, atom)| atom.read(urids.atom.int, ()).unwrap())
+            .collect();
+        assert_eq!(values, vec![1, 2, 0]);
+    }
+
+    #[test]
+    fn test_conversion() {
+        let mut mapper = Box::pin(HashURIDMapper::new());
+        let interface = mapper.as_mut().make_map_interface();
+        let map = Map::new(&interface);
+        let urids = TestURIDCollection::from_map(&map).unwrap();
+        let tempo = Tempo {
+            beats_per_minute: 120.0,
+            sample_rate: 48.0,
+        };
+
+        // writing beats from frames
+        let mut raw_space = AlignedBuffer::new(256);
+        {
+            let mut space = RootMutSpace::new(raw_space.as_mut());
+            let mut writer = (&mut space as &mut dyn MutSpace)
+                .init(
+                    urids.atom.sequence,
+                    TimeStampURID::BeatsPerMinute(urids.units.beat),
+                )
+                .unwrap();
+            writer
+                .init(TimeStamp::BeatsPerMinute(0.5), urids.atom.int, 0)
+                .unwrap();
+            writer.set_tempo(tempo);
+            writer
+                .init(TimeStamp::Frames(36), urids.atom.int, 1)
+                .unwrap();
+            assert_eq!(
+                writer
+                    .init(TimeStamp::Frames(12), urids.atom.int, 2)
+                    .map(|_| ()),
+                Err(AtomError::NonMonotonicTimeStamp)
+            );
+        }
+
+        // reading beats as frames
+        let mut reader = read_sequence(&raw_space, &urids);
+        assert_eq!(reader.unit(), TimeStampUnit::BeatsPerMinute);
+        let stamps: Vec<f64> = reader
+            .clone()
+            .map(|(stamp, _)| stamp.as_bpm().unwrap())
+            .collect();
+        assert_eq!(stamps, vec![0.5, 1.5]);
+
+        reader = reader.convert(TimeStampUnit::Frames, tempo);
+        assert_eq!(reader.unit(), TimeStampUnit::Frames);
+        let stamps: Vec<i64> = reader
+            .map(|(stamp, _)| stamp.as_frames().unwrap())
+            .collect();
+        assert_eq!(stamps, vec![12, 36]);
+
+        assert_eq!(
+            tempo
+                .convert(TimeStamp::BeatsPerMinute(0.51), TimeStampUnit::Frames)
+                .as_frames(),
+            Some(12)
+        );
+        assert_eq!(
+            tempo
+                .convert(TimeStamp::Frames(6), TimeStampUnit::BeatsPerMinute)
+                .as_bpm(),
+            Some(0.25)
+        );
+    }
+
+    #[test]
+    fn test_split_block() {
+        let mut mapper = Box::pin(HashURIDMapper::new());
+        let interface = mapper.as_mut().make_map_interface();
+        let map = Map::new(&interface);
+        let urids = TestURIDCollection::from_map(&map).unwrap();
+
+        let mut raw_space = AlignedBuffer::new(256);
+        {
+            let mut space = RootMutSpace::new(raw_space.as_mut());
+            let mut writer = (&mut space as &mut dyn MutSpace)
+                .init(
+                    urids.atom.sequence,
+                    TimeStampURID::Frames(urids.units.frame),
+                )
+                .unwrap();
+            for (i, frame) in [-1, 3, 3, 20].iter().enumerate() {
+                writer
+                    .init(TimeStamp::Frames(*frame), urids.atom.int, i as i32)
+                    .unwrap();
+            }
+        }
+        let (body, _) = raw_space
+            .space()
+            .split_atom_body(urids.atom.sequence)
+            .unwrap();
+
+        let input: Vec<f32> = (0..8).map(|frame| frame as f32).collect();
+        let mut output = [0.0f32; 8];
+        let blocks: Vec<(Range<usize>, Option<i32>)> = Sequence::read(body, urids.units.beat)
+            .unwrap()
+            .split_block((input.as_slice(), &mut output[..]), None)
+            .unwrap()
+            .map(|block| {
+                let (input, output) = block.audio;
+                assert!(input.iter().map(|x| *x as usize).eq(block.range.clone()));
+                for (input, output) in input.iter().zip(output.iter_mut()) {
+                    *output = *input * 2.0;
+                }
+                let event = block
+                    .event
+                    .map(|(_, atom)| atom.read(urids.atom.int, ()).unwrap());
+                (block.range, event)
+            })
+            .collect();
+        assert_eq!(
+            blocks,
+            vec![
+                (0..3, Some(0)),
+                (3..3, Some(1)),
+                (3..8, Some(2)),
+                (8..8, Some(3))
+            ]
+        );
+        assert_eq!(output, [0.0, 2.0, 4.0, 6.0, 8.0, 10.0, 12.0, 14.0]);
+
+        // no events
+        let (body, _) = raw_space
+            .space()
+            .split_atom_body(urids.atom.sequence)
+            .unwrap();
+        let mut iterator = Sequence::read(body, urids.units.beat).unwrap();
+        while iterator.next().is_some() {}
+        let blocks: Vec<(Range<usize>, bool)> = iterator
+            .split_block(&input[..4], None)
+            .unwrap()
+            .map(|block| (block.range, block.event.is_some()))
+            .collect();
+        assert_eq!(blocks, vec![(0..4, false)]);
+
+        // time stamps in beats
+        let mut raw_space = AlignedBuffer::new(256);
+        {
+            let mut space = RootMutSpace::new(raw_space.as_mut());
+            let mut writer = (&mut space as &mut dyn MutSpace)
+                .init(
+                    urids.atom.sequence,
+                    TimeStampURID::BeatsPerMinute(urids.units.beat),
+                )
+                .unwrap();
+            // Both time stamps are nearest to the frame 2.
+            writer
+                .init(TimeStamp::BeatsPerMinute(0.4), urids.atom.int, 0)
+                .unwrap();
+            writer
+                .init(TimeStamp::BeatsPerMinute(0.6), urids.atom.int, 1)
+                .unwrap();
+        }
+        let (body, _) = raw_space
+            .space()
+            .split_atom_body(urids.atom.sequence)
+            .unwrap();
+        let tempo = Tempo {
+            beats_per_minute: 120.0,
+            sample_rate: 8.0,
+        };
+        let events = Sequence::read(body, urids.units.beat).unwrap();
+        let blocks: Vec<(Range<usize>, bool)> = events
+            .clone()
+            .split_block(&input[..], Some(tempo))
+            .unwrap()
+            .map(|block| (block.range, block.event.is_some()))
+            .collect();
+        assert_eq!(blocks, vec![(0..2, false), (2..2, true), (2..8, true)]);
+
+        // Converting the time stamps uses the same rounding.
+        let frames: Vec<i64> = events
+            .clone()
+            .convert(TimeStampUnit::Frames, tempo)
+            .map(|(stamp, _)| stamp.as_frames().unwrap())
+            .collect();
+        assert_eq!(frames, vec![2, 2]);
+
+        // Time stamps in beats can't be split without a tempo.
+        assert!(matches!(
+            events.split_block(&input[..], None),
+            Err(AtomError::TimeStampUnitMismatch)
+        ));
+    }
 }
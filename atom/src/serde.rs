@@ -0,0 +1,1274 @@
+//! Serde integration for atoms.
+//!
+//! With the `serde` feature enabled, every type that implements `Serialize` can be written to a [`MutSpace`](../space/trait.MutSpace.html) and every type that implements `Deserialize` can be read from an [`UnidentifiedAtom`](../struct.UnidentifiedAtom.html). This removes the need to manually map configuration structs onto objects, for example to store them as state or to send them to the UI.
+//!
+//! Rust types are mapped to atoms as follows:
+//!
+//! | Rust type | Atom type |
+//! |-----------|-----------|
+//! | `bool` | `Bool` |
+//! | `i8`, `i16`, `i32`, `u8`, `u16` | `Int` |
+//! | `i64`, `u32`, `u64` | `Long` |
+//! | `f32` | `Float` |
+//! | `f64` | `Double` |
+//! | `char`, `str` | `String` |
+//! | bytes | `Chunk` |
+//! | `None`, `()` | The null atom |
+//! | sequences and tuples | `Tuple` |
+//! | structs and maps | `Object` |
+//! | unit variants | `String` containing the name of the variant |
+//! | tuple variants | `Tuple` starting with the name of the variant |
+//! | newtype and struct variants | `Object` with the variant as type |
+//!
+//! The deserializer additionally reads `Vector`s as sequences, `URI`s, `Path`s and `Literal`s as strings and `URID`s as `u32`. `Option` fields that are `None` are left out of objects.
+//!
+//! The keys of objects are the names of the struct fields or map keys. They are mapped to URIDs with the [`Map`](../../lv2_urid/struct.Map.html) feature of the [`Context`](struct.Context.html). Names that contain a colon are mapped as they are, all other names are prefixed with the namespace of the context. Deserializing maps also requires the `Unmap` feature to turn the keys back into strings.
+//!
+//! Mapping keys is generally not real-time-safe, which is why serialization and deserialization should not happen in the audio thread.
+//!
+//! # Example
+//!
+//! ```
+//! # use lv2_core::prelude::*;
+//! # use lv2_urid::prelude::*;
+//! # use lv2_urid::mapper::*;
+//! use lv2_atom::prelude::*;
+//! use lv2_atom::serde::*;
+//! use lv2_atom::space::*;
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Serialize, Deserialize, PartialEq, Debug)]
+//! struct Config {
+//!     gain: f32,
+//!     name: std::string::String,
+//!     taps: Vec<i32>,
+//! }
+//!
+//! # let mut mapper = Box::pin(HashURIDMapper::new());
+//! # let interface = mapper.as_mut().make_map_interface();
+//! # let map = Map::new(&interface);
+//! let urids: AtomURIDCollection = map.populate_collection().unwrap();
+//! let context = Context::new(&map, &urids, "urn:my-plugin#");
+//!
+//! let config = Config {
+//!     gain: 0.5,
+//!     name: "Default".to_owned(),
+//!     taps: vec![1, 2, 3],
+//! };
+//!
+//! let mut buffer = AlignedBuffer::new(256);
+//! to_space(&config, &mut buffer.mut_space(), &context).unwrap();
+//!
+//! let atom = UnidentifiedAtom::new(buffer.space());
+//! assert_eq!(config, from_atom::<Config>(atom, &context).unwrap());
+//! ```
+use crate::object::Property;
+use crate::space::*;
+use crate::{AtomError, AtomURIDCollection, UnidentifiedAtom};
+use serde::de::value::{SeqDeserializer, StrDeserializer};
+use serde::de::{self, IntoDeserializer};
+use serde::ser::{self, Serialize};
+use std::cell::Cell;
+use std::convert::TryFrom;
+use std::ffi::CString;
+use std::fmt;
+use urid::{Map, Unmap, URID};
+
+/// The URI of `rdf:value`, which is used as the key of newtype variants.
+const RDF_VALUE: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#value";
+
+/// Errors that may occur while serializing or deserializing atoms.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Error {
+    /// Reading or writing an atom failed.
+    Atom(AtomError),
+    /// A key couldn't be mapped to a URID.
+    UnmappableKey(std::string::String),
+    /// A URID couldn't be mapped back to a key, either because the context has no `Unmap` feature or because the URID is unknown.
+    UnknownKey(URID),
+    /// The atom type isn't supported by the deserializer.
+    UnsupportedAtom(URID),
+    /// The value can't be represented as an atom.
+    UnsupportedValue(&'static str),
+    /// A custom error of a `Serialize` or `Deserialize` implementation.
+    Custom(std::string::String),
+}
+
+impl From<AtomError> for Error {
+    fn from(error: AtomError) -> Self {
+        Error::Atom(error)
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Atom(error) => write!(f, "atom error: {:?}", error),
+            Error::UnmappableKey(key) => write!(f, "key \"{}\" can not be mapped", key),
+            Error::UnknownKey(urid) => write!(f, "URID {} can not be unmapped", urid.get()),
+            Error::UnsupportedAtom(urid) => write!(f, "atom type {} is not supported", urid.get()),
+            Error::UnsupportedValue(value) => {
+                write!(f, "{} can not be represented as an atom", value)
+            }
+            Error::Custom(message) => f.write_str(message),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl ser::Error for Error {
+    fn custom<T: fmt::Display>(message: T) -> Self {
+        Error::Custom(message.to_string())
+    }
+}
+
+impl de::Error for Error {
+    fn custom<T: fmt::Display>(message: T) -> Self {
+        Error::Custom(message.to_string())
+    }
+}
+
+/// Features and settings shared by the serializer and the deserializer.
+pub struct Context<'c> {
+    map: &'c Map<'c>,
+    unmap: Option<&'c Unmap<'c>>,
+    urids: &'c AtomURIDCollection,
+    namespace: &'c str,
+    /// Set if the last serialized value was `None`. Elements of tuples and sequences clear it, so that it only refers to the value of a property itself.
+    none: Cell<bool>,
+}
+
+impl<'c> Context<'c> {
+    /// Create a new context.
+    ///
+    /// Keys without a colon are prefixed with `namespace` before they are mapped.
+    pub fn new(map: &'c Map<'c>, urids: &'c AtomURIDCollection, namespace: &'c str) -> Self {
+        Self {
+            map,
+            unmap: None,
+            urids,
+            namespace,
+            none: Cell::new(false),
+        }
+    }
+
+    /// Add the `Unmap` feature to the context, which is needed to deserialize maps.
+    pub fn with_unmap(mut self, unmap: &'c Unmap<'c>) -> Self {
+        self.unmap = Some(unmap);
+        self
+    }
+
+    /// Map a key to it's URID.
+    pub fn map_key(&self, key: &str) -> Result<URID, Error> {
+        let uri = if key.contains(':') {
+            CString::new(key)
+        } else {
+            CString::new(format!("{}{}", self.namespace, key))
+        }
+        .map_err(|_| Error::UnmappableKey(key.to_owned()))?;
+        self.map
+            .map_uri(&uri)
+            .ok_or_else(|| Error::UnmappableKey(key.to_owned()))
+    }
+
+    /// Map a URID back to the key it was mapped from.
+    pub fn unmap_key(&self, urid: URID) -> Result<&'c str, Error> {
+        let uri = self
+            .unmap
+            .and_then(|unmap| unmap.unmap(urid))
+            .and_then(|uri| uri.to_str().ok())
+            .ok_or(Error::UnknownKey(urid))?;
+        Ok(
+            if uri.starts_with(self.namespace) && !self.namespace.is_empty() {
+                &uri[self.namespace.len()..]
+            } else {
+                uri
+            },
+        )
+    }
+}
+
+/// Serialize a value to a space.
+///
+/// If serialization fails, everything that has been written is rolled back.
+pub fn to_space<'a, T: Serialize + ?Sized>(
+    value: &T,
+    space: &mut dyn MutSpace<'a>,
+    context: &Context,
+) -> Result<(), Error> {
+    let checkpoint = space.checkpoint();
+    let result = value.serialize(Serializer::new(space, context));
+    if result.is_err() {
+        // The serializer has been consumed, see `MutSpace::rollback`.
+        unsafe { space.rollback(checkpoint) };
+    }
+    result
+}
+
+/// Deserialize a value from an atom.
+pub fn from_atom<'a, T: de::Deserialize<'a>>(
+    atom: UnidentifiedAtom<'a>,
+    context: &Context,
+) -> Result<T, Error> {
+    T::deserialize(Deserializer::new(atom, context))
+}
+
+/// Serializer that writes atoms to a space.
+///
+/// Every serialized value is written as one atom. Have a look at the [module documentation](index.html) for the mapping of types.
+pub struct Serializer<'a, 'b, 'c> {
+    space: &'b mut dyn MutSpace<'a>,
+    context: &'c Context<'c>,
+}
+
+impl<'a, 'b, 'c> Serializer<'a, 'b, 'c> {
+    /// Create a new serializer that writes to the given space.
+    pub fn new(space: &'b mut dyn MutSpace<'a>, context: &'c Context<'c>) -> Self {
+        Self { space, context }
+    }
+
+    fn write_str(self, value: &str) -> Result<(), Error> {
+        let mut writer = self.space.init(self.context.urids.string, ())?;
+        writer.append(value)?;
+        Ok(())
+    }
+
+    fn begin_tuple(self) -> Result<Compound<'a, 'b, 'c>, Error> {
+        let frame = FramedMutSpace::new(self.space, self.context.urids.tuple)?;
+        Ok(Compound {
+            frame,
+            context: self.context,
+            key: None,
+        })
+    }
+
+    fn begin_object(self, otype: URID) -> Result<Compound<'a, 'b, 'c>, Error> {
+        let mut frame = FramedMutSpace::new(self.space, self.context.urids.object)?;
+        (&mut frame as &mut dyn MutSpace).write(
+            &sys::LV2_Atom_Object_Body {
+                id: 0,
+                otype: otype.get(),
+            },
+            true,
+        )?;
+        Ok(Compound {
+            frame,
+            context: self.context,
+            key: None,
+        })
+    }
+}
+
+impl<'a, 'b, 'c> ser::Serializer for Serializer<'a, 'b, 'c> {
+    type Ok = ();
+    type Error = Error;
+    type SerializeSeq = Compound<'a, 'b, 'c>;
+    type SerializeTuple = Compound<'a, 'b, 'c>;
+    type SerializeTupleStruct = Compound<'a, 'b, 'c>;
+    type SerializeTupleVariant = Compound<'a, 'b, 'c>;
+    type SerializeMap = Compound<'a, 'b, 'c>;
+    type SerializeStruct = Compound<'a, 'b, 'c>;
+    type SerializeStructVariant = Compound<'a, 'b, 'c>;
+
+    fn serialize_bool(self, value: bool) -> Result<(), Error> {
+        self.space.init(self.context.urids.bool, value as i32)?;
+        Ok(())
+    }
+
+    fn serialize_i8(self, value: i8) -> Result<(), Error> {
+        self.serialize_i32(value.into())
+    }
+
+    fn serialize_i16(self, value: i16) -> Result<(), Error> {
+        self.serialize_i32(value.into())
+    }
+
+    fn serialize_i32(self, value: i32) -> Result<(), Error> {
+        self.space.init(self.context.urids.int, value)?;
+        Ok(())
+    }
+
+    fn serialize_i64(self, value: i64) -> Result<(), Error> {
+        self.space.init(self.context.urids.long, value)?;
+        Ok(())
+    }
+
+    fn serialize_u8(self, value: u8) -> Result<(), Error> {
+        self.serialize_i32(value.into())
+    }
+
+    fn serialize_u16(self, value: u16) -> Result<(), Error> {
+        self.serialize_i32(value.into())
+    }
+
+    fn serialize_u32(self, value: u32) -> Result<(), Error> {
+        self.serialize_i64(value.into())
+    }
+
+    fn serialize_u64(self, value: u64) -> Result<(), Error> {
+        let value =
+            i64::try_from(value).map_err(|_| Error::UnsupportedValue("u64 above i64::MAX"))?;
+        self.serialize_i64(value)
+    }
+
+    fn serialize_f32(self, value: f32) -> Result<(), Error> {
+        self.space.init(self.context.urids.float, value)?;
+        Ok(())
+    }
+
+    fn serialize_f64(self, value: f64) -> Result<(), Error> {
+        self.space.init(self.context.urids.double, value)?;
+        Ok(())
+    }
+
+    fn serialize_char(self, value: char) -> Result<(), Error> {
+        self.write_str(value.encode_utf8(&mut [0; 4]))
+    }
+
+    fn serialize_str(self, value: &str) -> Result<(), Error> {
+        self.write_str(value)
+    }
+
+    fn serialize_bytes(self, value: &[u8]) -> Result<(), Error> {
+        let mut frame = self.space.init(self.context.urids.chunk, ())?;
+        frame.write_raw(value, false)?;
+        Ok(())
+    }
+
+    fn serialize_none(self) -> Result<(), Error> {
+        self.space
+            .write(&sys::LV2_Atom { size: 0, type_: 0 }, true)?;
+        self.context.none.set(true);
+        Ok(())
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<(), Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<(), Error> {
+        self.space
+            .write(&sys::LV2_Atom { size: 0, type_: 0 }, true)?;
+        Ok(())
+    }
+
+    fn serialize_unit_struct(self, _: &'static str) -> Result<(), Error> {
+        self.serialize_unit()
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        variant: &'static str,
+    ) -> Result<(), Error> {
+        self.write_str(variant)
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _: &'static str,
+        _: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        let otype = self.context.map_key(variant)?;
+        let mut object = self.begin_object(otype)?;
+        ser::SerializeStruct::serialize_field(&mut object, RDF_VALUE, value)?;
+        Ok(())
+    }
+
+    fn serialize_seq(self, _: Option<usize>) -> Result<Compound<'a, 'b, 'c>, Error> {
+        self.begin_tuple()
+    }
+
+    fn serialize_tuple(self, _: usize) -> Result<Compound<'a, 'b, 'c>, Error> {
+        self.begin_tuple()
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Compound<'a, 'b, 'c>, Error> {
+        self.begin_tuple()
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        variant: &'static str,
+        _: usize,
+    ) -> Result<Compound<'a, 'b, 'c>, Error> {
+        let mut tuple = self.begin_tuple()?;
+        ser::SerializeSeq::serialize_element(&mut tuple, variant)?;
+        Ok(tuple)
+    }
+
+    fn serialize_map(self, _: Option<usize>) -> Result<Compound<'a, 'b, 'c>, Error> {
+        let otype = self.context.urids.object.into_general();
+        self.begin_object(otype)
+    }
+
+    fn serialize_struct(self, name: &'static str, _: usize) -> Result<Compound<'a, 'b, 'c>, Error> {
+        let otype = self.context.map_key(name)?;
+        self.begin_object(otype)
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        variant: &'static str,
+        _: usize,
+    ) -> Result<Compound<'a, 'b, 'c>, Error> {
+        let otype = self.context.map_key(variant)?;
+        self.begin_object(otype)
+    }
+}
+
+/// Serialization handle for tuples and objects.
+pub struct Compound<'a, 'b, 'c> {
+    frame: FramedMutSpace<'a, 'b>,
+    context: &'c Context<'c>,
+    /// The key of the next map value.
+    key: Option<URID>,
+}
+
+impl<'a, 'b, 'c> Compound<'a, 'b, 'c> {
+    fn element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        value.serialize(Serializer::new(&mut self.frame, self.context))?;
+        // A `None` element doesn't make the compound `None`.
+        self.context.none.set(false);
+        Ok(())
+    }
+
+    fn property<T: Serialize + ?Sized>(&mut self, key: URID, value: &T) -> Result<(), Error> {
+        let checkpoint = self.frame.checkpoint();
+        Property::write_header(&mut self.frame, key, None)?;
+        self.context.none.set(false);
+        value.serialize(Serializer::new(&mut self.frame, self.context))?;
+        if self.context.none.replace(false) {
+            // `None` values are left out.
+            unsafe { self.frame.rollback(checkpoint) };
+        }
+        Ok(())
+    }
+}
+
+impl<'a, 'b, 'c> ser::SerializeSeq for Compound<'a, 'b, 'c> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+impl<'a, 'b, 'c> ser::SerializeTuple for Compound<'a, 'b, 'c> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+impl<'a, 'b, 'c> ser::SerializeTupleStruct for Compound<'a, 'b, 'c> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+impl<'a, 'b, 'c> ser::SerializeTupleVariant for Compound<'a, 'b, 'c> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+impl<'a, 'b, 'c> ser::SerializeMap for Compound<'a, 'b, 'c> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), Error> {
+        let key = key.serialize(KeySerializer)?;
+        self.key = Some(self.context.map_key(&key)?);
+        Ok(())
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        let key = self
+            .key
+            .take()
+            .ok_or(Error::UnsupportedValue("map value without key"))?;
+        self.property(key, value)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+impl<'a, 'b, 'c> ser::SerializeStruct for Compound<'a, 'b, 'c> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        let key = self.context.map_key(key)?;
+        self.property(key, value)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+impl<'a, 'b, 'c> ser::SerializeStructVariant for Compound<'a, 'b, 'c> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        ser::SerializeStruct::serialize_field(self, key, value)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+/// Serializer for map keys, which only accepts strings.
+struct KeySerializer;
+
+impl KeySerializer {
+    fn unsupported<T>() -> Result<T, Error> {
+        Err(Error::UnsupportedValue("map key that is not a string"))
+    }
+}
+
+impl ser::Serializer for KeySerializer {
+    type Ok = std::string::String;
+    type Error = Error;
+    type SerializeSeq = ser::Impossible<std::string::String, Error>;
+    type SerializeTuple = ser::Impossible<std::string::String, Error>;
+    type SerializeTupleStruct = ser::Impossible<std::string::String, Error>;
+    type SerializeTupleVariant = ser::Impossible<std::string::String, Error>;
+    type SerializeMap = ser::Impossible<std::string::String, Error>;
+    type SerializeStruct = ser::Impossible<std::string::String, Error>;
+    type SerializeStructVariant = ser::Impossible<std::string::String, Error>;
+
+    fn serialize_str(self, value: &str) -> Result<std::string::String, Error> {
+        Ok(value.to_owned())
+    }
+
+    fn serialize_char(self, value: char) -> Result<std::string::String, Error> {
+        Ok(value.to_string())
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        variant: &'static str,
+    ) -> Result<std::string::String, Error> {
+        Ok(variant.to_owned())
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _: &'static str,
+        value: &T,
+    ) -> Result<std::string::String, Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_bool(self, _: bool) -> Result<std::string::String, Error> {
+        Self::unsupported()
+    }
+
+    fn serialize_i8(self, _: i8) -> Result<std::string::String, Error> {
+        Self::unsupported()
+    }
+
+    fn serialize_i16(self, _: i16) -> Result<std::string::String, Error> {
+        Self::unsupported()
+    }
+
+    fn serialize_i32(self, _: i32) -> Result<std::string::String, Error> {
+        Self::unsupported()
+    }
+
+    fn serialize_i64(self, _: i64) -> Result<std::string::String, Error> {
+        Self::unsupported()
+    }
+
+    fn serialize_u8(self, _: u8) -> Result<std::string::String, Error> {
+        Self::unsupported()
+    }
+
+    fn serialize_u16(self, _: u16) -> Result<std::string::String, Error> {
+        Self::unsupported()
+    }
+
+    fn serialize_u32(self, _: u32) -> Result<std::string::String, Error> {
+        Self::unsupported()
+    }
+
+    fn serialize_u64(self, _: u64) -> Result<std::string::String, Error> {
+        Self::unsupported()
+    }
+
+    fn serialize_f32(self, _: f32) -> Result<std::string::String, Error> {
+        Self::unsupported()
+    }
+
+    fn serialize_f64(self, _: f64) -> Result<std::string::String, Error> {
+        Self::unsupported()
+    }
+
+    fn serialize_bytes(self, _: &[u8]) -> Result<std::string::String, Error> {
+        Self::unsupported()
+    }
+
+    fn serialize_none(self) -> Result<std::string::String, Error> {
+        Self::unsupported()
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, _: &T) -> Result<std::string::String, Error> {
+        Self::unsupported()
+    }
+
+    fn serialize_unit(self) -> Result<std::string::String, Error> {
+        Self::unsupported()
+    }
+
+    fn serialize_unit_struct(self, _: &'static str) -> Result<std::string::String, Error> {
+        Self::unsupported()
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: &T,
+    ) -> Result<std::string::String, Error> {
+        Self::unsupported()
+    }
+
+    fn serialize_seq(self, _: Option<usize>) -> Result<Self::SerializeSeq, Error> {
+        Self::unsupported()
+    }
+
+    fn serialize_tuple(self, _: usize) -> Result<Self::SerializeTuple, Error> {
+        Self::unsupported()
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeTupleStruct, Error> {
+        Self::unsupported()
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeTupleVariant, Error> {
+        Self::unsupported()
+    }
+
+    fn serialize_map(self, _: Option<usize>) -> Result<Self::SerializeMap, Error> {
+        Self::unsupported()
+    }
+
+    fn serialize_struct(self, _: &'static str, _: usize) -> Result<Self::SerializeStruct, Error> {
+        Self::unsupported()
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeStructVariant, Error> {
+        Self::unsupported()
+    }
+}
+
+/// Deserializer that reads values from atoms.
+///
+/// Have a look at the [module documentation](index.html) for the mapping of types.
+pub struct Deserializer<'a, 'c> {
+    atom: UnidentifiedAtom<'a>,
+    context: &'c Context<'c>,
+}
+
+impl<'a, 'c> Deserializer<'a, 'c> {
+    /// Create a new deserializer that reads the given atom.
+    pub fn new(atom: UnidentifiedAtom<'a>, context: &'c Context<'c>) -> Self {
+        Self { atom, context }
+    }
+
+    /// Return the type and the body of the atom.
+    fn split(&self) -> Result<(u32, Space<'a>), Error> {
+        let (space, _) = self.atom.space().split_atom()?;
+        let (header, body) = space.split_type::<sys::LV2_Atom>()?;
+        Ok((header.type_, body))
+    }
+
+    fn unsupported(type_: u32) -> Error {
+        match URID::new(type_) {
+            Some(urid) => Error::UnsupportedAtom(urid),
+            None => Error::Atom(AtomError::TypeMismatch),
+        }
+    }
+
+    fn visit_vector<V: de::Visitor<'a>>(
+        self,
+        body: Space<'a>,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        let urids = self.context.urids;
+        let (header, _) = body.split_type::<sys::LV2_Atom_Vector_Body>()?;
+        let child_type = header.child_type;
+        if child_type == urids.int {
+            let data = self.atom.read(urids.vector(), urids.int)?;
+            visit_slice(data, visitor)
+        } else if child_type == urids.long {
+            let data = self.atom.read(urids.vector(), urids.long)?;
+            visit_slice(data, visitor)
+        } else if child_type == urids.float {
+            let data = self.atom.read(urids.vector(), urids.float)?;
+            visit_slice(data, visitor)
+        } else if child_type == urids.double {
+            let data = self.atom.read(urids.vector(), urids.double)?;
+            visit_slice(data, visitor)
+        } else if child_type == urids.bool {
+            let data = self.atom.read(urids.vector(), urids.bool)?;
+            visit_slice(data, visitor)
+        } else {
+            Err(Self::unsupported(child_type))
+        }
+    }
+
+    fn visit_object<V: de::Visitor<'a>>(
+        self,
+        fields: Option<&'static [&'static str]>,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        let (_, reader) = self.atom.read(self.context.urids.object, ())?;
+        let fields = match fields {
+            Some(fields) => Some(
+                fields
+                    .iter()
+                    .map(|field| Ok((self.context.map_key(field)?, *field)))
+                    .collect::<Result<Vec<_>, Error>>()?,
+            ),
+            None => None,
+        };
+        visitor.visit_map(ObjectAccess {
+            properties: reader.map(|(header, atom)| (header.key, atom)).collect(),
+            fields,
+            value: None,
+            context: self.context,
+        })
+    }
+}
+
+fn visit_slice<'a, T, V>(data: &'a [T], visitor: V) -> Result<V::Value, Error>
+where
+    T: Copy + IntoDeserializer<'a, Error>,
+    V: de::Visitor<'a>,
+{
+    de::Deserializer::deserialize_any(SeqDeserializer::new(data.iter().copied()), visitor)
+}
+
+impl<'a, 'c> de::Deserializer<'a> for Deserializer<'a, 'c> {
+    type Error = Error;
+
+    fn deserialize_any<V: de::Visitor<'a>>(self, visitor: V) -> Result<V::Value, Error> {
+        let urids = self.context.urids;
+        let (type_, body) = self.split()?;
+        if type_ == 0 {
+            visitor.visit_unit()
+        } else if type_ == urids.bool {
+            visitor.visit_bool(self.atom.read(urids.bool, ())? != 0)
+        } else if type_ == urids.int {
+            visitor.visit_i32(self.atom.read(urids.int, ())?)
+        } else if type_ == urids.long {
+            visitor.visit_i64(self.atom.read(urids.long, ())?)
+        } else if type_ == urids.float {
+            visitor.visit_f32(self.atom.read(urids.float, ())?)
+        } else if type_ == urids.double {
+            visitor.visit_f64(self.atom.read(urids.double, ())?)
+        } else if type_ == urids.urid {
+            visitor.visit_u32(self.atom.read(urids.urid, ())?.get())
+        } else if type_ == urids.string {
+            visitor.visit_borrowed_str(self.atom.read(urids.string, ())?)
+        } else if type_ == urids.uri {
+            visitor.visit_borrowed_str(self.atom.read(urids.uri, ())?)
+        } else if type_ == urids.path {
+            let path = self.atom.read(urids.path, ())?;
+            visitor.visit_borrowed_str(path.to_str().ok_or(AtomError::Malformed)?)
+        } else if type_ == urids.literal {
+            visitor.visit_borrowed_str(self.atom.read(urids.literal, ())?.1)
+        } else if type_ == urids.chunk {
+            visitor.visit_borrowed_bytes(self.atom.read(urids.chunk, ())?)
+        } else if type_ == urids.tuple {
+            let elements = self.atom.read(urids.tuple, ())?;
+            let mut access = TupleAccess {
+                elements,
+                context: self.context,
+            };
+            let value = visitor.visit_seq(&mut access)?;
+            match access.elements.next() {
+                Some(_) => Err(<Error as de::Error>::invalid_length(
+                    0,
+                    &"fewer elements in tuple",
+                )),
+                None => Ok(value),
+            }
+        } else if type_ == urids.vector::<crate::scalar::Int>() {
+            self.visit_vector(body, visitor)
+        } else if type_ == urids.object {
+            self.visit_object(None, visitor)
+        } else {
+            Err(Self::unsupported(type_))
+        }
+    }
+
+    fn deserialize_option<V: de::Visitor<'a>>(self, visitor: V) -> Result<V::Value, Error> {
+        if self.split()?.0 == 0 {
+            visitor.visit_none()
+        } else {
+            visitor.visit_some(self)
+        }
+    }
+
+    fn deserialize_newtype_struct<V: de::Visitor<'a>>(
+        self,
+        _: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_struct<V: de::Visitor<'a>>(
+        self,
+        _: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        self.visit_object(Some(fields), visitor)
+    }
+
+    fn deserialize_enum<V: de::Visitor<'a>>(
+        self,
+        _: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        let urids = self.context.urids;
+        let (type_, _) = self.split()?;
+        if type_ == urids.string {
+            let variant: &str = self.atom.read(urids.string, ())?;
+            visitor.visit_enum(variant.into_deserializer())
+        } else if type_ == urids.tuple {
+            let mut elements = self.atom.read(urids.tuple, ())?;
+            let variant = elements
+                .next()
+                .ok_or(AtomError::Malformed)?
+                .read(urids.string, ())?;
+            visitor.visit_enum(EnumAccess {
+                variant,
+                value: VariantValue::Tuple(elements),
+                context: self.context,
+            })
+        } else if type_ == urids.object {
+            let (header, _) = self.atom.read(urids.object, ())?;
+            let variant = variants
+                .iter()
+                .find(|variant| self.context.map_key(variant) == Ok(header.otype))
+                .copied()
+                .ok_or_else(|| <Error as de::Error>::custom("unknown variant"))?;
+            visitor.visit_enum(EnumAccess {
+                variant,
+                context: self.context,
+                value: VariantValue::Object(self),
+            })
+        } else {
+            Err(Self::unsupported(type_))
+        }
+    }
+
+    fn deserialize_unit<V: de::Visitor<'a>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self.split()?.0 {
+            0 => visitor.visit_unit(),
+            type_ => Err(Self::unsupported(type_)),
+        }
+    }
+
+    fn deserialize_unit_struct<V: de::Visitor<'a>>(
+        self,
+        _: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        self.deserialize_unit(visitor)
+    }
+
+    fn deserialize_ignored_any<V: de::Visitor<'a>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_unit()
+    }
+
+    serde::forward_to_deserialize_any! {
+        <W: Visitor<'a>>
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf seq tuple tuple_struct map identifier
+    }
+}
+
+/// Access to the elements of a tuple.
+struct TupleAccess<'a, 'c> {
+    elements: crate::tuple::TupleIterator<'a>,
+    context: &'c Context<'c>,
+}
+
+impl<'a, 'c> de::SeqAccess<'a> for TupleAccess<'a, 'c> {
+    type Error = Error;
+
+    fn next_element_seed<T: de::DeserializeSeed<'a>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, Error> {
+        match self.elements.next() {
+            Some(atom) => seed
+                .deserialize(Deserializer::new(atom, self.context))
+                .map(Some),
+            None => Ok(None),
+        }
+    }
+}
+
+/// Access to the properties of an object.
+struct ObjectAccess<'a, 'c> {
+    properties: std::collections::VecDeque<(URID, UnidentifiedAtom<'a>)>,
+    /// The URIDs and names of the fields if a struct is deserialized.
+    fields: Option<Vec<(URID, &'static str)>>,
+    value: Option<UnidentifiedAtom<'a>>,
+    context: &'c Context<'c>,
+}
+
+impl<'a, 'c> de::MapAccess<'a> for ObjectAccess<'a, 'c> {
+    type Error = Error;
+
+    fn next_key_seed<K: de::DeserializeSeed<'a>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, Error> {
+        while let Some((key, atom)) = self.properties.pop_front() {
+            let name = match &self.fields {
+                // Properties that aren't fields of the struct are skipped.
+                Some(fields) => match fields.iter().find(|(urid, _)| *urid == key) {
+                    Some((_, name)) => *name,
+                    None => continue,
+                },
+                None => self.context.unmap_key(key)?,
+            };
+            self.value = Some(atom);
+            let name: StrDeserializer<Error> = name.into_deserializer();
+            return seed.deserialize(name).map(Some);
+        }
+        Ok(None)
+    }
+
+    fn next_value_seed<V: de::DeserializeSeed<'a>>(&mut self, seed: V) -> Result<V::Value, Error> {
+        let atom = self
+            .value
+            .take()
+            .ok_or_else(|| <Error as de::Error>::custom("value without key"))?;
+        seed.deserialize(Deserializer::new(atom, self.context))
+    }
+}
+
+/// The content of an enum variant.
+enum VariantValue<'a, 'c> {
+    Tuple(crate::tuple::TupleIterator<'a>),
+    Object(Deserializer<'a, 'c>),
+}
+
+/// Access to an enum variant that isn't a unit variant.
+struct EnumAccess<'a, 'c> {
+    variant: &'a str,
+    value: VariantValue<'a, 'c>,
+    context: &'c Context<'c>,
+}
+
+impl<'a, 'c> de::EnumAccess<'a> for EnumAccess<'a, 'c> {
+    type Error = Error;
+    type Variant = Self;
+
+    fn variant_seed<V: de::DeserializeSeed<'a>>(self, seed: V) -> Result<(V::Value, Self), Error> {
+        let variant: StrDeserializer<Error> = self.variant.into_deserializer();
+        Ok((seed.deserialize(variant)?, self))
+    }
+}
+
+impl<'a, 'c> de::VariantAccess<'a> for EnumAccess<'a, 'c> {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<(), Error> {
+        Err(<Error as de::Error>::invalid_type(
+            de::Unexpected::Other("atom"),
+            &"unit variant",
+        ))
+    }
+
+    fn newtype_variant_seed<T: de::DeserializeSeed<'a>>(self, seed: T) -> Result<T::Value, Error> {
+        match self.value {
+            VariantValue::Object(deserializer) => {
+                let key = self.context.map_key(RDF_VALUE)?;
+                let (_, mut reader) = deserializer.atom.read(self.context.urids.object, ())?;
+                let (_, atom) = reader
+                    .find(|(header, _)| header.key == key)
+                    .ok_or_else(|| <Error as de::Error>::missing_field(RDF_VALUE))?;
+                seed.deserialize(Deserializer::new(atom, self.context))
+            }
+            VariantValue::Tuple(_) => Err(<Error as de::Error>::invalid_type(
+                de::Unexpected::TupleVariant,
+                &"newtype variant",
+            )),
+        }
+    }
+
+    fn tuple_variant<V: de::Visitor<'a>>(self, _: usize, visitor: V) -> Result<V::Value, Error> {
+        match self.value {
+            VariantValue::Tuple(elements) => visitor.visit_seq(TupleAccess {
+                elements,
+                context: self.context,
+            }),
+            VariantValue::Object(_) => Err(<Error as de::Error>::invalid_type(
+                de::Unexpected::StructVariant,
+                &"tuple variant",
+            )),
+        }
+    }
+
+    fn struct_variant<V: de::Visitor<'a>>(
+        self,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        match self.value {
+            VariantValue::Object(deserializer) => deserializer.visit_object(Some(fields), visitor),
+            VariantValue::Tuple(_) => Err(<Error as de::Error>::invalid_type(
+                de::Unexpected::TupleVariant,
+                &"struct variant",
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "host")]
+mod tests {
+    use crate::serde::*;
+    use serde::{Deserialize, Serialize};
+    use std::collections::BTreeMap;
+    use urid::mapper::*;
+    use urid::prelude::*;
+
+    #[derive(Serialize, Deserialize, PartialEq, Debug)]
+    enum Mode {
+        Off,
+        Gain(f64),
+        Pan(f32, f32),
+        Filter { cutoff: f32, resonance: f32 },
+    }
+
+    #[derive(Serialize, Deserialize, PartialEq, Debug)]
+    struct Config {
+        enabled: bool,
+        level: i32,
+        length: u64,
+        name: std::string::String,
+        comment: Option<std::string::String>,
+        taps: Vec<i16>,
+        pair: (char, f32),
+        modes: Vec<Mode>,
+        #[serde(rename = "urn:other#tags")]
+        tags: BTreeMap<std::string::String, i64>,
+        #[serde(with = "serde_bytes_helper")]
+        blob: Vec<u8>,
+        gaps: Vec<Option<i32>>,
+        last: (i32, Option<i32>),
+    }
+
+    mod serde_bytes_helper {
+        use serde::{Deserialize, Deserializer, Serializer};
+
+        pub fn serialize<S: Serializer>(value: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+            serializer.serialize_bytes(value)
+        }
+
+        pub fn deserialize<'de, D: Deserializer<'de>>(
+            deserializer: D,
+        ) -> Result<Vec<u8>, D::Error> {
+            <&[u8]>::deserialize(deserializer).map(|bytes| bytes.to_vec())
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut mapper = Box::pin(HashURIDMapper::new());
+        let map_interface = mapper.as_mut().make_map_interface();
+        let unmap_interface = mapper.as_mut().make_unmap_interface();
+        let map = Map::new(&map_interface);
+        let unmap = Unmap::new(&unmap_interface);
+        let urids = AtomURIDCollection::from_map(&map).unwrap();
+        let context = Context::new(&map, &urids, "urn:test#").with_unmap(&unmap);
+
+        let mut tags = BTreeMap::new();
+        tags.insert("a".to_owned(), 1);
+        tags.insert("urn:b".to_owned(), 2);
+        let config = Config {
+            enabled: true,
+            level: -3,
+            length: 1 << 40,
+            name: "Test".to_owned(),
+            comment: None,
+            taps: vec![1, 2, 3],
+            pair: ('x', 0.5),
+            modes: vec![
+                Mode::Off,
+                Mode::Gain(2.0),
+                Mode::Pan(0.25, 0.75),
+                Mode::Filter {
+                    cutoff: 440.0,
+                    resonance: 0.1,
+                },
+            ],
+            tags,
+            blob: vec![1, 2, 3, 4, 5],
+            gaps: vec![Some(1), None],
+            last: (1, None),
+        };
+
+        let mut buffer = AlignedBuffer::new(2048);
+        to_space(&config, &mut buffer.mut_space(), &context).unwrap();
+
+        // verifying
+        {
+            let atom = UnidentifiedAtom::new(buffer.space());
+            let (header, reader) = atom.read(urids.object, ()).unwrap();
+            assert_eq!(header.otype, context.map_key("Config").unwrap());
+            let properties: Vec<(URID, UnidentifiedAtom)> =
+                reader.map(|(header, atom)| (header.key, atom)).collect();
+            // The comment is left out, but not the properties that only contain `None` values.
+            assert_eq!(properties.len(), 11);
+            assert_eq!(
+                properties[0].0,
+                context.map_key("urn:test#enabled").unwrap()
+            );
+            assert_eq!(properties[0].1.read(urids.bool, ()).unwrap(), 1);
+            assert_eq!(properties[2].1.read(urids.long, ()).unwrap(), 1 << 40);
+            assert_eq!(properties[3].1.read(urids.string, ()).unwrap(), "Test");
+            assert_eq!(properties[7].0, context.map_key("urn:other#tags").unwrap());
+        }
+
+        // reading
+        let atom = UnidentifiedAtom::new(buffer.space());
+        assert_eq!(config, from_atom::<Config>(atom, &context).unwrap());
+    }
+
+    #[test]
+    fn test_vector_and_errors() {
+        let mut mapper = Box::pin(HashURIDMapper::new());
+        let interface = mapper.as_mut().make_map_interface();
+        let map = Map::new(&interface);
+        let urids = AtomURIDCollection::from_map(&map).unwrap();
+        let context = Context::new(&map, &urids, "urn:test#");
+
+        // Vectors are read as sequences.
+        let mut buffer = AlignedBuffer::new(256);
+        (&mut buffer.mut_space() as &mut dyn MutSpace)
+            .init(urids.vector(), urids.float)
+            .unwrap()
+            .append(&[1.0, 2.0, 3.0])
+            .unwrap();
+        let atom = UnidentifiedAtom::new(buffer.space());
+        assert_eq!(
+            vec![1.0, 2.0, 3.0],
+            from_atom::<Vec<f32>>(atom, &context).unwrap()
+        );
+        assert!(from_atom::<Vec<i32>>(atom, &context).is_err());
+
+        // Maps can't be read without `Unmap`.
+        let mut buffer = AlignedBuffer::new(256);
+        let mut values = BTreeMap::new();
+        values.insert("a", 1);
+        to_space(&values, &mut buffer.mut_space(), &context).unwrap();
+        let atom = UnidentifiedAtom::new(buffer.space());
+        assert!(matches!(
+            from_atom::<BTreeMap<std::string::String, i32>>(atom, &context),
+            Err(Error::UnknownKey(_))
+        ));
+
+        // Failed writes are rolled back.
+        let mut buffer = AlignedBuffer::new(32);
+        let mut space = buffer.mut_space();
+        assert_eq!(
+            Err(Error::Atom(AtomError::OutOfSpace)),
+            to_space(&vec![1i64, 2, 3], &mut space, &context)
+        );
+        assert_eq!(space.checkpoint().allocated(), 0);
+        assert_eq!(
+            Err(Error::UnsupportedValue("u64 above i64::MAX")),
+            to_space(&u64::MAX, &mut space, &context)
+        );
+    }
+}
@@ -7,10 +7,11 @@
 //! In the first case, we have to trust that the space behind the atom header is accessible since we have no way to check whether it is or not. Therefore, we have to assume that it is sound.
 //!
 //! The second case is sound since a) the data is contained in a slice and therefore is accessible, b) generic type parameter bounds assure that the type is plain-old-data and c) 64-bit padding is assured.
-use crate::Atom;
-use std::cell::Cell;
-use std::marker::Unpin;
-use std::mem::{size_of, size_of_val};
+use crate::{Atom, AtomError};
+use std::marker::{PhantomData, Unpin};
+use std::mem::{align_of, size_of, size_of_val};
+use std::ops::{Deref, DerefMut};
+use std::ptr::NonNull;
 use urid::URID;
 
 /// Specialized smart pointer to retrieve struct instances from a slice of memory.
@@ -41,19 +42,41 @@ impl<'a> Space<'a> {
 
     /// Create a new space from a slice.
     ///
-    /// Since everything regarding atoms is 64-bit-aligned, this method panics if the data slice is not 64-bit-aligned.
+    /// Since everything regarding atoms is 64-bit-aligned, this method panics if the data slice is not 64-bit-aligned. Use [`try_from_slice`](#method.try_from_slice) if the alignment of the data isn't known.
     pub fn from_slice(data: &'a [u8]) -> Self {
+        match Self::try_from_slice(data) {
+            Ok(space) => space,
+            Err(_) => panic!("Atom space has to be 64-bit-aligned"),
+        }
+    }
+
+    /// Try to create a new space from a slice.
+    ///
+    /// If the data slice is not empty and not 64-bit-aligned, `AtomError::Misaligned` is returned.
+    pub fn try_from_slice(data: &'a [u8]) -> Result<Self, AtomError> {
+        if !data.is_empty() && data.as_ptr() as usize & 7 != 0 {
+            return Err(AtomError::Misaligned);
+        }
+        Ok(Space { data: Some(data) })
+    }
+
+    /// Create a new space from a slice without checking its alignment.
+    ///
+    /// This is intended for data from foreign sources that doesn't have to be 64-bit-aligned, like the values of options. Reading typed data from the space still checks the alignment of the type, which means that reading may fail with `AtomError::Misaligned`.
+    pub fn from_unaligned_slice(data: &'a [u8]) -> Self {
         Space { data: Some(data) }
     }
 
     /// Try to retrieve a slice of bytes.
     ///
     /// This method basically splits off the lower part of the internal bytes slice and creates a new atom space pointer of the upper part. Since atoms have to be 64-bit-aligned, there might be a padding space that's neither in the lower nor in the upper part.
-    pub fn split_raw(self, size: usize) -> Option<(&'a [u8], Self)> {
-        let data = self.data?;
+    ///
+    /// If the space is smaller than `size`, this method returns `AtomError::OutOfSpace`.
+    pub fn split_raw(self, size: usize) -> Result<(&'a [u8], Self), AtomError> {
+        let data = self.data.ok_or(AtomError::OutOfSpace)?;
 
         if size > data.len() {
-            return None;
+            return Err(AtomError::OutOfSpace);
         }
         let (lower_space, upper_space) = data.split_at(size);
 
@@ -67,26 +90,31 @@ impl<'a> Space<'a> {
         };
         let upper_space = Self { data: upper_space };
 
-        Some((lower_space, upper_space))
+        Ok((lower_space, upper_space))
     }
 
     /// Try to retrieve space.
     ///
     /// This method calls [`split_raw`](#method.split_raw) and wraps the returned slice in an atom space. The second space is the space after the first one.
-    pub fn split_space(self, size: usize) -> Option<(Self, Self)> {
+    pub fn split_space(self, size: usize) -> Result<(Self, Self), AtomError> {
         self.split_raw(size)
-            .map(|(data, rhs)| (Self::from_slice(data), rhs))
+            .map(|(data, rhs)| (Self::from_unaligned_slice(data), rhs))
     }
 
     /// Try to retrieve a reference to a sized type.
     ///
     /// This method retrieves a slice of memory using the [`split_raw`](#method.split_raw) method and interprets it as an instance of `T`. Since there is no way to check that the memory is actually a valid instance of `T`, this method is unsafe. The second return value is the space after the instance of `T`.
-    pub fn split_type<T>(self) -> Option<(&'a T, Self)>
+    ///
+    /// If the memory isn't aligned for `T`, this method returns `AtomError::Misaligned`.
+    pub fn split_type<T>(self) -> Result<(&'a T, Self), AtomError>
     where
         T: Unpin + Copy + Send + Sync + Sized + 'static,
     {
-        self.split_raw(size_of::<T>())
-            .map(|(data, rhs)| (unsafe { &*(data.as_ptr() as *const T) }, rhs))
+        let (data, rhs) = self.split_raw(size_of::<T>())?;
+        if data.as_ptr() as usize & (align_of::<T>() - 1) != 0 {
+            return Err(AtomError::Misaligned);
+        }
+        Ok((unsafe { &*(data.as_ptr() as *const T) }, rhs))
     }
 
     /// Try to retrieve the space occupied by an atom.
@@ -94,20 +122,20 @@ impl<'a> Space<'a> {
     /// This method assumes that the space contains an atom and retrieves the space occupied by the atom, including the atom header. The second return value is the rest of the space behind the atom.
     ///
     /// The difference to [`split_atom_body`](#method.split_atom_body) is that the returned space contains the header of the atom and that the type of the atom is not checked.
-    pub fn split_atom(self) -> Option<(Self, Self)> {
+    pub fn split_atom(self) -> Result<(Self, Self), AtomError> {
         let (header, _) = self.split_type::<sys::LV2_Atom>()?;
         self.split_space(size_of::<sys::LV2_Atom>() + header.size as usize)
     }
 
     /// Try to retrieve the body of the atom.
     ///
-    /// This method retrieves the header of the atom. If the type URID in the header matches the given URID, it returns the body of the atom. If not, it returns `None`. The first space is the body of the atom, the second one is the space behind it.
+    /// This method retrieves the header of the atom. If the type URID in the header matches the given URID, it returns the body of the atom. If not, it returns `AtomError::TypeMismatch`. The first space is the body of the atom, the second one is the space behind it.
     ///
     /// The difference to [`split_atom`](#method.split_atom) is that the returned space does not contain the header of the atom and that the type of the atom is checked.
-    pub fn split_atom_body<T: ?Sized>(self, urid: URID<T>) -> Option<(Self, Self)> {
+    pub fn split_atom_body<T: ?Sized>(self, urid: URID<T>) -> Result<(Self, Self), AtomError> {
         let (header, space) = self.split_type::<sys::LV2_Atom>()?;
         if header.type_ != urid.get() {
-            return None;
+            return Err(AtomError::TypeMismatch);
         }
         space.split_space(header.size as usize)
     }
@@ -117,8 +145,7 @@ impl<'a> Space<'a> {
         let data = unsafe {
             std::slice::from_raw_parts(instance as *const T as *const u8, size_of_val(instance))
         };
-        assert_eq!(data.as_ptr() as usize % 8, 0);
-        Space { data: Some(data) }
+        Self::from_slice(data)
     }
 
     /// Concatenate two spaces.
@@ -134,7 +161,7 @@ impl<'a> Space<'a> {
             None => return Some(lhs),
         };
         if unsafe { lhs_data.as_ptr().add(lhs_data.len()) } == rhs_data.as_ptr() {
-            Some(Self::from_slice(unsafe {
+            Some(Self::from_unaligned_slice(unsafe {
                 std::slice::from_raw_parts(lhs_data.as_ptr(), lhs_data.len() + rhs_data.len())
             }))
         } else {
@@ -153,34 +180,81 @@ impl<'a> Space<'a> {
     }
 }
 
+/// A position in a `MutSpace` that it can be rolled back to.
+///
+/// Checkpoints are created with [`MutSpace::checkpoint`](trait.MutSpace.html#tymethod.checkpoint) and contain the number of bytes that have been allocated by the space so far.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Checkpoint {
+    allocated: usize,
+}
+
+impl Checkpoint {
+    /// Create a checkpoint after the given number of allocated bytes.
+    pub fn new(allocated: usize) -> Self {
+        Self { allocated }
+    }
+
+    /// Return the number of bytes that were allocated when the checkpoint was created.
+    pub fn allocated(self) -> usize {
+        self.allocated
+    }
+}
+
 /// A smart pointer that writes atom data to an internal slice.
 ///
 /// The methods provided by this trait are fairly minimalistic. More convenient writing methods are implemented for `dyn MutSpace`.
+///
+/// Allocations are transactional: A space can create a [`Checkpoint`](struct.Checkpoint.html) and later roll back all allocations made after it. The writing methods of this crate use this to undo the partial writes of a failed `init`, which keeps the surrounding atoms valid.
 pub trait MutSpace<'a> {
     /// Try to allocate memory on the internal data slice.
     ///
     /// If `apply_padding` is `true`, the method will assure that the allocated memory is 64-bit-aligned. The first return value is the number of padding bytes that has been used and the second return value is a mutable slice referencing the allocated data.
     ///
     /// After the memory has been allocated, the `MutSpace` can not allocate it again. The next allocated slice is directly behind it.
-    fn allocate(&mut self, size: usize, apply_padding: bool) -> Option<(usize, &'a mut [u8])>;
+    ///
+    /// If there isn't enough space left, this method returns `AtomError::OutOfSpace`.
+    fn allocate(
+        &mut self,
+        size: usize,
+        apply_padding: bool,
+    ) -> Result<(usize, &'a mut [u8]), AtomError>;
 
     /// Try to write data to the internal data slice.
     ///
     /// The method allocates a slice with the [`allocate`](#tymethod.allocate) method and copies the data to the slice.
-    fn write_raw(&mut self, data: &[u8], apply_padding: bool) -> Option<&'a mut [u8]> {
+    fn write_raw(&mut self, data: &[u8], apply_padding: bool) -> Result<&'a mut [u8], AtomError> {
         self.allocate(data.len(), apply_padding).map(|(_, space)| {
             space.copy_from_slice(data);
             space
         })
     }
+
+    /// Create a checkpoint of the current allocation state.
+    fn checkpoint(&self) -> Checkpoint;
+
+    /// Roll back all allocations that have been made after the checkpoint.
+    ///
+    /// The memory that has been allocated after the checkpoint is allocated again by the following calls to `allocate`. If this space is a frame, the size of the atom is reduced accordingly.
+    ///
+    /// # Safety
+    ///
+    /// The checkpoint has to be created by this space, and there may be no references to the memory allocated after the checkpoint, since this memory will be handed out again.
+    ///
+    /// References to written data can only be created by borrowing the space. Once a writing function has returned without handing out such a reference, for example because it returned an error or consumed its writer, nothing refers to the data it has written anymore and it may be rolled back.
+    unsafe fn rollback(&mut self, checkpoint: Checkpoint);
 }
 
 /// A `MutSpace` that directly manages it's own internal data slice.
 pub struct RootMutSpace<'a> {
-    space: Cell<Option<&'a mut [u8]>>,
+    origin: NonNull<u8>,
+    capacity: usize,
     allocated_bytes: usize,
+    space: PhantomData<&'a mut [u8]>,
 }
 
+// The space only hands out parts of the mutably borrowed slice, which may be sent to other threads.
+unsafe impl<'a> Send for RootMutSpace<'a> {}
+
 impl<'a> RootMutSpace<'a> {
     /// Create new space from an atom.
     ///
@@ -202,96 +276,167 @@ impl<'a> RootMutSpace<'a> {
     /// This method takes the space reserved for the value and interprets it as a slice of bytes (`&mut [u8]`).
     pub fn new(space: &'a mut [u8]) -> Self {
         RootMutSpace {
-            space: Cell::new(Some(space)),
+            capacity: space.len(),
+            origin: NonNull::from(space).cast(),
             allocated_bytes: 0,
+            space: PhantomData,
         }
     }
 }
 
 impl<'a> MutSpace<'a> for RootMutSpace<'a> {
-    fn allocate(&mut self, size: usize, apply_padding: bool) -> Option<(usize, &'a mut [u8])> {
-        if self.space.get_mut().is_none() {
-            return None;
-        }
-        let mut space = self.space.replace(None).unwrap();
-
+    fn allocate(
+        &mut self,
+        size: usize,
+        apply_padding: bool,
+    ) -> Result<(usize, &'a mut [u8]), AtomError> {
         let padding = if apply_padding {
             let alignment = self.allocated_bytes % 8;
-            let padding = if alignment == 0 { 0 } else { 8 - alignment };
-            if padding > space.len() {
-                return None;
+            if alignment == 0 {
+                0
+            } else {
+                8 - alignment
             }
-            space = space.split_at_mut(padding).1;
-            self.allocated_bytes += padding;
-            padding
         } else {
             0
         };
 
-        if size > space.len() {
-            return None;
+        let remaining = self.capacity - self.allocated_bytes;
+        if padding > remaining || size > remaining - padding {
+            return Err(AtomError::OutOfSpace);
         }
-        let (lower_slice, upper_slice) = space.split_at_mut(size);
-        self.allocated_bytes += size;
 
-        self.space.set(Some(upper_slice));
-        Some((padding, lower_slice))
+        // The allocated parts never overlap, since the allocation cursor only moves forward, except for rollbacks.
+        let data = unsafe {
+            std::slice::from_raw_parts_mut(
+                self.origin.as_ptr().add(self.allocated_bytes + padding),
+                size,
+            )
+        };
+        self.allocated_bytes += padding + size;
+        Ok((padding, data))
+    }
+
+    fn checkpoint(&self) -> Checkpoint {
+        Checkpoint::new(self.allocated_bytes)
+    }
+
+    unsafe fn rollback(&mut self, checkpoint: Checkpoint) {
+        debug_assert!(checkpoint.allocated() <= self.allocated_bytes);
+        self.allocated_bytes = checkpoint.allocated().min(self.allocated_bytes);
     }
 }
 
-/// Linked list element for dynamic atom writing.
+/// An owned, zero-initialized buffer that is always 64-bit-aligned.
+///
+/// The memory of the buffer is allocated as `u64` values, which is why it's start is always aligned, regardless of the allocator. It can be used like a byte slice and creates [`Space`s](struct.Space.html) for reading and [`RootMutSpace`s](struct.RootMutSpace.html) for writing.
 ///
-/// This struct works in conjunction with [`SpaceHead`](struct.SpaceHead.html) to provide a way to write atoms to dynamically allocated memory.
-pub struct SpaceElement {
-    next: Option<(Box<Self>, Box<[u8]>)>,
+/// # Usage example
+///
+/// ```
+/// # use lv2_core::prelude::*;
+/// # use lv2_urid::prelude::*;
+/// # use lv2_urid::mapper::*;
+/// # use lv2_atom::prelude::*;
+/// # use lv2_atom::space::*;
+/// # let mut mapper = Box::pin(HashURIDMapper::new());
+/// # let interface = mapper.as_mut().make_map_interface();
+/// # let map = Map::new(&interface);
+/// // URID cache creation is omitted.
+/// let urids: AtomURIDCollection = map.populate_collection().unwrap();
+///
+/// let mut buffer = AlignedBuffer::new(256);
+///
+/// // Writing an integer.
+/// (&mut buffer.mut_space() as &mut dyn MutSpace).init(urids.int, 42).unwrap();
+///
+/// // Reading it again.
+/// let atom = UnidentifiedAtom::new(buffer.space());
+/// assert_eq!(42, atom.read(urids.int, ()).unwrap());
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct AlignedBuffer {
+    data: Vec<u64>,
+    len: usize,
+}
+
+impl AlignedBuffer {
+    /// Create a new buffer with `len` zeroed bytes.
+    pub fn new(len: usize) -> Self {
+        Self {
+            data: vec![0; ((len + 7) & !7) / 8],
+            len,
+        }
+    }
+
+    /// Create a new buffer that contains a copy of the given bytes.
+    pub fn from_bytes(bytes: &[u8]) -> Self {
+        let mut buffer = Self::new(bytes.len());
+        buffer.copy_from_slice(bytes);
+        buffer
+    }
+
+    /// Create a space for reading the contents of the buffer.
+    pub fn space(&self) -> Space<'_> {
+        Space::from_slice(self)
+    }
+
+    /// Create a space for writing to the buffer.
+    pub fn mut_space(&mut self) -> RootMutSpace<'_> {
+        RootMutSpace::new(self)
+    }
 }
 
-impl Default for SpaceElement {
-    fn default() -> Self {
-        Self { next: None }
+impl Deref for AlignedBuffer {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        unsafe { std::slice::from_raw_parts(self.data.as_ptr() as *const u8, self.len) }
     }
 }
 
-impl SpaceElement {
-    /// Append an element to the list.
-    ///
-    /// If this is the last element of the list, allocate a slice of the required length and append a new element to the list. If not, do nothing and return `None`.
-    pub fn allocate(&mut self, size: usize) -> Option<(&mut Self, &mut [u8])> {
-        if self.next.is_some() {
-            return None;
+impl DerefMut for AlignedBuffer {
+    fn deref_mut(&mut self) -> &mut [u8] {
+        unsafe { std::slice::from_raw_parts_mut(self.data.as_mut_ptr() as *mut u8, self.len) }
+    }
+}
+
+/// A block of memory in an [`AtomVec`](struct.AtomVec.html).
+struct Block {
+    /// The offset of the first byte of the block in the buffer.
+    start: usize,
+    /// The offset of the first byte that has been written to this block.
+    first: usize,
+    data: Vec<u64>,
+}
+
+impl Block {
+    fn new(start: usize, first: usize, capacity: usize) -> Self {
+        Self {
+            start,
+            first,
+            data: vec![0; ((capacity + 7) & !7) / 8],
         }
+    }
 
-        let new_data = vec![0u8; size].into_boxed_slice();
-        let new_element = Box::new(Self::default());
-        self.next = Some((new_element, new_data));
-        self.next
-            .as_mut()
-            .map(|(new_element, new_data): &mut (Box<Self>, Box<[u8]>)| {
-                (new_element.as_mut(), new_data.as_mut())
-            })
+    fn capacity(&self) -> usize {
+        self.data.len() * size_of::<u64>()
     }
 
-    /// Create a vector containing the data from all elements following this one.
-    pub fn to_vec(&self) -> Vec<u8> {
-        self.iter()
-            .map(|slice| slice.iter())
-            .flatten()
-            .cloned()
-            .collect()
+    fn end(&self) -> usize {
+        self.start + self.capacity()
     }
 
-    /// Return an iterator over the chunks of all elements following this one.
-    pub fn iter(&self) -> impl Iterator<Item = &[u8]> {
-        std::iter::successors(self.next.as_ref(), |element| element.0.next.as_ref())
-            .map(|(_, data)| data.as_ref())
+    fn as_bytes(&self) -> &[u8] {
+        unsafe { std::slice::from_raw_parts(self.data.as_ptr() as *const u8, self.capacity()) }
     }
 }
 
-/// A mutable space that dynamically allocates memory.
+/// A growable, 8-byte-aligned buffer for atoms.
 ///
-/// This space uses a linked list of [`SpaceElement`s](struct.SpaceElement.html) to allocate memory. Every time `allocate` is called, a new element is appended to the list and a new byte slice is created.
+/// Atoms are written to the buffer with an [`AtomVecHead`](struct.AtomVecHead.html), which is created by [`head`](#method.head). Since the references handed out while writing have to stay valid, written data is never moved: If the buffer is exhausted, a new block of memory is allocated that is at least as large as all previous blocks together. Once writing is done, [`make_contiguous`](#method.make_contiguous) moves the data into a single block that is large enough to hold all of it, which makes the following rounds of writing allocation-free.
 ///
-/// In order to use this space and retrieve the written data once it was written, you create a `SpaceElement` and create a new head with it. Then, you use the head like any other `MutSpace` and when you're done, you retrieve the written data by either calling [`to_vec`](struct.SpaceElement.html#method.to_vec) or [`iter`](struct.SpaceElement.html#iter).
+/// The alignment of the written data is the same as it would be in a single block: Data that is padded to 8 bytes is also aligned to 8 bytes in memory.
 ///
 /// # Usage example
 ///
@@ -307,55 +452,169 @@ impl SpaceElement {
 /// // URID cache creation is omitted.
 /// let urids: AtomURIDCollection = map.populate_collection().unwrap();
 ///
-/// // Creating the first element in the list and the writing head.
-/// let mut element = SpaceElement::default();
-/// let mut head = SpaceHead::new(&mut element);
+/// // Creating the buffer and the writing head.
+/// let mut buffer = AtomVec::new();
+/// let mut head = buffer.head();
 ///
 /// // Writing an integer.
 /// (&mut head as &mut dyn MutSpace).init(urids.int, 42).unwrap();
 ///
-/// // Retrieving a continuos vector with the written data and verifying it's contents.
-/// let written_data: Vec<u8> = element.to_vec();
-/// let atom = UnidentifiedAtom::new(Space::from_slice(written_data.as_ref()));
+/// // Retrieving the written data and verifying it's contents.
+/// let written_data: &[u8] = buffer.make_contiguous();
+/// let atom = UnidentifiedAtom::new(Space::from_slice(written_data));
 /// assert_eq!(42, atom.read(urids.int, ()).unwrap());
 /// ```
-pub struct SpaceHead<'a> {
-    element: Option<&'a mut SpaceElement>,
-    allocated_space: usize,
+#[derive(Default)]
+pub struct AtomVec {
+    blocks: Vec<Block>,
+    len: usize,
 }
 
-impl<'a> SpaceHead<'a> {
-    /// Create a new head that references the given element.
-    pub fn new(element: &'a mut SpaceElement) -> Self {
+impl AtomVec {
+    /// Create a new, empty buffer.
+    ///
+    /// The buffer doesn't allocate memory until something is written to it.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a new, empty buffer that can hold at least `capacity` bytes without allocating.
+    pub fn with_capacity(capacity: usize) -> Self {
         Self {
-            element: Some(element),
-            allocated_space: 0,
+            blocks: vec![Block::new(0, 0, capacity)],
+            len: 0,
         }
     }
 
-    fn internal_allocate(&mut self, size: usize) -> Option<&'a mut [u8]> {
-        let element = self.element.take()?;
-        let (new_element, new_space) = element.allocate(size)?;
-        self.element = Some(new_element);
-        self.allocated_space += size;
-        Some(new_space)
+    /// Return the number of written bytes, including padding.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Return `true` if nothing has been written to the buffer.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Return the total number of bytes the buffer can hold without allocating.
+    pub fn capacity(&self) -> usize {
+        self.blocks.iter().map(Block::capacity).sum()
+    }
+
+    /// Remove all written data, but keep the allocated memory.
+    pub fn clear(&mut self) {
+        if self.blocks.len() > 1 {
+            self.blocks = vec![Block::new(0, 0, self.capacity())];
+        }
+        self.len = 0;
+    }
+
+    /// Move all written data into a single block of memory and return it.
+    ///
+    /// The returned slice starts at an 8-byte boundary.
+    pub fn make_contiguous(&mut self) -> &mut [u8] {
+        if self.blocks.len() > 1 {
+            let mut merged = Block::new(0, 0, self.capacity());
+            let target = unsafe {
+                std::slice::from_raw_parts_mut(
+                    merged.data.as_mut_ptr() as *mut u8,
+                    merged.capacity(),
+                )
+            };
+            for (index, block) in self.blocks.iter().enumerate() {
+                let end = self
+                    .blocks
+                    .get(index + 1)
+                    .map(|next| next.first)
+                    .unwrap_or(self.len);
+                target[block.first..end].copy_from_slice(
+                    &block.as_bytes()[block.first - block.start..end - block.start],
+                );
+            }
+            self.blocks = vec![merged];
+        }
+
+        match self.blocks.first_mut() {
+            Some(block) => unsafe {
+                std::slice::from_raw_parts_mut(block.data.as_mut_ptr() as *mut u8, self.len)
+            },
+            None => &mut [],
+        }
+    }
+
+    /// Create a writing head that appends atoms to the buffer.
+    pub fn head(&mut self) -> AtomVecHead<'_> {
+        AtomVecHead {
+            vec: NonNull::from(self),
+            lifetime: PhantomData,
+        }
+    }
+
+    fn truncate(&mut self, len: usize) {
+        self.len = len.min(self.len);
+        while self.blocks.len() > 1 && self.blocks.last().unwrap().first >= self.len {
+            self.blocks.pop();
+        }
     }
 }
 
-impl<'a> MutSpace<'a> for SpaceHead<'a> {
-    fn allocate(&mut self, size: usize, apply_padding: bool) -> Option<(usize, &'a mut [u8])> {
-        let padding: usize = if apply_padding {
-            (8 - self.allocated_space % 8) % 8
+/// A mutable space that appends to an [`AtomVec`](struct.AtomVec.html).
+///
+/// A head is created with [`AtomVec::head`](struct.AtomVec.html#method.head). Since the head mutably borrows the buffer, the written data can be retrieved once the head and all references to the written data are gone.
+pub struct AtomVecHead<'a> {
+    vec: NonNull<AtomVec>,
+    lifetime: PhantomData<&'a mut AtomVec>,
+}
+
+// The head only hands out parts of the mutably borrowed buffer, which may be sent to other threads.
+unsafe impl<'a> Send for AtomVecHead<'a> {}
+
+impl<'a> MutSpace<'a> for AtomVecHead<'a> {
+    fn allocate(
+        &mut self,
+        size: usize,
+        apply_padding: bool,
+    ) -> Result<(usize, &'a mut [u8]), AtomError> {
+        // The head is the only one with access to the buffer and the blocks never move their memory. Therefore, no other reference to the new data exists.
+        let vec = unsafe { &mut *self.vec.as_ptr() };
+
+        let padding = if apply_padding {
+            (8 - vec.len % 8) % 8
         } else {
             0
         };
-
-        if padding != 0 {
-            self.internal_allocate(padding);
+        let start = vec.len + padding;
+        let end = start + size;
+
+        if vec
+            .blocks
+            .last()
+            .map(|block| block.end() < end)
+            .unwrap_or(true)
+        {
+            // The new block starts at an 8-byte boundary to keep the alignment of the written data.
+            let block_start = vec.len & !7;
+            let capacity = vec.capacity().max(end - block_start);
+            vec.blocks.push(Block::new(block_start, vec.len, capacity));
         }
 
-        self.internal_allocate(size)
-            .map(|new_space| (padding, new_space))
+        let block = vec.blocks.last_mut().unwrap();
+        let data = unsafe {
+            std::slice::from_raw_parts_mut(
+                (block.data.as_mut_ptr() as *mut u8).add(start - block.start),
+                size,
+            )
+        };
+        vec.len = end;
+        Ok((padding, data))
+    }
+
+    fn checkpoint(&self) -> Checkpoint {
+        Checkpoint::new(unsafe { self.vec.as_ref() }.len)
+    }
+
+    unsafe fn rollback(&mut self, checkpoint: Checkpoint) {
+        self.vec.as_mut().truncate(checkpoint.allocated());
     }
 }
 
@@ -367,18 +626,25 @@ pub struct FramedMutSpace<'a, 'b> {
 
 impl<'a, 'b> FramedMutSpace<'a, 'b> {
     /// Create a new framed space with the given parent and type URID.
-    pub fn new<A: ?Sized>(parent: &'b mut dyn MutSpace<'a>, urid: URID<A>) -> Option<Self> {
+    pub fn new<A: ?Sized>(
+        parent: &'b mut dyn MutSpace<'a>,
+        urid: URID<A>,
+    ) -> Result<Self, AtomError> {
         let atom = sys::LV2_Atom {
             size: 0,
             type_: urid.get(),
         };
         let atom: &'a mut sys::LV2_Atom = parent.write(&atom, true)?;
-        Some(Self { atom, parent })
+        Ok(Self { atom, parent })
     }
 }
 
 impl<'a, 'b> MutSpace<'a> for FramedMutSpace<'a, 'b> {
-    fn allocate(&mut self, size: usize, apply_padding: bool) -> Option<(usize, &'a mut [u8])> {
+    fn allocate(
+        &mut self,
+        size: usize,
+        apply_padding: bool,
+    ) -> Result<(usize, &'a mut [u8]), AtomError> {
         self.parent
             .allocate(size, apply_padding)
             .map(|(padding, data)| {
@@ -386,13 +652,23 @@ impl<'a, 'b> MutSpace<'a> for FramedMutSpace<'a, 'b> {
                 (padding, data)
             })
     }
+
+    fn checkpoint(&self) -> Checkpoint {
+        self.parent.checkpoint()
+    }
+
+    unsafe fn rollback(&mut self, checkpoint: Checkpoint) {
+        let released = self.parent.checkpoint().allocated() - checkpoint.allocated();
+        self.atom.size -= released as u32;
+        self.parent.rollback(checkpoint);
+    }
 }
 
 impl<'a, 'b> dyn MutSpace<'a> + 'b {
     /// Write a sized object to the space.
     ///
     /// If `apply_padding` is `true`, the method will assure that the written instance is 64-bit-aligned.
-    pub fn write<T>(&mut self, instance: &T, apply_padding: bool) -> Option<&'a mut T>
+    pub fn write<T>(&mut self, instance: &T, apply_padding: bool) -> Result<&'a mut T, AtomError>
     where
         T: Unpin + Copy + Send + Sync + Sized + 'static,
     {
@@ -403,17 +679,26 @@ impl<'a, 'b> dyn MutSpace<'a> + 'b {
         let output_data = self.write_raw(input_data, apply_padding)?;
 
         assert_eq!(size, output_data.len());
-        Some(unsafe { &mut *(output_data.as_mut_ptr() as *mut T) })
+        Ok(unsafe { &mut *(output_data.as_mut_ptr() as *mut T) })
     }
 
     /// Initialize a new atom in the space.
+    ///
+    /// If the initialization fails, everything that has been written for the new atom is rolled back.
     pub fn init<'c, A: Atom<'a, 'c>>(
         &'c mut self,
         urid: URID<A>,
         parameter: A::WriteParameter,
-    ) -> Option<A::WriteHandle> {
-        let new_space = FramedMutSpace::new(self, urid)?;
-        A::init(new_space, parameter)
+    ) -> Result<A::WriteHandle, AtomError> {
+        let checkpoint = self.checkpoint();
+        // The frame and the rollback both borrow the space through this pointer, since the borrow of a successful result has to last for `'c`. An error doesn't borrow the space, which makes it safe to access it again.
+        let this: *mut Self = self;
+        let result = FramedMutSpace::new(unsafe { &mut *this }, urid)
+            .and_then(|space| A::init(space, parameter));
+        if result.is_err() {
+            unsafe { (*this).rollback(checkpoint) };
+        }
+        result
     }
 }
 
@@ -427,16 +712,16 @@ mod tests {
 
     #[test]
     fn test_space() {
-        let mut vector: Vec<u8> = vec![0; 256];
+        let mut vector = AlignedBuffer::new(256);
         for i in 0..128 {
             vector[i] = i as u8;
         }
         unsafe {
-            let ptr = vector.as_mut_slice().as_mut_ptr().add(128) as *mut u32;
+            let ptr = vector.as_mut_ptr().add(128) as *mut u32;
             *(ptr) = 0x42424242;
         }
 
-        let space = Space::from_slice(vector.as_slice());
+        let space = vector.space();
         let (lower_space, space) = space.split_raw(128).unwrap();
         for i in 0..128 {
             assert_eq!(lower_space[i], i as u8);
@@ -446,6 +731,42 @@ mod tests {
         assert_eq!(*integer, 0x42424242);
     }
 
+    #[test]
+    fn test_alignment_validation() {
+        let buffer = AlignedBuffer::new(16);
+        assert_eq!(buffer.as_ptr() as usize % 8, 0);
+        assert!(Space::try_from_slice(&buffer).is_ok());
+        assert_eq!(
+            Space::try_from_slice(&buffer[4..]).map(|_| ()),
+            Err(AtomError::Misaligned)
+        );
+        assert!(Space::try_from_slice(&buffer[4..4]).is_ok());
+
+        let space = Space::from_unaligned_slice(&buffer[4..]);
+        assert_eq!(
+            space.split_type::<u64>().map(|_| ()),
+            Err(AtomError::Misaligned)
+        );
+        assert!(space.split_type::<u32>().is_ok());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_unaligned_from_slice() {
+        let buffer = AlignedBuffer::new(16);
+        Space::from_slice(&buffer[4..]);
+    }
+
+    #[test]
+    fn test_aligned_buffer() {
+        let buffer = AlignedBuffer::from_bytes(&[1, 2, 3]);
+        assert_eq!(buffer.len(), 3);
+        assert_eq!(&buffer[..], &[1, 2, 3]);
+        assert_eq!(buffer.clone(), buffer);
+        assert_ne!(AlignedBuffer::from_bytes(&[1, 2]), buffer);
+        assert_eq!(buffer.space().data(), Some(&[1u8, 2, 3][..]));
+    }
+
     #[test]
     fn test_split_atom() {
         let mut data: Box<[u64]> = Box::new([0; 256]);
@@ -506,8 +827,8 @@ mod tests {
         }
 
         match space.write_raw(test_data.as_slice(), true) {
-            Some(written_data) => assert_eq!(test_data.as_slice(), written_data),
-            None => panic!("Writing failed!"),
+            Ok(written_data) => assert_eq!(test_data.as_slice(), written_data),
+            Err(_) => panic!("Writing failed!"),
         }
 
         let test_atom = sys::LV2_Atom { size: 42, type_: 1 };
@@ -517,15 +838,12 @@ mod tests {
         assert_eq!(written_atom.size, test_atom.size);
         assert_eq!(written_atom.type_, test_atom.type_);
 
-        let created_space = unsafe { RootMutSpace::from_atom(written_atom) }
-            .space
-            .take()
-            .unwrap();
+        let created_space = unsafe { RootMutSpace::from_atom(written_atom) };
         assert_eq!(
-            created_space.as_ptr() as usize,
+            created_space.origin.as_ptr() as usize,
             written_atom as *mut _ as usize
         );
-        assert_eq!(created_space.len(), size_of::<sys::LV2_Atom>() + 42);
+        assert_eq!(created_space.capacity, size_of::<sys::LV2_Atom>() + 42);
 
         let mut atom_frame =
             FramedMutSpace::new(&mut space as &mut dyn MutSpace, urids.chunk).unwrap();
@@ -550,25 +868,121 @@ mod tests {
         );
     }
 
+    fn test_rollback<'a, S: MutSpace<'a>>(mut space: S) {
+        let space = &mut space as &mut dyn MutSpace<'a>;
+        space.write::<u32>(&1, true).unwrap();
+        let checkpoint = space.checkpoint();
+        assert_eq!(checkpoint.allocated(), 4);
+
+        // rolling back a frame
+        {
+            let mut frame = FramedMutSpace::new(space, URID::<()>::new(1).unwrap()).unwrap();
+            let frame_checkpoint = frame.checkpoint();
+            (&mut frame as &mut dyn MutSpace)
+                .write::<u64>(&2, true)
+                .unwrap();
+            assert_eq!(frame.atom.size, 8);
+            unsafe { frame.rollback(frame_checkpoint) };
+            assert_eq!(frame.atom.size, 0);
+            assert_eq!(frame.checkpoint(), frame_checkpoint);
+        }
+
+        // rolling back the parent
+        unsafe { space.rollback(checkpoint) };
+        assert_eq!(space.checkpoint(), checkpoint);
+        let value = space.write::<u32>(&3, false).unwrap();
+        assert_eq!(*value, 3);
+        assert_eq!(space.checkpoint().allocated(), 8);
+    }
+
     #[test]
     fn test_root_mut_space() {
         const MEMORY_SIZE: usize = 256;
         let mut memory: [u64; MEMORY_SIZE] = [0; MEMORY_SIZE];
         let frame: RootMutSpace = RootMutSpace::new(unsafe {
             std::slice::from_raw_parts_mut(
-                (&mut memory).as_mut_ptr() as *mut u8,
+                memory.as_mut_ptr() as *mut u8,
                 MEMORY_SIZE * size_of::<u64>(),
             )
         });
 
         test_mut_space(frame);
+
+        let mut memory: [u64; MEMORY_SIZE] = [0; MEMORY_SIZE];
+        let frame: RootMutSpace = RootMutSpace::new(unsafe {
+            std::slice::from_raw_parts_mut(
+                memory.as_mut_ptr() as *mut u8,
+                MEMORY_SIZE * size_of::<u64>(),
+            )
+        });
+        test_rollback(frame);
+    }
+
+    #[test]
+    fn test_root_mut_space_atomic_allocation() {
+        let mut memory = [0u8; 12];
+        let mut root = RootMutSpace::new(&mut memory[..]);
+        root.allocate(6, false).unwrap();
+        // The padding fits, but the data doesn't.
+        assert_eq!(root.allocate(6, true), Err(AtomError::OutOfSpace));
+        assert_eq!(root.checkpoint().allocated(), 6);
+        assert_eq!(root.allocate(6, false).unwrap().1.len(), 6);
     }
 
     #[test]
-    fn test_space_head() {
-        let mut space = SpaceElement::default();
-        let head = SpaceHead::new(&mut space);
-        test_mut_space(head);
+    fn test_atom_vec() {
+        let mut vec = AtomVec::new();
+        test_mut_space(vec.head());
+
+        let mut vec = AtomVec::new();
+        test_rollback(vec.head());
+        assert_eq!(vec.make_contiguous(), &[1, 0, 0, 0, 3, 0, 0, 0]);
+    }
+
+    #[test]
+    fn test_atom_vec_growth() {
+        let mut vec = AtomVec::new();
+
+        // writing
+        {
+            let mut head = vec.head();
+            let head = &mut head as &mut dyn MutSpace;
+            let first = head.write::<u8>(&0, false).unwrap();
+            for i in 0..1000u32 {
+                let value = head.write::<u64>(&(i as u64), true).unwrap();
+                assert_eq!(value as *mut u64 as usize % 8, 0);
+            }
+            // Earlier data is still valid.
+            *first = 255;
+        }
+        assert!(vec.blocks.len() < 16);
+        assert!(vec.capacity() >= 8 * 1001);
+
+        // reading
+        {
+            let data = vec.make_contiguous();
+            assert_eq!(data.as_ptr() as usize % 8, 0);
+            assert_eq!(data.len(), 8 * 1001);
+            assert_eq!(data[0], 255);
+            for i in 0..1000u64 {
+                let value = unsafe { *(data.as_ptr().add(8 * (i as usize + 1)) as *const u64) };
+                assert_eq!(value, i);
+            }
+        }
+
+        // rewriting without new allocations
+        let capacity = vec.capacity();
+        vec.clear();
+        assert!(vec.is_empty());
+        {
+            let mut head = vec.head();
+            let head = &mut head as &mut dyn MutSpace;
+            for i in 0..1001u64 {
+                head.write::<u64>(&i, true).unwrap();
+            }
+        }
+        assert_eq!(vec.blocks.len(), 1);
+        assert_eq!(vec.capacity(), capacity);
     }
 
     #[test]
@@ -577,7 +991,7 @@ mod tests {
         let mut memory: [u64; MEMORY_SIZE] = [0; MEMORY_SIZE];
         let raw_space: &mut [u8] = unsafe {
             std::slice::from_raw_parts_mut(
-                (&mut memory).as_mut_ptr() as *mut u8,
+                memory.as_mut_ptr() as *mut u8,
                 MEMORY_SIZE * size_of::<u64>(),
             )
         };
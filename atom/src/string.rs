@@ -1,8 +1,8 @@
 //! String handling atoms.
 //!
-//! This module contains two different atoms: The [`String`](struct.String.html) and the [`Literal`](struct.Literal.html). The former is for simple, non-localized UTF-8 strings, like URIs or paths, and the later is either for localized text, e.g. descriptions in the user interface, or RDF literals.
+//! This module contains four different atoms: The [`String`](struct.String.html), the [`Literal`](struct.Literal.html), the [`URI`](struct.URI.html) and the [`Path`](struct.Path.html). The `String` is for simple, non-localized UTF-8 strings, the `Literal` is either for localized text, e.g. descriptions in the user interface, or RDF literals, and the `URI` and `Path` are strings that reference other resources or files.
 //!
-//! Reading and writing these atoms is pretty simple: They don't require a parameter and return a either a `&str` or the literal info and a `&str`. Writing is done with a writing handle which can append strings to the string/literal. When dropped, the handle will append the null character, you therefore don't have to handle it on your own.
+//! Reading and writing these atoms is pretty simple: They don't require a parameter and return a either a `&str`, the literal info and a `&str`, or a `&std::path::Path`. Writing is done with a writing handle which can append strings to the string/literal. When dropped, the handle will append the null character, you therefore don't have to handle it on your own.
 //!
 //! # Example
 //! ```
@@ -28,6 +28,8 @@
 //!
 //! [http://lv2plug.in/ns/ext/atom/atom.html#String](http://lv2plug.in/ns/ext/atom/atom.html#String)
 //! [http://lv2plug.in/ns/ext/atom/atom.html#Literal](http://lv2plug.in/ns/ext/atom/atom.html#Literal)
+//! [http://lv2plug.in/ns/ext/atom/atom.html#URI](http://lv2plug.in/ns/ext/atom/atom.html#URI)
+//! [http://lv2plug.in/ns/ext/atom/atom.html#Path](http://lv2plug.in/ns/ext/atom/atom.html#Path)
 use crate::prelude::*;
 use crate::space::*;
 use core::prelude::*;
@@ -45,6 +47,8 @@ unsafe impl UriBound for Literal {
 #[derive(Clone, Copy, PartialEq, Eq, Debug)]
 /// The type or language URID of a literal.
 pub enum LiteralInfo {
+    /// A plain literal without a language or datatype.
+    Plain,
     Language(URID),
     Datatype(URID),
 }
@@ -58,25 +62,35 @@ where
     type WriteParameter = LiteralInfo;
     type WriteHandle = StringWriter<'a, 'b>;
 
-    fn read(body: Space<'a>, _: ()) -> Option<(LiteralInfo, &'a str)> {
+    fn read(body: Space<'a>, _: ()) -> Result<(LiteralInfo, &'a str), AtomError> {
         let (header, body) = body.split_type::<sys::LV2_Atom_Literal_Body>()?;
-        let info = if header.lang != 0 && header.datatype == 0 {
-            LiteralInfo::Language(URID::new(header.lang)?)
-        } else if header.lang == 0 && header.datatype != 0 {
-            LiteralInfo::Datatype(URID::new(header.datatype)?)
-        } else {
-            return None;
+        let info = match (URID::new(header.lang), URID::new(header.datatype)) {
+            (None, None) => LiteralInfo::Plain,
+            (Some(lang), None) => LiteralInfo::Language(lang),
+            (None, Some(datatype)) => LiteralInfo::Datatype(datatype),
+            (Some(_), Some(_)) => return Err(AtomError::Malformed),
         };
-        let data = body.data()?;
-        std::str::from_utf8(&data[0..data.len() - 1])
+        let data = body.data().ok_or(AtomError::Malformed)?;
+        let data = match data.split_last() {
+            Some((0, data)) => data,
+            _ => return Err(AtomError::Malformed),
+        };
+        std::str::from_utf8(data)
             .or_else(|error| std::str::from_utf8(&data[0..error.valid_up_to()]))
-            .ok()
             .map(|string| (info, string))
+            .map_err(|_| AtomError::Malformed)
     }
 
-    fn init(mut frame: FramedMutSpace<'a, 'b>, info: LiteralInfo) -> Option<StringWriter<'a, 'b>> {
+    fn init(
+        mut frame: FramedMutSpace<'a, 'b>,
+        info: LiteralInfo,
+    ) -> Result<StringWriter<'a, 'b>, AtomError> {
         (&mut frame as &mut dyn MutSpace).write(
             &match info {
+                LiteralInfo::Plain => sys::LV2_Atom_Literal_Body {
+                    lang: 0,
+                    datatype: 0,
+                },
                 LiteralInfo::Language(lang) => sys::LV2_Atom_Literal_Body {
                     lang: lang.get(),
                     datatype: 0,
@@ -88,7 +102,7 @@ where
             },
             true,
         )?;
-        Some(StringWriter { frame })
+        Ok(StringWriter { frame })
     }
 }
 
@@ -110,14 +124,96 @@ where
     type WriteParameter = ();
     type WriteHandle = StringWriter<'a, 'b>;
 
-    fn read(body: Space<'a>, _: ()) -> Option<&'a str> {
-        body.data()
-            .and_then(|data| std::str::from_utf8(data).ok())
-            .map(|string| &string[..string.len() - 1]) // removing the null-terminator
+    fn read(body: Space<'a>, _: ()) -> Result<&'a str, AtomError> {
+        match body.data().and_then(<[u8]>::split_last) {
+            // removing the null-terminator
+            Some((0, data)) => std::str::from_utf8(data).map_err(|_| AtomError::Malformed),
+            _ => Err(AtomError::Malformed),
+        }
+    }
+
+    fn init(frame: FramedMutSpace<'a, 'b>, _: ()) -> Result<StringWriter<'a, 'b>, AtomError> {
+        Ok(StringWriter { frame })
+    }
+}
+
+/// An atom containing a URI.
+///
+/// The URI is stored like a [`String`](struct.String.html), but isn't mapped to a URID. This is useful for URIs that are only used once, like the URIs of files or web resources.
+///
+/// [See also the module documentation.](index.html)
+pub struct URI;
+
+unsafe impl UriBound for URI {
+    const URI: &'static [u8] = sys::LV2_ATOM__URI;
+}
+
+impl<'a, 'b> Atom<'a, 'b> for URI
+where
+    'a: 'b,
+{
+    type ReadParameter = ();
+    type ReadHandle = &'a str;
+    type WriteParameter = ();
+    type WriteHandle = StringWriter<'a, 'b>;
+
+    fn read(body: Space<'a>, _: ()) -> Result<&'a str, AtomError> {
+        String::read(body, ())
     }
 
-    fn init(frame: FramedMutSpace<'a, 'b>, _: ()) -> Option<StringWriter<'a, 'b>> {
-        Some(StringWriter { frame })
+    fn init(frame: FramedMutSpace<'a, 'b>, _: ()) -> Result<StringWriter<'a, 'b>, AtomError> {
+        String::init(frame, ())
+    }
+}
+
+/// An atom containing a local file path.
+///
+/// The path is stored as a UTF-8 string and read as a [`std::path::Path`](https://doc.rust-lang.org/std/path/struct.Path.html).
+///
+/// [See also the module documentation.](index.html)
+pub struct Path;
+
+unsafe impl UriBound for Path {
+    const URI: &'static [u8] = sys::LV2_ATOM__Path;
+}
+
+impl<'a, 'b> Atom<'a, 'b> for Path
+where
+    'a: 'b,
+{
+    type ReadParameter = ();
+    type ReadHandle = &'a std::path::Path;
+    type WriteParameter = ();
+    type WriteHandle = PathWriter<'a, 'b>;
+
+    fn read(body: Space<'a>, _: ()) -> Result<&'a std::path::Path, AtomError> {
+        String::read(body, ()).map(std::path::Path::new)
+    }
+
+    fn init(frame: FramedMutSpace<'a, 'b>, _: ()) -> Result<PathWriter<'a, 'b>, AtomError> {
+        String::init(frame, ()).map(|writer| PathWriter { writer })
+    }
+}
+
+/// Handle to append to a path.
+pub struct PathWriter<'a, 'b> {
+    writer: StringWriter<'a, 'b>,
+}
+
+impl<'a, 'b> PathWriter<'a, 'b> {
+    /// Append a path.
+    ///
+    /// The path is appended as it is, without a separator. This method copies the path to the end of the atom and returns a reference to the copy.
+    ///
+    /// If the path isn't valid UTF-8, this method returns `AtomError::Malformed`, and if the internal space for the atom is not big enough, it returns `AtomError::OutOfSpace`.
+    pub fn append<P: AsRef<std::path::Path>>(
+        &mut self,
+        path: P,
+    ) -> Result<&std::path::Path, AtomError> {
+        let path = path.as_ref().to_str().ok_or(AtomError::Malformed)?;
+        self.writer
+            .append(path)
+            .map(|path| std::path::Path::new(&*path))
     }
 }
 
@@ -131,18 +227,18 @@ impl<'a, 'b> StringWriter<'a, 'b> {
     ///
     /// This method copies the given string to the end of the string atom/literal and then returns a mutable reference to the copy.
     ///
-    /// If the internal space for the atom is not big enough, this method returns `None`.
-    pub fn append(&mut self, string: &str) -> Option<&mut str> {
+    /// If the internal space for the atom is not big enough, this method returns `AtomError::OutOfSpace`.
+    pub fn append(&mut self, string: &str) -> Result<&mut str, AtomError> {
         let data = string.as_bytes();
         let space = self.frame.write_raw(data, false)?;
-        unsafe { Some(std::str::from_utf8_unchecked_mut(space)) }
+        unsafe { Ok(std::str::from_utf8_unchecked_mut(space)) }
     }
 }
 
 impl<'a, 'b> Drop for StringWriter<'a, 'b> {
     fn drop(&mut self) {
         // Null terminator.
-        (&mut self.frame as &mut dyn MutSpace).write(&0u8, false);
+        let _ = (&mut self.frame as &mut dyn MutSpace).write(&0u8, false);
     }
 }
 
@@ -178,7 +274,7 @@ mod tests {
         let map = Map::new(&interface);
         let urids = TestURIDs::from_map(&map).unwrap();
 
-        let mut raw_space: Box<[u8]> = Box::new([0; 256]);
+        let mut raw_space = AlignedBuffer::new(256);
 
         // writing
         {
@@ -228,6 +324,73 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_empty_literal() {
+        let mut raw_space = AlignedBuffer::new(16);
+        unsafe {
+            *(raw_space.as_mut_ptr() as *mut sys::LV2_Atom_Literal_Body) =
+                sys::LV2_Atom_Literal_Body {
+                    lang: 1,
+                    datatype: 0,
+                };
+        }
+
+        let body = Space::from_slice(&raw_space[..size_of::<sys::LV2_Atom_Literal_Body>()]);
+        assert_eq!(Err(AtomError::Malformed), Literal::read(body, ()));
+
+        // The text has to be terminated by a null character.
+        raw_space[8] = b'a';
+        let body = Space::from_slice(&raw_space[..9]);
+        assert_eq!(Err(AtomError::Malformed), Literal::read(body, ()));
+
+        raw_space[8] = 0;
+        let body = Space::from_slice(&raw_space[..9]);
+        assert_eq!(
+            (LiteralInfo::Language(URID::new(1).unwrap()), ""),
+            Literal::read(body, ()).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_path_and_uri() {
+        let mut mapper = Box::pin(HashURIDMapper::new());
+        let interface = mapper.as_mut().make_map_interface();
+        let map = Map::new(&interface);
+        let urids = crate::AtomURIDCollection::from_map(&map).unwrap();
+
+        let mut raw_space = AlignedBuffer::new(256);
+
+        // writing
+        {
+            let mut space = RootMutSpace::new(raw_space.as_mut());
+            let space = &mut space as &mut dyn MutSpace;
+            let mut writer = space.init(urids.path, ()).unwrap();
+            writer.append("/home/lv2/").unwrap();
+            assert_eq!(
+                std::path::Path::new("sample.wav"),
+                writer.append(std::path::Path::new("sample.wav")).unwrap()
+            );
+            drop(writer);
+            space
+                .init(urids.uri, ())
+                .unwrap()
+                .append("http://lv2plug.in/")
+                .unwrap();
+        }
+
+        // reading
+        {
+            let space = Space::from_slice(raw_space.as_ref());
+            let (body, space) = space.split_atom_body(urids.path).unwrap();
+            assert_eq!(
+                std::path::Path::new("/home/lv2/sample.wav"),
+                Path::read(body, ()).unwrap()
+            );
+            let (body, _) = space.split_atom_body(urids.uri).unwrap();
+            assert_eq!("http://lv2plug.in/", URI::read(body, ()).unwrap());
+        }
+    }
+
     #[test]
     fn test_string() {
         let mut mapper = Box::pin(HashURIDMapper::new());
@@ -235,7 +398,7 @@ mod tests {
         let map = Map::new(&interface);
         let urids = crate::AtomURIDCollection::from_map(&map).unwrap();
 
-        let mut raw_space: Box<[u8]> = Box::new([0; 256]);
+        let mut raw_space = AlignedBuffer::new(256);
 
         // writing
         {
@@ -267,4 +430,24 @@ mod tests {
             assert_eq!(string, SAMPLE0.to_owned() + SAMPLE1);
         }
     }
+
+    #[test]
+    fn test_empty_string() {
+        let mut raw_space = AlignedBuffer::new(8);
+
+        assert_eq!(
+            Err(AtomError::Malformed),
+            String::read(Space::from_slice(&raw_space[..0]), ())
+        );
+
+        // The string has to be terminated by a null character.
+        raw_space[0] = b'a';
+        assert_eq!(
+            Err(AtomError::Malformed),
+            String::read(Space::from_slice(&raw_space[..1]), ())
+        );
+
+        raw_space[0] = 0;
+        assert_eq!(Ok(""), String::read(Space::from_slice(&raw_space[..1]), ()));
+    }
 }
@@ -0,0 +1,120 @@
+//! Atom support for the test harness of `lv2-core`.
+//!
+//! The [`AtomHarness`](trait.AtomHarness.html) trait extends [`Harness`](../../lv2_core/test/struct.Harness.html) with methods to write atoms to the buffers of input ports and to read atoms from the buffers of output ports. This module is only available with the `host` feature.
+//!
+//! # Example
+//!
+//! ```
+//! use lv2_atom::prelude::*;
+//! use lv2_atom::sequence::*;
+//! use lv2_atom::test::AtomHarness;
+//! use lv2_core::prelude::*;
+//! use lv2_core::test::Harness;
+//! use lv2_units::prelude::*;
+//! use lv2_urid::prelude::*;
+//!
+//! #[derive(PortCollection)]
+//! struct Ports {
+//!     input: InputPort<AtomPort>,
+//!     output: OutputPort<AtomPort>,
+//! }
+//!
+//! #[derive(FeatureCollection)]
+//! struct Features<'a> {
+//!     map: Map<'a>,
+//! }
+//!
+//! #[derive(URIDCollection)]
+//! struct URIDs {
+//!     atom: AtomURIDCollection,
+//!     units: UnitURIDCollection,
+//! }
+//!
+//! /// A plugin that doubles every `Int` event.
+//! struct Doubler {
+//!     urids: URIDs,
+//! }
+//!
+//! unsafe impl UriBound for Doubler {
+//!     const URI: &'static [u8] = b"urn:rust-lv2:doubler\0";
+//! }
+//!
+//! impl Plugin for Doubler {
+//!     type Ports = Ports;
+//!     type Features = Features<'static>;
+//!
+//!     fn new(_: &PluginInfo, features: Features<'static>) -> Option<Self> {
+//!         Some(Self {
+//!             urids: features.map.populate_collection()?,
+//!         })
+//!     }
+//!
+//!     fn run(&mut self, ports: &mut Ports) {
+//!         let urids = &self.urids;
+//!         let input = ports.input.read(urids.atom.sequence, urids.units.beat).unwrap();
+//!         let mut output = ports
+//!             .output
+//!             .init(urids.atom.sequence, TimeStampURID::Frames(urids.units.frame))
+//!             .unwrap();
+//!         for (stamp, atom) in input {
+//!             if let Ok(value) = atom.read(urids.atom.int, ()) {
+//!                 output.init(stamp, urids.atom.int, value * 2).unwrap();
+//!             }
+//!         }
+//!     }
+//! }
+//!
+//! let mut harness = Harness::<Doubler>::new(44100.0, 64);
+//! let urids: URIDs = Map::new(harness.map_feature()).populate_collection().unwrap();
+//! assert!(harness.instantiate());
+//! harness.activate();
+//!
+//! {
+//!     let mut space = harness.input_space("input");
+//!     let mut writer = (&mut space as &mut dyn MutSpace)
+//!         .init(urids.atom.sequence, TimeStampURID::Frames(urids.units.frame))
+//!         .unwrap();
+//!     writer.init(TimeStamp::Frames(3), urids.atom.int, 21).unwrap();
+//! }
+//! harness.run(64);
+//!
+//! let mut output = harness
+//!     .output_atom("output")
+//!     .read(urids.atom.sequence, urids.units.beat)
+//!     .unwrap();
+//! let (stamp, atom) = output.next().unwrap();
+//! assert_eq!(Some(3), stamp.as_frames());
+//! assert_eq!(42, atom.read(urids.atom.int, ()).unwrap());
+//! assert!(output.next().is_none());
+//! ```
+use crate::space::{RootMutSpace, Space};
+use crate::UnidentifiedAtom;
+use core::plugin::Plugin;
+use core::test::Harness;
+
+/// Extension of the test harness to read and write atoms.
+pub trait AtomHarness {
+    /// Return the space of an input port's buffer, to write an atom into.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is no atom port with the given symbol.
+    fn input_space(&mut self, symbol: &str) -> RootMutSpace<'_>;
+
+    /// Return the atom in an output port's buffer, as written by the last `run` call.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is no atom port with the given symbol.
+    fn output_atom(&self, symbol: &str) -> UnidentifiedAtom<'_>;
+}
+
+impl<P: Plugin> AtomHarness for Harness<P> {
+    fn input_space(&mut self, symbol: &str) -> RootMutSpace<'_> {
+        RootMutSpace::new(self.atom_buffer_mut(symbol))
+    }
+
+    fn output_atom(&self, symbol: &str) -> UnidentifiedAtom<'_> {
+        UnidentifiedAtom::new(Space::from_slice(self.atom_buffer(symbol)))
+    }
+}
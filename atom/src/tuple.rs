@@ -19,7 +19,7 @@
 //!     let input: TupleIterator = ports.input.read(urids.tuple, ()).unwrap();
 //!     let mut output: TupleWriter = ports.output.init(urids.tuple, ()).unwrap();
 //!     for atom in input {
-//!         if let Some(integer) = atom.read(urids.int, ()) {
+//!         if let Ok(integer) = atom.read(urids.int, ()) {
 //!             output.init(urids.int, integer * 2).unwrap();
 //!         } else {
 //!             output.init(urids.int, -1).unwrap();
@@ -54,12 +54,12 @@ where
     type WriteParameter = ();
     type WriteHandle = TupleWriter<'a, 'b>;
 
-    fn read(body: Space<'a>, _: ()) -> Option<TupleIterator<'a>> {
-        Some(TupleIterator { space: body })
+    fn read(body: Space<'a>, _: ()) -> Result<TupleIterator<'a>, AtomError> {
+        Ok(TupleIterator { space: body })
     }
 
-    fn init(frame: FramedMutSpace<'a, 'b>, _: ()) -> Option<TupleWriter<'a, 'b>> {
-        Some(TupleWriter { frame })
+    fn init(frame: FramedMutSpace<'a, 'b>, _: ()) -> Result<TupleWriter<'a, 'b>, AtomError> {
+        Ok(TupleWriter { frame })
     }
 }
 
@@ -74,7 +74,7 @@ impl<'a> Iterator for TupleIterator<'a> {
     type Item = UnidentifiedAtom<'a>;
 
     fn next(&mut self) -> Option<UnidentifiedAtom<'a>> {
-        let (atom, space) = self.space.split_atom()?;
+        let (atom, space) = self.space.split_atom().ok()?;
         self.space = space;
         Some(UnidentifiedAtom::new(atom))
     }
@@ -91,7 +91,7 @@ impl<'a, 'b> TupleWriter<'a, 'b> {
         &'c mut self,
         child_urid: URID<A>,
         child_parameter: A::WriteParameter,
-    ) -> Option<A::WriteHandle> {
+    ) -> Result<A::WriteHandle, AtomError> {
         (&mut self.frame as &mut dyn MutSpace).init(child_urid, child_parameter)
     }
 }
@@ -111,7 +111,7 @@ mod tests {
         let map = Map::new(&interface);
         let urids = crate::AtomURIDCollection::from_map(&map).unwrap();
 
-        let mut raw_space: Box<[u8]> = Box::new([0; 256]);
+        let mut raw_space = AlignedBuffer::new(256);
 
         // writing
         {
@@ -0,0 +1,1706 @@
+//! Turtle serialization of atoms.
+//!
+//! LV2 presets and state files are written in [Turtle](https://www.w3.org/TR/turtle/) and hosts serialize atoms in the format of [sratom](http://drobilla.net/docs/sratom/). This module writes any atom as the object of a statement in this format and parses such statements back into atoms, which allows you to store and inspect atoms offline.
+//!
+//! Atoms are represented as follows:
+//!
+//! | Atom type | Turtle node |
+//! |-----------|-------------|
+//! | `Int`, `Long`, `Float`, `Double`, `Bool` | A literal with the datatype `xsd:int`, `xsd:long`, `xsd:float`, `xsd:double` or `xsd:boolean`. |
+//! | `URID` | The URI. |
+//! | `String` | A plain literal. |
+//! | `URI`, `Path` | A literal with the datatype `xsd:anyURI` or `atom:Path`. |
+//! | `Literal` | A literal with a language tag or a datatype. Plain literals and literals with a language that isn't a Lexvo URI are written like atoms of other types. |
+//! | `Chunk` | A literal with the datatype `xsd:base64Binary`. |
+//! | `Tuple` | `[ a atom:Tuple ; rdf:value ( ... ) ]` |
+//! | `Vector` | `[ a atom:Vector ; atom:childType ... ; rdf:value ( ... ) ]` |
+//! | `Sequence` | `[ a atom:Sequence ; atom:timeUnit ... ; rdf:value ( [ atom:frameTime ... ; rdf:value ... ] ... ) ]` |
+//! | `Object`, `Blank` | A blank node with the class as it's type and the properties as predicates. Objects with an id are written as separate statements about the id and referenced by `[ a <urn:lv2_atom:ObjectReference> ; rdf:value <id> ]`. |
+//! | Other types | `[ a <urn:lv2_atom:RawAtom> ; <urn:lv2_atom:atomType> <type> ; rdf:value "..."^^xsd:base64Binary ]` |
+//! | The null atom | `()` |
+//!
+//! The parser supports the Turtle syntax, except for relative IRIs other than the empty IRI and the anonymous blank node as a statement subject. Bare numbers and booleans are read as `Int` or `Long`, `Double` and `Bool` atoms. Language tags are expanded to [Lexvo](http://lexvo.org) URIs.
+//!
+//! Some information can not be represented in Turtle and is lost: The contexts of properties are ignored, `Blank` atoms are read as `Object`s and literals with a datatype of another atom type are read as atoms of that type.
+//!
+//! # Example
+//!
+//! ```
+//! # use lv2_core::prelude::*;
+//! # use lv2_urid::prelude::*;
+//! # use lv2_urid::mapper::*;
+//! use lv2_atom::prelude::*;
+//! use lv2_atom::space::*;
+//! use lv2_atom::turtle::*;
+//!
+//! # let mut mapper = Box::pin(HashURIDMapper::new());
+//! # let map_interface = mapper.as_mut().make_map_interface();
+//! # let unmap_interface = mapper.as_mut().make_unmap_interface();
+//! # let map = Map::new(&map_interface);
+//! # let unmap = Unmap::new(&unmap_interface);
+//! let urids: AtomURIDCollection = map.populate_collection().unwrap();
+//!
+//! let mut buffer = AlignedBuffer::new(256);
+//! (&mut buffer.mut_space() as &mut dyn MutSpace)
+//!     .init(urids.int, 42)
+//!     .unwrap();
+//! let atom = UnidentifiedAtom::new(buffer.space());
+//!
+//! let text = to_turtle(atom, "urn:my-plugin", "urn:my-plugin#gain", &urids, &unmap).unwrap();
+//! assert!(text.ends_with("<urn:my-plugin>\n\t<urn:my-plugin#gain> \"42\"^^xsd:int .\n"));
+//!
+//! let mut copy = AlignedBuffer::new(256);
+//! from_turtle(&text, "urn:my-plugin", "urn:my-plugin#gain", &mut copy.mut_space(), &urids, &map).unwrap();
+//! assert_eq!(42, UnidentifiedAtom::new(copy.space()).read(urids.int, ()).unwrap());
+//! ```
+use crate::conversion::as_str;
+use crate::object::{Object, Property};
+use crate::scalar::*;
+use crate::space::*;
+use crate::string::LiteralInfo;
+use crate::{Atom, AtomError, AtomURIDCollection, UnidentifiedAtom};
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::ffi::CString;
+use std::fmt;
+use std::str::FromStr;
+use urid::{Map, Unmap, URID};
+
+const RDF: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#";
+const RDF_TYPE: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#type";
+const RDF_VALUE: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#value";
+const XSD: &str = "http://www.w3.org/2001/XMLSchema#";
+const XSD_INT: &str = "http://www.w3.org/2001/XMLSchema#int";
+const XSD_LONG: &str = "http://www.w3.org/2001/XMLSchema#long";
+const XSD_INTEGER: &str = "http://www.w3.org/2001/XMLSchema#integer";
+const XSD_FLOAT: &str = "http://www.w3.org/2001/XMLSchema#float";
+const XSD_DOUBLE: &str = "http://www.w3.org/2001/XMLSchema#double";
+const XSD_DECIMAL: &str = "http://www.w3.org/2001/XMLSchema#decimal";
+const XSD_BOOLEAN: &str = "http://www.w3.org/2001/XMLSchema#boolean";
+const XSD_BASE64: &str = "http://www.w3.org/2001/XMLSchema#base64Binary";
+const XSD_ANY_URI: &str = "http://www.w3.org/2001/XMLSchema#anyURI";
+/// Prefix of the language URIs of literals.
+const LEXVO: &str = "http://lexvo.org/id/iso639-3/";
+/// Class of the nodes that contain the body of an atom without another representation.
+const RAW_ATOM: &str = "urn:lv2_atom:RawAtom";
+/// The type of the atom in a raw atom node.
+const ATOM_TYPE: &str = "urn:lv2_atom:atomType";
+/// Class of the nodes that stand for an object with an id, which is described by separate statements.
+const OBJECT_REFERENCE: &str = "urn:lv2_atom:ObjectReference";
+
+make_conversion_error! {
+    /// Errors that may occur while converting atoms to Turtle or back.
+    pub enum Error {
+        /// The text isn't valid Turtle. The line of the error and a description are attached.
+        Syntax { line: usize, message: &'static str },
+        /// The document doesn't contain a statement with the requested subject and predicate.
+        MissingStatement,
+        /// A node can't be converted to an atom. The string describes the problem.
+        UnsupportedNode(&'static str),
+    }
+
+    fn fmt(f) {
+        Error::Syntax { line, message } => {
+            write!(f, "syntax error in line {}: {}", line, message)
+        }
+        Error::MissingStatement => f.write_str("the statement is missing"),
+        Error::UnsupportedNode(problem) => write!(f, "unsupported node: {}", problem),
+    }
+}
+
+/// Write an atom as the object of a statement.
+///
+/// The returned document contains the prefix declarations, the statement `<subject> <predicate> atom .` and the descriptions of objects with ids.
+pub fn to_turtle(
+    atom: UnidentifiedAtom,
+    subject: &str,
+    predicate: &str,
+    urids: &AtomURIDCollection,
+    unmap: &Unmap,
+) -> Result<String, Error> {
+    let mut writer = Writer {
+        urids,
+        unmap,
+        descriptions: Vec::new(),
+    };
+    let object = writer.node(atom, 1)?;
+
+    let mut document = String::new();
+    for (prefix, namespace) in PREFIXES.iter() {
+        document.push_str(&format!("@prefix {}: <{}> .\n", prefix, namespace));
+    }
+    let statement = vec![(write_iri(predicate), object)];
+    document.push_str(&write_description(&write_iri(subject), &statement));
+    for (subject, properties) in writer.descriptions.iter() {
+        document.push_str(&write_description(subject, properties));
+    }
+    Ok(document)
+}
+
+/// Parse a Turtle document and write the object of a statement as an atom.
+///
+/// The document has to contain a statement with the given subject and predicate. If the conversion fails, everything that has been written is rolled back.
+pub fn from_turtle(
+    text: &str,
+    subject: &str,
+    predicate: &str,
+    space: &mut dyn MutSpace,
+    urids: &AtomURIDCollection,
+    map: &Map,
+) -> Result<(), Error> {
+    let graph = Parser::new(text).parse()?;
+    let node = graph
+        .resources
+        .get(subject)
+        .and_then(|properties| find(properties, predicate))
+        .ok_or(Error::MissingStatement)?;
+
+    let checkpoint = space.checkpoint();
+    let mut converter = Converter {
+        graph: &graph,
+        urids,
+        map,
+        expanding: Vec::new(),
+    };
+    let result = converter.write(node, space);
+    if result.is_err() {
+        // The converter has returned an error, see `MutSpace::rollback`.
+        unsafe { space.rollback(checkpoint) };
+    }
+    result
+}
+
+/// The prefixes that are declared in written documents.
+const PREFIXES: [(&str, &str); 3] = [
+    ("atom", "http://lv2plug.in/ns/ext/atom#"),
+    ("rdf", RDF),
+    ("xsd", XSD),
+];
+
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Encode bytes with base64.
+fn encode_base64(data: &[u8]) -> String {
+    let mut encoded = String::with_capacity(data.len() * 4 / 3 + 4);
+    for chunk in data.chunks(3) {
+        let bits = chunk.iter().enumerate().fold(0u32, |bits, (i, byte)| {
+            bits | (*byte as u32) << (16 - 8 * i)
+        });
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(BASE64[(bits >> (18 - 6 * i) & 63) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+/// Decode base64 data, ignoring whitespace.
+fn decode_base64(text: &str) -> Option<Vec<u8>> {
+    let text: Vec<u8> = text
+        .bytes()
+        .filter(|byte| !byte.is_ascii_whitespace())
+        .collect();
+    if text.len() & 3 != 0 {
+        return None;
+    }
+    let mut data = Vec::with_capacity(text.len() / 4 * 3);
+    for chunk in text.chunks(4) {
+        let padding = chunk.iter().rev().take_while(|byte| **byte == b'=').count();
+        if padding > 2 {
+            return None;
+        }
+        let mut bits = 0u32;
+        for byte in chunk[..4 - padding].iter() {
+            let value = BASE64.iter().position(|digit| digit == byte)? as u32;
+            bits = bits << 6 | value;
+        }
+        bits <<= 6 * padding;
+        for i in 0..3 - padding {
+            data.push((bits >> (16 - 8 * i)) as u8);
+        }
+    }
+    Some(data)
+}
+
+/// Check whether a string can be written as the local part of a prefixed name.
+fn is_local_name(name: &str) -> bool {
+    !name.is_empty()
+        && !name.ends_with('.')
+        && !name.starts_with(&['-', '.'][..])
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-' || c == '.')
+}
+
+/// Write an IRI, abbreviated with a prefix if possible.
+fn write_iri(iri: &str) -> String {
+    for (prefix, namespace) in PREFIXES.iter() {
+        if iri.starts_with(namespace) && is_local_name(&iri[namespace.len()..]) {
+            return format!("{}:{}", prefix, &iri[namespace.len()..]);
+        }
+    }
+    let mut written = String::with_capacity(iri.len() + 2);
+    written.push('<');
+    for c in iri.chars() {
+        match c {
+            '>' | '<' | '"' | '{' | '}' | '|' | '^' | '`' | '\\' | '\0'..=' ' => {
+                written.push_str(&format!("\\u{:04X}", c as u32))
+            }
+            c => written.push(c),
+        }
+    }
+    written.push('>');
+    written
+}
+
+/// Write a quoted string.
+fn write_string(value: &str) -> String {
+    let mut written = String::with_capacity(value.len() + 2);
+    written.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => written.push_str("\\\""),
+            '\\' => written.push_str("\\\\"),
+            '\n' => written.push_str("\\n"),
+            '\r' => written.push_str("\\r"),
+            '\t' => written.push_str("\\t"),
+            c if c.is_control() => written.push_str(&format!("\\u{:04X}", c as u32)),
+            c => written.push(c),
+        }
+    }
+    written.push('"');
+    written
+}
+
+/// Write a literal with a datatype.
+fn write_literal(value: &str, datatype: &str) -> String {
+    format!("{}^^{}", write_string(value), write_iri(datatype))
+}
+
+/// Write a floating-point number in the lexical form of XML Schema.
+fn write_float<T: fmt::Display>(value: T) -> String {
+    let value = value.to_string();
+    match value.as_str() {
+        "inf" => "INF".to_owned(),
+        "-inf" => "-INF".to_owned(),
+        _ => value,
+    }
+}
+
+/// Create the indentation of a line.
+fn indentation(indent: usize) -> String {
+    "\t".repeat(indent)
+}
+
+/// Write a blank node with the given predicates and objects.
+fn write_blank(properties: &[(String, String)], indent: usize) -> String {
+    if properties.is_empty() {
+        return "[]".to_owned();
+    }
+    let mut written = "[\n".to_owned();
+    for (i, (predicate, object)) in properties.iter().enumerate() {
+        let separator = if i + 1 < properties.len() { " ;" } else { "" };
+        written.push_str(&format!(
+            "{}{} {}{}\n",
+            indentation(indent + 1),
+            predicate,
+            object,
+            separator
+        ));
+    }
+    written.push_str(&indentation(indent));
+    written.push(']');
+    written
+}
+
+/// Write a collection.
+fn write_collection(items: &[String], indent: usize) -> String {
+    if items.is_empty() {
+        return "()".to_owned();
+    }
+    let mut written = "(\n".to_owned();
+    for item in items {
+        written.push_str(&format!("{}{}\n", indentation(indent + 1), item));
+    }
+    written.push_str(&indentation(indent));
+    written.push(')');
+    written
+}
+
+/// Write the statements about a subject.
+fn write_description(subject: &str, properties: &[(String, String)]) -> String {
+    let mut written = format!("\n{}\n", subject);
+    for (i, (predicate, object)) in properties.iter().enumerate() {
+        let separator = if i + 1 < properties.len() { " ;" } else { " ." };
+        written.push_str(&format!("\t{} {}{}\n", predicate, object, separator));
+    }
+    written
+}
+
+/// Converter from atoms to Turtle.
+struct Writer<'c> {
+    urids: &'c AtomURIDCollection,
+    unmap: &'c Unmap<'c>,
+    /// The subjects and properties of objects with an id, which are written as separate statements.
+    descriptions: Vec<(String, Vec<(String, String)>)>,
+}
+
+impl<'c> Writer<'c> {
+    /// Unmap a URID.
+    fn uri(&self, urid: URID) -> Result<&'c str, Error> {
+        self.unmap
+            .unmap(urid)
+            .and_then(|uri| uri.to_str().ok())
+            .ok_or(Error::UnknownURID(urid))
+    }
+
+    /// Unmap a URID and write it as an IRI.
+    fn iri(&self, urid: URID) -> Result<String, Error> {
+        self.uri(urid).map(write_iri)
+    }
+
+    /// Write an atom as a node, starting at the given indentation.
+    fn node(&mut self, atom: UnidentifiedAtom, indent: usize) -> Result<String, Error> {
+        let (space, _) = atom.space().split_atom()?;
+        let (header, body) = space.split_type::<sys::LV2_Atom>()?;
+        let type_ = match URID::new(header.type_) {
+            Some(type_) => type_,
+            None => return Ok("()".to_owned()),
+        };
+        let urids = self.urids;
+
+        Ok(if type_ == urids.int {
+            write_literal(&atom.read(urids.int, ())?.to_string(), XSD_INT)
+        } else if type_ == urids.long {
+            write_literal(&atom.read(urids.long, ())?.to_string(), XSD_LONG)
+        } else if type_ == urids.float {
+            write_literal(&write_float(atom.read(urids.float, ())?), XSD_FLOAT)
+        } else if type_ == urids.double {
+            write_literal(&write_float(atom.read(urids.double, ())?), XSD_DOUBLE)
+        } else if type_ == urids.bool {
+            (atom.read(urids.bool, ())? != 0).to_string()
+        } else if type_ == urids.urid {
+            self.iri(atom.read(urids.urid, ())?)?
+        } else if type_ == urids.string {
+            write_string(atom.read(urids.string, ())?)
+        } else if type_ == urids.uri {
+            write_literal(atom.read(urids.uri, ())?, XSD_ANY_URI)
+        } else if type_ == urids.path {
+            let path = atom.read(urids.path, ())?;
+            let path = path.to_str().ok_or(AtomError::Malformed)?;
+            write_literal(path, as_str(sys::LV2_ATOM__Path))
+        } else if type_ == urids.literal {
+            let (info, value) = atom.read(urids.literal, ())?;
+            match info {
+                // A plain literal in Turtle is read as a string.
+                LiteralInfo::Plain => return self.raw(type_, body, indent),
+                LiteralInfo::Language(language) => {
+                    let language = self.uri(language)?;
+                    if language.starts_with(LEXVO) && !language[LEXVO.len()..].is_empty() {
+                        format!("{}@{}", write_string(value), &language[LEXVO.len()..])
+                    } else {
+                        return self.raw(type_, body, indent);
+                    }
+                }
+                LiteralInfo::Datatype(datatype) => write_literal(value, self.uri(datatype)?),
+            }
+        } else if type_ == urids.chunk {
+            write_literal(&encode_base64(atom.read(urids.chunk, ())?), XSD_BASE64)
+        } else if type_ == urids.tuple {
+            let items = atom
+                .read(urids.tuple, ())?
+                .map(|atom| self.node(atom, indent + 2))
+                .collect::<Result<Vec<String>, Error>>()?;
+            write_blank(
+                &[
+                    ("a".to_owned(), write_iri(as_str(sys::LV2_ATOM__Tuple))),
+                    (write_iri(RDF_VALUE), write_collection(&items, indent + 1)),
+                ],
+                indent,
+            )
+        } else if type_ == urids.vector::<Int>() {
+            let (vector_header, _) = body.split_type::<sys::LV2_Atom_Vector_Body>()?;
+            let child_type = URID::new(vector_header.child_type).ok_or(AtomError::Malformed)?;
+            let items: Vec<String> = if child_type == urids.int {
+                let children = atom.read(urids.vector(), urids.int)?;
+                children
+                    .iter()
+                    .map(|child| write_literal(&child.to_string(), XSD_INT))
+                    .collect()
+            } else if child_type == urids.long {
+                let children = atom.read(urids.vector(), urids.long)?;
+                children
+                    .iter()
+                    .map(|child| write_literal(&child.to_string(), XSD_LONG))
+                    .collect()
+            } else if child_type == urids.float {
+                let children = atom.read(urids.vector(), urids.float)?;
+                children
+                    .iter()
+                    .map(|child| write_literal(&write_float(child), XSD_FLOAT))
+                    .collect()
+            } else if child_type == urids.double {
+                let children = atom.read(urids.vector(), urids.double)?;
+                children
+                    .iter()
+                    .map(|child| write_literal(&write_float(child), XSD_DOUBLE))
+                    .collect()
+            } else if child_type == urids.bool {
+                let children = atom.read(urids.vector(), urids.bool)?;
+                children
+                    .iter()
+                    .map(|child| (*child != 0).to_string())
+                    .collect()
+            } else if child_type == urids.urid {
+                let children = atom.read(urids.vector(), urids.urid)?;
+                children
+                    .iter()
+                    .map(|child| self.iri(*child))
+                    .collect::<Result<_, Error>>()?
+            } else {
+                return self.raw(type_, body, indent);
+            };
+            write_blank(
+                &[
+                    ("a".to_owned(), write_iri(as_str(sys::LV2_ATOM__Vector))),
+                    (
+                        write_iri(as_str(sys::LV2_ATOM__childType)),
+                        self.iri(child_type)?,
+                    ),
+                    (write_iri(RDF_VALUE), write_collection(&items, indent + 1)),
+                ],
+                indent,
+            )
+        } else if type_ == urids.object || self.uri(type_)? == as_str(sys::LV2_ATOM__Blank) {
+            let (object_header, _) = body.split_type::<sys::LV2_Atom_Object_Body>()?;
+            let (_, reader) = Object::read(body, ())?;
+            let id = URID::new(object_header.id);
+            let property_indent = if id.is_some() { 0 } else { indent };
+
+            let mut properties = Vec::new();
+            if let Some(otype) = URID::new(object_header.otype) {
+                properties.push(("a".to_owned(), self.iri(otype)?));
+            }
+            for (header, value) in reader {
+                properties.push((
+                    self.iri(header.key)?,
+                    self.node(value, property_indent + 1)?,
+                ));
+            }
+
+            match id {
+                Some(id) => {
+                    let id = self.iri(id)?;
+                    self.descriptions.push((id.clone(), properties));
+                    write_blank(
+                        &[
+                            ("a".to_owned(), write_iri(OBJECT_REFERENCE)),
+                            (write_iri(RDF_VALUE), id),
+                        ],
+                        indent,
+                    )
+                }
+                None => write_blank(&properties, indent),
+            }
+        } else if type_ == urids.sequence {
+            let (sequence_header, mut events) = body.split_type::<sys::LV2_Atom_Sequence_Body>()?;
+            let unit = URID::new(sequence_header.unit);
+            let beats = match unit {
+                Some(unit) => self.uri(unit)? == as_str(sys::LV2_UNITS__beat),
+                None => false,
+            };
+            let mut items = Vec::new();
+            while !events.data().map(<[u8]>::is_empty).unwrap_or(true) {
+                let (stamp, space) = if beats {
+                    let (stamp, space) = events.split_type::<f64>()?;
+                    (
+                        (
+                            write_iri(as_str(sys::LV2_ATOM__beatTime)),
+                            write_literal(&write_float(stamp), XSD_DOUBLE),
+                        ),
+                        space,
+                    )
+                } else {
+                    let (stamp, space) = events.split_type::<i64>()?;
+                    (
+                        (
+                            write_iri(as_str(sys::LV2_ATOM__frameTime)),
+                            stamp.to_string(),
+                        ),
+                        space,
+                    )
+                };
+                let (atom, space) = space.split_atom()?;
+                let value = self.node(UnidentifiedAtom::new(atom), indent + 3)?;
+                items.push(write_blank(
+                    &[stamp, (write_iri(RDF_VALUE), value)],
+                    indent + 2,
+                ));
+                events = space;
+            }
+
+            let mut properties = vec![("a".to_owned(), write_iri(as_str(sys::LV2_ATOM__Sequence)))];
+            if let Some(unit) = unit {
+                properties.push((write_iri(as_str(sys::LV2_ATOM__timeUnit)), self.iri(unit)?));
+            }
+            properties.push((write_iri(RDF_VALUE), write_collection(&items, indent + 1)));
+            write_blank(&properties, indent)
+        } else {
+            return self.raw(type_, body, indent);
+        })
+    }
+
+    /// Write the raw body of an atom.
+    fn raw(&self, type_: URID, body: Space, indent: usize) -> Result<String, Error> {
+        let data = body.data().unwrap_or(&[]);
+        Ok(write_blank(
+            &[
+                ("a".to_owned(), write_iri(RAW_ATOM)),
+                (write_iri(ATOM_TYPE), self.iri(type_)?),
+                (
+                    write_iri(RDF_VALUE),
+                    write_literal(&encode_base64(data), XSD_BASE64),
+                ),
+            ],
+            indent,
+        ))
+    }
+}
+
+/// A node of a parsed document.
+#[derive(Clone, Debug, PartialEq)]
+enum Node {
+    /// A resource with an IRI.
+    Iri(String),
+    /// A blank node, identified by it's index in the graph.
+    Blank(usize),
+    /// A literal with an optional datatype or language tag.
+    Literal {
+        value: String,
+        datatype: Option<String>,
+        language: Option<String>,
+    },
+    /// A collection of nodes.
+    Collection(Vec<Node>),
+}
+
+/// The properties of a subject, in the order of their appearance.
+type Properties = Vec<(String, Node)>;
+
+/// Find the first object of a predicate.
+fn find<'a>(properties: &'a [(String, Node)], predicate: &str) -> Option<&'a Node> {
+    properties
+        .iter()
+        .find(|(key, _)| key == predicate)
+        .map(|(_, object)| object)
+}
+
+/// The statements of a parsed document, grouped by their subjects.
+#[derive(Default)]
+struct Graph {
+    resources: HashMap<String, Properties>,
+    blanks: Vec<Properties>,
+}
+
+/// Parser for Turtle documents.
+struct Parser<'t> {
+    text: &'t str,
+    position: usize,
+    line: usize,
+    base: String,
+    prefixes: HashMap<String, String>,
+    labels: HashMap<String, usize>,
+    graph: Graph,
+}
+
+impl<'t> Parser<'t> {
+    fn new(text: &'t str) -> Self {
+        Self {
+            text,
+            position: 0,
+            line: 1,
+            base: String::new(),
+            prefixes: HashMap::new(),
+            labels: HashMap::new(),
+            graph: Graph::default(),
+        }
+    }
+
+    /// Create a syntax error at the current position.
+    fn error<T>(&self, message: &'static str) -> Result<T, Error> {
+        Err(Error::Syntax {
+            line: self.line,
+            message,
+        })
+    }
+
+    fn rest(&self) -> &'t str {
+        &self.text[self.position..]
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    fn advance(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.position += c.len_utf8();
+        if c == '\n' {
+            self.line += 1;
+        }
+        Some(c)
+    }
+
+    /// Skip whitespace and comments.
+    fn skip_whitespace(&mut self) {
+        while let Some(c) = self.peek() {
+            if c == '#' {
+                while !matches!(self.advance(), Some('\n') | None) {}
+            } else if c.is_whitespace() {
+                self.advance();
+            } else {
+                break;
+            }
+        }
+    }
+
+    /// Skip whitespace and consume the character if it's next.
+    fn consume(&mut self, expected: char) -> bool {
+        self.skip_whitespace();
+        if self.peek() == Some(expected) {
+            self.advance();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, expected: char, message: &'static str) -> Result<(), Error> {
+        if self.consume(expected) {
+            Ok(())
+        } else {
+            self.error(message)
+        }
+    }
+
+    /// Check whether a keyword follows, ignoring the case.
+    fn keyword(&mut self, keyword: &str) -> bool {
+        let rest = self.rest();
+        let matches = rest.len() >= keyword.len()
+            && rest.is_char_boundary(keyword.len())
+            && rest[..keyword.len()].eq_ignore_ascii_case(keyword)
+            && !rest[keyword.len()..].starts_with(is_name_char);
+        if matches {
+            self.position += keyword.len();
+        }
+        matches
+    }
+
+    fn parse(mut self) -> Result<Graph, Error> {
+        loop {
+            self.skip_whitespace();
+            if self.peek().is_none() {
+                return Ok(self.graph);
+            }
+            if self.consume('@') {
+                if self.keyword("prefix") {
+                    self.parse_prefix()?;
+                } else if self.keyword("base") {
+                    self.parse_base()?;
+                } else {
+                    return self.error("unknown directive");
+                }
+                self.expect('.', "expected '.' after the directive")?;
+            } else if self.keyword("prefix") {
+                self.parse_prefix()?;
+            } else if self.keyword("base") {
+                self.parse_base()?;
+            } else {
+                self.parse_triples()?;
+                self.expect('.', "expected '.' after the statement")?;
+            }
+        }
+    }
+
+    fn parse_prefix(&mut self) -> Result<(), Error> {
+        self.skip_whitespace();
+        let start = self.position;
+        while self.peek().map(is_name_char).unwrap_or(false) {
+            self.advance();
+        }
+        let prefix = self.text[start..self.position].to_owned();
+        if !self.consume(':') {
+            return self.error("expected ':' after the prefix name");
+        }
+        let namespace = self.parse_iri_ref()?;
+        self.prefixes.insert(prefix, namespace);
+        Ok(())
+    }
+
+    fn parse_base(&mut self) -> Result<(), Error> {
+        self.base = self.parse_iri_ref()?;
+        Ok(())
+    }
+
+    fn parse_triples(&mut self) -> Result<(), Error> {
+        self.skip_whitespace();
+        let subject = match self.peek() {
+            Some('[') => {
+                let subject = self.parse_blank_property_list()?;
+                self.skip_whitespace();
+                if self.peek() == Some('.') {
+                    return Ok(());
+                }
+                subject
+            }
+            Some('(') => return self.error("collections are not supported as subjects"),
+            _ => {
+                let subject = self.parse_resource()?;
+                if let Node::Literal { .. } = subject {
+                    return self.error("literals can not be subjects");
+                }
+                subject
+            }
+        };
+        self.parse_predicate_object_list(&subject)
+    }
+
+    fn parse_predicate_object_list(&mut self, subject: &Node) -> Result<(), Error> {
+        loop {
+            self.skip_whitespace();
+            let predicate = if self.peek() == Some('a') && self.keyword("a") {
+                RDF_TYPE.to_owned()
+            } else {
+                match self.parse_resource()? {
+                    Node::Iri(iri) => iri,
+                    _ => return self.error("expected an IRI as the predicate"),
+                }
+            };
+            loop {
+                let object = self.parse_object()?;
+                self.add(subject, predicate.clone(), object);
+                if !self.consume(',') {
+                    break;
+                }
+            }
+            if !self.consume(';') {
+                return Ok(());
+            }
+            while self.consume(';') {}
+            self.skip_whitespace();
+            if matches!(self.peek(), Some('.') | Some(']') | None) {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Add a statement to the graph.
+    fn add(&mut self, subject: &Node, predicate: String, object: Node) {
+        let properties = match subject {
+            Node::Iri(iri) => self.graph.resources.entry(iri.clone()).or_default(),
+            Node::Blank(index) => &mut self.graph.blanks[*index],
+            _ => unreachable!(),
+        };
+        properties.push((predicate, object));
+    }
+
+    /// Create a new blank node.
+    fn new_blank(&mut self) -> usize {
+        self.graph.blanks.push(Vec::new());
+        self.graph.blanks.len() - 1
+    }
+
+    fn parse_blank_property_list(&mut self) -> Result<Node, Error> {
+        self.expect('[', "expected '['")?;
+        let node = Node::Blank(self.new_blank());
+        if !self.consume(']') {
+            self.parse_predicate_object_list(&node)?;
+            self.expect(']', "expected ']' after the blank node")?;
+        }
+        Ok(node)
+    }
+
+    fn parse_object(&mut self) -> Result<Node, Error> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some('[') => self.parse_blank_property_list(),
+            Some('(') => {
+                self.advance();
+                let mut items = Vec::new();
+                while !self.consume(')') {
+                    if self.peek().is_none() {
+                        return self.error("unterminated collection");
+                    }
+                    items.push(self.parse_object()?);
+                }
+                Ok(Node::Collection(items))
+            }
+            Some('"') | Some('\'') => self.parse_literal(),
+            Some(c) if c.is_ascii_digit() || c == '+' || c == '-' || c == '.' => {
+                self.parse_number()
+            }
+            _ => {
+                if self.keyword("true") || self.keyword("false") {
+                    let value = self.text[..self.position].ends_with("true");
+                    return Ok(Node::Literal {
+                        value: value.to_string(),
+                        datatype: Some(XSD_BOOLEAN.to_owned()),
+                        language: None,
+                    });
+                }
+                self.parse_resource()
+            }
+        }
+    }
+
+    /// Parse an IRI, a prefixed name or a labeled blank node.
+    fn parse_resource(&mut self) -> Result<Node, Error> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some('<') => self.parse_iri_ref().map(Node::Iri),
+            Some('_') if self.rest().starts_with("_:") => {
+                self.position += 2;
+                let start = self.position;
+                while self.peek().map(is_name_char).unwrap_or(false) {
+                    self.advance();
+                }
+                let label = self.text[start..self.position].trim_end_matches('.');
+                self.position = start + label.len();
+                if label.is_empty() {
+                    return self.error("expected the label of the blank node");
+                }
+                let index = match self.labels.get(label) {
+                    Some(index) => *index,
+                    None => {
+                        let index = self.new_blank();
+                        self.labels.insert(label.to_owned(), index);
+                        index
+                    }
+                };
+                Ok(Node::Blank(index))
+            }
+            _ => self.parse_prefixed_name().map(Node::Iri),
+        }
+    }
+
+    fn parse_iri_ref(&mut self) -> Result<String, Error> {
+        self.expect('<', "expected '<'")?;
+        let mut iri = String::new();
+        loop {
+            match self.advance() {
+                Some('>') => break,
+                Some('\\') => iri.push(self.parse_unicode_escape()?),
+                Some(c) if c == '\n' || c == ' ' => return self.error("invalid character in IRI"),
+                Some(c) => iri.push(c),
+                None => return self.error("unterminated IRI"),
+            }
+        }
+        if iri.is_empty() || !iri.contains(':') {
+            if self.base.is_empty() && !iri.is_empty() {
+                return self.error("relative IRIs are not supported");
+            }
+            iri = format!("{}{}", self.base, iri);
+        }
+        Ok(iri)
+    }
+
+    fn parse_prefixed_name(&mut self) -> Result<String, Error> {
+        let start = self.position;
+        while self.peek().map(is_name_char).unwrap_or(false) {
+            self.advance();
+        }
+        let prefix = self.text[start..self.position].to_owned();
+        if self.peek() != Some(':') {
+            return self.error("expected an IRI or a prefixed name");
+        }
+        self.advance();
+        let namespace = match self.prefixes.get(&prefix) {
+            Some(namespace) => namespace.clone(),
+            None => return self.error("undefined prefix"),
+        };
+        let start = self.position;
+        while self
+            .peek()
+            .map(|c| is_name_char(c) || c == ':')
+            .unwrap_or(false)
+        {
+            self.advance();
+        }
+        let local = self.text[start..self.position].trim_end_matches('.');
+        self.position = start + local.len();
+        Ok(format!("{}{}", namespace, local))
+    }
+
+    /// Parse the rest of a `\u` or `\U` escape sequence.
+    fn parse_unicode_escape(&mut self) -> Result<char, Error> {
+        let length = match self.advance() {
+            Some('u') => 4,
+            Some('U') => 8,
+            _ => return self.error("invalid escape sequence"),
+        };
+        let rest = self.rest();
+        let code = rest
+            .get(..length)
+            .and_then(|digits| u32::from_str_radix(digits, 16).ok())
+            .and_then(std::char::from_u32);
+        match code {
+            Some(c) => {
+                self.position += length;
+                Ok(c)
+            }
+            None => self.error("invalid escape sequence"),
+        }
+    }
+
+    fn parse_literal(&mut self) -> Result<Node, Error> {
+        let quote = self.advance().unwrap();
+        let long = self.rest().starts_with(&format!("{}{}", quote, quote)[..]);
+        if long {
+            self.position += 2;
+        }
+        let mut value = String::new();
+        loop {
+            if long
+                && self
+                    .rest()
+                    .starts_with(&format!("{}{}{}", quote, quote, quote)[..])
+            {
+                self.position += 3;
+                break;
+            }
+            match self.advance() {
+                Some(c) if c == quote && !long => break,
+                Some('\\') => match self.peek() {
+                    Some('u') | Some('U') => value.push(self.parse_unicode_escape()?),
+                    Some(c) => {
+                        self.advance();
+                        value.push(match c {
+                            't' => '\t',
+                            'b' => '\u{8}',
+                            'n' => '\n',
+                            'r' => '\r',
+                            'f' => '\u{c}',
+                            '"' | '\'' | '\\' => c,
+                            _ => return self.error("invalid escape sequence"),
+                        });
+                    }
+                    None => return self.error("unterminated string"),
+                },
+                Some('\n') if !long => return self.error("unterminated string"),
+                Some(c) => value.push(c),
+                None => return self.error("unterminated string"),
+            }
+        }
+
+        let mut datatype = None;
+        let mut language = None;
+        if self.rest().starts_with('@') {
+            self.advance();
+            let start = self.position;
+            while self
+                .peek()
+                .map(|c| c.is_ascii_alphanumeric() || c == '-')
+                .unwrap_or(false)
+            {
+                self.advance();
+            }
+            if start == self.position {
+                return self.error("expected a language tag");
+            }
+            language = Some(self.text[start..self.position].to_owned());
+        } else if self.rest().starts_with("^^") {
+            self.position += 2;
+            match self.parse_resource()? {
+                Node::Iri(iri) => datatype = Some(iri),
+                _ => return self.error("expected an IRI as the datatype"),
+            }
+        }
+        Ok(Node::Literal {
+            value,
+            datatype,
+            language,
+        })
+    }
+
+    fn parse_number(&mut self) -> Result<Node, Error> {
+        let start = self.position;
+        if matches!(self.peek(), Some('+') | Some('-')) {
+            self.advance();
+        }
+        let mut datatype = XSD_INTEGER;
+        while self.peek().map(|c| c.is_ascii_digit()).unwrap_or(false) {
+            self.advance();
+        }
+        let rest = self.rest();
+        if rest.starts_with('.') && rest[1..].starts_with(|c: char| c.is_ascii_digit()) {
+            datatype = XSD_DECIMAL;
+            self.advance();
+            while self.peek().map(|c| c.is_ascii_digit()).unwrap_or(false) {
+                self.advance();
+            }
+        }
+        if matches!(self.peek(), Some('e') | Some('E')) {
+            datatype = XSD_DOUBLE;
+            self.advance();
+            if matches!(self.peek(), Some('+') | Some('-')) {
+                self.advance();
+            }
+            while self.peek().map(|c| c.is_ascii_digit()).unwrap_or(false) {
+                self.advance();
+            }
+        }
+        let value = &self.text[start..self.position];
+        if !value.contains(|c: char| c.is_ascii_digit()) {
+            return self.error("invalid number");
+        }
+        Ok(Node::Literal {
+            value: value.to_owned(),
+            datatype: Some(datatype.to_owned()),
+            language: None,
+        })
+    }
+}
+
+/// Check whether a character may appear in a prefix, local name or blank node label.
+fn is_name_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '-' || c == '.'
+}
+
+/// Parse the lexical form of a number.
+fn parse_number<T: FromStr>(value: &str) -> Result<T, Error> {
+    let value = match value {
+        "INF" | "+INF" => "inf",
+        "-INF" => "-inf",
+        value => value.trim_start_matches('+'),
+    };
+    value
+        .parse()
+        .map_err(|_| Error::UnsupportedNode("invalid number"))
+}
+
+/// Converter from parsed nodes to atoms.
+struct Converter<'g> {
+    graph: &'g Graph,
+    urids: &'g AtomURIDCollection,
+    map: &'g Map<'g>,
+    /// The ids of the objects that are currently written, to detect cycles.
+    expanding: Vec<&'g str>,
+}
+
+impl<'g> Converter<'g> {
+    /// Map a URI to a URID.
+    fn urid(&self, uri: &str) -> Result<URID, Error> {
+        CString::new(uri)
+            .ok()
+            .and_then(|cstring| self.map.map_uri(&cstring))
+            .ok_or_else(|| Error::UnmappableURI(uri.to_owned()))
+    }
+
+    /// Map the IRI of a node to a URID.
+    fn node_urid(&self, node: &Node) -> Result<URID, Error> {
+        match node {
+            Node::Iri(iri) => self.urid(iri),
+            _ => Err(Error::UnsupportedNode("expected an IRI")),
+        }
+    }
+
+    /// Retrieve the lexical form of a literal.
+    fn lexical(node: &Node) -> Result<&str, Error> {
+        match node {
+            Node::Literal { value, .. } => Ok(value),
+            _ => Err(Error::UnsupportedNode("expected a literal")),
+        }
+    }
+
+    /// Retrieve the items of a collection.
+    fn items(node: Option<&Node>) -> Result<&[Node], Error> {
+        match node {
+            Some(Node::Collection(items)) => Ok(items),
+            _ => Err(Error::UnsupportedNode("expected a collection")),
+        }
+    }
+
+    fn write(&mut self, node: &'g Node, space: &mut dyn MutSpace) -> Result<(), Error> {
+        let urids = self.urids;
+        match node {
+            Node::Iri(iri) => {
+                space.init(urids.urid, self.urid(iri)?)?;
+                Ok(())
+            }
+            Node::Collection(items) => {
+                if items.is_empty() {
+                    space.write(&sys::LV2_Atom { size: 0, type_: 0 }, true)?;
+                    Ok(())
+                } else {
+                    self.write_tuple(items, space)
+                }
+            }
+            Node::Literal {
+                value,
+                datatype,
+                language,
+            } => self.write_literal(value, datatype.as_deref(), language.as_deref(), space),
+            Node::Blank(index) => {
+                let properties = &self.graph.blanks[*index];
+                let type_ = find(properties, RDF_TYPE);
+                let type_iri = match type_ {
+                    Some(Node::Iri(iri)) => Some(iri.as_str()),
+                    _ => None,
+                };
+                let value = find(properties, RDF_VALUE);
+
+                if type_iri == Some(OBJECT_REFERENCE) {
+                    let id = match value {
+                        Some(Node::Iri(id)) => id.as_str(),
+                        _ => return Err(Error::UnsupportedNode("expected the IRI of an object")),
+                    };
+                    if self.expanding.contains(&id) {
+                        return Err(Error::UnsupportedNode("the object contains itself"));
+                    }
+                    let properties = self
+                        .graph
+                        .resources
+                        .get(id)
+                        .ok_or(Error::UnsupportedNode("the object isn't described"))?;
+                    self.expanding.push(id);
+                    let result = self.write_object(Some(self.urid(id)?), properties, space);
+                    self.expanding.pop();
+                    result
+                } else if type_iri == Some(RAW_ATOM) {
+                    let type_ = find(properties, ATOM_TYPE)
+                        .ok_or(Error::UnsupportedNode("the raw atom has no type"))?;
+                    let data = match value {
+                        Some(Node::Literal {
+                            value,
+                            datatype: Some(datatype),
+                            ..
+                        }) if datatype == XSD_BASE64 => decode_base64(value)
+                            .ok_or(Error::UnsupportedNode("invalid base64 data"))?,
+                        _ => return Err(Error::UnsupportedNode("expected base64 data")),
+                    };
+                    let mut frame = FramedMutSpace::new(space, self.node_urid(type_)?)?;
+                    frame.write_raw(&data, false)?;
+                    Ok(())
+                } else if type_iri == Some(as_str(sys::LV2_ATOM__Tuple)) {
+                    self.write_tuple(Self::items(value)?, space)
+                } else if type_iri == Some(as_str(sys::LV2_ATOM__Vector)) {
+                    let child_type = find(properties, as_str(sys::LV2_ATOM__childType))
+                        .ok_or(Error::UnsupportedNode("the vector has no child type"))?;
+                    self.write_vector(self.node_urid(child_type)?, Self::items(value)?, space)
+                } else if type_iri == Some(as_str(sys::LV2_ATOM__Sequence)) {
+                    let unit = match find(properties, as_str(sys::LV2_ATOM__timeUnit)) {
+                        Some(unit) => self.node_urid(unit)?.get(),
+                        None => 0,
+                    };
+                    self.write_sequence(unit, Self::items(value)?, space)
+                } else {
+                    self.write_object(None, properties, space)
+                }
+            }
+        }
+    }
+
+    fn write_literal(
+        &self,
+        value: &str,
+        datatype: Option<&str>,
+        language: Option<&str>,
+        space: &mut dyn MutSpace,
+    ) -> Result<(), Error> {
+        let urids = self.urids;
+        if let Some(language) = language {
+            let language = self.urid(&format!("{}{}", LEXVO, language.to_lowercase()))?;
+            space
+                .init(urids.literal, LiteralInfo::Language(language))?
+                .append(value)?;
+            return Ok(());
+        }
+        match datatype {
+            None => {
+                space.init(urids.string, ())?.append(value)?;
+            }
+            Some(XSD_INT) => {
+                space.init(urids.int, parse_number(value)?)?;
+            }
+            Some(XSD_LONG) => {
+                space.init(urids.long, parse_number(value)?)?;
+            }
+            Some(XSD_INTEGER) => {
+                let integer: i64 = parse_number(value)?;
+                match i32::try_from(integer) {
+                    Ok(integer) => space.init(urids.int, integer).map(|_| ())?,
+                    Err(_) => space.init(urids.long, integer).map(|_| ())?,
+                }
+            }
+            Some(XSD_FLOAT) => {
+                space.init(urids.float, parse_number(value)?)?;
+            }
+            Some(XSD_DOUBLE) | Some(XSD_DECIMAL) => {
+                space.init(urids.double, parse_number(value)?)?;
+            }
+            Some(XSD_BOOLEAN) => {
+                space.init(urids.bool, parse_bool(value)? as i32)?;
+            }
+            Some(XSD_BASE64) => {
+                let data =
+                    decode_base64(value).ok_or(Error::UnsupportedNode("invalid base64 data"))?;
+                space.init(urids.chunk, ())?.write_raw(&data, false)?;
+            }
+            Some(XSD_ANY_URI) => {
+                space.init(urids.uri, ())?.append(value)?;
+            }
+            Some(datatype) if datatype == as_str(sys::LV2_ATOM__Path) => {
+                space.init(urids.path, ())?.append(value)?;
+            }
+            Some(datatype) => {
+                space
+                    .init(urids.literal, LiteralInfo::Datatype(self.urid(datatype)?))?
+                    .append(value)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn write_tuple(&mut self, items: &'g [Node], space: &mut dyn MutSpace) -> Result<(), Error> {
+        let mut frame = FramedMutSpace::new(space, self.urids.tuple)?;
+        for item in items {
+            self.write(item, &mut frame)?;
+        }
+        Ok(())
+    }
+
+    fn write_vector(
+        &self,
+        child_type: URID,
+        items: &[Node],
+        space: &mut dyn MutSpace,
+    ) -> Result<(), Error> {
+        let urids = self.urids;
+        if child_type == urids.int {
+            let mut writer = space.init(urids.vector(), urids.int)?;
+            for item in items {
+                writer.push(parse_number(Self::lexical(item)?)?)?;
+            }
+        } else if child_type == urids.long {
+            let mut writer = space.init(urids.vector(), urids.long)?;
+            for item in items {
+                writer.push(parse_number(Self::lexical(item)?)?)?;
+            }
+        } else if child_type == urids.float {
+            let mut writer = space.init(urids.vector(), urids.float)?;
+            for item in items {
+                writer.push(parse_number(Self::lexical(item)?)?)?;
+            }
+        } else if child_type == urids.double {
+            let mut writer = space.init(urids.vector(), urids.double)?;
+            for item in items {
+                writer.push(parse_number(Self::lexical(item)?)?)?;
+            }
+        } else if child_type == urids.bool {
+            let mut writer = space.init(urids.vector(), urids.bool)?;
+            for item in items {
+                writer.push(parse_bool(Self::lexical(item)?)? as i32)?;
+            }
+        } else if child_type == urids.urid {
+            let mut writer = space.init(urids.vector(), urids.urid)?;
+            for item in items {
+                writer.push(self.node_urid(item)?)?;
+            }
+        } else {
+            return Err(Error::UnsupportedNode("unsupported child type of a vector"));
+        }
+        Ok(())
+    }
+
+    fn write_sequence(
+        &mut self,
+        unit: u32,
+        items: &'g [Node],
+        space: &mut dyn MutSpace,
+    ) -> Result<(), Error> {
+        let mut frame = FramedMutSpace::new(space, self.urids.sequence)?;
+        let frame = &mut frame as &mut dyn MutSpace;
+        frame.write(&sys::LV2_Atom_Sequence_Body { unit, pad: 0 }, true)?;
+        for item in items {
+            let properties = match item {
+                Node::Blank(index) => &self.graph.blanks[*index],
+                _ => return Err(Error::UnsupportedNode("expected an event")),
+            };
+            if let Some(beats) = find(properties, as_str(sys::LV2_ATOM__beatTime)) {
+                frame.write(&parse_number::<f64>(Self::lexical(beats)?)?, true)?;
+            } else if let Some(frames) = find(properties, as_str(sys::LV2_ATOM__frameTime)) {
+                frame.write(&parse_number::<i64>(Self::lexical(frames)?)?, true)?;
+            } else {
+                return Err(Error::UnsupportedNode("the event has no time stamp"));
+            }
+            let value = find(properties, RDF_VALUE)
+                .ok_or(Error::UnsupportedNode("the event has no value"))?;
+            self.write(value, frame)?;
+        }
+        Ok(())
+    }
+
+    fn write_object(
+        &mut self,
+        id: Option<URID>,
+        properties: &'g [(String, Node)],
+        space: &mut dyn MutSpace,
+    ) -> Result<(), Error> {
+        let mut frame = FramedMutSpace::new(space, self.urids.object)?;
+        let frame = &mut frame as &mut dyn MutSpace;
+        let otype = match find(properties, RDF_TYPE) {
+            Some(otype) => self.node_urid(otype)?.get(),
+            None => 0,
+        };
+        frame.write(
+            &sys::LV2_Atom_Object_Body {
+                id: id.map(|id| id.get()).unwrap_or(0),
+                otype,
+            },
+            true,
+        )?;
+
+        let mut skipped_type = false;
+        for (key, value) in properties.iter() {
+            // The first type is the class of the object, all others are properties.
+            if key == RDF_TYPE && !skipped_type {
+                skipped_type = true;
+                continue;
+            }
+            Property::write_header(frame, self.urid(key)?, None)?;
+            self.write(value, frame)?;
+        }
+        Ok(())
+    }
+}
+
+/// Parse the lexical form of a boolean.
+fn parse_bool(value: &str) -> Result<bool, Error> {
+    match value {
+        "true" | "1" => Ok(true),
+        "false" | "0" => Ok(false),
+        _ => Err(Error::UnsupportedNode("invalid boolean")),
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "host")]
+mod tests {
+    use crate::conversion::test_support::*;
+    use crate::prelude::*;
+    use crate::turtle::*;
+    use core::prelude::*;
+    use urid::mapper::*;
+    use urid::prelude::*;
+
+    struct German;
+    unsafe impl UriBound for German {
+        const URI: &'static [u8] = b"http://lexvo.org/id/iso639-3/de\0";
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut mapper = Box::pin(HashURIDMapper::new());
+        let map_interface = mapper.as_mut().make_map_interface();
+        let unmap_interface = mapper.as_mut().make_unmap_interface();
+        let map = Map::new(&map_interface);
+        let unmap = Unmap::new(&unmap_interface);
+        let urids = AtomURIDCollection::from_map(&map).unwrap();
+        let key = map.map_type::<Key>().unwrap();
+        let custom = map.map_type::<Custom>().unwrap();
+        let subject = map
+            .map_uri(&CString::new("urn:test#subject").unwrap())
+            .unwrap();
+        let value = map.map_uri(&CString::new(RDF_VALUE).unwrap()).unwrap();
+
+        // writing
+        let mut buffer = AlignedBuffer::new(1024);
+        {
+            let mut space = buffer.mut_space();
+            let mut tuple = (&mut space as &mut dyn MutSpace)
+                .init(urids.tuple, ())
+                .unwrap();
+            write_atoms(&mut tuple, &urids, &map);
+            // URIDs of described resources are not confused with the objects.
+            tuple.init(urids.urid, key.into_general()).unwrap();
+            tuple.init(urids.urid, subject).unwrap();
+            // An object that looks like an atom of another type.
+            tuple
+                .init(
+                    urids.object,
+                    ObjectHeader {
+                        id: None,
+                        otype: custom.into_general(),
+                    },
+                )
+                .unwrap()
+                .init(value, None, urids.chunk, ())
+                .unwrap()
+                .write_raw(&[1, 2, 3], false)
+                .unwrap();
+        }
+
+        // converting to Turtle
+        let text = to_turtle(
+            UnidentifiedAtom::new(buffer.space()),
+            "urn:test#subject",
+            "urn:test#predicate",
+            &urids,
+            &unmap,
+        )
+        .unwrap();
+        assert!(text.starts_with("@prefix atom: <http://lv2plug.in/ns/ext/atom#> .\n"));
+        for expected in [
+            "\"-42\"^^xsd:int",
+            "\"1099511627776\"^^xsd:long",
+            "\"0.1\"^^xsd:float",
+            "\"-INF\"^^xsd:double",
+            "\ttrue\n",
+            "<urn:test#class>",
+            "\"Hello \\\"World\\\"\\n\"",
+            "\"urn:uri\"^^xsd:anyURI",
+            "\"/tmp\"^^atom:Path",
+            "\"Hello\"@eng",
+            "\"value\"^^<urn:test#key>",
+            "\"AAH+/w==\"^^xsd:base64Binary",
+            "atom:childType atom:Float",
+            "\"INF\"^^xsd:float",
+            "a <urn:lv2_atom:ObjectReference> ;",
+            "a <urn:lv2_atom:RawAtom> ;",
+            "<urn:lv2_atom:atomType> <urn:test#custom> ;",
+            "\"AQID\"^^xsd:base64Binary",
+            "atom:timeUnit <http://lv2plug.in/ns/extensions/units#beat>",
+            "atom:beatTime \"0.25\"^^xsd:double",
+            "atom:frameTime 7",
+            "\n<urn:test#key>\n\ta <urn:test#class> ;\n\t<urn:test#key> \"1\"^^xsd:int ;\n",
+        ]
+        .iter()
+        {
+            assert!(
+                text.contains(expected),
+                "{} is missing in:\n{}",
+                expected,
+                text
+            );
+        }
+
+        // converting back
+        let mut copy = AlignedBuffer::new(1024);
+        from_turtle(
+            &text,
+            "urn:test#subject",
+            "urn:test#predicate",
+            &mut copy.mut_space(),
+            &urids,
+            &map,
+        )
+        .unwrap();
+        assert_eq!(atom_bytes(&buffer), atom_bytes(&copy));
+        let copy = UnidentifiedAtom::new(copy.space());
+        assert_eq!(copy.read(urids.tuple, ()).unwrap().count(), ATOM_COUNT + 3);
+
+        // the null atom
+        let mut null = AlignedBuffer::new(16);
+        (&mut null.mut_space() as &mut dyn MutSpace)
+            .write(&sys::LV2_Atom { size: 0, type_: 0 }, true)
+            .unwrap();
+        let text = to_turtle(
+            UnidentifiedAtom::new(null.space()),
+            "urn:test#subject",
+            "urn:test#predicate",
+            &urids,
+            &unmap,
+        )
+        .unwrap();
+        assert!(text.ends_with("<urn:test#subject>\n\t<urn:test#predicate> () .\n"));
+        let mut copy = AlignedBuffer::new(16);
+        from_turtle(
+            &text,
+            "urn:test#subject",
+            "urn:test#predicate",
+            &mut copy.mut_space(),
+            &urids,
+            &map,
+        )
+        .unwrap();
+        assert_eq!(atom_bytes(&copy), &[0; 8]);
+    }
+
+    #[test]
+    fn test_parsing() {
+        let mut mapper = Box::pin(HashURIDMapper::new());
+        let map_interface = mapper.as_mut().make_map_interface();
+        let map = Map::new(&map_interface);
+        let urids = AtomURIDCollection::from_map(&map).unwrap();
+        let class = map.map_type::<Class>().unwrap();
+        let key = map.map_type::<Key>().unwrap();
+
+        let text = r#"
+            # A hand-written preset.
+            PREFIX test: <urn:test#>
+            @prefix xsd: <http://www.w3.org/2001/XMLSchema#> .
+
+            test:subject test:predicate _:state ; test:other 1, 2 .
+
+            _:state a test:class ;
+                test:key 42 , 1e3 ;
+                test:key -0.5 ;
+                test:key false ;
+                test:key '''long
+string''' ;
+                test:key "\u00e4"@de ;
+                test:key ( 3000000000 "2"^^xsd:float ) ;
+                test:key [] .
+        "#;
+
+        let mut buffer = AlignedBuffer::new(512);
+        from_turtle(
+            text,
+            "urn:test#subject",
+            "urn:test#predicate",
+            &mut buffer.mut_space(),
+            &urids,
+            &map,
+        )
+        .unwrap();
+
+        let (header, reader) = UnidentifiedAtom::new(buffer.space())
+            .read(urids.object, ())
+            .unwrap();
+        assert_eq!(header.id, None);
+        assert_eq!(header.otype, class);
+        let values: Vec<UnidentifiedAtom> = reader
+            .map(|(header, value)| {
+                assert_eq!(header.key, key);
+                value
+            })
+            .collect();
+        assert_eq!(values.len(), 8);
+        assert_eq!(values[0].read(urids.int, ()).unwrap(), 42);
+        assert_eq!(values[1].read(urids.double, ()).unwrap(), 1000.0);
+        assert_eq!(values[2].read(urids.double, ()).unwrap(), -0.5);
+        assert_eq!(values[3].read(urids.bool, ()).unwrap(), 0);
+        assert_eq!(values[4].read(urids.string, ()).unwrap(), "long\nstring");
+        let (info, value) = values[5].read(urids.literal, ()).unwrap();
+        assert_eq!(value, "ä");
+        match info {
+            LiteralInfo::Language(language) => {
+                assert_eq!(language, map.map_type::<German>().unwrap())
+            }
+            _ => panic!("Not a language"),
+        }
+        let mut tuple = values[6].read(urids.tuple, ()).unwrap();
+        assert_eq!(
+            tuple.next().unwrap().read(urids.long, ()).unwrap(),
+            3_000_000_000
+        );
+        assert_eq!(tuple.next().unwrap().read(urids.float, ()).unwrap(), 2.0);
+        assert!(tuple.next().is_none());
+        let (header, body) = values[7].space().split_type::<sys::LV2_Atom>().unwrap();
+        assert_eq!(header.type_, urids.object);
+        assert_eq!(body.data().unwrap(), &[0; 8]);
+
+        // the other statement is ignored, unless it's requested
+        let mut buffer = AlignedBuffer::new(64);
+        from_turtle(
+            text,
+            "urn:test#subject",
+            "urn:test#other",
+            &mut buffer.mut_space(),
+            &urids,
+            &map,
+        )
+        .unwrap();
+        assert_eq!(
+            UnidentifiedAtom::new(buffer.space())
+                .read(urids.int, ())
+                .unwrap(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        let mut mapper = Box::pin(HashURIDMapper::new());
+        let map_interface = mapper.as_mut().make_map_interface();
+        let map = Map::new(&map_interface);
+        let urids = AtomURIDCollection::from_map(&map).unwrap();
+
+        let invalid = [
+            (
+                "<urn:s> <urn:p> 1",
+                Error::Syntax {
+                    line: 1,
+                    message: "expected '.' after the statement",
+                },
+            ),
+            (
+                "<urn:s>\n<urn:p> \"open .",
+                Error::Syntax {
+                    line: 2,
+                    message: "unterminated string",
+                },
+            ),
+            (
+                "<urn:s> <urn:p> test:value .",
+                Error::Syntax {
+                    line: 1,
+                    message: "undefined prefix",
+                },
+            ),
+            ("<urn:s> <urn:q> 1 .", Error::MissingStatement),
+            (
+                "<urn:s> <urn:p> \"abc\"^^<http://www.w3.org/2001/XMLSchema#int> .",
+                Error::UnsupportedNode("invalid number"),
+            ),
+            (
+                "<urn:s> <urn:p> [ a <http://lv2plug.in/ns/ext/atom#Tuple> ; <urn:k> 1 ] .",
+                Error::UnsupportedNode("expected a collection"),
+            ),
+        ];
+
+        for (text, error) in invalid.iter() {
+            let mut buffer = AlignedBuffer::new(256);
+            assert_eq!(
+                from_turtle(
+                    text,
+                    "urn:s",
+                    "urn:p",
+                    &mut buffer.mut_space(),
+                    &urids,
+                    &map,
+                ),
+                Err(error.clone()),
+                "{}",
+                text
+            );
+        }
+
+        // a failed conversion is rolled back
+        let mut buffer = AlignedBuffer::new(256);
+        {
+            let mut space = buffer.mut_space();
+            from_turtle(
+                "<urn:s> <urn:p> ( 1 2 \"x\"^^<http://www.w3.org/2001/XMLSchema#long> ) .",
+                "urn:s",
+                "urn:p",
+                &mut space,
+                &urids,
+                &map,
+            )
+            .unwrap_err();
+            (&mut space as &mut dyn MutSpace)
+                .init(urids.int, 5)
+                .unwrap();
+        }
+        assert_eq!(
+            UnidentifiedAtom::new(buffer.space())
+                .read(urids.int, ())
+                .unwrap(),
+            5
+        );
+    }
+}
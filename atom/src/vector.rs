@@ -28,15 +28,18 @@
 //!
 //! You may note that, unlike other atoms, the vector's URID is retrieved by calling the `vector` method. This is because two vectors with a different item type are considered two different types, and therefore would have the different URIDs. In reality, however, all vectors have the same URID and the `vector` method returns it with the fitting type.
 //!
+//! The [`Sound`](struct.Sound.html) atom is a vector of `Float`s that contains audio samples. It is read and written just like a vector of floats.
+//!
 //! # Specification
 //!
 //! [http://lv2plug.in/ns/ext/atom/atom.html#Vector](http://lv2plug.in/ns/ext/atom/atom.html#Vector)
-use crate::scalar::ScalarAtom;
+//! [http://lv2plug.in/ns/ext/atom/atom.html#Sound](http://lv2plug.in/ns/ext/atom/atom.html#Sound)
+use crate::scalar::{Float, ScalarAtom};
 use crate::space::*;
 use crate::*;
 use core::prelude::*;
 use std::marker::PhantomData;
-use std::mem::size_of;
+use std::mem::{align_of, size_of};
 use urid::prelude::*;
 
 /// An atom containg an array of scalar atom bodies.
@@ -60,43 +63,81 @@ where
     type WriteParameter = URID<C>;
     type WriteHandle = VectorWriter<'a, 'b, C>;
 
-    fn read(body: Space<'a>, child_urid: URID<C>) -> Option<&'a [C::InternalType]> {
+    fn read(body: Space<'a>, child_urid: URID<C>) -> Result<&'a [C::InternalType], AtomError> {
         let (header, body) = body.split_type::<sys::LV2_Atom_Vector_Body>()?;
 
-        if header.child_type != child_urid
-            || header.child_size as usize != size_of::<C::InternalType>()
-        {
-            return None;
+        if header.child_type != child_urid {
+            return Err(AtomError::TypeMismatch);
+        }
+        if header.child_size as usize != size_of::<C::InternalType>() {
+            return Err(AtomError::Malformed);
         }
 
-        let data = body.data()?;
+        let data = body.data().ok_or(AtomError::Malformed)?;
 
-        assert_eq!(data.len() % size_of::<C::InternalType>(), 0);
+        if data.len() % size_of::<C::InternalType>() != 0 {
+            return Err(AtomError::Malformed);
+        }
+        if data.as_ptr() as usize & (align_of::<C::InternalType>() - 1) != 0 {
+            return Err(AtomError::Misaligned);
+        }
         let children_count = data.len() / size_of::<C::InternalType>();
 
         let children = unsafe {
             std::slice::from_raw_parts(data.as_ptr() as *const C::InternalType, children_count)
         };
-        Some(children)
+        Ok(children)
     }
 
     fn init(
         mut frame: FramedMutSpace<'a, 'b>,
         child_urid: URID<C>,
-    ) -> Option<VectorWriter<'a, 'b, C>> {
+    ) -> Result<VectorWriter<'a, 'b, C>, AtomError> {
         let body = sys::LV2_Atom_Vector_Body {
             child_type: child_urid.get(),
             child_size: size_of::<C::InternalType>() as u32,
         };
         (&mut frame as &mut dyn MutSpace).write(&body, false)?;
 
-        Some(VectorWriter {
+        Ok(VectorWriter {
             frame,
             type_: PhantomData,
         })
     }
 }
 
+/// An atom containing audio samples.
+///
+/// The body of this atom is a vector of floats. Reading and writing it requires the URID of the `Float` atom, just like the vector.
+///
+/// [See also the module documentation.](index.html)
+pub struct Sound;
+
+unsafe impl UriBound for Sound {
+    const URI: &'static [u8] = sys::LV2_ATOM__Sound;
+}
+
+impl<'a, 'b> Atom<'a, 'b> for Sound
+where
+    'a: 'b,
+{
+    type ReadParameter = URID<Float>;
+    type ReadHandle = &'a [f32];
+    type WriteParameter = URID<Float>;
+    type WriteHandle = VectorWriter<'a, 'b, Float>;
+
+    fn read(body: Space<'a>, float_urid: URID<Float>) -> Result<&'a [f32], AtomError> {
+        Vector::<Float>::read(body, float_urid)
+    }
+
+    fn init(
+        frame: FramedMutSpace<'a, 'b>,
+        float_urid: URID<Float>,
+    ) -> Result<VectorWriter<'a, 'b, Float>, AtomError> {
+        Vector::<Float>::init(frame, float_urid)
+    }
+}
+
 /// Handle to append elements to a vector.
 ///
 /// This works by allocating a slice of memory behind the vector and then writing your data to it.
@@ -107,14 +148,14 @@ pub struct VectorWriter<'a, 'b, A: ScalarAtom> {
 
 impl<'a, 'b, A: ScalarAtom> VectorWriter<'a, 'b, A> {
     /// Push a single value to the vector.
-    pub fn push(&mut self, child: A::InternalType) -> Option<&mut A::InternalType> {
+    pub fn push(&mut self, child: A::InternalType) -> Result<&mut A::InternalType, AtomError> {
         (&mut self.frame as &mut dyn MutSpace).write(&child, false)
     }
 
     /// Append a slice of undefined memory to the vector.
     ///
     /// Using this method, you don't need to have the elements in memory before you can write them.
-    pub fn allocate(&mut self, size: usize) -> Option<&mut [A::InternalType]> {
+    pub fn allocate(&mut self, size: usize) -> Result<&mut [A::InternalType], AtomError> {
         self.frame
             .allocate(size_of::<A::InternalType>() * size, false)
             .map(|(_, data)| unsafe {
@@ -123,7 +164,10 @@ impl<'a, 'b, A: ScalarAtom> VectorWriter<'a, 'b, A> {
     }
 
     /// Append multiple elements to the vector.
-    pub fn append(&mut self, data: &[A::InternalType]) -> Option<&mut [A::InternalType]> {
+    pub fn append(
+        &mut self,
+        data: &[A::InternalType],
+    ) -> Result<&mut [A::InternalType], AtomError> {
         let raw_data = unsafe {
             std::slice::from_raw_parts(data.as_ptr() as *const u8, std::mem::size_of_val(data))
         };
@@ -157,7 +201,7 @@ mod tests {
         let map = Map::new(&interface);
         let urids = crate::AtomURIDCollection::from_map(&map).unwrap();
 
-        let mut raw_space: Box<[u8]> = Box::new([0; 256]);
+        let mut raw_space = AlignedBuffer::new(256);
 
         // writing
         {
@@ -165,8 +209,8 @@ mod tests {
             let mut writer = (&mut space as &mut dyn MutSpace)
                 .init(urids.vector(), urids.int)
                 .unwrap();
-            writer.append(&[42; CHILD_COUNT - 1]);
-            writer.push(1);
+            writer.append(&[42; CHILD_COUNT - 1]).unwrap();
+            writer.push(1).unwrap();
         }
 
         // verifying
@@ -203,4 +247,61 @@ mod tests {
             assert_eq!(children[children.len() - 1], 1);
         }
     }
+
+    #[test]
+    fn test_misaligned_vector() {
+        let mut mapper = Box::pin(HashURIDMapper::new());
+        let interface = mapper.as_mut().make_map_interface();
+        let map = Map::new(&interface);
+        let urids = crate::AtomURIDCollection::from_map(&map).unwrap();
+
+        let mut raw_space = AlignedBuffer::new(32);
+        let header = sys::LV2_Atom_Vector_Body {
+            child_type: urids.long.get(),
+            child_size: size_of::<i64>() as u32,
+        };
+        unsafe {
+            let body = raw_space.as_mut_ptr().add(4);
+            *(body as *mut sys::LV2_Atom_Vector_Body) = header;
+        }
+
+        // The children of the vector start at byte 12, which isn't aligned for `i64`.
+        let body = Space::from_unaligned_slice(&raw_space[4..28]);
+        assert_eq!(
+            Err(AtomError::Misaligned),
+            Vector::<Long>::read(body, urids.long)
+        );
+    }
+
+    #[test]
+    fn test_sound() {
+        let mut mapper = Box::pin(HashURIDMapper::new());
+        let interface = mapper.as_mut().make_map_interface();
+        let map = Map::new(&interface);
+        let urids = crate::AtomURIDCollection::from_map(&map).unwrap();
+
+        let mut raw_space = AlignedBuffer::new(256);
+
+        // writing
+        {
+            let mut space = RootMutSpace::new(raw_space.as_mut());
+            let mut writer = (&mut space as &mut dyn MutSpace)
+                .init(urids.sound, urids.float)
+                .unwrap();
+            writer.append(&[0.0, 0.5, 1.0]).unwrap();
+            writer.push(-1.0).unwrap();
+        }
+
+        // reading
+        {
+            let space = Space::from_slice(raw_space.as_ref());
+            assert_eq!(
+                Err(AtomError::TypeMismatch),
+                space.split_atom_body(urids.vector::<Float>()).map(|_| ())
+            );
+            let (body, _) = space.split_atom_body(urids.sound).unwrap();
+            let samples = Sound::read(body, urids.float).unwrap();
+            assert_eq!(&[0.0, 0.5, 1.0, -1.0], samples);
+        }
+    }
 }
@@ -59,12 +59,12 @@ impl Plugin for AtomPlugin {
 
         for (time_stamp, atom) in sequence_reader {
             match atom.read(self.urids.atom.int, ()) {
-                Some(number) => {
+                Ok(number) => {
                     sequence_writer
                         .init::<Int>(time_stamp, self.urids.atom.int, number * 2)
                         .unwrap();
                 }
-                None => {
+                Err(_) => {
                     sequence_writer.forward(time_stamp, atom).unwrap();
                 }
             }
@@ -98,7 +98,7 @@ fn main() {
     let urids: URIDs = map.populate_collection().unwrap();
 
     // Preparing the input atom.
-    let mut input_atom_space: Box<[u8]> = Box::new([0; 256]);
+    let mut input_atom_space = AlignedBuffer::new(256);
     {
         let mut space = RootMutSpace::new(input_atom_space.as_mut());
         let mut writer = (&mut space as &mut dyn MutSpace)
@@ -119,7 +119,7 @@ fn main() {
     }
 
     // preparing the output atom.
-    let mut output_atom_space: Box<[u8]> = Box::new([0; 256]);
+    let mut output_atom_space = AlignedBuffer::new(256);
     {
         let mut space = RootMutSpace::new(output_atom_space.as_mut());
         (&mut space as &mut dyn MutSpace)
@@ -180,3 +180,21 @@ fn main() {
         }
     }
 }
+
+#[test]
+fn test_metadata() {
+    use core::metadata::PluginMetadata;
+
+    let mut turtle: Vec<u8> = Vec::new();
+    PluginMetadata::new::<AtomPlugin>()
+        .write_turtle(&mut turtle)
+        .unwrap();
+    let turtle = std::string::String::from_utf8(turtle).unwrap();
+    assert!(turtle.contains("@prefix atom: <http://lv2plug.in/ns/ext/atom#> .\n"));
+    assert!(turtle.contains(
+        "        a lv2:InputPort , <http://lv2plug.in/ns/ext/atom#AtomPort> ;\n        lv2:index 0 ;\n        lv2:symbol \"input\" ;\n        lv2:name \"input\" ;\n        atom:bufferType atom:Sequence\n"
+    ));
+    assert!(turtle.contains(
+        "        a lv2:OutputPort , <http://lv2plug.in/ns/ext/atom#AtomPort> ;\n        lv2:index 1 ;\n        lv2:symbol \"output\" ;\n        lv2:name \"output\" ;\n        atom:bufferType atom:Sequence\n"
+    ));
+}
@@ -0,0 +1,215 @@
+extern crate lv2_atom as atom;
+extern crate lv2_core as core;
+extern crate lv2_urid as urid;
+
+use atom::prelude::*;
+use atom::space::*;
+use core::prelude::*;
+use urid::mapper::*;
+use urid::prelude::*;
+
+struct Note;
+unsafe impl UriBound for Note {
+    const URI: &'static [u8] = b"urn:rust-lv2:note\0";
+}
+
+struct Rest;
+unsafe impl UriBound for Rest {
+    const URI: &'static [u8] = b"urn:rust-lv2:rest\0";
+}
+
+struct Pitch;
+unsafe impl UriBound for Pitch {
+    const URI: &'static [u8] = b"urn:rust-lv2:pitch\0";
+}
+
+struct Velocity;
+unsafe impl UriBound for Velocity {
+    const URI: &'static [u8] = b"urn:rust-lv2:velocity\0";
+}
+
+struct Label;
+unsafe impl UriBound for Label {
+    const URI: &'static [u8] = b"urn:rust-lv2:label\0";
+}
+
+struct Muted;
+unsafe impl UriBound for Muted {
+    const URI: &'static [u8] = b"urn:rust-lv2:muted\0";
+}
+
+struct Payload;
+unsafe impl UriBound for Payload {
+    const URI: &'static [u8] = b"urn:rust-lv2:payload\0";
+}
+
+#[derive(AtomObject)]
+#[object(Note)]
+struct NoteObject<'a> {
+    #[property(Pitch)]
+    pitch: i32,
+    #[property(Velocity)]
+    velocity: Option<f32>,
+    #[property(Label)]
+    label: Option<&'a str>,
+    #[property(Muted)]
+    muted: bool,
+    #[property(Payload)]
+    payload: Option<UnidentifiedAtom<'a>>,
+}
+
+#[derive(AtomObject)]
+#[object(Rest)]
+struct RestObject {
+    #[property(Velocity)]
+    length: f64,
+}
+
+#[derive(AtomObject)]
+#[object(Rest)]
+struct EmptyRestObject {}
+
+#[test]
+fn test_atom_object() {
+    let mut mapper = Box::pin(HashURIDMapper::new());
+    let interface = mapper.as_mut().make_map_interface();
+    let map = Map::new(&interface);
+    let urids: NoteObjectURIDCollection = map.populate_collection().unwrap();
+    let rest_urids: RestObjectURIDCollection = map.populate_collection().unwrap();
+    let empty_urids: EmptyRestObjectURIDCollection = map.populate_collection().unwrap();
+
+    let mut payload = AlignedBuffer::new(16);
+    (&mut payload.mut_space() as &mut dyn MutSpace)
+        .init(urids.atom.long, 17)
+        .unwrap();
+    let payload = UnidentifiedAtom::new(payload.space());
+
+    // writing
+    let mut buffer = AlignedBuffer::new(256);
+    let note = NoteObject {
+        pitch: 60,
+        velocity: None,
+        label: Some("C4"),
+        muted: true,
+        payload: Some(payload),
+    };
+    note.write(&mut buffer.mut_space(), &urids).unwrap();
+
+    // verifying
+    {
+        let atom = UnidentifiedAtom::new(buffer.space());
+        let (header, reader) = atom.read(urids.atom.object, ()).unwrap();
+        assert_eq!(header.otype, urids.class);
+        assert!(header.id.is_none());
+        let keys: Vec<URID> = reader.map(|(header, _)| header.key).collect();
+        assert_eq!(
+            keys,
+            vec![
+                urids.pitch.into_general(),
+                urids.label.into_general(),
+                urids.muted.into_general(),
+                urids.payload.into_general(),
+            ]
+        );
+    }
+
+    // reading
+    {
+        let atom = UnidentifiedAtom::new(buffer.space());
+        let note = NoteObject::read(atom, &urids).unwrap();
+        assert_eq!(note.pitch, 60);
+        assert_eq!(note.velocity, None);
+        assert_eq!(note.label, Some("C4"));
+        assert!(note.muted);
+        assert_eq!(note.payload.unwrap().read(urids.atom.long, ()).unwrap(), 17);
+
+        assert_eq!(
+            RestObject::read(atom, &rest_urids).err(),
+            Some(AtomError::TypeMismatch)
+        );
+    }
+
+    // missing and mistyped properties
+    {
+        let mut buffer = AlignedBuffer::new(256);
+        {
+            let mut space = buffer.mut_space();
+            let mut writer = (&mut space as &mut dyn MutSpace)
+                .init(
+                    urids.atom.object,
+                    ObjectHeader {
+                        id: None,
+                        otype: urids.class.into_general(),
+                    },
+                )
+                .unwrap();
+            writer.init(urids.pitch, None, urids.atom.int, 60).unwrap();
+        }
+        let atom = UnidentifiedAtom::new(buffer.space());
+        assert_eq!(
+            NoteObject::read(atom, &urids).err(),
+            Some(AtomError::MissingProperty(urids.muted.into_general()))
+        );
+
+        let mut buffer = AlignedBuffer::new(256);
+        {
+            let mut space = buffer.mut_space();
+            let mut writer = (&mut space as &mut dyn MutSpace)
+                .init(
+                    urids.atom.object,
+                    ObjectHeader {
+                        id: None,
+                        otype: rest_urids.class.into_general(),
+                    },
+                )
+                .unwrap();
+            writer
+                .init(urids.velocity, None, urids.atom.int, 3)
+                .unwrap();
+        }
+        let atom = UnidentifiedAtom::new(buffer.space());
+        assert_eq!(
+            RestObject::read(atom, &rest_urids).err(),
+            Some(AtomError::TypeMismatch)
+        );
+        assert!(EmptyRestObject::read(atom, &empty_urids).is_ok());
+    }
+
+    // duplicate properties
+    {
+        let mut buffer = AlignedBuffer::new(256);
+        {
+            let mut space = buffer.mut_space();
+            let mut writer = (&mut space as &mut dyn MutSpace)
+                .init(
+                    urids.atom.object,
+                    ObjectHeader {
+                        id: None,
+                        otype: urids.class.into_general(),
+                    },
+                )
+                .unwrap();
+            writer.init(urids.pitch, None, urids.atom.int, 60).unwrap();
+            writer.init(urids.muted, None, urids.atom.bool, 1).unwrap();
+            writer.init(urids.pitch, None, urids.atom.int, 72).unwrap();
+        }
+        let atom = UnidentifiedAtom::new(buffer.space());
+        assert_eq!(NoteObject::read(atom, &urids).unwrap().pitch, 60);
+    }
+
+    // rolling back
+    {
+        let mut buffer = AlignedBuffer::new(64);
+        {
+            let mut space = buffer.mut_space();
+            assert_eq!(note.write(&mut space, &urids), Err(AtomError::OutOfSpace));
+            RestObject { length: 1.5 }
+                .write(&mut space, &rest_urids)
+                .unwrap();
+            EmptyRestObject {}.write(&mut space, &empty_urids).unwrap();
+        }
+
+        let atom = UnidentifiedAtom::new(buffer.space());
+        assert_eq!(RestObject::read(atom, &rest_urids).unwrap().length, 1.5);
+    }
+}
@@ -1,17 +1,19 @@
 use proc_macro::TokenStream;
 use syn::export::Span;
 use syn::Field;
-use syn::{parse_macro_input, Data, DataStruct, Ident};
+use syn::{parse_macro_input, Data, DataStruct, Ident, Type};
 use syn::{DeriveInput, Generics, Lifetime};
 
 struct FeatureCollectionField<'a> {
     identifier: &'a Ident,
+    feature_type: &'a Type,
 }
 
 impl<'a> FeatureCollectionField<'a> {
     fn from_input_field(input: &'a Field) -> Self {
         FeatureCollectionField {
             identifier: input.ident.as_ref().unwrap(),
+            feature_type: &input.ty,
         }
     }
 
@@ -19,6 +21,11 @@ impl<'a> FeatureCollectionField<'a> {
         let identifier = self.identifier;
         quote! {#identifier: cache.retrieve_feature()?,}
     }
+
+    fn make_info(&self) -> impl ::quote::ToTokens {
+        let feature_type = self.feature_type;
+        quote! {<#feature_type as ::lv2_core::feature::FromResolvedFeature<_>>::feature_info(),}
+    }
 }
 
 struct FeatureCollectionStruct<'a> {
@@ -47,6 +54,7 @@ impl<'a> FeatureCollectionStruct<'a> {
         let struct_name = self.struct_name;
         let generics = self.generics;
         let retrievals = self.fields.iter().map(|field| field.make_retrieval());
+        let infos = self.fields.iter().map(|field| field.make_info());
         // retrieve the first lifetime of the struct, or set it to `'static` if there is none.
         let lifetime = self
             .generics
@@ -64,6 +72,10 @@ impl<'a> FeatureCollectionStruct<'a> {
                         #(#retrievals)*
                     })
                 }
+
+                fn feature_info() -> Vec<::lv2_core::feature::FeatureInfo> {
+                    vec![#(#infos)*]
+                }
             }
         })
         .into()
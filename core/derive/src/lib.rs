@@ -19,7 +19,9 @@ pub fn lv2_descriptors(input: TokenStream) -> TokenStream {
 }
 
 /// Implement the `PortCollection` trait for a port struct.
-#[proc_macro_derive(PortCollection)]
+///
+/// The metadata of a port can be set with a `#[port(...)]` attribute on its field, for example `#[port(symbol = "gain", name = "Gain", min = -90.0, max = 24.0, default = 0.0, unit = Decibel)]`. All entries are optional and the symbol defaults to the name of the field.
+#[proc_macro_derive(PortCollection, attributes(port))]
 pub fn port_collection_derive(input: TokenStream) -> TokenStream {
     port_collection_derive::port_collection_derive_impl(input)
 }
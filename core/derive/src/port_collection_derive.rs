@@ -1,22 +1,157 @@
 use proc_macro::TokenStream;
 use syn::export::Span;
-use syn::DeriveInput;
-use syn::Field;
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
 use syn::{parse_macro_input, Data, DataStruct, Ident, Type};
+use syn::{DeriveInput, Error, Field, Lit, LitStr, Path, Result, Token};
+
+/// A single entry of a `#[port(...)]` attribute.
+enum PortAttribute {
+    Symbol(LitStr),
+    Name(LitStr),
+    Minimum(f32),
+    Maximum(f32),
+    Default(f32),
+    Unit(Path),
+}
+
+impl Parse for PortAttribute {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let key: Ident = input.parse()?;
+        input.parse::<Token![=]>()?;
+        match key.to_string().as_str() {
+            "symbol" => Ok(PortAttribute::Symbol(input.parse()?)),
+            "name" => Ok(PortAttribute::Name(input.parse()?)),
+            "min" => Ok(PortAttribute::Minimum(parse_number(input)?)),
+            "max" => Ok(PortAttribute::Maximum(parse_number(input)?)),
+            "default" => Ok(PortAttribute::Default(parse_number(input)?)),
+            "unit" => Ok(PortAttribute::Unit(input.parse()?)),
+            _ => Err(Error::new(
+                key.span(),
+                "Unknown port attribute, expected one of `symbol`, `name`, `min`, `max`, `default` or `unit`",
+            )),
+        }
+    }
+}
+
+/// Parse a number literal with an optional sign.
+fn parse_number(input: ParseStream) -> Result<f32> {
+    let negative = input.parse::<Option<Token![-]>>()?.is_some();
+    let literal: Lit = input.parse()?;
+    let value: f32 = match &literal {
+        Lit::Float(literal) => literal.base10_parse()?,
+        Lit::Int(literal) => literal.base10_parse()?,
+        _ => return Err(Error::new(literal.span(), "Expected a number")),
+    };
+    if !value.is_finite() {
+        return Err(Error::new(literal.span(), "The number has to be finite"));
+    }
+    Ok(if negative { -value } else { value })
+}
+
+/// Check whether a string is a valid LV2 symbol.
+fn is_valid_symbol(symbol: &str) -> bool {
+    let mut chars = symbol.chars();
+    match chars.next() {
+        Some(c) if c == '_' || c.is_ascii_alphabetic() => (),
+        _ => return false,
+    }
+    chars.all(|c| c == '_' || c.is_ascii_alphanumeric())
+}
+
+/// Turn an optional value into the tokens of an `Option`.
+fn make_option<T: ::quote::ToTokens>(value: Option<T>) -> impl ::quote::ToTokens {
+    match value {
+        Some(value) => quote! {Some(#value)},
+        None => quote! {None},
+    }
+}
 
 /// A field in the struct we implement `PortCollection` for.
 struct PortCollectionField<'a> {
     identifier: &'a Ident,
     port_type: &'a Type,
+    symbol: String,
+    name: String,
+    minimum: Option<f32>,
+    maximum: Option<f32>,
+    default: Option<f32>,
+    unit: Option<Path>,
 }
 
 impl<'a> PortCollectionField<'a> {
     /// Create a `Self` instance from a field object.
-    fn from_input_field(input: &'a Field) -> Self {
-        PortCollectionField {
-            identifier: input.ident.as_ref().unwrap(),
-            port_type: &input.ty,
+    ///
+    /// This parses the `#[port(...)]` attributes of the field and checks their values.
+    fn from_input_field(input: &'a Field) -> Result<Self> {
+        let identifier = input.ident.as_ref().unwrap();
+        let mut symbol: Option<LitStr> = None;
+        let mut name: Option<LitStr> = None;
+        let mut minimum: Option<f32> = None;
+        let mut maximum: Option<f32> = None;
+        let mut default: Option<f32> = None;
+        let mut unit: Option<Path> = None;
+
+        for attribute in input.attrs.iter().filter(|attr| attr.path.is_ident("port")) {
+            let entries = attribute
+                .parse_args_with(Punctuated::<PortAttribute, Token![,]>::parse_terminated)?;
+            for entry in entries {
+                match entry {
+                    PortAttribute::Symbol(value) => symbol = Some(value),
+                    PortAttribute::Name(value) => name = Some(value),
+                    PortAttribute::Minimum(value) => minimum = Some(value),
+                    PortAttribute::Maximum(value) => maximum = Some(value),
+                    PortAttribute::Default(value) => default = Some(value),
+                    PortAttribute::Unit(value) => unit = Some(value),
+                }
+            }
         }
+
+        let symbol = match symbol {
+            Some(symbol) => {
+                if !is_valid_symbol(&symbol.value()) {
+                    return Err(Error::new(
+                        symbol.span(),
+                        "A symbol may only contain letters, digits and underscores and must not start with a digit",
+                    ));
+                }
+                symbol.value()
+            }
+            None => identifier.to_string().trim_start_matches("r#").to_owned(),
+        };
+        let name = name
+            .map(|name| name.value())
+            .unwrap_or_else(|| symbol.clone());
+
+        if let (Some(minimum), Some(maximum)) = (minimum, maximum) {
+            if minimum > maximum {
+                return Err(Error::new_spanned(
+                    identifier,
+                    "The minimum of the port is greater than its maximum",
+                ));
+            }
+        }
+        if let Some(default) = default {
+            if minimum.map(|minimum| default < minimum).unwrap_or(false)
+                || maximum.map(|maximum| default > maximum).unwrap_or(false)
+            {
+                return Err(Error::new_spanned(
+                    identifier,
+                    "The default value of the port is out of its range",
+                ));
+            }
+        }
+
+        Ok(PortCollectionField {
+            identifier,
+            port_type: &input.ty,
+            symbol,
+            name,
+            minimum,
+            maximum,
+            default,
+            unit,
+        })
     }
 
     /// Create the field initialization line for the implementing struct.
@@ -35,6 +170,60 @@ impl<'a> PortCollectionField<'a> {
         }
     }
 
+    /// Create the entry of the port info table.
+    fn make_port_info(&self, index: u32) -> impl ::quote::ToTokens {
+        let port_type = self.port_type;
+        let symbol = &self.symbol;
+        let name = &self.name;
+        let minimum = make_option(self.minimum);
+        let maximum = make_option(self.maximum);
+        let default = make_option(self.default);
+        let unit = make_option(
+            self.unit
+                .as_ref()
+                .map(|unit| quote! {<#unit as ::lv2_core::UriBound>::URI}),
+        );
+        quote! {
+            ::lv2_core::port::PortInfo {
+                index: #index,
+                symbol: #symbol,
+                name: #name,
+                direction: <#port_type as ::lv2_core::port::PortHandle>::DIRECTION,
+                class: <#port_type as ::lv2_core::port::PortHandle>::CLASS,
+                optional: <#port_type as ::lv2_core::port::PortHandle>::OPTIONAL,
+                minimum: #minimum,
+                maximum: #maximum,
+                default: #default,
+                unit: #unit,
+            },
+        }
+    }
+
+    /// Create the term of the check whether the collection contains in-place ports.
+    fn make_in_place_check(&self) -> impl ::quote::ToTokens {
+        let port_type = self.port_type;
+        quote! {
+            || <#port_type as ::lv2_core::port::PortHandle>::IN_PLACE
+        }
+    }
+
+    /// Create the element of the port buffer array.
+    fn make_buffer(&self) -> impl ::quote::ToTokens {
+        let identifier = self.identifier;
+        let port_type = self.port_type;
+        quote! {
+            <#port_type as ::lv2_core::port::PortHandle>::raw_buffer(connections.#identifier, sample_count),
+        }
+    }
+
+    /// Create the line that passes the analyzed aliasing to the port.
+    fn make_aliasing_update(&self, index: usize) -> impl ::quote::ToTokens {
+        let identifier = self.identifier;
+        quote! {
+            ::lv2_core::port::PortHandle::set_aliasing(&mut ports.#identifier, aliasing[#index]);
+        }
+    }
+
     /// Create the corresponding field declaration line for the raw pointer struct.
     fn make_raw_field_declaration(&self) -> impl ::quote::ToTokens {
         let identifier = self.identifier;
@@ -79,19 +268,27 @@ impl<'a> PortCollectionStruct<'a> {
     }
 
     /// Construct a `Self` instance from a `DeriveInput`.
-    fn from_derive_input(input: &'a DeriveInput) -> Self {
+    fn from_derive_input(input: &'a DeriveInput) -> Result<Self> {
         let struct_name = &input.ident;
         let fields = match &input.data {
             Data::Enum(_) | Data::Union(_) => panic!("Only structs can implement PortCollection"),
             Data::Struct(DataStruct { fields, .. }) => fields
                 .iter()
                 .map(PortCollectionField::from_input_field)
-                .collect(),
+                .collect::<Result<Vec<_>>>()?,
         };
-        PortCollectionStruct {
+        for (i, field) in fields.iter().enumerate() {
+            if fields[..i].iter().any(|other| other.symbol == field.symbol) {
+                return Err(Error::new_spanned(
+                    field.identifier,
+                    format!("The symbol `{}` is used by multiple ports", field.symbol),
+                ));
+            }
+        }
+        Ok(PortCollectionStruct {
             struct_name,
             fields,
-        }
+        })
     }
 
     /// Implement `PortCollection` for the struct.
@@ -111,6 +308,27 @@ impl<'a> PortCollectionStruct<'a> {
             .fields
             .iter()
             .map(PortCollectionField::make_raw_field_initialization);
+        let port_infos = self
+            .fields
+            .iter()
+            .enumerate()
+            .map(|(i, f)| f.make_port_info(i as u32));
+        let in_place_checks: Vec<_> = self
+            .fields
+            .iter()
+            .map(PortCollectionField::make_in_place_check)
+            .collect();
+        let buffers: Vec<_> = self
+            .fields
+            .iter()
+            .map(PortCollectionField::make_buffer)
+            .collect();
+        let aliasing_updates = self
+            .fields
+            .iter()
+            .enumerate()
+            .map(|(i, f)| f.make_aliasing_update(i));
+        let n_ports = self.fields.len();
         let connect_matchers = self
             .fields
             .iter()
@@ -123,11 +341,47 @@ impl<'a> PortCollectionStruct<'a> {
 
                 #[inline]
                 unsafe fn from_connections(connections: &<Self as PortCollection>::Cache, sample_count: u32) -> Option<Self> {
-                    Some(
-                        Self {
-                            #(#connections_from_raw)*
+                    // Ports that may share their buffers need to know how they are shared. The buffers are checked before any handle borrows them.
+                    const IN_PLACE: bool = false #(#in_place_checks)*;
+                    let mut aliasing = [::lv2_core::port::Aliasing::Shared; #n_ports];
+                    if IN_PLACE {
+                        let buffers: [Option<::lv2_core::port::PortBuffer>; #n_ports] = [
+                            #(#buffers)*
+                        ];
+                        if !::lv2_core::port::analyze_aliasing(&buffers, &mut aliasing) {
+                            return None;
                         }
-                    )
+                    }
+
+                    #[allow(unused_mut)]
+                    let mut ports = Self {
+                        #(#connections_from_raw)*
+                    };
+                    if IN_PLACE {
+                        #(#aliasing_updates)*
+                    }
+
+                    Some(ports)
+                }
+
+                fn check_aliasing(connections: &<Self as PortCollection>::Cache, sample_count: u32) -> bool {
+                    const IN_PLACE: bool = false #(#in_place_checks)*;
+                    if IN_PLACE {
+                        let buffers: [Option<::lv2_core::port::PortBuffer>; #n_ports] = [
+                            #(#buffers)*
+                        ];
+                        let mut aliasing = [::lv2_core::port::Aliasing::Shared; #n_ports];
+                        ::lv2_core::port::analyze_aliasing(&buffers, &mut aliasing)
+                    } else {
+                        true
+                    }
+                }
+
+                fn port_info() -> &'static [::lv2_core::port::PortInfo] {
+                    const PORT_INFO: &[::lv2_core::port::PortInfo] = &[
+                        #(#port_infos)*
+                    ];
+                    PORT_INFO
                 }
             }
 
@@ -164,6 +418,8 @@ impl<'a> PortCollectionStruct<'a> {
 #[inline]
 pub fn port_collection_derive_impl(input: TokenStream) -> TokenStream {
     let input: DeriveInput = parse_macro_input!(input);
-    let list = PortCollectionStruct::from_derive_input(&input);
-    list.make_derived_contents()
+    match PortCollectionStruct::from_derive_input(&input) {
+        Ok(list) => list.make_derived_contents(),
+        Err(error) => error.to_compile_error().into(),
+    }
 }
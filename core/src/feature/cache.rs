@@ -52,7 +52,9 @@ impl<'a> FeatureCache<'a> {
     }
 }
 
-use crate::feature::{Feature, FeatureCollection, FeatureDescriptor, MissingFeatureError};
+use crate::feature::{
+    Feature, FeatureCollection, FeatureDescriptor, FeatureInfo, MissingFeatureError,
+};
 use std::collections::{hash_map, HashMap};
 use std::ffi::{c_void, CStr};
 use std::iter::Map;
@@ -90,7 +92,18 @@ impl<'a> FeatureCollection<'a> for FeatureCache<'a> {
 /// For now this only covers `&T` and `Option<&T>` (where T is a `Feature`), but this may be
 /// extended in the future.
 pub trait FromResolvedFeature<F: Feature>: Sized {
+    /// Whether the host has to provide the feature.
+    const REQUIRED: bool = true;
+
     fn from_resolved_feature(feature: Option<F>) -> Result<Self, MissingFeatureError>;
+
+    /// Return information about the feature, used to generate the metadata of a plugin.
+    fn feature_info() -> FeatureInfo {
+        FeatureInfo {
+            uri: F::uri(),
+            required: Self::REQUIRED,
+        }
+    }
 }
 
 impl<F: Feature> FromResolvedFeature<F> for F {
@@ -100,6 +113,8 @@ impl<F: Feature> FromResolvedFeature<F> for F {
 }
 
 impl<F: Feature> FromResolvedFeature<F> for Option<F> {
+    const REQUIRED: bool = false;
+
     #[inline]
     fn from_resolved_feature(feature: Option<F>) -> Result<Self, MissingFeatureError> {
         Ok(feature)
@@ -5,7 +5,7 @@ mod cache;
 mod core_features;
 mod descriptor;
 
-pub use cache::FeatureCache;
+pub use cache::{FeatureCache, FromResolvedFeature};
 pub use core_features::*;
 pub use descriptor::FeatureDescriptor;
 
@@ -50,6 +50,17 @@ impl std::fmt::Display for MissingFeatureError {
     }
 }
 
+/// Information about a feature of a feature collection.
+///
+/// This information is used to generate the metadata of a plugin.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FeatureInfo {
+    /// The URI of the feature.
+    pub uri: &'static Uri,
+    /// Whether the host has to provide the feature.
+    pub required: bool,
+}
+
 /// Convenience trait for feature collections.
 ///
 /// The feature cache is only for temporary use; Once a feature is retrieved, it is removed from the cache. Therefore you need a way to properly store features.
@@ -69,6 +80,13 @@ impl std::fmt::Display for MissingFeatureError {
 pub trait FeatureCollection<'a>: Sized + 'a {
     /// Populate a collection with features from the cache.
     fn from_cache(cache: &mut FeatureCache<'a>) -> Result<Self, MissingFeatureError>;
+
+    /// Return information about all features of the collection.
+    ///
+    /// The derive macro generates one entry per field. The default implementation returns an empty list.
+    fn feature_info() -> Vec<FeatureInfo> {
+        Vec::new()
+    }
 }
 
 impl<'a> FeatureCollection<'a> for () {
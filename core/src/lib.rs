@@ -5,12 +5,17 @@
 //! Since this crate depends on `-sys` crates that use `bindgen` to create the C API bindings,
 //! you need to have clang installed on your machine.
 extern crate lv2_sys as sys;
+// Allow the derive macros to refer to this crate by its name within the crate too.
+extern crate self as lv2_core;
 
 pub mod extension;
 pub mod feature;
+pub mod metadata;
 pub mod plugin;
 pub mod port;
 pub mod prelude;
+#[cfg(feature = "host")]
+pub mod test;
 
 pub type Uri = ::std::ffi::CStr;
 pub type UriBuf = ::std::ffi::CString;
@@ -0,0 +1,288 @@
+//! Generation of the Turtle metadata of plugins.
+//!
+//! Hosts discover plugins and their ports by reading the `manifest.ttl` and the data files of a bundle. Since the ports of a plugin are declared by its [`PortCollection`](../port/trait.PortCollection.html), where the index of a port is defined by the order of the fields, writing these files by hand is error-prone. Instead, the information can be collected from the plugin's types with [`PluginMetadata`](struct.PluginMetadata.html) and written as Turtle:
+//!
+//! * The ports, their indices, symbols, names, directions, classes, ranges and units are taken from [`PortCollection::port_info`](../port/trait.PortCollection.html#method.port_info). Atom ports are declared to carry event sequences.
+//! * The required and optional features are taken from [`FeatureCollection::feature_info`](../feature/trait.FeatureCollection.html#method.feature_info).
+//! * The extensions are found by probing the plugin's `extension_data` method with the URIs of all known extensions. Other extensions can be probed with [`probe_extension`](struct.PluginMetadata.html#method.probe_extension).
+//!
+//! Build scripts can't use the types of the crate they are building. Therefore, the metadata is usually written by a small binary or an example of the plugin crate, which requires the crate to be built as a `rlib` too:
+//!
+//! ```toml
+//! [lib]
+//! crate-type = ["cdylib", "rlib"]
+//! ```
+//!
+//! # Example
+//!
+//! ```
+//! use lv2_core::metadata::*;
+//! use lv2_core::prelude::*;
+//!
+//! #[derive(PortCollection)]
+//! struct Ports {
+//!     gain: InputPort<Control>,
+//!     input: InputPort<Audio>,
+//!     output: OutputPort<Audio>,
+//! }
+//!
+//! struct Amp;
+//!
+//! unsafe impl UriBound for Amp {
+//!     const URI: &'static [u8] = b"urn:rust-lv2:amp\0";
+//! }
+//!
+//! impl Plugin for Amp {
+//!     type Ports = Ports;
+//!     type Features = ();
+//!
+//!     fn new(_: &PluginInfo, _: ()) -> Option<Self> {
+//!         Some(Self)
+//!     }
+//!
+//!     fn run(&mut self, _: &mut Ports) {}
+//! }
+//!
+//! // This would be the `main` function of a binary or example.
+//! let metadata = PluginMetadata::new::<Amp>().with_name("Amp");
+//!
+//! let mut plugin_file: Vec<u8> = Vec::new();
+//! metadata.write_turtle(&mut plugin_file).unwrap();
+//!
+//! let mut manifest: Vec<u8> = Vec::new();
+//! write_manifest(&mut manifest, &binary_name("amp"), "amp.ttl", &[&metadata]).unwrap();
+//!
+//! let plugin_file = String::from_utf8(plugin_file).unwrap();
+//! assert!(plugin_file.contains("lv2:symbol \"gain\""));
+//! ```
+use crate::feature::{FeatureCollection, FeatureInfo};
+use crate::plugin::Plugin;
+use crate::port::{PortCollection, PortDirection, PortInfo};
+use crate::Uri;
+use std::any::Any;
+use std::io::{Result, Write};
+
+/// The URIs of all extensions that are probed by default.
+const KNOWN_EXTENSIONS: &[&[u8]] = &[
+    sys::LV2_OPTIONS__interface,
+    sys::LV2_STATE__interface,
+    sys::LV2_WORKER__interface,
+];
+
+/// The prefixes used by the generated files.
+const PREFIXES: &str = "@prefix atom: <http://lv2plug.in/ns/ext/atom#> .\n\
+                        @prefix doap: <http://usefulinc.com/ns/doap#> .\n\
+                        @prefix lv2: <http://lv2plug.in/ns/lv2core#> .\n\
+                        @prefix rdfs: <http://www.w3.org/2000/01/rdf-schema#> .\n\
+                        @prefix units: <http://lv2plug.in/ns/extensions/units#> .\n";
+
+/// The metadata of a plugin, collected from its types.
+///
+/// [See also the module documentation.](index.html)
+pub struct PluginMetadata {
+    uri: &'static Uri,
+    name: Option<String>,
+    classes: Vec<String>,
+    ports: &'static [PortInfo],
+    features: Vec<FeatureInfo>,
+    extensions: Vec<&'static Uri>,
+    extension_data: fn(&Uri) -> Option<&'static dyn Any>,
+}
+
+impl PluginMetadata {
+    /// Collect the metadata of a plugin.
+    pub fn new<P: Plugin>() -> Self {
+        let mut metadata = Self {
+            uri: P::uri(),
+            name: None,
+            classes: Vec::new(),
+            ports: P::Ports::port_info(),
+            features: P::Features::feature_info(),
+            extensions: Vec::new(),
+            extension_data: P::extension_data,
+        };
+        for uri in KNOWN_EXTENSIONS {
+            metadata = metadata.probe_extension(Uri::from_bytes_with_nul(uri).unwrap());
+        }
+        metadata
+    }
+
+    /// Set the human-readable name of the plugin.
+    pub fn with_name(mut self, name: &str) -> Self {
+        self.name = Some(name.to_owned());
+        self
+    }
+
+    /// Add a class to the plugin, like `http://lv2plug.in/ns/lv2core#AmplifierPlugin`.
+    pub fn with_class(mut self, class: &str) -> Self {
+        self.classes.push(class.to_owned());
+        self
+    }
+
+    /// Probe the plugin for an extension that isn't known to this crate.
+    ///
+    /// The extension is only added if the plugin's `extension_data` method returns data for it.
+    pub fn probe_extension(mut self, uri: &'static Uri) -> Self {
+        if (self.extension_data)(uri).is_some() && !self.extensions.contains(&uri) {
+            self.extensions.push(uri);
+        }
+        self
+    }
+
+    /// The URI of the plugin.
+    pub fn uri(&self) -> &'static Uri {
+        self.uri
+    }
+
+    /// The ports of the plugin.
+    pub fn ports(&self) -> &'static [PortInfo] {
+        self.ports
+    }
+
+    /// The required and optional features of the plugin.
+    pub fn features(&self) -> &[FeatureInfo] {
+        self.features.as_ref()
+    }
+
+    /// The extensions of the plugin.
+    pub fn extensions(&self) -> &[&'static Uri] {
+        self.extensions.as_ref()
+    }
+
+    /// Write the description of the plugin as Turtle.
+    ///
+    /// The written document contains the prefix declarations and can be used as the data file of the plugin.
+    pub fn write_turtle<W: Write>(&self, writer: &mut W) -> Result<()> {
+        writeln!(writer, "{}", PREFIXES)?;
+        writeln!(writer, "<{}>", self.uri.to_string_lossy())?;
+        write!(writer, "    a lv2:Plugin")?;
+        for class in self.classes.iter() {
+            write!(writer, " , <{}>", class)?;
+        }
+        if let Some(name) = self.name.as_ref() {
+            write!(writer, " ;\n    doap:name \"{}\"", escape(name))?;
+        }
+        for feature in self.features.iter() {
+            let predicate = if feature.required {
+                "lv2:requiredFeature"
+            } else {
+                "lv2:optionalFeature"
+            };
+            write!(
+                writer,
+                " ;\n    {} <{}>",
+                predicate,
+                feature.uri.to_string_lossy()
+            )?;
+        }
+        for extension in self.extensions.iter() {
+            write!(
+                writer,
+                " ;\n    lv2:extensionData <{}>",
+                extension.to_string_lossy()
+            )?;
+        }
+        for (i, port) in self.ports.iter().enumerate() {
+            if i == 0 {
+                writeln!(writer, " ;\n    lv2:port [")?;
+            } else {
+                writeln!(writer, " , [")?;
+            }
+            write_port(writer, port)?;
+            write!(writer, "    ]")?;
+        }
+        writeln!(writer, " .")
+    }
+}
+
+/// Write the properties of a port.
+fn write_port<W: Write>(writer: &mut W, port: &PortInfo) -> Result<()> {
+    let direction = match port.direction {
+        PortDirection::Input => "lv2:InputPort",
+        PortDirection::Output => "lv2:OutputPort",
+    };
+    write!(
+        writer,
+        "        a {} , <{}> ;\n        lv2:index {} ;\n        lv2:symbol \"{}\" ;\n        lv2:name \"{}\"",
+        direction,
+        port.class_uri().to_string_lossy(),
+        port.index,
+        escape(port.symbol),
+        escape(port.name),
+    )?;
+    if port.class == sys::LV2_ATOM__AtomPort {
+        // Hosts need the buffer type to connect event sequences to atom ports.
+        write!(writer, " ;\n        atom:bufferType atom:Sequence")?;
+    }
+    if let Some(default) = port.default {
+        write!(writer, " ;\n        lv2:default {:?}", default)?;
+    }
+    if let Some(minimum) = port.minimum {
+        write!(writer, " ;\n        lv2:minimum {:?}", minimum)?;
+    }
+    if let Some(maximum) = port.maximum {
+        write!(writer, " ;\n        lv2:maximum {:?}", maximum)?;
+    }
+    if let Some(unit) = port.unit_uri() {
+        write!(
+            writer,
+            " ;\n        units:unit <{}>",
+            unit.to_string_lossy()
+        )?;
+    }
+    if port.optional {
+        write!(
+            writer,
+            " ;\n        lv2:portProperty lv2:connectionOptional"
+        )?;
+    }
+    writeln!(writer)
+}
+
+/// Write the `manifest.ttl` of a bundle.
+///
+/// The manifest lists the given plugins, which are all contained in the library `binary` and described in the data file `data_file`. Both paths are relative to the bundle directory.
+pub fn write_manifest<W: Write>(
+    writer: &mut W,
+    binary: &str,
+    data_file: &str,
+    plugins: &[&PluginMetadata],
+) -> Result<()> {
+    write!(writer, "{}", PREFIXES)?;
+    for plugin in plugins {
+        writeln!(writer)?;
+        writeln!(writer, "<{}>", plugin.uri.to_string_lossy())?;
+        writeln!(writer, "    a lv2:Plugin ;")?;
+        writeln!(writer, "    lv2:binary <{}> ;", binary)?;
+        writeln!(writer, "    rdfs:seeAlso <{}> .", data_file)?;
+    }
+    Ok(())
+}
+
+/// Return the file name of a plugin library on the current platform, like `libamp.so` for `amp`.
+///
+/// Note that Cargo replaces dashes in the name of the library with underscores.
+pub fn binary_name(name: &str) -> String {
+    format!(
+        "{}{}{}",
+        std::env::consts::DLL_PREFIX,
+        name,
+        std::env::consts::DLL_SUFFIX
+    )
+}
+
+/// Escape a string for use in a Turtle string literal.
+fn escape(string: &str) -> String {
+    let mut escaped = String::with_capacity(string.len());
+    for c in string.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '"' => escaped.push_str("\\\""),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
@@ -1,8 +1,10 @@
 //! Types to create plugins.
 pub(crate) mod info;
+mod panic;
 
 pub use info::PluginInfo;
 pub use lv2_core_derive::*;
+pub use panic::*;
 
 use crate::feature::*;
 use crate::port::*;
@@ -10,6 +12,8 @@ use crate::{Uri, UriBound};
 use std::any::Any;
 use std::ffi::c_void;
 use std::os::raw::c_char;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::atomic::{AtomicBool, Ordering};
 use sys::LV2_Handle;
 
 /// The central trait to describe LV2 plugins.
@@ -54,6 +58,30 @@ pub trait Plugin: UriBound + Sized + Send + Sync + 'static {
     fn extension_data(_uri: &Uri) -> Option<&'static dyn Any> {
         None
     }
+
+    /// The way an instance behaves after one of its methods panicked.
+    ///
+    /// [See `PanicPolicy` for more information.](enum.PanicPolicy.html)
+    const PANIC_POLICY: PanicPolicy = PanicPolicy::Fault;
+
+    /// Report a panic that has been caught at the C interface.
+    ///
+    /// This method is called before the panic policy is applied. The default implementation prints the report to the standard error output. Since it may be called from the audio thread, implementations should not block. If this method panics too, the process is aborted.
+    fn report_panic(report: &PanicReport) {
+        eprintln!("{}", report);
+    }
+
+    /// Report that `run` is skipped because of the way the host connected the ports.
+    ///
+    /// Hosts may connect an input and an output to the same buffer, unless the plugin requires [`InPlaceBroken`](../feature/struct.InPlaceBroken.html). If the port collection contains in-place ports, this is checked by [`PortCollection::check_aliasing`](../port/trait.PortCollection.html#method.check_aliasing) and if a regular port shares its buffer with an in-place port, `run` isn't called and the outputs are silenced. This doesn't fault the instance, since the connections are legal and may change again.
+    ///
+    /// This method is called once for the first skipped cycle after the ports have been connected. The default implementation prints a message to the standard error output. Like [`report_panic`](#method.report_panic), it may be called from the audio thread and the process is aborted if it panics.
+    fn report_skipped_run() {
+        eprintln!(
+            "Plugin <{}> skipped `run`: A port that may not share its buffer is connected to the buffer of an in-place port",
+            Self::uri().to_string_lossy()
+        );
+    }
 }
 
 /// Plugin wrapper which translated between the host and the plugin.
@@ -61,13 +89,103 @@ pub trait Plugin: UriBound + Sized + Send + Sync + 'static {
 /// The host interacts with the plugin via a C API, but the plugin is implemented with ideomatic, safe Rust. To bridge this gap, this wrapper is used to translate and abstract the communcation between the host and the plugin.
 ///
 /// This struct is `repr(C)` and has the plugin as it's first field. Therefore, a valid `*mut PluginInstance<T>` is also a valid `*mut T`.
+///
+/// All methods of the C interface catch panics of the plugin and apply the plugin's [`PanicPolicy`](enum.PanicPolicy.html).
 #[repr(C)]
 pub struct PluginInstance<T: Plugin> {
     instance: T,
     connections: <T::Ports as PortCollection>::Cache,
+    port_pointers: Box<[*mut c_void]>,
+    faulted: AtomicBool,
+    /// Set once a skipped `run` has been reported, and cleared when a port is connected.
+    skip_reported: bool,
 }
 
 impl<T: Plugin> PluginInstance<T> {
+    /// Check whether the instance behind a handle is faulted.
+    ///
+    /// An instance is faulted if one of its methods panicked and the plugin's panic policy is [`Fault`](enum.PanicPolicy.html#variant.Fault). A null handle is also treated as faulted. Since extensions may be called from other threads than the audio thread, the flag is atomic.
+    ///
+    /// # Safety
+    ///
+    /// The handle has to be null or a handle created by [`instantiate`](#method.instantiate).
+    pub unsafe fn is_faulted(handle: *mut c_void) -> bool {
+        match Self::faulted_flag(handle) {
+            Some(faulted) => faulted.load(Ordering::Acquire),
+            None => true,
+        }
+    }
+
+    /// Return the fault flag of the instance behind a handle without borrowing the rest of the instance.
+    unsafe fn faulted_flag<'a>(handle: *mut c_void) -> Option<&'a AtomicBool> {
+        let instance = handle as *const Self;
+        if instance.is_null() {
+            None
+        } else {
+            Some(&*std::ptr::addr_of!((*instance).faulted))
+        }
+    }
+
+    /// Report a caught panic and apply the plugin's panic policy to the instance behind a handle.
+    ///
+    /// This is used by extensions that catch panics of the plugin. If the plugin's panic policy is [`Fault`](enum.PanicPolicy.html#variant.Fault), the instance is marked as faulted and the `PluginInstance` won't call its methods anymore.
+    ///
+    /// # Safety
+    ///
+    /// The handle has to be null or a handle created by [`instantiate`](#method.instantiate).
+    pub unsafe fn handle_panic(
+        handle: *mut c_void,
+        method: &'static str,
+        payload: Box<dyn Any + Send>,
+    ) {
+        if Self::apply_panic_policy(method, payload) {
+            if let Some(faulted) = Self::faulted_flag(handle) {
+                faulted.store(true, Ordering::Release);
+            }
+        }
+    }
+
+    /// Report a caught panic and apply the plugin's panic policy.
+    ///
+    /// Returns `true` if the instance has to be marked as faulted.
+    fn apply_panic_policy(method: &'static str, payload: Box<dyn Any + Send>) -> bool {
+        let report = PanicReport::new(T::uri(), method, payload.as_ref());
+        if catch_unwind(AssertUnwindSafe(|| T::report_panic(&report))).is_err() {
+            // A panicking report can't be reported and must not unwind into the host.
+            std::process::abort();
+        }
+        match T::PANIC_POLICY {
+            PanicPolicy::Fault => true,
+            PanicPolicy::Continue => false,
+            PanicPolicy::Abort => std::process::abort(),
+        }
+    }
+
+    /// Mark the instance as faulted if the panic policy demands it.
+    fn fault_if(&self, faulted: bool) {
+        if faulted {
+            self.faulted.store(true, Ordering::Release);
+        }
+    }
+
+    /// Write silence to all connected audio and CV outputs.
+    ///
+    /// The outputs are found with [`PortCollection::port_info`](../port/trait.PortCollection.html#method.port_info). Therefore, this only works for derived port collections.
+    unsafe fn silence(&mut self, sample_count: u32) {
+        for info in T::Ports::port_info() {
+            if info.direction != PortDirection::Output
+                || (info.class != Audio::URI && info.class != CV::URI)
+            {
+                continue;
+            }
+            if let Some(pointer) = self.port_pointers.get(info.index as usize) {
+                if !pointer.is_null() {
+                    std::ptr::write_bytes(*pointer as *mut f32, 0, sample_count as usize);
+                }
+            }
+        }
+    }
+
     /// Instantiate the plugin.
     ///
     /// This method provides a required method for the C interface of a plugin and is used by the `lv2_descriptors` macro.
@@ -80,6 +198,24 @@ impl<T: Plugin> PluginInstance<T> {
         sample_rate: f64,
         bundle_path: *const c_char,
         features: *const *const sys::LV2_Feature,
+    ) -> LV2_Handle {
+        match catch_unwind(|| {
+            Self::instantiate_unchecked(descriptor, sample_rate, bundle_path, features)
+        }) {
+            Ok(handle) => handle,
+            Err(payload) => {
+                Self::apply_panic_policy("instantiate", payload);
+                std::ptr::null_mut()
+            }
+        }
+    }
+
+    /// Instantiate the plugin without catching panics.
+    unsafe fn instantiate_unchecked(
+        descriptor: *const sys::LV2_Descriptor,
+        sample_rate: f64,
+        bundle_path: *const c_char,
+        features: *const *const sys::LV2_Feature,
     ) -> LV2_Handle {
         // Dereference the descriptor.
         let descriptor = match descriptor.as_ref() {
@@ -119,6 +255,10 @@ impl<T: Plugin> PluginInstance<T> {
                 let instance = Box::new(Self {
                     instance,
                     connections: <<T::Ports as PortCollection>::Cache as Default>::default(),
+                    port_pointers: vec![std::ptr::null_mut(); T::Ports::port_info().len()]
+                        .into_boxed_slice(),
+                    faulted: AtomicBool::new(false),
+                    skip_reported: false,
                 });
                 Box::leak(instance) as *mut Self as LV2_Handle
             }
@@ -134,8 +274,11 @@ impl<T: Plugin> PluginInstance<T> {
     ///
     /// This method is unsafe since it derefences multiple raw pointers and is part of the C interface.
     pub unsafe extern "C" fn cleanup(instance: *mut c_void) {
-        let instance = instance as *mut Self;
-        Box::from_raw(instance);
+        let instance = Box::from_raw(instance as *mut Self);
+        if let Err(payload) = catch_unwind(AssertUnwindSafe(|| drop(instance))) {
+            // The instance is gone, so there is nothing to fault.
+            Self::apply_panic_policy("cleanup", payload);
+        }
     }
 
     /// Call `activate`.
@@ -146,8 +289,14 @@ impl<T: Plugin> PluginInstance<T> {
     ///
     /// This method is unsafe since it derefences multiple raw pointers and is part of the C interface.
     pub unsafe extern "C" fn activate(instance: *mut c_void) {
-        let instance = &mut *(instance as *mut Self);
-        instance.instance.activate()
+        if Self::is_faulted(instance) {
+            return;
+        }
+        let this = &mut *(instance as *mut Self);
+        let plugin = &mut this.instance;
+        if let Err(payload) = catch_unwind(AssertUnwindSafe(|| plugin.activate())) {
+            this.fault_if(Self::apply_panic_policy("activate", payload));
+        }
     }
 
     /// Call `deactivate`.
@@ -158,8 +307,14 @@ impl<T: Plugin> PluginInstance<T> {
     ///
     /// This method is unsafe since it derefences multiple raw pointers and is part of the C interface.
     pub unsafe extern "C" fn deactivate(instance: *mut c_void) {
-        let instance = &mut *(instance as *mut Self);
-        instance.instance.deactivate()
+        if Self::is_faulted(instance) {
+            return;
+        }
+        let this = &mut *(instance as *mut Self);
+        let plugin = &mut this.instance;
+        if let Err(payload) = catch_unwind(AssertUnwindSafe(|| plugin.deactivate())) {
+            this.fault_if(Self::apply_panic_policy("deactivate", payload));
+        }
     }
 
     /// Update a port pointer.
@@ -170,8 +325,15 @@ impl<T: Plugin> PluginInstance<T> {
     ///
     /// This method is unsafe since it derefences multiple raw pointers and is part of the C interface.
     pub unsafe extern "C" fn connect_port(instance: *mut c_void, port: u32, data: *mut c_void) {
-        let instance = instance as *mut Self;
-        (*instance).connections.connect(port, data)
+        let this = &mut *(instance as *mut Self);
+        if let Some(pointer) = this.port_pointers.get_mut(port as usize) {
+            *pointer = data;
+        }
+        this.skip_reported = false;
+        let connections = &mut this.connections;
+        if let Err(payload) = catch_unwind(AssertUnwindSafe(|| connections.connect(port, data))) {
+            this.fault_if(Self::apply_panic_policy("connect_port", payload));
+        }
     }
 
     /// Construct a port collection and call the `run` method.
@@ -182,11 +344,41 @@ impl<T: Plugin> PluginInstance<T> {
     ///
     /// This method is unsafe since it derefences multiple raw pointers and is part of the C interface.
     pub unsafe extern "C" fn run(instance: *mut c_void, sample_count: u32) {
-        let instance = &mut *(instance as *mut Self);
-        let ports =
-            <T::Ports as PortCollection>::from_connections(&instance.connections, sample_count);
-        if let Some(mut ports) = ports {
-            instance.instance.run(&mut ports);
+        let this = &mut *(instance as *mut Self);
+        if this.faulted.load(Ordering::Acquire) {
+            this.silence(sample_count);
+            return;
+        }
+
+        let plugin = &mut this.instance;
+        let connections = &this.connections;
+        let result = catch_unwind(AssertUnwindSafe(|| {
+            let ports = <T::Ports as PortCollection>::from_connections(connections, sample_count);
+            if let Some(mut ports) = ports {
+                plugin.run(&mut ports);
+                true
+            } else {
+                // Missing ports skip the cycle silently, only rejected buffers are reported.
+                <T::Ports as PortCollection>::check_aliasing(connections, sample_count)
+            }
+        }));
+
+        match result {
+            Ok(true) => (),
+            Ok(false) => {
+                if !this.skip_reported {
+                    this.skip_reported = true;
+                    if catch_unwind(T::report_skipped_run).is_err() {
+                        // Like a panicking panic report, this must not unwind into the host.
+                        std::process::abort();
+                    }
+                }
+                this.silence(sample_count);
+            }
+            Err(payload) => {
+                this.fault_if(Self::apply_panic_policy("run", payload));
+                this.silence(sample_count);
+            }
         }
     }
 
@@ -199,10 +391,13 @@ impl<T: Plugin> PluginInstance<T> {
     /// This method is unsafe since it derefences multiple raw pointers and is part of the C interface.
     pub unsafe extern "C" fn extension_data(uri: *const c_char) -> *const c_void {
         let uri = Uri::from_ptr(uri);
-        if let Some(data) = T::extension_data(uri) {
-            data as *const _ as *const c_void
-        } else {
-            std::ptr::null()
+        match catch_unwind(|| T::extension_data(uri)) {
+            Ok(Some(data)) => data as *const _ as *const c_void,
+            Ok(None) => std::ptr::null(),
+            Err(payload) => {
+                Self::apply_panic_policy("extension_data", payload);
+                std::ptr::null()
+            }
         }
     }
 }
@@ -0,0 +1,61 @@
+use crate::Uri;
+use std::any::Any;
+use std::fmt;
+
+/// The way a plugin instance behaves after one of its methods panicked.
+///
+/// Unwinding across the C interface would abort the host or cause undefined behaviour. Therefore, every method that is called by the host catches panics of the plugin, reports them with [`Plugin::report_panic`](trait.Plugin.html#method.report_panic) and then applies the panic policy of the plugin, which is set with [`Plugin::PANIC_POLICY`](trait.Plugin.html#associatedconstant.PANIC_POLICY).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PanicPolicy {
+    /// Mark the instance as faulted.
+    ///
+    /// The `PluginInstance` doesn't call the methods of a faulted instance anymore, except for dropping it. Instead, `run` writes silence to all audio and CV outputs. This is the default policy.
+    Fault,
+    /// Write silence to all audio and CV outputs if `run` panicked, but keep calling the methods of the instance.
+    ///
+    /// This is only useful if the plugin keeps its internal state consistent even if one of its methods panics.
+    Continue,
+    /// Abort the process, which is what happens if a panic unwinds into the host.
+    Abort,
+}
+
+/// Information about a panic that has been caught at the C interface.
+#[derive(Clone, Copy, Debug)]
+pub struct PanicReport<'a> {
+    /// The URI of the plugin.
+    pub plugin: &'a Uri,
+    /// The name of the method that panicked, like `run` or `save`.
+    pub method: &'static str,
+    /// The message of the panic, if it was a string.
+    pub message: Option<&'a str>,
+}
+
+impl<'a> PanicReport<'a> {
+    /// Create a report from the payload of a caught panic.
+    pub fn new(plugin: &'a Uri, method: &'static str, payload: &'a (dyn Any + Send)) -> Self {
+        let message = match payload.downcast_ref::<&'static str>() {
+            Some(message) => Some(*message),
+            None => payload.downcast_ref::<String>().map(String::as_str),
+        };
+        Self {
+            plugin,
+            method,
+            message,
+        }
+    }
+}
+
+impl<'a> fmt::Display for PanicReport<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Plugin <{}> panicked in `{}`",
+            self.plugin.to_string_lossy(),
+            self.method
+        )?;
+        if let Some(message) = self.message {
+            write!(f, ": {}", message)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,386 @@
+use super::{Audio, PortDirection, PortHandle, PortType, CV};
+use std::cell::Cell;
+use std::ffi::c_void;
+use std::marker::PhantomData;
+use std::ops::{Deref, Range};
+use std::ptr::NonNull;
+
+/// Marker trait for port types that can be processed in-place.
+///
+/// The buffers of these port types are slices of samples, which may be shared by an input and an output port.
+pub trait InPlacePortType: PortType {}
+
+impl InPlacePortType for Audio {}
+impl InPlacePortType for CV {}
+
+/// The way a port's buffer is shared with other ports of the collection.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Aliasing {
+    /// The buffer isn't shared with any other port.
+    Unique,
+    /// The buffer is exactly the buffer of a single port with the opposite direction.
+    Paired,
+    /// The buffer overlaps with other ports in any other way, or the aliasing is unknown.
+    Shared,
+}
+
+/// The memory borrowed by a port handle.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PortBuffer {
+    /// The range of addresses of the buffer.
+    pub range: Range<usize>,
+    /// The direction of the port.
+    pub direction: PortDirection,
+    /// Whether the port handle allows the buffer to be shared with other ports.
+    pub in_place: bool,
+}
+
+impl PortBuffer {
+    /// Create a new port buffer, or `None` if the range is empty.
+    pub fn new(range: Range<usize>, direction: PortDirection, in_place: bool) -> Option<Self> {
+        if range.start < range.end {
+            Some(Self {
+                range,
+                direction,
+                in_place,
+            })
+        } else {
+            None
+        }
+    }
+
+    /// Check whether two buffers overlap.
+    pub fn overlaps(&self, other: &Self) -> bool {
+        self.range.start < other.range.end && other.range.start < self.range.end
+    }
+}
+
+/// Create the range of addresses of a buffer.
+pub(crate) fn buffer_range(pointer: NonNull<c_void>, size: usize) -> Range<usize> {
+    let start = pointer.as_ptr() as usize;
+    start..start.saturating_add(size)
+}
+
+/// Analyze the aliasing of the buffers of a port collection.
+///
+/// The aliasing of every buffer is written to the corresponding element of `aliasing`. Returns `false` if a port that may not share its buffer overlaps with an in-place port in a way that involves writing.
+///
+/// This function is used by the derive macro for `PortCollection`.
+#[doc(hidden)]
+pub fn analyze_aliasing(buffers: &[Option<PortBuffer>], aliasing: &mut [Aliasing]) -> bool {
+    for (i, buffer) in buffers.iter().enumerate() {
+        let buffer = match buffer {
+            Some(buffer) => buffer,
+            None => {
+                aliasing[i] = Aliasing::Unique;
+                continue;
+            }
+        };
+
+        let mut overlaps = 0;
+        let mut paired = false;
+        for (j, other) in buffers.iter().enumerate() {
+            let other = match other {
+                Some(other) if i != j && buffer.overlaps(other) => other,
+                _ => continue,
+            };
+            let writing = buffer.direction == PortDirection::Output
+                || other.direction == PortDirection::Output;
+            if writing && buffer.in_place != other.in_place {
+                // Overlapping ports that don't allow aliasing are the plugin's responsibility.
+                return false;
+            }
+            overlaps += 1;
+            paired = buffer.in_place
+                && other.in_place
+                && buffer.range == other.range
+                && buffer.direction != other.direction;
+        }
+
+        aliasing[i] = match overlaps {
+            0 => Aliasing::Unique,
+            1 if paired => Aliasing::Paired,
+            _ => Aliasing::Shared,
+        };
+    }
+    true
+}
+
+/// Handle for input ports that may share their buffer with an output port.
+///
+/// In contrast to [`InputPort`](struct.InputPort.html), the samples of this port are not exposed as a slice, since the host may write to the buffer via an [`InPlaceOutput`](struct.InPlaceOutput.html). Instead, they are read one by one, or together with the output via [`InPlaceOutput::with_input`](struct.InPlaceOutput.html#method.with_input).
+pub struct InPlaceInput<T: InPlacePortType> {
+    pointer: NonNull<f32>,
+    len: usize,
+    aliasing: Aliasing,
+    port_type: PhantomData<T>,
+}
+
+impl<T: InPlacePortType> InPlaceInput<T> {
+    /// Return the number of samples.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Check whether there are no samples.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Return the sample with the given index.
+    pub fn get(&self, index: usize) -> Option<f32> {
+        self.samples().get(index)
+    }
+
+    /// Iterate over all samples.
+    pub fn iter(&self) -> impl Iterator<Item = f32> + '_ {
+        self.samples().iter()
+    }
+
+    fn samples(&self) -> InPlaceSamples<'_> {
+        InPlaceSamples {
+            cells: unsafe {
+                std::slice::from_raw_parts(self.pointer.as_ptr() as *const Cell<f32>, self.len)
+            },
+        }
+    }
+
+    fn range(&self) -> Range<usize> {
+        buffer_range(self.pointer.cast(), self.len * std::mem::size_of::<f32>())
+    }
+}
+
+impl<T: InPlacePortType> PortHandle for InPlaceInput<T> {
+    const DIRECTION: PortDirection = PortDirection::Input;
+    const CLASS: &'static [u8] = T::URI;
+    const IN_PLACE: bool = true;
+
+    #[inline]
+    unsafe fn from_raw(pointer: *mut c_void, sample_count: u32) -> Option<Self> {
+        NonNull::new(pointer as *mut f32).map(|pointer| Self {
+            pointer,
+            len: sample_count as usize,
+            aliasing: Aliasing::Shared,
+            port_type: PhantomData,
+        })
+    }
+
+    fn raw_buffer(pointer: *mut c_void, sample_count: u32) -> Option<PortBuffer> {
+        let range = buffer_range(
+            NonNull::new(pointer)?,
+            sample_count as usize * std::mem::size_of::<f32>(),
+        );
+        PortBuffer::new(range, Self::DIRECTION, Self::IN_PLACE)
+    }
+
+    fn set_aliasing(&mut self, aliasing: Aliasing) {
+        self.aliasing = aliasing;
+    }
+}
+
+/// Handle for output ports that may share their buffer with an input port.
+///
+/// Fields of this type can be dereferenced to a slice of `Cell`s, which stays sound if the buffer is shared with an [`InPlaceInput`](struct.InPlaceInput.html). If the input and output are processed together, [`with_input`](#method.with_input) provides plain slices where possible.
+pub struct InPlaceOutput<T: InPlacePortType> {
+    pointer: NonNull<f32>,
+    len: usize,
+    aliasing: Aliasing,
+    port_type: PhantomData<T>,
+}
+
+impl<T: InPlacePortType> InPlaceOutput<T> {
+    /// Borrow the output together with an input.
+    ///
+    /// The way the buffers are borrowed depends on their aliasing, which has been analyzed by [`PortCollection::from_connections`](trait.PortCollection.html#tymethod.from_connections):
+    ///
+    /// * If the input and the output are connected to the same buffer and no other port uses it, the buffer is borrowed as a single mutable slice.
+    /// * If the input and output don't share their buffers with any other port, they are borrowed as separate slices.
+    /// * Otherwise, the input is borrowed as read-only [`InPlaceSamples`](struct.InPlaceSamples.html) and the output as a slice of `Cell`s.
+    pub fn with_input<'a>(&'a mut self, input: &'a mut InPlaceInput<T>) -> InPlace<'a> {
+        let output_range = self.range();
+        let input_range = input.range();
+        match (input.aliasing, self.aliasing) {
+            (Aliasing::Paired, Aliasing::Paired) if input_range == output_range => unsafe {
+                InPlace::Shared(std::slice::from_raw_parts_mut(
+                    self.pointer.as_ptr(),
+                    self.len,
+                ))
+            },
+            (Aliasing::Unique, Aliasing::Unique) => unsafe {
+                InPlace::Separate(
+                    std::slice::from_raw_parts(input.pointer.as_ptr(), input.len),
+                    std::slice::from_raw_parts_mut(self.pointer.as_ptr(), self.len),
+                )
+            },
+            _ => {
+                let input: &'a InPlaceInput<T> = input;
+                let output: &'a Self = self;
+                InPlace::Cells(input.samples(), output.deref())
+            }
+        }
+    }
+
+    fn range(&self) -> Range<usize> {
+        buffer_range(self.pointer.cast(), self.len * std::mem::size_of::<f32>())
+    }
+}
+
+impl<T: InPlacePortType> Deref for InPlaceOutput<T> {
+    type Target = [Cell<f32>];
+
+    #[inline]
+    fn deref(&self) -> &[Cell<f32>] {
+        unsafe { std::slice::from_raw_parts(self.pointer.as_ptr() as *const Cell<f32>, self.len) }
+    }
+}
+
+impl<T: InPlacePortType> PortHandle for InPlaceOutput<T> {
+    const DIRECTION: PortDirection = PortDirection::Output;
+    const CLASS: &'static [u8] = T::URI;
+    const IN_PLACE: bool = true;
+
+    #[inline]
+    unsafe fn from_raw(pointer: *mut c_void, sample_count: u32) -> Option<Self> {
+        NonNull::new(pointer as *mut f32).map(|pointer| Self {
+            pointer,
+            len: sample_count as usize,
+            aliasing: Aliasing::Shared,
+            port_type: PhantomData,
+        })
+    }
+
+    fn raw_buffer(pointer: *mut c_void, sample_count: u32) -> Option<PortBuffer> {
+        let range = buffer_range(
+            NonNull::new(pointer)?,
+            sample_count as usize * std::mem::size_of::<f32>(),
+        );
+        PortBuffer::new(range, Self::DIRECTION, Self::IN_PLACE)
+    }
+
+    fn set_aliasing(&mut self, aliasing: Aliasing) {
+        self.aliasing = aliasing;
+    }
+}
+
+/// The samples of an input buffer that may be shared with an output.
+///
+/// The samples can only be read, since the buffer belongs to the host and the port is an input. Reading a sample after the sample at the same address has been written via an output returns the new value.
+#[derive(Clone, Copy)]
+pub struct InPlaceSamples<'a> {
+    cells: &'a [Cell<f32>],
+}
+
+impl<'a> InPlaceSamples<'a> {
+    /// Return the number of samples.
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    /// Check whether there are no samples.
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    /// Return the sample with the given index.
+    pub fn get(&self, index: usize) -> Option<f32> {
+        self.cells.get(index).map(Cell::get)
+    }
+
+    /// Iterate over all samples.
+    pub fn iter(&self) -> impl Iterator<Item = f32> + 'a {
+        self.cells.iter().map(Cell::get)
+    }
+}
+
+/// The buffers of an input and an output port, borrowed together.
+///
+/// This is returned by [`InPlaceOutput::with_input`](struct.InPlaceOutput.html#method.with_input).
+pub enum InPlace<'a> {
+    /// The input and the output share the same buffer, which has to be processed in-place.
+    Shared(&'a mut [f32]),
+    /// The input and the output have separate buffers.
+    Separate(&'a [f32], &'a mut [f32]),
+    /// The buffers are shared in some other way. The input can only be read, and the output is a slice of `Cell`s.
+    Cells(InPlaceSamples<'a>, &'a [Cell<f32>]),
+}
+
+impl<'a> InPlace<'a> {
+    /// Apply a function to every input sample and write the result to the output.
+    ///
+    /// The samples are processed in order, which is sound for all kinds of aliasing.
+    pub fn map<F: FnMut(f32) -> f32>(self, mut f: F) {
+        match self {
+            InPlace::Shared(buffer) => {
+                for sample in buffer.iter_mut() {
+                    *sample = f(*sample);
+                }
+            }
+            InPlace::Separate(input, output) => {
+                for (input, output) in input.iter().zip(output.iter_mut()) {
+                    *output = f(*input);
+                }
+            }
+            InPlace::Cells(input, output) => {
+                for (input, output) in input.iter().zip(output.iter()) {
+                    output.set(f(input));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::port::*;
+
+    #[test]
+    fn test_analyze_aliasing() {
+        let buffer = |start: usize, end: usize, direction, in_place| {
+            PortBuffer::new(start..end, direction, in_place)
+        };
+        let mut aliasing = [Aliasing::Shared; 4];
+
+        // A paired input and output and an unrelated input.
+        let buffers = [
+            buffer(0, 16, PortDirection::Input, true),
+            buffer(0, 16, PortDirection::Output, true),
+            buffer(16, 32, PortDirection::Input, false),
+            None,
+        ];
+        assert!(analyze_aliasing(&buffers, &mut aliasing));
+        assert_eq!(
+            [
+                Aliasing::Paired,
+                Aliasing::Paired,
+                Aliasing::Unique,
+                Aliasing::Unique
+            ],
+            aliasing
+        );
+
+        // Partially overlapping in-place ports and two overlapping regular inputs.
+        let buffers = [
+            buffer(0, 16, PortDirection::Input, true),
+            buffer(8, 24, PortDirection::Output, true),
+            buffer(32, 48, PortDirection::Input, false),
+            buffer(32, 48, PortDirection::Input, true),
+        ];
+        assert!(analyze_aliasing(&buffers, &mut aliasing));
+        assert_eq!(
+            [
+                Aliasing::Shared,
+                Aliasing::Shared,
+                Aliasing::Shared,
+                Aliasing::Shared
+            ],
+            aliasing
+        );
+
+        // An in-place output that overlaps with a regular input.
+        let buffers = [
+            buffer(0, 16, PortDirection::Input, false),
+            buffer(0, 16, PortDirection::Output, true),
+        ];
+        assert!(!analyze_aliasing(&buffers, &mut aliasing[..2]));
+    }
+}
@@ -1,7 +1,11 @@
 //! Types to declare derivable port collections.
 //!
 //! Every plugin has a type of [`PortCollection`](trait.PortCollection.html) which is used to handle input/output ports. In order to make the creation of these port collection types easier, `PortCollection` can simply be derived. However, the macro that implements `PortCollection` requires the fields of the struct to have specific types. These types are provided in this module.
-use crate::UriBound;
+mod in_place;
+
+pub use in_place::*;
+
+use crate::{Uri, UriBound};
 use std::ffi::c_void;
 use std::ops::{Deref, DerefMut};
 use std::ptr::NonNull;
@@ -11,7 +15,9 @@ pub use lv2_core_derive::*;
 /// Generalization of port types.
 ///
 /// A port can read input or create a pointer to the output, but the exact type of input/output (pointer) depends on the type of port. This trait generalizes these types and behaviour.
-pub trait PortType {
+///
+/// The URI of a port type is the URI of its port class, like `lv2:AudioPort`.
+pub trait PortType: UriBound {
     /// The type of input read by the port.
     type InputPortType: Sized;
     /// The type of output reference created by the port.
@@ -34,11 +40,20 @@ pub trait PortType {
     ///
     /// This method is unsafe because one needs to de-reference a raw pointer to implement this method.
     unsafe fn output_from_raw(pointer: NonNull<c_void>, sample_count: u32) -> Self::OutputPortType;
+
+    /// Return the size of the port's buffer in bytes.
+    ///
+    /// This is the size of the memory that is borrowed by the port handles during a `run` call, which is used to detect aliasing ports. Port types that copy their input, like [`Control`](struct.Control.html), don't borrow any memory and use the default implementation, which returns 0.
+    fn buffer_size(_sample_count: u32) -> usize {
+        0
+    }
 }
 
 /// Audio port type.
 ///
 /// Audio ports are the most common type of input/output ports: Their input is a slice of audio samples, as well as their output.
+///
+/// Hosts may connect an input and an output port to the same buffer, which would create aliasing references with [`InputPort`](struct.InputPort.html) and [`OutputPort`](struct.OutputPort.html). Therefore, plugins that use these handles for audio ports have to require the [`InPlaceBroken`](../feature/struct.InPlaceBroken.html) feature. Plugins that want to support in-place processing use [`InPlaceInput`](struct.InPlaceInput.html) and [`InPlaceOutput`](struct.InPlaceOutput.html) instead.
 pub struct Audio;
 
 unsafe impl UriBound for Audio {
@@ -58,6 +73,11 @@ impl PortType for Audio {
     unsafe fn output_from_raw(pointer: NonNull<c_void>, sample_count: u32) -> Self::OutputPortType {
         std::slice::from_raw_parts_mut(pointer.as_ptr() as *mut f32, sample_count as usize)
     }
+
+    #[inline]
+    fn buffer_size(sample_count: u32) -> usize {
+        sample_count as usize * std::mem::size_of::<f32>()
+    }
 }
 
 /// Control value port type.
@@ -88,6 +108,8 @@ impl PortType for Control {
 /// CV port type.
 ///
 /// Control ports in general are used to control the behaviour of the plugin. CV ports are sampled just like [audio data](struct.Audio.html). This means that audio data is often valid CV data, but CV data generally is not audio data, because it may not be within the audio bounds of -1.0 to 1.0.
+///
+/// Like audio ports, CV ports may be processed in-place with [`InPlaceInput`](struct.InPlaceInput.html) and [`InPlaceOutput`](struct.InPlaceOutput.html).
 pub struct CV;
 
 unsafe impl UriBound for CV {
@@ -107,10 +129,92 @@ impl PortType for CV {
     unsafe fn output_from_raw(pointer: NonNull<c_void>, sample_count: u32) -> Self::OutputPortType {
         std::slice::from_raw_parts_mut(pointer.as_ptr() as *mut f32, sample_count as usize)
     }
+
+    #[inline]
+    fn buffer_size(sample_count: u32) -> usize {
+        sample_count as usize * std::mem::size_of::<f32>()
+    }
+}
+
+/// The direction of a port.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PortDirection {
+    /// The host writes to the port and the plugin reads from it.
+    Input,
+    /// The plugin writes to the port and the host reads from it.
+    Output,
+}
+
+/// Information about a port of a port collection.
+///
+/// This information is used to generate the metadata of a plugin. A table with the information about all ports of a collection is returned by [`PortCollection::port_info`](trait.PortCollection.html#method.port_info).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PortInfo {
+    /// The index of the port.
+    pub index: u32,
+    /// The symbol of the port, which is the name of the field if it isn't set with the `port` attribute.
+    pub symbol: &'static str,
+    /// The human-readable name of the port, which is the symbol if it isn't set with the `port` attribute.
+    pub name: &'static str,
+    /// The direction of the port.
+    pub direction: PortDirection,
+    /// The URI of the port's class, with a null terminator.
+    pub class: &'static [u8],
+    /// Whether the port doesn't have to be connected by the host.
+    pub optional: bool,
+    /// The minimum value of the port.
+    pub minimum: Option<f32>,
+    /// The maximum value of the port.
+    pub maximum: Option<f32>,
+    /// The default value of the port.
+    pub default: Option<f32>,
+    /// The URI of the port's unit, with a null terminator.
+    pub unit: Option<&'static [u8]>,
+}
+
+impl PortInfo {
+    /// Return the URI of the port's class.
+    pub fn class_uri(&self) -> &'static Uri {
+        Uri::from_bytes_with_nul(self.class).unwrap()
+    }
+
+    /// Return the URI of the port's unit, if it has one.
+    pub fn unit_uri(&self) -> Option<&'static Uri> {
+        self.unit
+            .map(|unit| Uri::from_bytes_with_nul(unit).unwrap())
+    }
+
+    /// Clamp a value to the range of the port.
+    ///
+    /// Hosts are supposed to respect the range of a control port, but they aren't forced to. Missing bounds don't limit the value.
+    pub fn clamp(&self, value: f32) -> f32 {
+        let value = match self.minimum {
+            Some(minimum) if value < minimum => minimum,
+            _ => value,
+        };
+        match self.maximum {
+            Some(maximum) if value > maximum => maximum,
+            _ => value,
+        }
+    }
 }
 
 /// Abstraction of safe port handles.
 pub trait PortHandle: Sized {
+    /// The direction of the port.
+    const DIRECTION: PortDirection;
+
+    /// The URI of the port's class, with a null terminator.
+    const CLASS: &'static [u8];
+
+    /// Whether the port doesn't have to be connected by the host.
+    const OPTIONAL: bool = false;
+
+    /// Whether the port may share its buffer with other ports.
+    ///
+    /// If a port collection contains ports that may share their buffers, the derived implementation of [`PortCollection::from_connections`](trait.PortCollection.html#tymethod.from_connections) checks the buffers of all ports for aliasing.
+    const IN_PLACE: bool = false;
+
     /// Try to create a port handle from a port connection pointer and the sample count.
     ///
     /// If the pointer is null, this method will return `None`.
@@ -119,6 +223,18 @@ pub trait PortHandle: Sized {
     ///
     /// Implementing this method requires a de-referentation of a raw pointer and therefore, it is unsafe.
     unsafe fn from_raw(pointer: *mut c_void, sample_count: u32) -> Option<Self>;
+
+    /// Return the buffer that a handle created from the pointer would borrow, if there is one.
+    ///
+    /// This is used to detect aliasing before any handle is created, which is why it only looks at the address of the pointer. The default implementation returns `None`.
+    fn raw_buffer(_pointer: *mut c_void, _sample_count: u32) -> Option<PortBuffer> {
+        None
+    }
+
+    /// Tell the port handle how its buffer is shared with other ports.
+    ///
+    /// This is called by the derived implementation of [`PortCollection::from_connections`](trait.PortCollection.html#tymethod.from_connections) after the aliasing of all buffers has been analyzed. Since the aliasing of a port is unknown otherwise, port handles have to assume the worst until this method has been called.
+    fn set_aliasing(&mut self, _aliasing: Aliasing) {}
 }
 
 /// Handle for input ports.
@@ -138,6 +254,9 @@ impl<T: PortType> Deref for InputPort<T> {
 }
 
 impl<T: PortType> PortHandle for InputPort<T> {
+    const DIRECTION: PortDirection = PortDirection::Input;
+    const CLASS: &'static [u8] = T::URI;
+
     #[inline]
     unsafe fn from_raw(pointer: *mut c_void, sample_count: u32) -> Option<Self> {
         if let Some(pointer) = NonNull::new(pointer) {
@@ -148,6 +267,11 @@ impl<T: PortType> PortHandle for InputPort<T> {
             None
         }
     }
+
+    fn raw_buffer(pointer: *mut c_void, sample_count: u32) -> Option<PortBuffer> {
+        let range = buffer_range(NonNull::new(pointer)?, T::buffer_size(sample_count));
+        PortBuffer::new(range, Self::DIRECTION, Self::IN_PLACE)
+    }
 }
 
 /// Handle for output ports.
@@ -174,6 +298,9 @@ impl<T: PortType> DerefMut for OutputPort<T> {
 }
 
 impl<T: PortType> PortHandle for OutputPort<T> {
+    const DIRECTION: PortDirection = PortDirection::Output;
+    const CLASS: &'static [u8] = T::URI;
+
     #[inline]
     unsafe fn from_raw(pointer: *mut c_void, sample_count: u32) -> Option<Self> {
         if let Some(pointer) = NonNull::new(pointer) {
@@ -184,12 +311,32 @@ impl<T: PortType> PortHandle for OutputPort<T> {
             None
         }
     }
+
+    fn raw_buffer(pointer: *mut c_void, sample_count: u32) -> Option<PortBuffer> {
+        let range = buffer_range(NonNull::new(pointer)?, T::buffer_size(sample_count));
+        PortBuffer::new(range, Self::DIRECTION, Self::IN_PLACE)
+    }
 }
 
 impl<T: PortHandle> PortHandle for Option<T> {
+    const DIRECTION: PortDirection = T::DIRECTION;
+    const CLASS: &'static [u8] = T::CLASS;
+    const OPTIONAL: bool = true;
+    const IN_PLACE: bool = T::IN_PLACE;
+
     unsafe fn from_raw(pointer: *mut c_void, sample_count: u32) -> Option<Self> {
         Some(T::from_raw(pointer, sample_count))
     }
+
+    fn raw_buffer(pointer: *mut c_void, sample_count: u32) -> Option<PortBuffer> {
+        T::raw_buffer(pointer, sample_count)
+    }
+
+    fn set_aliasing(&mut self, aliasing: Aliasing) {
+        if let Some(port) = self.as_mut() {
+            port.set_aliasing(aliasing);
+        }
+    }
 }
 
 /// Collection of IO ports.
@@ -212,6 +359,28 @@ impl<T: PortHandle> PortHandle for Option<T> {
 ///     }
 ///
 /// Please note that port indices are mapped in the order of occurence; In our example, the implementation will treat `audio_input` as port `0`, `audio_output` as port `1` and so on. Therefore, your plugin definition and your port collection have to match. Otherwise, undefined behaviour will occur.
+///
+/// # Port attributes
+///
+/// The metadata of a port can be declared with a `#[port(...)]` attribute on its field. It accepts the entries `symbol` and `name`, which are strings, `min`, `max` and `default`, which are numbers, and `unit`, which is a type that implements [`UriBound`](../trait.UriBound.html), like the units of the `lv2-units` crate. The derive macro checks these values and stores them in the table returned by [`port_info`](#method.port_info), which can be used to generate the plugin's metadata or to clamp control inputs:
+///
+///     use lv2_core::port::*;
+///
+///     #[derive(PortCollection)]
+///     struct MyPortCollection {
+///         #[port(name = "Gain", min = -90.0, max = 24.0, default = 0.0)]
+///         gain: InputPort<Control>,
+///         #[port(symbol = "in")]
+///         input: InputPort<Audio>,
+///         #[port(symbol = "out")]
+///         output: OutputPort<Audio>,
+///     }
+///
+///     let gain_info = &MyPortCollection::port_info()[0];
+///     assert_eq!("gain", gain_info.symbol);
+///     assert_eq!("Gain", gain_info.name);
+///     assert_eq!(24.0, gain_info.clamp(42.0));
+///     assert_eq!("in", MyPortCollection::port_info()[1].symbol);
 pub trait PortCollection: Sized {
     /// The type of the port pointer cache.
     ///
@@ -225,7 +394,23 @@ pub trait PortCollection: Sized {
     /// # Safety
     ///
     /// Since the pointer cache is only storing the pointers, implementing this method requires the de-referencation of raw pointers and therefore, this method is unsafe.
+    ///
+    /// If the collection contains ports that may share their buffers, like [`InPlaceOutput`](struct.InPlaceOutput.html), the derived implementation also checks the buffers of all ports for aliasing before any handle is created. If a buffer is shared in a way that would create aliasing references, it returns `None` and the plugin isn't run.
     unsafe fn from_connections(cache: &Self::Cache, sample_count: u32) -> Option<Self>;
+
+    /// Check whether the ports can borrow the buffers they are connected to.
+    ///
+    /// Hosts may connect an input and an output to the same buffer unless the plugin requires [`InPlaceBroken`](../feature/struct.InPlaceBroken.html). Regular port handles can't share their buffers, but in-place ports like [`InPlaceOutput`](struct.InPlaceOutput.html) can. If a collection contains in-place ports, the derived implementation returns `false` if a regular port overlaps with an in-place port in a way that involves writing, which is exactly when [`from_connections`](#tymethod.from_connections) rejects the buffers. Only the addresses of the buffers are compared. The default implementation returns `true`.
+    fn check_aliasing(_cache: &Self::Cache, _sample_count: u32) -> bool {
+        true
+    }
+
+    /// Return information about all ports of the collection, ordered by their index.
+    ///
+    /// The derive macro generates a static table with one entry per field, which also contains the metadata from the `#[port(...)]` attributes of the fields. The default implementation returns an empty table.
+    fn port_info() -> &'static [PortInfo] {
+        &[]
+    }
 }
 
 impl PortCollection for () {
@@ -0,0 +1,504 @@
+//! A host to test plugins without a real host.
+//!
+//! Testing a plugin with the C interface requires building feature arrays, allocating port buffers and passing raw pointers around. The [`Harness`](struct.Harness.html) does all of this: It provides the URID map and unmap features, owns a buffer for every port of the plugin and drives the plugin through the same [`PluginInstance`](../plugin/struct.PluginInstance.html) entry points a real host uses.
+//!
+//! The buffers are created from the [`port_info`](../port/trait.PortCollection.html#method.port_info) table of the port collection, which means that the port collection has to be derived. Ports are identified by their symbol.
+//!
+//! This module is only available with the `host` feature. Other crates extend the harness: For example, `lv2-atom` lets tests write atoms to input ports and read atoms from output ports.
+//!
+//! # Example
+//!
+//! ```
+//! use lv2_core::prelude::*;
+//! use lv2_core::test::Harness;
+//!
+//! #[derive(PortCollection)]
+//! struct Ports {
+//!     #[port(default = 1.0)]
+//!     gain: InputPort<Control>,
+//!     input: InputPort<Audio>,
+//!     output: OutputPort<Audio>,
+//! }
+//!
+//! struct Amp;
+//!
+//! unsafe impl UriBound for Amp {
+//!     const URI: &'static [u8] = b"urn:rust-lv2:amp\0";
+//! }
+//!
+//! impl Plugin for Amp {
+//!     type Ports = Ports;
+//!     type Features = ();
+//!
+//!     fn new(_: &PluginInfo, _: ()) -> Option<Self> {
+//!         Some(Self)
+//!     }
+//!
+//!     fn run(&mut self, ports: &mut Ports) {
+//!         for (input, output) in ports.input.iter().zip(ports.output.iter_mut()) {
+//!             *output = *input * *ports.gain;
+//!         }
+//!     }
+//! }
+//!
+//! let mut harness = Harness::<Amp>::new(44100.0, 64);
+//! assert!(harness.instantiate());
+//! harness.activate();
+//!
+//! harness.set_control("gain", 2.0);
+//! harness.samples_mut("input")[..4].copy_from_slice(&[1.0, 2.0, 3.0, 4.0]);
+//! harness.run(4);
+//!
+//! assert_eq!(&[2.0, 4.0, 6.0, 8.0], &harness.samples("output")[..4]);
+//! ```
+use crate::plugin::{Plugin, PluginInstance};
+use crate::port::{Audio, PortCollection, PortDirection, PortInfo, CV};
+use crate::{Uri, UriBound, UriBuf};
+use std::any::Any;
+use std::collections::HashMap;
+use std::ffi::c_void;
+use std::marker::PhantomData;
+use std::os::raw::c_char;
+use std::sync::Mutex;
+
+/// The default capacity of atom port buffers, in bytes.
+pub const DEFAULT_ATOM_CAPACITY: usize = 8192;
+
+/// The URI ↔ URID mapper of the harness.
+#[derive(Default)]
+struct Mapper {
+    urids: Mutex<(HashMap<UriBuf, u32>, Vec<UriBuf>)>,
+}
+
+impl Mapper {
+    fn map(&self, uri: &Uri) -> u32 {
+        let mut urids = self.urids.lock().unwrap();
+        let (map, uris) = &mut *urids;
+        if let Some(urid) = map.get(uri) {
+            return *urid;
+        }
+        uris.push(uri.to_owned());
+        let urid = uris.len() as u32;
+        map.insert(uri.to_owned(), urid);
+        urid
+    }
+
+    unsafe extern "C" fn extern_map(handle: *mut c_void, uri: *const c_char) -> u32 {
+        match ((handle as *const Self).as_ref(), uri.is_null()) {
+            (Some(mapper), false) => mapper.map(Uri::from_ptr(uri)),
+            _ => 0,
+        }
+    }
+
+    unsafe extern "C" fn extern_unmap(handle: *mut c_void, urid: u32) -> *const c_char {
+        let mapper = match (handle as *const Self).as_ref() {
+            Some(mapper) => mapper,
+            None => return std::ptr::null(),
+        };
+        let urids = mapper.urids.lock().unwrap();
+        match urid
+            .checked_sub(1)
+            .and_then(|index| urids.1.get(index as usize))
+        {
+            // The string is owned by the mapper and never moved or dropped before the mapper.
+            Some(uri) => uri.as_ptr(),
+            None => std::ptr::null(),
+        }
+    }
+}
+
+/// The buffer of a port.
+enum Buffer {
+    /// Audio and CV samples.
+    Samples(Vec<f32>),
+    /// A single control value.
+    Control(Box<f32>),
+    /// Atoms or any other data, aligned to 64 bits.
+    Atom(Vec<u64>),
+}
+
+impl Buffer {
+    fn new(info: &PortInfo, block_size: usize, atom_capacity: usize) -> Self {
+        if info.class == Audio::URI || info.class == CV::URI {
+            Buffer::Samples(vec![0.0; block_size])
+        } else if info.class == crate::port::Control::URI {
+            Buffer::Control(Box::new(info.default.unwrap_or(0.0)))
+        } else {
+            // The capacity is rounded up to whole 64-bit words.
+            let len = ((atom_capacity + 7) & !7) / 8;
+            Buffer::Atom(vec![0; len.max(1)])
+        }
+    }
+
+    fn as_ptr(&mut self) -> *mut c_void {
+        match self {
+            Buffer::Samples(samples) => samples.as_mut_ptr() as *mut c_void,
+            Buffer::Control(value) => value.as_mut() as *mut f32 as *mut c_void,
+            Buffer::Atom(data) => data.as_mut_ptr() as *mut c_void,
+        }
+    }
+}
+
+/// A host to test plugins.
+///
+/// [See also the module documentation.](index.html)
+pub struct Harness<P: Plugin> {
+    descriptor: Box<sys::LV2_Descriptor>,
+    sample_rate: f64,
+    bundle_path: UriBuf,
+    block_size: usize,
+    mapper: Box<Mapper>,
+    map: Box<sys::LV2_URID_Map>,
+    unmap: Box<sys::LV2_URID_Unmap>,
+    features: Vec<(UriBuf, *mut c_void)>,
+    feature_data: Vec<Box<dyn Any>>,
+    buffers: Vec<Buffer>,
+    handle: sys::LV2_Handle,
+    active: bool,
+    plugin: PhantomData<P>,
+}
+
+impl<P: Plugin> Harness<P> {
+    /// Create a new harness.
+    ///
+    /// `block_size` is the maximal number of frames per `run` call, which is also the length of the audio and CV buffers. The plugin isn't instantiated yet, which means that features can still be added.
+    pub fn new(sample_rate: f64, block_size: u32) -> Self {
+        let mapper = Box::new(Mapper::default());
+        let mapper_handle = mapper.as_ref() as *const Mapper as *mut c_void;
+        let mut map = Box::new(sys::LV2_URID_Map {
+            handle: mapper_handle,
+            map: Some(Mapper::extern_map),
+        });
+        let mut unmap = Box::new(sys::LV2_URID_Unmap {
+            handle: mapper_handle,
+            unmap: Some(Mapper::extern_unmap),
+        });
+        let features = vec![
+            (
+                Uri::from_bytes_with_nul(sys::LV2_URID__map)
+                    .unwrap()
+                    .to_owned(),
+                map.as_mut() as *mut sys::LV2_URID_Map as *mut c_void,
+            ),
+            (
+                Uri::from_bytes_with_nul(sys::LV2_URID__unmap)
+                    .unwrap()
+                    .to_owned(),
+                unmap.as_mut() as *mut sys::LV2_URID_Unmap as *mut c_void,
+            ),
+        ];
+
+        let block_size = block_size as usize;
+        let buffers = P::Ports::port_info()
+            .iter()
+            .map(|info| Buffer::new(info, block_size, DEFAULT_ATOM_CAPACITY))
+            .collect();
+
+        Self {
+            descriptor: Box::new(sys::LV2_Descriptor {
+                URI: P::URI.as_ptr() as *const c_char,
+                instantiate: Some(PluginInstance::<P>::instantiate),
+                connect_port: Some(PluginInstance::<P>::connect_port),
+                activate: Some(PluginInstance::<P>::activate),
+                run: Some(PluginInstance::<P>::run),
+                deactivate: Some(PluginInstance::<P>::deactivate),
+                cleanup: Some(PluginInstance::<P>::cleanup),
+                extension_data: Some(PluginInstance::<P>::extension_data),
+            }),
+            sample_rate,
+            bundle_path: UriBuf::new("/tmp/rust-lv2-test.lv2/").unwrap(),
+            block_size,
+            mapper,
+            map,
+            unmap,
+            features,
+            feature_data: Vec::new(),
+            buffers,
+            handle: std::ptr::null_mut(),
+            active: false,
+            plugin: PhantomData,
+        }
+    }
+
+    /// Set the bundle path that is passed to the plugin.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the path contains a null character.
+    pub fn set_bundle_path(&mut self, path: &str) {
+        self.bundle_path = UriBuf::new(path).unwrap();
+    }
+
+    /// Add a feature that is passed to the plugin when it is instantiated.
+    ///
+    /// The data is owned by the harness and lives as long as the harness. Use `()` for features without data.
+    pub fn add_feature<T: 'static>(&mut self, uri: &Uri, data: T) {
+        let mut data = Box::new(data);
+        let pointer = if std::mem::size_of::<T>() == 0 {
+            std::ptr::null_mut()
+        } else {
+            data.as_mut() as *mut T as *mut c_void
+        };
+        self.features.push((uri.to_owned(), pointer));
+        self.feature_data.push(data);
+    }
+
+    /// Keep a value alive as long as the harness.
+    ///
+    /// This is useful for data that is referenced by the data of a feature.
+    pub fn keep_alive<T: 'static>(&mut self, value: Box<T>) {
+        self.feature_data.push(value);
+    }
+
+    /// Set the capacity of the buffers of atom ports, in bytes.
+    ///
+    /// The contents of the buffers are cleared.
+    pub fn set_atom_capacity(&mut self, capacity: usize) {
+        for (info, buffer) in P::Ports::port_info().iter().zip(self.buffers.iter_mut()) {
+            if let Buffer::Atom(_) = buffer {
+                *buffer = Buffer::new(info, self.block_size, capacity);
+            }
+        }
+    }
+
+    /// Return the URID map feature of the harness.
+    pub fn map_feature(&self) -> &sys::LV2_URID_Map {
+        self.map.as_ref()
+    }
+
+    /// Return the URID unmap feature of the harness.
+    pub fn unmap_feature(&self) -> &sys::LV2_URID_Unmap {
+        self.unmap.as_ref()
+    }
+
+    /// Map a URI to a URID with the map of the harness.
+    pub fn map(&self, uri: &Uri) -> u32 {
+        self.mapper.map(uri)
+    }
+
+    /// Instantiate the plugin.
+    ///
+    /// Returns `false` if the plugin couldn't be instantiated, for example because a required feature is missing.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the plugin has already been instantiated.
+    pub fn instantiate(&mut self) -> bool {
+        assert!(self.handle.is_null(), "The plugin is already instantiated");
+
+        let features: Vec<sys::LV2_Feature> = self
+            .features
+            .iter()
+            .map(|(uri, data)| sys::LV2_Feature {
+                URI: uri.as_ptr(),
+                data: *data,
+            })
+            .collect();
+        let mut feature_pointers: Vec<*const sys::LV2_Feature> = features
+            .iter()
+            .map(|feature| feature as *const sys::LV2_Feature)
+            .collect();
+        feature_pointers.push(std::ptr::null());
+
+        self.handle = unsafe {
+            PluginInstance::<P>::instantiate(
+                self.descriptor.as_ref(),
+                self.sample_rate,
+                self.bundle_path.as_ptr(),
+                feature_pointers.as_ptr(),
+            )
+        };
+        !self.handle.is_null()
+    }
+
+    /// Return a reference to the plugin, if it has been instantiated.
+    pub fn plugin(&self) -> Option<&P> {
+        unsafe { (self.handle as *const P).as_ref() }
+    }
+
+    /// Return a mutable reference to the plugin, if it has been instantiated.
+    pub fn plugin_mut(&mut self) -> Option<&mut P> {
+        unsafe { (self.handle as *mut P).as_mut() }
+    }
+
+    /// Return the handle of the plugin instance, which is null if the plugin hasn't been instantiated.
+    ///
+    /// The handle can be used to call the methods of extensions.
+    pub fn handle(&self) -> sys::LV2_Handle {
+        self.handle
+    }
+
+    /// Check whether the instance is faulted because one of its methods panicked.
+    pub fn is_faulted(&self) -> bool {
+        unsafe { PluginInstance::<P>::is_faulted(self.handle) }
+    }
+
+    /// Activate the plugin.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the plugin hasn't been instantiated or is already active.
+    pub fn activate(&mut self) {
+        assert!(!self.handle.is_null(), "The plugin isn't instantiated");
+        assert!(!self.active, "The plugin is already active");
+        unsafe { PluginInstance::<P>::activate(self.handle) };
+        self.active = true;
+    }
+
+    /// Deactivate the plugin.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the plugin isn't active.
+    pub fn deactivate(&mut self) {
+        assert!(self.active, "The plugin isn't active");
+        unsafe { PluginInstance::<P>::deactivate(self.handle) };
+        self.active = false;
+    }
+
+    /// Connect all ports and run the plugin for the given number of frames.
+    ///
+    /// Before the plugin is run, the buffers of atom output ports are prepared to receive atoms, which clears their previous contents.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the plugin isn't active or if the number of frames is greater than the block size.
+    pub fn run(&mut self, sample_count: u32) {
+        assert!(self.active, "The plugin isn't active");
+        assert!(
+            sample_count as usize <= self.block_size,
+            "The number of frames exceeds the block size"
+        );
+
+        let chunk = self.map(Uri::from_bytes_with_nul(sys::LV2_ATOM__Chunk).unwrap());
+        for (info, buffer) in P::Ports::port_info().iter().zip(self.buffers.iter_mut()) {
+            if let (PortDirection::Output, Buffer::Atom(data)) = (info.direction, &mut *buffer) {
+                // An output atom port contains an empty chunk with the capacity of the buffer.
+                let size = data.len() * 8 - std::mem::size_of::<sys::LV2_Atom>();
+                let header = data.as_mut_ptr() as *mut sys::LV2_Atom;
+                unsafe {
+                    (*header).size = size as u32;
+                    (*header).type_ = chunk;
+                }
+            }
+            unsafe { PluginInstance::<P>::connect_port(self.handle, info.index, buffer.as_ptr()) };
+        }
+
+        unsafe { PluginInstance::<P>::run(self.handle, sample_count) };
+    }
+
+    /// Find the port with the given symbol.
+    fn buffer(&self, symbol: &str) -> &Buffer {
+        match P::Ports::port_info()
+            .iter()
+            .position(|info| info.symbol == symbol)
+        {
+            Some(index) => &self.buffers[index],
+            None => panic!("The plugin has no port with the symbol `{}`", symbol),
+        }
+    }
+
+    /// Find the port with the given symbol.
+    fn buffer_mut(&mut self, symbol: &str) -> &mut Buffer {
+        match P::Ports::port_info()
+            .iter()
+            .position(|info| info.symbol == symbol)
+        {
+            Some(index) => &mut self.buffers[index],
+            None => panic!("The plugin has no port with the symbol `{}`", symbol),
+        }
+    }
+
+    /// Return the value of a control port.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is no control port with the given symbol.
+    pub fn control(&self, symbol: &str) -> f32 {
+        match self.buffer(symbol) {
+            Buffer::Control(value) => **value,
+            _ => panic!("The port `{}` isn't a control port", symbol),
+        }
+    }
+
+    /// Set the value of a control port.
+    ///
+    /// Initially, control ports have their default value, or `0.0` if they don't have one.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is no control port with the given symbol.
+    pub fn set_control(&mut self, symbol: &str, value: f32) {
+        match self.buffer_mut(symbol) {
+            Buffer::Control(control) => **control = value,
+            _ => panic!("The port `{}` isn't a control port", symbol),
+        }
+    }
+
+    /// Return the samples of an audio or CV port.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is no audio or CV port with the given symbol.
+    pub fn samples(&self, symbol: &str) -> &[f32] {
+        match self.buffer(symbol) {
+            Buffer::Samples(samples) => samples.as_ref(),
+            _ => panic!("The port `{}` isn't an audio or CV port", symbol),
+        }
+    }
+
+    /// Return the mutable samples of an audio or CV port.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is no audio or CV port with the given symbol.
+    pub fn samples_mut(&mut self, symbol: &str) -> &mut [f32] {
+        match self.buffer_mut(symbol) {
+            Buffer::Samples(samples) => samples.as_mut(),
+            _ => panic!("The port `{}` isn't an audio or CV port", symbol),
+        }
+    }
+
+    /// Return the buffer of an atom port, or of any other port with an unknown class.
+    ///
+    /// The buffer is aligned to 64 bits.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is no atom port with the given symbol.
+    pub fn atom_buffer(&self, symbol: &str) -> &[u8] {
+        match self.buffer(symbol) {
+            Buffer::Atom(data) => unsafe {
+                std::slice::from_raw_parts(data.as_ptr() as *const u8, data.len() * 8)
+            },
+            _ => panic!("The port `{}` isn't an atom port", symbol),
+        }
+    }
+
+    /// Return the mutable buffer of an atom port, or of any other port with an unknown class.
+    ///
+    /// The buffer is aligned to 64 bits.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is no atom port with the given symbol.
+    pub fn atom_buffer_mut(&mut self, symbol: &str) -> &mut [u8] {
+        match self.buffer_mut(symbol) {
+            Buffer::Atom(data) => unsafe {
+                std::slice::from_raw_parts_mut(data.as_mut_ptr() as *mut u8, data.len() * 8)
+            },
+            _ => panic!("The port `{}` isn't an atom port", symbol),
+        }
+    }
+}
+
+impl<P: Plugin> Drop for Harness<P> {
+    fn drop(&mut self) {
+        if self.active {
+            self.deactivate();
+        }
+        if !self.handle.is_null() {
+            unsafe { PluginInstance::<P>::cleanup(self.handle) };
+        }
+    }
+}
@@ -12,8 +12,15 @@ unsafe impl UriBound for Amp {
     const URI: &'static [u8] = b"http://lv2plug.in/plugins.rs/example_amp\0";
 }
 
+struct Coefficient;
+
+unsafe impl UriBound for Coefficient {
+    const URI: &'static [u8] = b"http://lv2plug.in/ns/extensions/units#coef\0";
+}
+
 #[derive(PortCollection)]
 struct AmpPorts {
+    #[port(name = "Gain", min = 0.0, max = 4.0, default = 1.0, unit = Coefficient)]
     gain: InputPort<Control>,
     input: InputPort<Audio>,
     output: OutputPort<Audio>,
@@ -154,3 +161,59 @@ fn test_plugin() {
         assert!((input[i] * gain - output[i]).abs() < std::f32::EPSILON);
     }
 }
+
+#[test]
+fn test_metadata() {
+    use lv2_core::metadata::*;
+
+    let metadata = PluginMetadata::new::<Amp>().with_name("Amp \"Example\"");
+
+    let ports = metadata.ports();
+    assert_eq!(3, ports.len());
+    assert_eq!(0, ports[0].index);
+    assert_eq!("gain", ports[0].symbol);
+    assert_eq!(PortDirection::Input, ports[0].direction);
+    assert_eq!(Control::uri(), ports[0].class_uri());
+    assert_eq!("Gain", ports[0].name);
+    assert_eq!(Some(0.0), ports[0].minimum);
+    assert_eq!(Some(4.0), ports[0].maximum);
+    assert_eq!(Some(1.0), ports[0].default);
+    assert_eq!(Some(Coefficient::uri()), ports[0].unit_uri());
+    assert_eq!(4.0, ports[0].clamp(5.0));
+    assert_eq!(0.0, ports[0].clamp(-1.0));
+    assert_eq!("input", ports[1].name);
+    assert_eq!(None, ports[1].minimum);
+    assert_eq!(None, ports[1].unit_uri());
+    assert_eq!(2, ports[2].index);
+    assert_eq!("output", ports[2].symbol);
+    assert_eq!(PortDirection::Output, ports[2].direction);
+    assert_eq!(Audio::uri(), ports[2].class_uri());
+
+    let features = metadata.features();
+    assert_eq!(2, features.len());
+    assert_eq!(HardRTCapable::uri(), features[0].uri);
+    assert!(features[0].required);
+    assert_eq!(IsLive::uri(), features[1].uri);
+    assert!(!features[1].required);
+
+    assert!(metadata.extensions().is_empty());
+
+    let mut turtle: Vec<u8> = Vec::new();
+    metadata.write_turtle(&mut turtle).unwrap();
+    let turtle = String::from_utf8(turtle).unwrap();
+    assert!(turtle.contains("<http://lv2plug.in/plugins.rs/example_amp>\n    a lv2:Plugin ;"));
+    assert!(turtle.contains("doap:name \"Amp \\\"Example\\\"\""));
+    assert!(turtle.contains("lv2:requiredFeature <http://lv2plug.in/ns/lv2core#hardRTCapable>"));
+    assert!(turtle.contains("lv2:optionalFeature <http://lv2plug.in/ns/lv2core#isLive>"));
+    assert!(turtle.contains(
+        "        lv2:name \"Gain\" ;\n        lv2:default 1.0 ;\n        lv2:minimum 0.0 ;\n        lv2:maximum 4.0 ;\n        units:unit <http://lv2plug.in/ns/extensions/units#coef>\n"
+    ));
+    assert!(turtle.contains(
+        "        a lv2:OutputPort , <http://lv2plug.in/ns/lv2core#AudioPort> ;\n        lv2:index 2 ;\n        lv2:symbol \"output\" ;\n        lv2:name \"output\"\n    ] .\n"
+    ));
+
+    let mut manifest: Vec<u8> = Vec::new();
+    write_manifest(&mut manifest, "libamp.so", "amp.ttl", &[&metadata]).unwrap();
+    let manifest = String::from_utf8(manifest).unwrap();
+    assert!(manifest.contains("    lv2:binary <libamp.so> ;\n    rdfs:seeAlso <amp.ttl> .\n"));
+}
@@ -0,0 +1,191 @@
+use lv2_core::plugin::PluginInstance;
+use lv2_core::prelude::*;
+use std::os::raw::c_char;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// The number of skipped cycles that have been reported.
+static SKIPPED: AtomicUsize = AtomicUsize::new(0);
+
+struct InPlaceAmp;
+
+unsafe impl UriBound for InPlaceAmp {
+    const URI: &'static [u8] = b"http://lv2plug.in/plugins.rs/example_in_place_amp\0";
+}
+
+#[derive(PortCollection)]
+struct Ports {
+    gain: InputPort<Control>,
+    input: InPlaceInput<Audio>,
+    output: InPlaceOutput<Audio>,
+    sidechain: Option<InputPort<Audio>>,
+}
+
+impl Plugin for InPlaceAmp {
+    type Ports = Ports;
+    type Features = ();
+
+    fn new(_: &PluginInfo, _: ()) -> Option<Self> {
+        Some(Self)
+    }
+
+    fn run(&mut self, ports: &mut Ports) {
+        let gain = *ports.gain;
+        ports.output.with_input(&mut ports.input).map(|x| x * gain);
+    }
+
+    fn report_skipped_run() {
+        SKIPPED.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+lv2_descriptors! {
+    InPlaceAmp
+}
+
+/// Run the plugin once with the given connections.
+unsafe fn run(connections: [*mut f32; 4], sample_count: u32) {
+    let features: &[*const lv2_sys::LV2_Feature] = &[std::ptr::null()];
+    let descriptor = lv2_descriptor(0).as_ref().unwrap();
+    let plugin = (descriptor.instantiate.unwrap())(
+        descriptor,
+        44100.0,
+        "/home/lv2/amp.lv2/\0".as_ptr() as *const c_char,
+        features.as_ptr(),
+    );
+    assert_ne!(plugin, std::ptr::null_mut());
+    for (index, connection) in connections.iter().enumerate() {
+        (descriptor.connect_port.unwrap())(plugin, index as u32, *connection as *mut _);
+    }
+    (descriptor.activate.unwrap())(plugin);
+    (descriptor.run.unwrap())(plugin, sample_count);
+    (descriptor.deactivate.unwrap())(plugin);
+    (descriptor.cleanup.unwrap())(plugin);
+}
+
+#[test]
+fn test_separate_buffers() {
+    let mut gain: f32 = 2.0;
+    let mut input: Box<[f32; 16]> = Box::new([0.0; 16]);
+    for (i, sample) in input.iter_mut().enumerate() {
+        *sample = i as f32;
+    }
+    let mut output: Box<[f32; 16]> = Box::new([0.0; 16]);
+
+    unsafe {
+        run(
+            [
+                &mut gain,
+                input.as_mut_ptr(),
+                output.as_mut_ptr(),
+                std::ptr::null_mut(),
+            ],
+            16,
+        )
+    };
+
+    for i in 0..16 {
+        assert_eq!(i as f32, input[i]);
+        assert_eq!(i as f32 * 2.0, output[i]);
+    }
+}
+
+#[test]
+fn test_shared_buffer() {
+    let mut gain: f32 = 2.0;
+    let mut buffer: Box<[f32; 16]> = Box::new([0.0; 16]);
+    for (i, sample) in buffer.iter_mut().enumerate() {
+        *sample = i as f32;
+    }
+
+    unsafe {
+        run(
+            [
+                &mut gain,
+                buffer.as_mut_ptr(),
+                buffer.as_mut_ptr(),
+                std::ptr::null_mut(),
+            ],
+            16,
+        )
+    };
+
+    for i in 0..16 {
+        assert_eq!(i as f32 * 2.0, buffer[i]);
+    }
+}
+
+#[test]
+fn test_overlapping_buffers() {
+    let mut gain: f32 = 2.0;
+    let mut buffer: Box<[f32; 24]> = Box::new([1.0; 24]);
+
+    // The output starts eight samples after the input.
+    unsafe {
+        run(
+            [
+                &mut gain,
+                buffer.as_mut_ptr(),
+                buffer.as_mut_ptr().add(8),
+                std::ptr::null_mut(),
+            ],
+            16,
+        )
+    };
+
+    // Processing the samples in order reads the samples that have been written before.
+    for i in 0..8 {
+        assert_eq!(1.0, buffer[i]);
+    }
+    for i in 8..16 {
+        assert_eq!(2.0, buffer[i]);
+    }
+    for i in 16..24 {
+        assert_eq!(4.0, buffer[i]);
+    }
+}
+
+#[test]
+fn test_rejected_aliasing() {
+    let mut gain: f32 = 2.0;
+    let mut input: Box<[f32; 16]> = Box::new([1.0; 16]);
+    let mut output: Box<[f32; 16]> = Box::new([5.0; 16]);
+
+    unsafe {
+        let features: &[*const lv2_sys::LV2_Feature] = &[std::ptr::null()];
+        let descriptor = lv2_descriptor(0).as_ref().unwrap();
+        let plugin = (descriptor.instantiate.unwrap())(
+            descriptor,
+            44100.0,
+            "/home/lv2/amp.lv2/\0".as_ptr() as *const c_char,
+            features.as_ptr(),
+        );
+        assert_ne!(plugin, std::ptr::null_mut());
+        let connect_port = descriptor.connect_port.unwrap();
+        connect_port(plugin, 0, &mut gain as *mut f32 as *mut _);
+        connect_port(plugin, 1, input.as_mut_ptr() as *mut _);
+        connect_port(plugin, 2, output.as_mut_ptr() as *mut _);
+        (descriptor.activate.unwrap())(plugin);
+
+        // The regular sidechain input shares its buffer with the in-place output, so the cycles are skipped and reported once.
+        connect_port(plugin, 3, output.as_mut_ptr() as *mut _);
+        (descriptor.run.unwrap())(plugin, 16);
+        (descriptor.run.unwrap())(plugin, 16);
+        assert_eq!(1, SKIPPED.load(Ordering::SeqCst));
+        assert!(!PluginInstance::<InPlaceAmp>::is_faulted(plugin));
+        for i in 0..16 {
+            assert_eq!(1.0, input[i]);
+            assert_eq!(0.0, output[i]);
+        }
+
+        // The instance runs again once the connections are legal.
+        connect_port(plugin, 3, std::ptr::null_mut());
+        (descriptor.run.unwrap())(plugin, 16);
+        for i in 0..16 {
+            assert_eq!(2.0, output[i]);
+        }
+
+        (descriptor.deactivate.unwrap())(plugin);
+        (descriptor.cleanup.unwrap())(plugin);
+    }
+    assert_eq!(1, SKIPPED.load(Ordering::SeqCst));
+}
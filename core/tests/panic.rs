@@ -0,0 +1,174 @@
+use lv2_core::plugin::{PanicPolicy, PanicReport, PluginInstance};
+use lv2_core::prelude::*;
+use std::os::raw::c_char;
+use std::sync::Mutex;
+
+// The reports are collected and checked by the test, since the report hooks must not panic.
+static FAULTY_REPORTS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+static UNSTABLE_REPORTS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+/// Return the collected reports.
+fn reports(reports: &Mutex<Vec<String>>) -> Vec<String> {
+    reports.lock().unwrap().clone()
+}
+
+#[derive(PortCollection)]
+struct Ports {
+    gain: InputPort<Control>,
+    input: InputPort<Audio>,
+    output: OutputPort<Audio>,
+}
+
+/// Run the amplifier, but panic if the gain is negative.
+fn amplify(ports: &mut Ports) {
+    let gain = *ports.gain;
+    if gain < 0.0 {
+        panic!("Negative gain");
+    }
+    for (input, output) in ports.input.iter().zip(ports.output.iter_mut()) {
+        *output = *input * gain;
+    }
+}
+
+/// A plugin that faults when it panics.
+struct Faulty;
+
+unsafe impl UriBound for Faulty {
+    const URI: &'static [u8] = b"http://lv2plug.in/plugins.rs/faulty\0";
+}
+
+impl Plugin for Faulty {
+    type Ports = Ports;
+    type Features = ();
+
+    fn new(plugin_info: &PluginInfo, _: ()) -> Option<Self> {
+        if plugin_info.sample_rate() <= 0.0 {
+            panic!("Invalid sample rate");
+        }
+        Some(Self)
+    }
+
+    fn run(&mut self, ports: &mut Ports) {
+        amplify(ports);
+    }
+
+    fn report_panic(report: &PanicReport) {
+        if let Ok(mut reports) = FAULTY_REPORTS.lock() {
+            reports.push(report.to_string());
+        }
+    }
+}
+
+/// A plugin that continues to run after panicking.
+struct Unstable;
+
+unsafe impl UriBound for Unstable {
+    const URI: &'static [u8] = b"http://lv2plug.in/plugins.rs/unstable\0";
+}
+
+impl Plugin for Unstable {
+    type Ports = Ports;
+    type Features = ();
+
+    const PANIC_POLICY: PanicPolicy = PanicPolicy::Continue;
+
+    fn new(_: &PluginInfo, _: ()) -> Option<Self> {
+        Some(Self)
+    }
+
+    fn run(&mut self, ports: &mut Ports) {
+        amplify(ports);
+    }
+
+    fn report_panic(report: &PanicReport) {
+        if let Ok(mut reports) = UNSTABLE_REPORTS.lock() {
+            reports.push(report.to_string());
+        }
+    }
+}
+
+lv2_descriptors! {
+    Faulty,
+    Unstable
+}
+
+/// Instantiate a plugin and connect its ports.
+unsafe fn instantiate(
+    index: u32,
+    sample_rate: f64,
+    gain: *mut f32,
+    input: &mut [f32],
+    output: &mut [f32],
+) -> (&'static LV2_Descriptor, lv2_sys::LV2_Handle) {
+    let features: &[*const lv2_sys::LV2_Feature] = &[std::ptr::null()];
+    let descriptor = lv2_descriptor(index).as_ref().unwrap();
+    let plugin = (descriptor.instantiate.unwrap())(
+        descriptor,
+        sample_rate,
+        "/home/lv2/amp.lv2/\0".as_ptr() as *const c_char,
+        features.as_ptr(),
+    );
+    if !plugin.is_null() {
+        let connect_port = descriptor.connect_port.unwrap();
+        (connect_port)(plugin, 0, gain as *mut _);
+        (connect_port)(plugin, 1, input.as_mut_ptr() as *mut _);
+        (connect_port)(plugin, 2, output.as_mut_ptr() as *mut _);
+    }
+    (descriptor, plugin)
+}
+
+#[test]
+fn test_panic_policies() {
+    let mut gain_buffer: Box<f32> = Box::new(2.0);
+    let gain: *mut f32 = gain_buffer.as_mut();
+    let mut input = [1.0; 16];
+    let mut output = [0.0; 16];
+
+    unsafe {
+        // A panic in `new` is reported and the instantiation fails.
+        let (_, plugin) = instantiate(0, 0.0, gain, &mut input, &mut output);
+        assert!(plugin.is_null());
+        assert_eq!(
+            vec!["Plugin <http://lv2plug.in/plugins.rs/faulty> panicked in `instantiate`: Invalid sample rate"],
+            reports(&FAULTY_REPORTS)
+        );
+
+        // After a panic in `run`, the plugin writes silence.
+        let (descriptor, plugin) = instantiate(0, 44100.0, gain, &mut input, &mut output);
+        assert!(!PluginInstance::<Faulty>::is_faulted(plugin));
+        (descriptor.run.unwrap())(plugin, 16);
+        assert_eq!([2.0; 16], output);
+
+        *gain = -1.0;
+        (descriptor.run.unwrap())(plugin, 16);
+        assert_eq!([0.0; 16], output);
+        assert!(PluginInstance::<Faulty>::is_faulted(plugin));
+        assert_eq!(
+            "Plugin <http://lv2plug.in/plugins.rs/faulty> panicked in `run`: Negative gain",
+            reports(&FAULTY_REPORTS)[1]
+        );
+
+        // The faulted plugin isn't run anymore.
+        *gain = 2.0;
+        (descriptor.run.unwrap())(plugin, 16);
+        assert_eq!([0.0; 16], output);
+        assert_eq!(2, reports(&FAULTY_REPORTS).len());
+        (descriptor.cleanup.unwrap())(plugin);
+
+        // With the `Continue` policy, the plugin is run again.
+        let (descriptor, plugin) = instantiate(1, 44100.0, gain, &mut input, &mut output);
+        *gain = -1.0;
+        (descriptor.run.unwrap())(plugin, 16);
+        assert_eq!([0.0; 16], output);
+        assert!(!PluginInstance::<Unstable>::is_faulted(plugin));
+        assert_eq!(
+            vec!["Plugin <http://lv2plug.in/plugins.rs/unstable> panicked in `run`: Negative gain"],
+            reports(&UNSTABLE_REPORTS)
+        );
+
+        *gain = 3.0;
+        (descriptor.run.unwrap())(plugin, 16);
+        assert_eq!([3.0; 16], output);
+        (descriptor.cleanup.unwrap())(plugin);
+    }
+}
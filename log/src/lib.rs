@@ -0,0 +1,362 @@
+//! Logging facility for LV2 plugins.
+//!
+//! The [LV2 Log specification](http://lv2plug.in/ns/ext/log/log.html) provides a host feature that plugins can use to send messages to the host's log. Instead of printing to the standard error stream, which might not be visible to the user at all, the plugin passes it's messages to the host, which may present them in a sensible way.
+//!
+//! Every entry in the log has a type, which is one of the [`ErrorClass`](struct.ErrorClass.html), [`WarningClass`](struct.WarningClass.html), [`NoteClass`](struct.NoteClass.html) or [`TraceClass`](struct.TraceClass.html) marker types. Their URIDs are cached in a [`LogURIDCollection`](struct.LogURIDCollection.html).
+//!
+//! Messages are formatted with the [`log_error`](macro.log_error.html), [`log_warning`](macro.log_warning.html), [`log_note`](macro.log_note.html) and [`log_trace`](macro.log_trace.html) macros, which work like `eprintln!`. Formatting is done in a fixed-size buffer on the stack and therefore, it does not allocate any memory on the heap. Messages that don't fit in the buffer are truncated.
+//!
+//! Note that the specification only guarantees that trace entries can be logged in a realtime context like the `run` method. Other entry types may block, depending on the host.
+//!
+//! # Example
+//!
+//! ```
+//! use lv2_core::prelude::*;
+//! use lv2_log::prelude::*;
+//! use lv2_urid::prelude::*;
+//!
+//! #[derive(PortCollection)]
+//! struct Ports {
+//!     gain: InputPort<Control>,
+//! }
+//!
+//! #[derive(FeatureCollection)]
+//! struct Features<'a> {
+//!     map: Map<'a>,
+//!     log: Log<'a>,
+//! }
+//!
+//! struct LoggingPlugin {
+//!     log: Log<'static>,
+//!     urids: LogURIDCollection,
+//! }
+//!
+//! unsafe impl UriBound for LoggingPlugin {
+//!     const URI: &'static [u8] = b"urn:lv2_log:logging-plugin\0";
+//! }
+//!
+//! impl Plugin for LoggingPlugin {
+//!     type Ports = Ports;
+//!     type Features = Features<'static>;
+//!
+//!     fn new(_: &PluginInfo, features: Features<'static>) -> Option<Self> {
+//!         let urids: LogURIDCollection = features.map.populate_collection()?;
+//!         let _ = log_note!(features.log, urids, "Plugin created!");
+//!         Some(Self {
+//!             log: features.log,
+//!             urids,
+//!         })
+//!     }
+//!
+//!     fn run(&mut self, ports: &mut Ports) {
+//!         if *ports.gain > 24.0 {
+//!             // Trace entries may be logged in the realtime context.
+//!             let _ = log_trace!(self.log, self.urids, "The gain is very high: {}dB", *ports.gain);
+//!         }
+//!     }
+//! }
+//! ```
+extern crate lv2_core as core;
+extern crate lv2_sys as sys;
+extern crate lv2_urid as urid;
+
+use core::feature::Feature;
+use core::UriBound;
+use std::ffi::{c_void, CStr};
+use std::fmt;
+use std::os::raw::c_char;
+use urid::{URIDCollection, URID};
+
+/// Prelude of `lv2_log` for wildcard usage.
+pub mod prelude {
+    pub use crate::{
+        log_entry, log_error, log_note, log_trace, log_warning, EntryType, ErrorClass, Log,
+        LogError, LogURIDCollection, NoteClass, TraceClass, WarningClass,
+    };
+}
+
+/// Marker trait for the types of log entries.
+///
+/// Only URIDs of types implementing this trait can be used as the type of a log entry. Implement it for your own marker type if you want to use a custom entry type.
+pub trait EntryType: UriBound {}
+
+/// The base class of all log entries.
+pub struct EntryClass;
+unsafe impl UriBound for EntryClass {
+    const URI: &'static [u8] = sys::LV2_LOG__Entry;
+}
+
+/// An error message.
+pub struct ErrorClass;
+unsafe impl UriBound for ErrorClass {
+    const URI: &'static [u8] = sys::LV2_LOG__Error;
+}
+impl EntryType for ErrorClass {}
+
+/// An informative message.
+pub struct NoteClass;
+unsafe impl UriBound for NoteClass {
+    const URI: &'static [u8] = sys::LV2_LOG__Note;
+}
+impl EntryType for NoteClass {}
+
+/// A debugging trace.
+///
+/// These entries should not be displayed during normal operation, but the host may implement an option to display them for debugging purposes. This is the only entry type that may be logged in the realtime context.
+pub struct TraceClass;
+unsafe impl UriBound for TraceClass {
+    const URI: &'static [u8] = sys::LV2_LOG__Trace;
+}
+impl EntryType for TraceClass {}
+
+/// A warning message.
+pub struct WarningClass;
+unsafe impl UriBound for WarningClass {
+    const URI: &'static [u8] = sys::LV2_LOG__Warning;
+}
+impl EntryType for WarningClass {}
+
+#[derive(Clone, URIDCollection)]
+/// Collection with the URIDs of all entry types in this crate.
+pub struct LogURIDCollection {
+    pub error: URID<ErrorClass>,
+    pub note: URID<NoteClass>,
+    pub trace: URID<TraceClass>,
+    pub warning: URID<WarningClass>,
+}
+
+/// Errors that may occur when a message is logged.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LogError {
+    /// The host did not provide a printing function.
+    NoCallback,
+    /// The host reported that the message could not be printed.
+    PrintFailed,
+}
+
+/// The maximal length of a formatted message in bytes, including the terminating null character.
+pub const MESSAGE_CAPACITY: usize = 512;
+
+/// Stack buffer to format messages without heap allocations.
+struct MessageBuffer {
+    data: [u8; MESSAGE_CAPACITY],
+    len: usize,
+}
+
+impl MessageBuffer {
+    fn new() -> Self {
+        Self {
+            data: [0; MESSAGE_CAPACITY],
+            len: 0,
+        }
+    }
+
+    /// Return the written message, including the terminating null character.
+    fn as_bytes_with_nul(&self) -> &[u8] {
+        &self.data[..=self.len]
+    }
+}
+
+impl fmt::Write for MessageBuffer {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        // One byte is always reserved for the null character.
+        let available = MESSAGE_CAPACITY - 1 - self.len;
+        let mut length = s.len().min(available);
+        while !s.is_char_boundary(length) {
+            length -= 1;
+        }
+        self.data[self.len..self.len + length].copy_from_slice(&s.as_bytes()[..length]);
+        self.len += length;
+        if length < s.len() {
+            Err(fmt::Error)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Host feature to log messages.
+///
+/// The easiest way to use it are the formatting macros of this crate, like [`log_error`](macro.log_error.html).
+#[repr(transparent)]
+pub struct Log<'a> {
+    internal: &'a sys::LV2_Log_Log,
+}
+
+// The host is required to support logging from any thread.
+unsafe impl<'a> Send for Log<'a> {}
+unsafe impl<'a> Sync for Log<'a> {}
+
+unsafe impl<'a> UriBound for Log<'a> {
+    const URI: &'static [u8] = sys::LV2_LOG__log;
+}
+
+unsafe impl<'a> Feature for Log<'a> {
+    unsafe fn from_feature_ptr(feature: *const c_void) -> Option<Self> {
+        (feature as *const sys::LV2_Log_Log)
+            .as_ref()
+            .map(|internal| Self { internal })
+    }
+}
+
+impl<'a> Log<'a> {
+    /// Create a logger from the log interface of a host.
+    ///
+    /// This is only available with the `host` feature and meant for hosts and tests that provide the interface themselves. Plugins receive the logger as a feature instead.
+    #[cfg(feature = "host")]
+    pub fn new(internal: &'a sys::LV2_Log_Log) -> Self {
+        Self { internal }
+    }
+
+    /// Send a message to the host's log.
+    ///
+    /// The message is passed to the host without further formatting.
+    pub fn print_cstr<T: EntryType>(
+        &self,
+        entry_type: URID<T>,
+        message: &CStr,
+    ) -> Result<(), LogError> {
+        let printf = self.internal.printf.ok_or(LogError::NoCallback)?;
+        let result = unsafe {
+            (printf)(
+                self.internal.handle,
+                entry_type.get(),
+                b"%s\0".as_ptr() as *const c_char,
+                message.as_ptr(),
+            )
+        };
+        if result < 0 {
+            Err(LogError::PrintFailed)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Format a message and send it to the host's log.
+    ///
+    /// The message is formatted in a buffer on the stack, which holds up to [`MESSAGE_CAPACITY`](constant.MESSAGE_CAPACITY.html) bytes, including the terminating null character. Longer messages are truncated and if the message contains a null character, it ends there.
+    ///
+    /// You usually don't call this method directly, but use one of the formatting macros.
+    pub fn print_fmt<T: EntryType>(
+        &self,
+        entry_type: URID<T>,
+        arguments: fmt::Arguments,
+    ) -> Result<(), LogError> {
+        let mut buffer = MessageBuffer::new();
+        // An error only means that the message was truncated.
+        let _ = fmt::write(&mut buffer, arguments);
+        let message =
+            unsafe { CStr::from_ptr(buffer.as_bytes_with_nul().as_ptr() as *const c_char) };
+        self.print_cstr(entry_type, message)
+    }
+}
+
+/// Format a log entry of the given type.
+///
+/// The first argument is the [`Log`](struct.Log.html) feature, the second one is the URID of the entry type and the rest are formatting arguments, just like for `format!`. A newline is appended to the message.
+///
+/// The macro evaluates to a `Result<(), LogError>`.
+#[macro_export]
+macro_rules! log_entry {
+    ($log:expr, $entry_type:expr, $fmt:literal) => {
+        ($log).print_fmt($entry_type, format_args!(concat!($fmt, "\n")))
+    };
+    ($log:expr, $entry_type:expr, $fmt:literal, $($arg:tt)*) => {
+        ($log).print_fmt($entry_type, format_args!(concat!($fmt, "\n"), $($arg)*))
+    };
+}
+
+/// Format an error entry.
+///
+/// The first argument is the [`Log`](struct.Log.html) feature, the second one is a [`LogURIDCollection`](struct.LogURIDCollection.html) and the rest are formatting arguments, just like for `format!`.
+#[macro_export]
+macro_rules! log_error {
+    ($log:expr, $urids:expr, $($arg:tt)+) => {
+        $crate::log_entry!($log, ($urids).error, $($arg)+)
+    };
+}
+
+/// Format a warning entry.
+///
+/// The first argument is the [`Log`](struct.Log.html) feature, the second one is a [`LogURIDCollection`](struct.LogURIDCollection.html) and the rest are formatting arguments, just like for `format!`.
+#[macro_export]
+macro_rules! log_warning {
+    ($log:expr, $urids:expr, $($arg:tt)+) => {
+        $crate::log_entry!($log, ($urids).warning, $($arg)+)
+    };
+}
+
+/// Format a note entry.
+///
+/// The first argument is the [`Log`](struct.Log.html) feature, the second one is a [`LogURIDCollection`](struct.LogURIDCollection.html) and the rest are formatting arguments, just like for `format!`.
+#[macro_export]
+macro_rules! log_note {
+    ($log:expr, $urids:expr, $($arg:tt)+) => {
+        $crate::log_entry!($log, ($urids).note, $($arg)+)
+    };
+}
+
+/// Format a trace entry.
+///
+/// The first argument is the [`Log`](struct.Log.html) feature, the second one is a [`LogURIDCollection`](struct.LogURIDCollection.html) and the rest are formatting arguments, just like for `format!`.
+///
+/// Trace entries are the only entries that may be logged in the realtime context.
+#[macro_export]
+macro_rules! log_trace {
+    ($log:expr, $urids:expr, $($arg:tt)+) => {
+        $crate::log_entry!($log, ($urids).trace, $($arg)+)
+    };
+}
+
+#[cfg(test)]
+#[cfg(feature = "host")]
+mod tests {
+    use crate::*;
+    use std::fmt::Write;
+    use urid::mapper::*;
+    use urid::prelude::*;
+
+    #[test]
+    fn test_message_buffer() {
+        let mut buffer = MessageBuffer::new();
+        let name = "World";
+        write!(buffer, "Hello {}!", name).unwrap();
+        assert_eq!(b"Hello World!\0", buffer.as_bytes_with_nul());
+
+        // Filling the buffer up until one byte is left.
+        let filler = "a".repeat(MESSAGE_CAPACITY - 14);
+        write!(buffer, "{}", filler).unwrap();
+        assert_eq!(MESSAGE_CAPACITY - 2, buffer.len);
+
+        // A two-byte character doesn't fit anymore and must not be split.
+        assert!(write!(buffer, "ä").is_err());
+        assert_eq!(MESSAGE_CAPACITY - 2, buffer.len);
+        assert!(write!(buffer, "bc").is_err());
+        assert_eq!(MESSAGE_CAPACITY - 1, buffer.len);
+        assert_eq!(
+            Some(&0),
+            buffer.as_bytes_with_nul().last(),
+            "The message is not null-terminated"
+        );
+    }
+
+    #[test]
+    fn test_missing_callback() {
+        let mut mapper = Box::pin(HashURIDMapper::new());
+        let interface = mapper.as_mut().make_map_interface();
+        let map = Map::new(&interface);
+        let urids: LogURIDCollection = map.populate_collection().unwrap();
+
+        let raw_log = sys::LV2_Log_Log {
+            handle: std::ptr::null_mut(),
+            printf: None,
+            vprintf: None,
+        };
+        let log = Log::new(&raw_log);
+        assert_eq!(Err(LogError::NoCallback), log_error!(log, urids, "error"));
+        assert_eq!(
+            Err(LogError::NoCallback),
+            log_note!(log, urids, "{} + {} = {}", 1, 2, 3)
+        );
+    }
+}
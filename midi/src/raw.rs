@@ -24,11 +24,11 @@ where
     type WriteParameter = ();
     type WriteHandle = FramedMutSpace<'a, 'b>;
 
-    fn read(body: Space<'a>, _: ()) -> Option<&'a [u8]> {
-        body.data()
+    fn read(body: Space<'a>, _: ()) -> Result<&'a [u8], AtomError> {
+        body.data().ok_or(AtomError::Malformed)
     }
 
-    fn init(frame: FramedMutSpace<'a, 'b>, _: ()) -> Option<FramedMutSpace<'a, 'b>> {
-        Some(frame)
+    fn init(frame: FramedMutSpace<'a, 'b>, _: ()) -> Result<FramedMutSpace<'a, 'b>, AtomError> {
+        Ok(frame)
     }
 }
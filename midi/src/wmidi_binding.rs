@@ -36,7 +36,7 @@
 //!
 //!     for (timestamp, atom) in input_sequence {
 //!         // If the atom encodes a message...
-//!         if let Some(message) = atom.read(urids.midi.wmidi, ()) {
+//!         if let Ok(message) = atom.read(urids.midi.wmidi, ()) {
 //!             // Calculate the message to send.
 //!             let message_to_send = match message {
 //!                 MidiMessage::NoteOn(channel, note, velocity) => {
@@ -84,16 +84,24 @@ where
     type WriteParameter = wmidi::MidiMessage<'static>;
     type WriteHandle = ();
 
-    fn read(space: Space<'a>, _: ()) -> Option<wmidi::MidiMessage<'a>> {
+    fn read(space: Space<'a>, _: ()) -> Result<wmidi::MidiMessage<'a>, AtomError> {
         space
             .data()
             .and_then(|bytes| wmidi::MidiMessage::try_from(bytes).ok())
+            .ok_or(AtomError::Malformed)
     }
 
-    fn init(mut frame: FramedMutSpace<'a, 'b>, message: wmidi::MidiMessage<'b>) -> Option<()> {
+    fn init(
+        mut frame: FramedMutSpace<'a, 'b>,
+        message: wmidi::MidiMessage<'b>,
+    ) -> Result<(), AtomError> {
         frame
             .allocate(message.bytes_size(), false)
-            .and_then(|(_, space)| message.copy_to_slice(space).ok())
+            .and_then(|(_, space)| {
+                message
+                    .copy_to_slice(space)
+                    .map_err(|_| AtomError::OutOfSpace)
+            })
             .map(|_| ())
     }
 }
@@ -118,14 +126,14 @@ where
     type WriteParameter = ();
     type WriteHandle = Writer<'a, 'b>;
 
-    fn read(space: Space<'a>, _: ()) -> Option<wmidi::MidiMessage<'a>> {
+    fn read(space: Space<'a>, _: ()) -> Result<wmidi::MidiMessage<'a>, AtomError> {
         WMidiEvent::read(space, ())
     }
 
-    fn init(frame: FramedMutSpace<'a, 'b>, _: ()) -> Option<Writer<'a, 'b>> {
+    fn init(frame: FramedMutSpace<'a, 'b>, _: ()) -> Result<Writer<'a, 'b>, AtomError> {
         let mut writer = Writer { frame };
-        writer.write::<u8>(&0xf0);
-        Some(writer)
+        writer.write::<u8>(&0xf0)?;
+        Ok(writer)
     }
 }
 
@@ -139,15 +147,15 @@ pub struct Writer<'a, 'b> {
 }
 
 impl<'a, 'b> Writer<'a, 'b> {
-    pub fn allocate(&mut self, size: usize) -> Option<&'a mut [u8]> {
+    pub fn allocate(&mut self, size: usize) -> Result<&'a mut [u8], AtomError> {
         self.frame.allocate(size, false).map(|(_, slice)| slice)
     }
 
-    pub fn write_raw(&mut self, data: &[u8]) -> Option<&'a mut [u8]> {
+    pub fn write_raw(&mut self, data: &[u8]) -> Result<&'a mut [u8], AtomError> {
         self.frame.write_raw(data, false)
     }
 
-    pub fn write<T>(&mut self, instance: &T) -> Option<&'a mut T>
+    pub fn write<T>(&mut self, instance: &T) -> Result<&'a mut T, AtomError>
     where
         T: Unpin + Copy + Send + Sync + Sized + 'static,
     {
@@ -157,14 +165,14 @@ impl<'a, 'b> Writer<'a, 'b> {
 
 impl<'a, 'b> Drop for Writer<'a, 'b> {
     fn drop(&mut self) {
-        self.write::<u8>(&0xf7);
+        let _ = self.write::<u8>(&0xf7);
     }
 }
 
 #[cfg(test)]
 mod tests {
     use crate::wmidi_binding::*;
-    use atom::space::RootMutSpace;
+    use atom::space::{AlignedBuffer, RootMutSpace};
     use std::convert::TryFrom;
     use std::mem::size_of;
     use urid::mapper::*;
@@ -178,7 +186,7 @@ mod tests {
         let map = Map::new(&map_interface);
         let urid = map.map_type::<WMidiEvent>().unwrap();
 
-        let mut raw_space: Box<[u8]> = Box::new([0; 256]);
+        let mut raw_space = AlignedBuffer::new(256);
         let reference_message =
             MidiMessage::NoteOn(Channel::Ch1, Note::A0, Velocity::try_from(125).unwrap());
 
@@ -218,7 +226,7 @@ mod tests {
         let map = Map::new(&map_interface);
         let urid = map.map_type::<SystemExclusiveWMidiEvent>().unwrap();
 
-        let mut raw_space: Box<[u8]> = Box::new([0; 256]);
+        let mut raw_space = AlignedBuffer::new(256);
 
         // writing
         {
@@ -0,0 +1,93 @@
+//! Access to, and restrictions on, buffer sizes.
+//!
+//! The [LV2 Buf-Size specification](http://lv2plug.in/ns/ext/buf-size/buf-size.html) defines marker features a plugin can require to restrict the block length the host may use when calling `run`, as well as option keys for the block lengths and the size of sequence buffers. The values of these options are passed as `Int` atoms.
+//!
+//! # Example
+//!
+//! ```
+//! use lv2_core::prelude::*;
+//! use lv2_options::buf_size::*;
+//! use lv2_options::Options;
+//!
+//! /// This plugin only works with a block length that is a power of two and the host has to tell it the maximal block length.
+//! #[derive(FeatureCollection)]
+//! pub struct Features<'a> {
+//!     options: Options<'a>,
+//!     bounded: BoundedBlockLength,
+//!     power_of_2: PowerOf2BlockLength,
+//! }
+//! ```
+use core::feature::Feature;
+use core::UriBound;
+use std::ffi::c_void;
+
+/// Marker feature to signal that the host will provide the minimal and maximal block length.
+///
+/// If the plugin requires this feature, the host has to pass the [`MinBlockLength`](struct.MinBlockLength.html) and [`MaxBlockLength`](struct.MaxBlockLength.html) options when instantiating the plugin, and it may never call `run` with a sample count out of these bounds.
+pub struct BoundedBlockLength;
+
+unsafe impl UriBound for BoundedBlockLength {
+    const URI: &'static [u8] = sys::LV2_BUF_SIZE__boundedBlockLength;
+}
+
+unsafe impl Feature for BoundedBlockLength {
+    unsafe fn from_feature_ptr(_feature: *const c_void) -> Option<Self> {
+        Some(Self)
+    }
+}
+
+/// Marker feature to signal that the host will always call `run` with the same sample count.
+pub struct FixedBlockLength;
+
+unsafe impl UriBound for FixedBlockLength {
+    const URI: &'static [u8] = sys::LV2_BUF_SIZE__fixedBlockLength;
+}
+
+unsafe impl Feature for FixedBlockLength {
+    unsafe fn from_feature_ptr(_feature: *const c_void) -> Option<Self> {
+        Some(Self)
+    }
+}
+
+/// Marker feature to signal that the host will always call `run` with a sample count that is a power of two.
+pub struct PowerOf2BlockLength;
+
+unsafe impl UriBound for PowerOf2BlockLength {
+    const URI: &'static [u8] = sys::LV2_BUF_SIZE__powerOf2BlockLength;
+}
+
+unsafe impl Feature for PowerOf2BlockLength {
+    unsafe fn from_feature_ptr(_feature: *const c_void) -> Option<Self> {
+        Some(Self)
+    }
+}
+
+/// Option key of the maximal block length, as an `Int` atom.
+pub struct MaxBlockLength;
+
+unsafe impl UriBound for MaxBlockLength {
+    const URI: &'static [u8] = sys::LV2_BUF_SIZE__maxBlockLength;
+}
+
+/// Option key of the minimal block length, as an `Int` atom.
+pub struct MinBlockLength;
+
+unsafe impl UriBound for MinBlockLength {
+    const URI: &'static [u8] = sys::LV2_BUF_SIZE__minBlockLength;
+}
+
+/// Option key of the nominal block length, as an `Int` atom.
+///
+/// This is the block length the host will usually use, but it is not guaranteed that `run` is always called with it.
+pub struct NominalBlockLength;
+
+unsafe impl UriBound for NominalBlockLength {
+    const URI: &'static [u8] = sys::LV2_BUF_SIZE__nominalBlockLength;
+}
+
+/// Option key of the size of sequence buffers in bytes, as an `Int` atom.
+pub struct SequenceSize;
+
+unsafe impl UriBound for SequenceSize {
+    const URI: &'static [u8] = sys::LV2_BUF_SIZE__sequenceSize;
+}
@@ -0,0 +1,246 @@
+use crate::{OptionContext, OptionsError};
+use atom::prelude::*;
+use core::feature::Feature;
+use core::UriBound;
+use std::ffi::c_void;
+use urid::URID;
+
+/// Host feature with the options of the plugin instance.
+///
+/// The host passes this feature when the plugin is instantiated. It contains a list of options, which can be searched with [`get`](#method.get) or iterated with [`iter`](#method.iter). The same type is also passed to [`OptionsInterface::set`](trait.OptionsInterface.html#tymethod.set).
+#[derive(Clone, Copy)]
+pub struct Options<'a> {
+    options: &'a [sys::LV2_Options_Option],
+}
+
+// The options are immutable and only contain pointers to immutable data.
+unsafe impl<'a> Send for Options<'a> {}
+unsafe impl<'a> Sync for Options<'a> {}
+
+unsafe impl<'a> UriBound for Options<'a> {
+    const URI: &'static [u8] = sys::LV2_OPTIONS__options;
+}
+
+unsafe impl<'a> Feature for Options<'a> {
+    unsafe fn from_feature_ptr(feature: *const c_void) -> Option<Self> {
+        Self::from_raw(feature as *const sys::LV2_Options_Option)
+    }
+}
+
+impl<'a> Options<'a> {
+    /// Create a list of options from a raw pointer.
+    ///
+    /// If the pointer is null, `None` is returned.
+    ///
+    /// # Safety
+    ///
+    /// The pointer has to point to an array of options that is terminated by an option with the key `0`, as defined by the specification. The values of the options have to live as long as `'a`.
+    pub unsafe fn from_raw(options: *const sys::LV2_Options_Option) -> Option<Self> {
+        if options.is_null() {
+            return None;
+        }
+        let mut len: usize = 0;
+        while (*options.add(len)).key != 0 {
+            len += 1;
+        }
+        Some(Self {
+            options: std::slice::from_raw_parts(options, len),
+        })
+    }
+
+    /// Return the number of options in the list.
+    pub fn len(&self) -> usize {
+        self.options.len()
+    }
+
+    /// Return `true` if the list doesn't contain any options.
+    pub fn is_empty(&self) -> bool {
+        self.options.is_empty()
+    }
+
+    /// Iterate over all options in the list.
+    ///
+    /// Options with an invalid context or type are skipped.
+    pub fn iter(&self) -> impl Iterator<Item = OptionValue<'a>> {
+        self.options
+            .iter()
+            .filter_map(|option| unsafe { OptionValue::from_raw(option) })
+    }
+
+    /// Try to find the option with the given key that applies to the plugin instance.
+    ///
+    /// If there is no such option, `None` is returned.
+    pub fn get<K: ?Sized>(&self, key: URID<K>) -> Option<OptionValue<'a>> {
+        self.iter()
+            .find(|option| option.context == OptionContext::Instance && option.key == key)
+    }
+}
+
+/// A single option.
+///
+/// This handle contains the context, subject, key and value of an option. The value is the body of an atom, which can be read with [`read`](#method.read).
+#[derive(Clone, Copy)]
+pub struct OptionValue<'a> {
+    context: OptionContext,
+    subject: u32,
+    key: URID,
+    type_: URID,
+    value: Space<'a>,
+}
+
+impl<'a> OptionValue<'a> {
+    /// Create an option handle from a raw option.
+    ///
+    /// Returns `None` if the context, key or type of the option are invalid.
+    ///
+    /// # Safety
+    ///
+    /// The value pointer of the option has to point to `size` readable bytes that live as long as `'a`.
+    pub unsafe fn from_raw(option: &sys::LV2_Options_Option) -> Option<Self> {
+        let value: &'a [u8] = if option.value.is_null() {
+            &[]
+        } else {
+            std::slice::from_raw_parts(option.value as *const u8, option.size as usize)
+        };
+        Some(Self {
+            context: OptionContext::from_raw(option.context)?,
+            subject: option.subject,
+            key: URID::new(option.key)?,
+            type_: URID::new(option.type_)?,
+            value: Space::from_unaligned_slice(value),
+        })
+    }
+
+    /// Return the context of the option.
+    pub fn context(&self) -> OptionContext {
+        self.context
+    }
+
+    /// Return the subject of the option.
+    ///
+    /// The meaning of the subject depends on the [context](#method.context).
+    pub fn subject(&self) -> u32 {
+        self.subject
+    }
+
+    /// Return the key of the option.
+    pub fn key(&self) -> URID {
+        self.key
+    }
+
+    /// Return the type of the option's value.
+    pub fn type_(&self) -> URID {
+        self.type_
+    }
+
+    /// Return the raw value of the option.
+    pub fn value(&self) -> Space<'a> {
+        self.value
+    }
+
+    /// Try to interpret the value as an atom.
+    ///
+    /// This works like any atom reader: You pass the URID of the atom type as well as the type-specific argument, and if the desired type is the actual type of the data, a read handle is returned.
+    ///
+    /// If the desired and actual data types don't match or the value is malformed, `Err(OptionsError::BadValue)` is returned.
+    pub fn read<A: Atom<'a, 'a>>(
+        &self,
+        urid: URID<A>,
+        parameter: A::ReadParameter,
+    ) -> Result<A::ReadHandle, OptionsError> {
+        if urid == self.type_ {
+            A::read(self.value, parameter).map_err(|_| OptionsError::BadValue)
+        } else {
+            Err(OptionsError::BadValue)
+        }
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "host")]
+mod tests {
+    use crate::*;
+    use atom::prelude::*;
+    use std::ffi::c_void;
+    use std::mem::size_of;
+    use urid::mapper::*;
+    use urid::prelude::*;
+
+    #[test]
+    fn test_options_list() {
+        let mut mapper = Box::pin(HashURIDMapper::new());
+        let interface = mapper.as_mut().make_map_interface();
+        let map = Map::new(&interface);
+        let atom_urids: AtomURIDCollection = map.populate_collection().unwrap();
+        let urids: OptionsURIDCollection = map.populate_collection().unwrap();
+
+        let block_length: i32 = 512;
+        let sample_rate: f32 = 44100.0;
+        let raw_options = [
+            sys::LV2_Options_Option {
+                context: sys::LV2_Options_Context_LV2_OPTIONS_INSTANCE,
+                subject: 0,
+                key: urids.max_block_length.get(),
+                size: size_of::<i32>() as u32,
+                type_: atom_urids.int.get(),
+                value: &block_length as *const i32 as *const c_void,
+            },
+            sys::LV2_Options_Option {
+                context: sys::LV2_Options_Context_LV2_OPTIONS_PORT,
+                subject: 3,
+                key: urids.sample_rate.get(),
+                size: size_of::<f32>() as u32,
+                type_: atom_urids.float.get(),
+                value: &sample_rate as *const f32 as *const c_void,
+            },
+            sys::LV2_Options_Option {
+                context: sys::LV2_Options_Context_LV2_OPTIONS_INSTANCE,
+                subject: 0,
+                key: urids.sample_rate.get(),
+                size: size_of::<f32>() as u32,
+                type_: atom_urids.float.get(),
+                value: &sample_rate as *const f32 as *const c_void,
+            },
+            sys::LV2_Options_Option {
+                context: 0,
+                subject: 0,
+                key: 0,
+                size: 0,
+                type_: 0,
+                value: std::ptr::null(),
+            },
+        ];
+
+        let options = unsafe {
+            <Options as core::feature::Feature>::from_feature_ptr(
+                raw_options.as_ptr() as *const c_void
+            )
+        }
+        .unwrap();
+        assert_eq!(3, options.len());
+        assert_eq!(3, options.iter().count());
+
+        let option = options.get(urids.max_block_length).unwrap();
+        assert_eq!(OptionContext::Instance, option.context());
+        assert_eq!(urids.max_block_length, option.key());
+        assert_eq!(atom_urids.int, option.type_());
+        assert_eq!(512, option.read(atom_urids.int, ()).unwrap());
+        assert_eq!(
+            Err(OptionsError::BadValue),
+            option.read(atom_urids.float, ())
+        );
+
+        let option = options.get(urids.sample_rate).unwrap();
+        assert_eq!(OptionContext::Instance, option.context());
+        assert_eq!(44100.0, option.read(atom_urids.float, ()).unwrap());
+
+        let port_option = options
+            .iter()
+            .find(|option| option.context() == OptionContext::Port)
+            .unwrap();
+        assert_eq!(3, port_option.subject());
+
+        assert!(options.get(urids.nominal_block_length).is_none());
+        assert!(unsafe { Options::from_raw(std::ptr::null()) }.is_none());
+    }
+}
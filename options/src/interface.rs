@@ -0,0 +1,316 @@
+use crate::{OptionContext, Options, OptionsError};
+use atom::scalar::ScalarAtom;
+use core::extension::ExtensionDescriptor;
+use core::plugin::PluginInstance;
+use core::prelude::*;
+use std::ffi::c_void;
+use std::marker::PhantomData;
+use std::mem::size_of;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use urid::URID;
+
+/// A plugin extension that lets the host get and set the options of a plugin instance.
+///
+/// The host may use [`get`](#tymethod.get) to retrieve the current value of an option and [`set`](#tymethod.set) to change options, for example the block length or the sample rate, while the plugin is running. Both methods are called in the instantiation threading context, which means that no other method of the plugin is called concurrently.
+pub trait OptionsInterface: Plugin {
+    /// Respond to a request for an option.
+    ///
+    /// This method is called once for every option the host requests. If the plugin knows the requested option, it has to respond with a reference to the value, which has to live as long as the plugin is borrowed. Otherwise, it should return `Err(OptionsError::BadKey)` or `Err(OptionsError::BadSubject)`.
+    fn get<'a>(&'a self, request: &mut OptionRequest<'a>) -> Result<(), OptionsError>;
+
+    /// Set the given options.
+    ///
+    /// If one of the options is invalid, an error should be returned.
+    fn set(&mut self, options: Options) -> Result<(), OptionsError>;
+}
+
+/// A request of the host for an option.
+///
+/// The request contains the context, subject and key of the requested option and the plugin can respond with the value.
+pub struct OptionRequest<'a> {
+    option: &'a mut sys::LV2_Options_Option,
+}
+
+impl<'a> OptionRequest<'a> {
+    /// Create a new request that writes the response to the given raw option.
+    pub fn new(option: &'a mut sys::LV2_Options_Option) -> Self {
+        Self { option }
+    }
+
+    /// Return the context of the requested option.
+    ///
+    /// If the host provided an invalid context, `None` is returned.
+    pub fn context(&self) -> Option<OptionContext> {
+        OptionContext::from_raw(self.option.context)
+    }
+
+    /// Return the subject of the requested option.
+    ///
+    /// The meaning of the subject depends on the [context](#method.context).
+    pub fn subject(&self) -> u32 {
+        self.option.subject
+    }
+
+    /// Return the key of the requested option.
+    pub fn key(&self) -> Option<URID> {
+        URID::new(self.option.key)
+    }
+
+    /// Respond with the body of an atom of the given type.
+    pub fn respond_raw<T: ?Sized>(&mut self, type_: URID<T>, value: &'a [u8]) {
+        self.option.type_ = type_.get();
+        self.option.size = value.len() as u32;
+        self.option.value = value.as_ptr() as *const c_void;
+    }
+
+    /// Respond with the value of a scalar atom.
+    pub fn respond_scalar<S: ScalarAtom>(&mut self, urid: URID<S>, value: &'a S::InternalType) {
+        self.option.type_ = urid.get();
+        self.option.size = size_of::<S::InternalType>() as u32;
+        self.option.value = value as *const S::InternalType as *const c_void;
+    }
+}
+
+/// Raw wrapper of the [`OptionsInterface`](trait.OptionsInterface.html) extension.
+///
+/// This is a marker type that has the required external methods for the extension.
+pub struct OptionsInterfaceDescriptor<P: OptionsInterface> {
+    plugin: PhantomData<P>,
+}
+
+unsafe impl<P: OptionsInterface> UriBound for OptionsInterfaceDescriptor<P> {
+    const URI: &'static [u8] = sys::LV2_OPTIONS__interface;
+}
+
+impl<P: OptionsInterface> OptionsInterfaceDescriptor<P> {
+    /// Handle a get request by the host.
+    ///
+    /// This involves creating the plugin reference and a request for every requested option. The returned status is the combination of the statuses of every request. Panics of the plugin are caught and handled according to the plugin's panic policy, and a faulted instance isn't asked for options.
+    ///
+    /// # Safety
+    ///
+    /// This method is unsafe since it is an interface for hosts written in C and since it dereferences raw pointers.
+    pub unsafe extern "C" fn extern_get(
+        instance: sys::LV2_Handle,
+        options: *mut sys::LV2_Options_Option,
+    ) -> u32 {
+        if PluginInstance::<P>::is_faulted(instance) || options.is_null() {
+            return sys::LV2_Options_Status_LV2_OPTIONS_ERR_UNKNOWN;
+        }
+        let plugin = &*(instance as *const P);
+
+        let result = catch_unwind(AssertUnwindSafe(|| {
+            let mut status = sys::LV2_Options_Status_LV2_OPTIONS_SUCCESS;
+            let mut option_ptr = options;
+            while (*option_ptr).key != 0 {
+                let mut request = OptionRequest::new(&mut *option_ptr);
+                status |= OptionsError::into(plugin.get(&mut request));
+                option_ptr = option_ptr.add(1);
+            }
+            status
+        }));
+        match result {
+            Ok(status) => status,
+            Err(payload) => {
+                PluginInstance::<P>::handle_panic(instance, "get", payload);
+                sys::LV2_Options_Status_LV2_OPTIONS_ERR_UNKNOWN
+            }
+        }
+    }
+
+    /// Handle a set request by the host.
+    ///
+    /// This involves creating the plugin reference and the list of options. Panics of the plugin are caught and handled according to the plugin's panic policy, and the options of a faulted instance aren't set.
+    ///
+    /// # Safety
+    ///
+    /// This method is unsafe since it is an interface for hosts written in C and since it dereferences raw pointers.
+    pub unsafe extern "C" fn extern_set(
+        instance: sys::LV2_Handle,
+        options: *const sys::LV2_Options_Option,
+    ) -> u32 {
+        if PluginInstance::<P>::is_faulted(instance) {
+            return sys::LV2_Options_Status_LV2_OPTIONS_ERR_UNKNOWN;
+        }
+        let plugin = &mut *(instance as *mut P);
+
+        let options = if let Some(options) = Options::from_raw(options) {
+            options
+        } else {
+            return sys::LV2_Options_Status_LV2_OPTIONS_ERR_UNKNOWN;
+        };
+
+        match catch_unwind(AssertUnwindSafe(|| plugin.set(options))) {
+            Ok(result) => OptionsError::into(result),
+            Err(payload) => {
+                PluginInstance::<P>::handle_panic(instance, "set", payload);
+                sys::LV2_Options_Status_LV2_OPTIONS_ERR_UNKNOWN
+            }
+        }
+    }
+}
+
+impl<P: OptionsInterface> ExtensionDescriptor for OptionsInterfaceDescriptor<P> {
+    type ExtensionInterface = sys::LV2_Options_Interface;
+
+    const INTERFACE: &'static sys::LV2_Options_Interface = &sys::LV2_Options_Interface {
+        get: Some(Self::extern_get),
+        set: Some(Self::extern_set),
+    };
+}
+
+#[cfg(test)]
+#[cfg(feature = "host")]
+mod tests {
+    use crate::*;
+    use atom::prelude::*;
+    use lv2_core::plugin::PanicReport;
+    use lv2_core::prelude::*;
+    use lv2_core::test::Harness;
+    use std::ffi::c_void;
+    use std::mem::size_of;
+    use urid::prelude::*;
+
+    #[derive(FeatureCollection)]
+    struct Features<'a> {
+        map: Map<'a>,
+    }
+
+    struct Resampler {
+        sample_rate: f32,
+        atom_urids: AtomURIDCollection,
+        urids: OptionsURIDCollection,
+    }
+
+    unsafe impl UriBound for Resampler {
+        const URI: &'static [u8] = b"urn:lv2_options:resampler\0";
+    }
+
+    impl Plugin for Resampler {
+        type Features = Features<'static>;
+        type Ports = ();
+
+        fn new(_: &PluginInfo, features: Features<'static>) -> Option<Self> {
+            Some(Self {
+                sample_rate: 44100.0,
+                atom_urids: features.map.populate_collection()?,
+                urids: features.map.populate_collection()?,
+            })
+        }
+
+        #[cfg_attr(tarpaulin, skip)]
+        fn run(&mut self, _: &mut ()) {}
+
+        fn report_panic(_: &PanicReport) {}
+    }
+
+    impl OptionsInterface for Resampler {
+        fn get<'a>(&'a self, request: &mut OptionRequest<'a>) -> Result<(), OptionsError> {
+            if request.context() != Some(OptionContext::Instance) {
+                Err(OptionsError::BadSubject)
+            } else if request.key() == Some(self.urids.sample_rate.into_general()) {
+                request.respond_scalar(self.atom_urids.float, &self.sample_rate);
+                Ok(())
+            } else {
+                Err(OptionsError::BadKey)
+            }
+        }
+
+        fn set(&mut self, options: Options) -> Result<(), OptionsError> {
+            if options.get(self.urids.max_block_length).is_some() {
+                panic!("The block length is fixed");
+            }
+            if let Some(option) = options.get(self.urids.sample_rate) {
+                self.sample_rate = option.read(self.atom_urids.float, ())?;
+            }
+            Ok(())
+        }
+    }
+
+    fn raw_option(context: u32, key: u32) -> sys::LV2_Options_Option {
+        sys::LV2_Options_Option {
+            context,
+            subject: 0,
+            key,
+            size: 0,
+            type_: 0,
+            value: std::ptr::null(),
+        }
+    }
+
+    #[test]
+    fn test_get_n_set() {
+        type Descriptor = OptionsInterfaceDescriptor<Resampler>;
+
+        let mut harness = Harness::<Resampler>::new(44100.0, 0);
+        assert!(harness.instantiate());
+        let plugin = harness.plugin().unwrap();
+        let float_urid = plugin.atom_urids.float.get();
+        let sample_rate_urid = plugin.urids.sample_rate.get();
+        let block_length_urid = plugin.urids.max_block_length.get();
+        let handle = harness.handle();
+
+        // Setting the sample rate.
+        let new_sample_rate: f32 = 48000.0;
+        let mut set_options = [
+            raw_option(
+                sys::LV2_Options_Context_LV2_OPTIONS_INSTANCE,
+                sample_rate_urid,
+            ),
+            raw_option(0, 0),
+        ];
+        set_options[0].size = size_of::<f32>() as u32;
+        set_options[0].type_ = float_urid;
+        set_options[0].value = &new_sample_rate as *const f32 as *const c_void;
+        assert_eq!(sys::LV2_Options_Status_LV2_OPTIONS_SUCCESS, unsafe {
+            Descriptor::extern_set(handle, set_options.as_ptr())
+        });
+
+        // Setting a malformed sample rate.
+        set_options[0].type_ = block_length_urid;
+        assert_eq!(sys::LV2_Options_Status_LV2_OPTIONS_ERR_BAD_VALUE, unsafe {
+            Descriptor::extern_set(handle, set_options.as_ptr())
+        });
+
+        // Getting the sample rate and an unknown option.
+        let mut get_options = [
+            raw_option(
+                sys::LV2_Options_Context_LV2_OPTIONS_INSTANCE,
+                sample_rate_urid,
+            ),
+            raw_option(
+                sys::LV2_Options_Context_LV2_OPTIONS_INSTANCE,
+                block_length_urid,
+            ),
+            raw_option(0, 0),
+        ];
+        assert_eq!(sys::LV2_Options_Status_LV2_OPTIONS_ERR_BAD_KEY, unsafe {
+            Descriptor::extern_get(handle, get_options.as_mut_ptr())
+        });
+        assert_eq!(float_urid, get_options[0].type_);
+        assert_eq!(size_of::<f32>() as u32, get_options[0].size);
+        assert_eq!(48000.0, unsafe { *(get_options[0].value as *const f32) });
+        assert!(get_options[1].value.is_null());
+
+        // Illegal paths.
+        assert_eq!(sys::LV2_Options_Status_LV2_OPTIONS_ERR_UNKNOWN, unsafe {
+            Descriptor::extern_get(std::ptr::null_mut(), get_options.as_mut_ptr())
+        });
+        assert_eq!(sys::LV2_Options_Status_LV2_OPTIONS_ERR_UNKNOWN, unsafe {
+            Descriptor::extern_set(handle, std::ptr::null())
+        });
+
+        // Panicking while setting the block length faults the instance.
+        let block_length: i32 = 512;
+        set_options[0].key = block_length_urid;
+        set_options[0].size = size_of::<i32>() as u32;
+        set_options[0].value = &block_length as *const i32 as *const c_void;
+        assert_eq!(sys::LV2_Options_Status_LV2_OPTIONS_ERR_UNKNOWN, unsafe {
+            Descriptor::extern_set(handle, set_options.as_ptr())
+        });
+        assert!(harness.is_faulted());
+        assert_eq!(sys::LV2_Options_Status_LV2_OPTIONS_ERR_UNKNOWN, unsafe {
+            Descriptor::extern_get(handle, get_options.as_mut_ptr())
+        });
+    }
+}
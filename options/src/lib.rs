@@ -0,0 +1,254 @@
+//! Instance options and buffer size properties for LV2 plugins.
+//!
+//! The [LV2 Options specification](http://lv2plug.in/ns/ext/options/options.html) provides a way to pass options to a plugin instance. Options are key-value pairs where the key is a URID and the value is the body of an atom. The host may pass options when the plugin is instantiated, using the [`Options`](struct.Options.html) feature, and it may get or set options later using the [`OptionsInterface`](trait.OptionsInterface.html) extension.
+//!
+//! The most common options are defined by the [LV2 Buf-Size specification](http://lv2plug.in/ns/ext/buf-size/buf-size.html), which is wrapped by the [`buf_size`](buf_size/index.html) module, and the sample rate.
+//!
+//! Option values are read with the atom types from `lv2_atom`: You retrieve an [`OptionValue`](struct.OptionValue.html) and read it like any other atom, by passing the URID of the expected atom type and the atom-specific parameter.
+//!
+//! # Example
+//!
+//! ```
+//! use lv2_atom::prelude::*;
+//! use lv2_core::prelude::*;
+//! use lv2_options::*;
+//! use lv2_urid::prelude::*;
+//!
+//! #[derive(FeatureCollection)]
+//! pub struct Features<'a> {
+//!     map: Map<'a>,
+//!     options: Options<'a>,
+//!     _bounded: buf_size::BoundedBlockLength,
+//! }
+//!
+//! #[derive(URIDCollection)]
+//! pub struct URIDs {
+//!     atom: AtomURIDCollection,
+//!     options: OptionsURIDCollection,
+//! }
+//!
+//! pub struct OptionsPlugin {
+//!     urids: URIDs,
+//!     max_block_length: i32,
+//!     sample_rate: f32,
+//! }
+//!
+//! unsafe impl UriBound for OptionsPlugin {
+//!     const URI: &'static [u8] = b"urn:lv2_options:options-plugin\0";
+//! }
+//!
+//! impl Plugin for OptionsPlugin {
+//!     type Ports = ();
+//!     type Features = Features<'static>;
+//!
+//!     fn new(info: &PluginInfo, features: Features<'static>) -> Option<Self> {
+//!         let urids: URIDs = features.map.populate_collection()?;
+//!         // The maximal block length is required by the bounded block length feature.
+//!         let max_block_length = features
+//!             .options
+//!             .get(urids.options.max_block_length)?
+//!             .read(urids.atom.int, ())
+//!             .ok()?;
+//!         Some(Self {
+//!             urids,
+//!             max_block_length,
+//!             sample_rate: info.sample_rate() as f32,
+//!         })
+//!     }
+//!
+//!     fn run(&mut self, _: &mut ()) {}
+//!
+//!     fn extension_data(uri: &Uri) -> Option<&'static dyn std::any::Any> {
+//!         match_extensions!(uri, OptionsInterfaceDescriptor<Self>)
+//!     }
+//! }
+//!
+//! impl OptionsInterface for OptionsPlugin {
+//!     fn get<'a>(&'a self, request: &mut OptionRequest<'a>) -> Result<(), OptionsError> {
+//!         if request.key() == Some(self.urids.options.sample_rate.into_general()) {
+//!             request.respond_scalar(self.urids.atom.float, &self.sample_rate);
+//!             Ok(())
+//!         } else {
+//!             Err(OptionsError::BadKey)
+//!         }
+//!     }
+//!
+//!     fn set(&mut self, options: Options) -> Result<(), OptionsError> {
+//!         // React to changes of the sample rate.
+//!         if let Some(sample_rate) = options.get(self.urids.options.sample_rate) {
+//!             self.sample_rate = sample_rate.read(self.urids.atom.float, ())?;
+//!         }
+//!         Ok(())
+//!     }
+//! }
+//! ```
+extern crate lv2_atom as atom;
+extern crate lv2_core as core;
+extern crate lv2_sys as sys;
+extern crate lv2_urid as urid;
+
+pub mod buf_size;
+
+mod feature;
+pub use feature::*;
+
+mod interface;
+pub use interface::*;
+
+use core::UriBound;
+use urid::{URIDCollection, URID};
+
+/// The sample rate of the plugin instance, as a `Float` or `Double` atom.
+///
+/// The sample rate is usually passed when the plugin is instantiated, but this option allows the host to change it while the plugin is running.
+pub struct SampleRate;
+
+unsafe impl UriBound for SampleRate {
+    const URI: &'static [u8] = sys::LV2_PARAMETERS__sampleRate;
+}
+
+#[derive(Clone, URIDCollection)]
+/// Collection with the URIDs of all option keys in this crate.
+pub struct OptionsURIDCollection {
+    pub max_block_length: URID<buf_size::MaxBlockLength>,
+    pub min_block_length: URID<buf_size::MinBlockLength>,
+    pub nominal_block_length: URID<buf_size::NominalBlockLength>,
+    pub sequence_size: URID<buf_size::SequenceSize>,
+    pub sample_rate: URID<SampleRate>,
+}
+
+/// The context of an option, which describes what the subject of the option is.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OptionContext {
+    /// The option applies to the plugin instance. The subject is ignored.
+    Instance,
+    /// The option applies to some resource, identified by the URID in the subject.
+    Resource,
+    /// The option applies to a blank node, identified by the subject.
+    Blank,
+    /// The option applies to the port with the index in the subject.
+    Port,
+}
+
+impl OptionContext {
+    /// Convert a raw context flag.
+    ///
+    /// Returns `None` if the value is not a valid context.
+    pub fn from_raw(value: u32) -> Option<Self> {
+        match value {
+            sys::LV2_Options_Context_LV2_OPTIONS_INSTANCE => Some(OptionContext::Instance),
+            sys::LV2_Options_Context_LV2_OPTIONS_RESOURCE => Some(OptionContext::Resource),
+            sys::LV2_Options_Context_LV2_OPTIONS_BLANK => Some(OptionContext::Blank),
+            sys::LV2_Options_Context_LV2_OPTIONS_PORT => Some(OptionContext::Port),
+            _ => None,
+        }
+    }
+
+    /// Convert the context to a raw context flag.
+    pub fn into_raw(self) -> u32 {
+        match self {
+            OptionContext::Instance => sys::LV2_Options_Context_LV2_OPTIONS_INSTANCE,
+            OptionContext::Resource => sys::LV2_Options_Context_LV2_OPTIONS_RESOURCE,
+            OptionContext::Blank => sys::LV2_Options_Context_LV2_OPTIONS_BLANK,
+            OptionContext::Port => sys::LV2_Options_Context_LV2_OPTIONS_PORT,
+        }
+    }
+}
+
+/// Kinds of errors that may occur in the crate.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OptionsError {
+    /// The kind of the error is unknown or doesn't have a representation.
+    Unknown,
+    /// The subject of the option is invalid.
+    BadSubject,
+    /// The key of the option is unknown or not supported.
+    BadKey,
+    /// The value of the option is invalid or has the wrong type.
+    BadValue,
+}
+
+impl OptionsError {
+    /// Convert a raw status flag to a result or possible error value.
+    ///
+    /// Since status flags may be combined, the first error in the order of the variants is returned.
+    pub fn from(value: u32) -> Result<(), OptionsError> {
+        if value == sys::LV2_Options_Status_LV2_OPTIONS_SUCCESS {
+            Ok(())
+        } else if value & sys::LV2_Options_Status_LV2_OPTIONS_ERR_UNKNOWN != 0 {
+            Err(OptionsError::Unknown)
+        } else if value & sys::LV2_Options_Status_LV2_OPTIONS_ERR_BAD_SUBJECT != 0 {
+            Err(OptionsError::BadSubject)
+        } else if value & sys::LV2_Options_Status_LV2_OPTIONS_ERR_BAD_KEY != 0 {
+            Err(OptionsError::BadKey)
+        } else if value & sys::LV2_Options_Status_LV2_OPTIONS_ERR_BAD_VALUE != 0 {
+            Err(OptionsError::BadValue)
+        } else {
+            Err(OptionsError::Unknown)
+        }
+    }
+
+    /// Convert a result to a raw status flag.
+    pub fn into(result: Result<(), OptionsError>) -> u32 {
+        match result {
+            Ok(()) => sys::LV2_Options_Status_LV2_OPTIONS_SUCCESS,
+            Err(OptionsError::Unknown) => sys::LV2_Options_Status_LV2_OPTIONS_ERR_UNKNOWN,
+            Err(OptionsError::BadSubject) => sys::LV2_Options_Status_LV2_OPTIONS_ERR_BAD_SUBJECT,
+            Err(OptionsError::BadKey) => sys::LV2_Options_Status_LV2_OPTIONS_ERR_BAD_KEY,
+            Err(OptionsError::BadValue) => sys::LV2_Options_Status_LV2_OPTIONS_ERR_BAD_VALUE,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+
+    #[test]
+    fn test_options_error_conversion() {
+        assert_eq!(
+            Ok(()),
+            OptionsError::from(sys::LV2_Options_Status_LV2_OPTIONS_SUCCESS)
+        );
+        assert_eq!(
+            Err(OptionsError::BadKey),
+            OptionsError::from(sys::LV2_Options_Status_LV2_OPTIONS_ERR_BAD_KEY)
+        );
+        assert_eq!(
+            Err(OptionsError::BadSubject),
+            OptionsError::from(
+                sys::LV2_Options_Status_LV2_OPTIONS_ERR_BAD_SUBJECT
+                    | sys::LV2_Options_Status_LV2_OPTIONS_ERR_BAD_VALUE
+            )
+        );
+        assert_eq!(Err(OptionsError::Unknown), OptionsError::from(1024));
+
+        assert_eq!(
+            sys::LV2_Options_Status_LV2_OPTIONS_SUCCESS,
+            OptionsError::into(Ok(()))
+        );
+        assert_eq!(
+            sys::LV2_Options_Status_LV2_OPTIONS_ERR_BAD_VALUE,
+            OptionsError::into(Err(OptionsError::BadValue))
+        );
+        assert_eq!(
+            sys::LV2_Options_Status_LV2_OPTIONS_ERR_UNKNOWN,
+            OptionsError::into(Err(OptionsError::Unknown))
+        );
+    }
+
+    #[test]
+    fn test_context_conversion() {
+        for context in [
+            OptionContext::Instance,
+            OptionContext::Resource,
+            OptionContext::Blank,
+            OptionContext::Port,
+        ]
+        .iter()
+        {
+            assert_eq!(Some(*context), OptionContext::from_raw(context.into_raw()));
+        }
+        assert_eq!(None, OptionContext::from_raw(42));
+    }
+}
@@ -0,0 +1,152 @@
+//! Messages to get and set properties of plugins.
+//!
+//! The [LV2 Patch specification](http://lv2plug.in/ns/ext/patch/patch.html) defines messages to access and manipulate properties of a subject, which is usually the plugin instance itself. These messages are object atoms and are mostly used to exchange parameters between a plugin and its user interface via atom sequences.
+//!
+//! This crate supports the three most common messages: `patch:Set` sets a single property, `patch:Get` requests the value of one or all properties and `patch:Put` sets multiple properties at once. They are written with the [`PatchWriter`](trait.PatchWriter.html) trait, which is implemented by `SequenceWriter`, and read with [`PatchMessage::parse`](enum.PatchMessage.html#method.parse).
+//!
+//! # Example
+//!
+//! ```
+//! use lv2_atom::prelude::*;
+//! use lv2_atom::sequence::*;
+//! use lv2_core::prelude::*;
+//! use lv2_patch::*;
+//! use lv2_units::prelude::*;
+//! use lv2_urid::prelude::*;
+//!
+//! struct Gain;
+//!
+//! unsafe impl UriBound for Gain {
+//!     const URI: &'static [u8] = b"urn:lv2_patch:gain\0";
+//! }
+//!
+//! #[derive(PortCollection)]
+//! struct MyPorts {
+//!     control: InputPort<AtomPort>,
+//!     notify: OutputPort<AtomPort>,
+//! }
+//!
+//! #[derive(URIDCollection)]
+//! struct MyURIDs {
+//!     atom: AtomURIDCollection,
+//!     patch: PatchURIDCollection,
+//!     units: UnitURIDCollection,
+//!     gain: URID<Gain>,
+//! }
+//!
+//! /// Something like a plugin's run method.
+//! fn run(ports: &mut MyPorts, urids: &MyURIDs, gain: &mut f32) {
+//!     let input = ports.control.read(urids.atom.sequence, urids.units.beat).unwrap();
+//!     let mut output = ports
+//!         .notify
+//!         .init(urids.atom.sequence, TimeStampURID::Frames(urids.units.frame))
+//!         .unwrap();
+//!
+//!     for (timestamp, atom) in input {
+//!         match PatchMessage::parse(&urids.patch, atom) {
+//!             // Set the gain.
+//!             Ok(PatchMessage::Set { property, value, .. }) if property == urids.gain => {
+//!                 if let Ok(new_gain) = value.read(urids.atom.float, ()) {
+//!                     *gain = new_gain;
+//!                 }
+//!             }
+//!             // Respond to requests for the gain or for all properties.
+//!             Ok(PatchMessage::Get { property, .. })
+//!                 if property.map_or(true, |property| property == urids.gain) =>
+//!             {
+//!                 output
+//!                     .write_set(
+//!                         &urids.patch,
+//!                         timestamp,
+//!                         None,
+//!                         urids.gain,
+//!                         urids.atom.float,
+//!                         *gain,
+//!                     )
+//!                     .unwrap();
+//!             }
+//!             _ => (),
+//!         }
+//!     }
+//! }
+//! ```
+extern crate lv2_atom as atom;
+extern crate lv2_core as core;
+extern crate lv2_sys as sys;
+extern crate lv2_urid as urid;
+
+mod message;
+pub use message::*;
+
+mod writer;
+pub use writer::*;
+
+use atom::prelude::*;
+use core::UriBound;
+use urid::{URIDCollection, URID};
+
+/// The class of `patch:Set` messages, which set a single property of the subject.
+pub struct SetClass;
+
+unsafe impl UriBound for SetClass {
+    const URI: &'static [u8] = sys::LV2_PATCH__Set;
+}
+
+/// The class of `patch:Get` messages, which request the value of one or all properties of the subject.
+pub struct GetClass;
+
+unsafe impl UriBound for GetClass {
+    const URI: &'static [u8] = sys::LV2_PATCH__Get;
+}
+
+/// The class of `patch:Put` messages, which set multiple properties of the subject.
+pub struct PutClass;
+
+unsafe impl UriBound for PutClass {
+    const URI: &'static [u8] = sys::LV2_PATCH__Put;
+}
+
+/// The key of the subject of a message, as a `URID` atom.
+pub struct SubjectKey;
+
+unsafe impl UriBound for SubjectKey {
+    const URI: &'static [u8] = sys::LV2_PATCH__subject;
+}
+
+/// The key of the property a message refers to, as a `URID` atom.
+pub struct PropertyKey;
+
+unsafe impl UriBound for PropertyKey {
+    const URI: &'static [u8] = sys::LV2_PATCH__property;
+}
+
+/// The key of the new value of a property in a `patch:Set` message.
+pub struct ValueKey;
+
+unsafe impl UriBound for ValueKey {
+    const URI: &'static [u8] = sys::LV2_PATCH__value;
+}
+
+/// The key of the object with the new properties in a `patch:Put` message.
+pub struct BodyKey;
+
+unsafe impl UriBound for BodyKey {
+    const URI: &'static [u8] = sys::LV2_PATCH__body;
+}
+
+#[derive(Clone, URIDCollection)]
+/// Collection with the URIDs of all `UriBound`s in this crate.
+///
+/// The collection also contains the URIDs of the atom types that are used by the messages.
+pub struct PatchURIDCollection {
+    pub set: URID<SetClass>,
+    pub get: URID<GetClass>,
+    pub put: URID<PutClass>,
+    pub subject: URID<SubjectKey>,
+    pub property: URID<PropertyKey>,
+    pub value: URID<ValueKey>,
+    pub body: URID<BodyKey>,
+    atom: AtomURIDCollection,
+    #[allow(deprecated)]
+    blank: URID<atom::object::Blank>,
+}
@@ -0,0 +1,472 @@
+use crate::PatchURIDCollection;
+use atom::object::ObjectReader;
+use atom::prelude::*;
+use urid::URID;
+
+/// A parsed patch message.
+///
+/// If the message didn't contain a subject, it refers to the plugin instance or the receiver of the message.
+pub enum PatchMessage<'a> {
+    /// A request to set a single property to a new value.
+    Set {
+        subject: Option<URID>,
+        property: URID,
+        value: UnidentifiedAtom<'a>,
+    },
+    /// A request for the value of a single property, or all properties if `property` is `None`.
+    Get {
+        subject: Option<URID>,
+        property: Option<URID>,
+    },
+    /// A request to set all properties in the body object.
+    Put {
+        subject: Option<URID>,
+        header: ObjectHeader,
+        body: ObjectReader<'a>,
+    },
+}
+
+impl<'a> PatchMessage<'a> {
+    /// Try to parse an atom as a patch message.
+    ///
+    /// Messages may be `Object` or `Blank` atoms. If a property appears multiple times, the first one is used.
+    ///
+    /// Returns `AtomError::TypeMismatch` if the atom isn't an object of one of the supported message classes, `AtomError::MissingProperty` if the message is missing a required property and `AtomError::Malformed` if it is otherwise malformed.
+    pub fn parse(
+        urids: &PatchURIDCollection,
+        atom: UnidentifiedAtom<'a>,
+    ) -> Result<Self, AtomError> {
+        let (header, reader) = read_object(urids, atom)?;
+
+        let mut subject: Option<URID> = None;
+        let mut property: Option<URID> = None;
+
+        if header.otype == urids.set {
+            let mut value: Option<UnidentifiedAtom<'a>> = None;
+            reader.get_many(
+                &urids.atom,
+                &mut [
+                    PropertyQuery::optional(urids.subject, &mut subject),
+                    PropertyQuery::required(urids.property, &mut property),
+                    PropertyQuery::required(urids.value, &mut value),
+                ],
+            )?;
+            Ok(PatchMessage::Set {
+                subject,
+                property: property.ok_or(AtomError::Malformed)?,
+                value: value.ok_or(AtomError::Malformed)?,
+            })
+        } else if header.otype == urids.get {
+            reader.get_many(
+                &urids.atom,
+                &mut [
+                    PropertyQuery::optional(urids.subject, &mut subject),
+                    PropertyQuery::optional(urids.property, &mut property),
+                ],
+            )?;
+            Ok(PatchMessage::Get { subject, property })
+        } else if header.otype == urids.put {
+            let mut body: Option<UnidentifiedAtom<'a>> = None;
+            reader.get_many(
+                &urids.atom,
+                &mut [
+                    PropertyQuery::optional(urids.subject, &mut subject),
+                    PropertyQuery::required(urids.body, &mut body),
+                ],
+            )?;
+            let (header, body) = read_object(urids, body.ok_or(AtomError::Malformed)?)?;
+            Ok(PatchMessage::Put {
+                subject,
+                header,
+                body,
+            })
+        } else {
+            Err(AtomError::TypeMismatch)
+        }
+    }
+}
+
+/// Read an atom as an `Object`, or as a `Blank`, which some hosts still use.
+fn read_object<'a>(
+    urids: &PatchURIDCollection,
+    atom: UnidentifiedAtom<'a>,
+) -> Result<(ObjectHeader, ObjectReader<'a>), AtomError> {
+    match atom.read(urids.atom.object, ()) {
+        Err(AtomError::TypeMismatch) => atom.read(urids.blank, ()),
+        result => result,
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "host")]
+mod tests {
+    use crate::*;
+    use atom::prelude::*;
+    use atom::space::*;
+    use core::prelude::*;
+    use lv2_units::prelude::*;
+    use urid::mapper::*;
+    use urid::prelude::*;
+
+    struct Gain;
+
+    unsafe impl UriBound for Gain {
+        const URI: &'static [u8] = b"urn:lv2_patch:gain\0";
+    }
+
+    struct Name;
+
+    unsafe impl UriBound for Name {
+        const URI: &'static [u8] = b"urn:lv2_patch:name\0";
+    }
+
+    #[derive(URIDCollection)]
+    struct TestURIDCollection {
+        atom: AtomURIDCollection,
+        patch: PatchURIDCollection,
+        units: UnitURIDCollection,
+        gain: URID<Gain>,
+        name: URID<Name>,
+    }
+
+    #[test]
+    fn test_write_n_parse() {
+        let mut mapper = Box::pin(HashURIDMapper::new());
+        let interface = mapper.as_mut().make_map_interface();
+        let map = Map::new(&interface);
+        let urids: TestURIDCollection = map.populate_collection().unwrap();
+
+        // Writing a string atom that is forwarded later.
+        let mut string_space = AlignedBuffer::new(64);
+        {
+            let mut space = RootMutSpace::new(string_space.as_mut());
+            let mut writer = (&mut space as &mut dyn MutSpace)
+                .init(urids.atom.string, ())
+                .unwrap();
+            writer.append("Hello").unwrap();
+        }
+        let string_atom = UnidentifiedAtom::new(Space::from_slice(string_space.as_ref()));
+
+        // Writing the messages.
+        let mut raw_space = AlignedBuffer::new(1024);
+        {
+            let mut space = RootMutSpace::new(raw_space.as_mut());
+            let mut writer = (&mut space as &mut dyn MutSpace)
+                .init(
+                    urids.atom.sequence,
+                    TimeStampURID::Frames(urids.units.frame),
+                )
+                .unwrap();
+            writer
+                .write_set(
+                    &urids.patch,
+                    TimeStamp::Frames(0),
+                    None,
+                    urids.gain,
+                    urids.atom.float,
+                    0.5,
+                )
+                .unwrap();
+            writer
+                .forward_set(
+                    &urids.patch,
+                    TimeStamp::Frames(1),
+                    Some(urids.gain.into_general()),
+                    urids.name,
+                    string_atom,
+                )
+                .unwrap();
+            writer
+                .write_get(
+                    &urids.patch,
+                    TimeStamp::Frames(2),
+                    None,
+                    Some(urids.gain.into_general()),
+                )
+                .unwrap();
+            writer
+                .write_get(&urids.patch, TimeStamp::Frames(3), None, None)
+                .unwrap();
+            writer
+                .write_put(
+                    &urids.patch,
+                    TimeStamp::Frames(4),
+                    None,
+                    ObjectHeader {
+                        id: None,
+                        otype: urids.patch.put.into_general(),
+                    },
+                    |body| {
+                        body.init(urids.gain, None, urids.atom.float, 0.25)?;
+                        Ok(())
+                    },
+                )
+                .unwrap();
+            // An object that isn't a message.
+            writer
+                .init(
+                    TimeStamp::Frames(5),
+                    urids.atom.object,
+                    ObjectHeader {
+                        id: None,
+                        otype: urids.gain.into_general(),
+                    },
+                )
+                .unwrap();
+        }
+
+        // Parsing the messages.
+        let space = Space::from_slice(raw_space.as_ref());
+        let (body, _) = space.split_atom_body(urids.atom.sequence).unwrap();
+        let mut sequence = Sequence::read(body, urids.units.beat).unwrap();
+
+        match PatchMessage::parse(&urids.patch, sequence.next().unwrap().1).unwrap() {
+            PatchMessage::Set {
+                subject,
+                property,
+                value,
+            } => {
+                assert!(subject.is_none());
+                assert_eq!(urids.gain, property);
+                assert_eq!(0.5, value.read(urids.atom.float, ()).unwrap());
+            }
+            _ => panic!("Invalid message"),
+        }
+
+        match PatchMessage::parse(&urids.patch, sequence.next().unwrap().1).unwrap() {
+            PatchMessage::Set {
+                subject,
+                property,
+                value,
+            } => {
+                assert_eq!(Some(urids.gain.into_general()), subject);
+                assert_eq!(urids.name, property);
+                assert_eq!("Hello", value.read(urids.atom.string, ()).unwrap());
+            }
+            _ => panic!("Invalid message"),
+        }
+
+        match PatchMessage::parse(&urids.patch, sequence.next().unwrap().1).unwrap() {
+            PatchMessage::Get { subject, property } => {
+                assert!(subject.is_none());
+                assert_eq!(Some(urids.gain.into_general()), property);
+            }
+            _ => panic!("Invalid message"),
+        }
+
+        match PatchMessage::parse(&urids.patch, sequence.next().unwrap().1).unwrap() {
+            PatchMessage::Get { subject, property } => {
+                assert!(subject.is_none());
+                assert!(property.is_none());
+            }
+            _ => panic!("Invalid message"),
+        }
+
+        match PatchMessage::parse(&urids.patch, sequence.next().unwrap().1).unwrap() {
+            PatchMessage::Put {
+                subject,
+                header,
+                mut body,
+            } => {
+                assert!(subject.is_none());
+                assert_eq!(urids.patch.put, header.otype);
+                let (property_header, value) = body.next().unwrap();
+                assert_eq!(urids.gain, property_header.key);
+                assert_eq!(0.25, value.read(urids.atom.float, ()).unwrap());
+                assert!(body.next().is_none());
+            }
+            _ => panic!("Invalid message"),
+        }
+
+        assert_eq!(
+            Err(AtomError::TypeMismatch),
+            PatchMessage::parse(&urids.patch, sequence.next().unwrap().1).map(|_| ())
+        );
+        assert!(sequence.next().is_none());
+    }
+    #[test]
+    fn test_rollback() {
+        let mut mapper = Box::pin(HashURIDMapper::new());
+        let interface = mapper.as_mut().make_map_interface();
+        let map = Map::new(&interface);
+        let urids: TestURIDCollection = map.populate_collection().unwrap();
+
+        // A string that is too long for the sequence.
+        let mut string_space = AlignedBuffer::new(512);
+        {
+            let mut space = RootMutSpace::new(string_space.as_mut());
+            let mut writer = (&mut space as &mut dyn MutSpace)
+                .init(urids.atom.string, ())
+                .unwrap();
+            writer.append(&"a".repeat(400)).unwrap();
+        }
+        let string_atom = UnidentifiedAtom::new(Space::from_slice(string_space.as_ref()));
+
+        let mut raw_space = AlignedBuffer::new(256);
+        {
+            let mut space = RootMutSpace::new(raw_space.as_mut());
+            let mut writer = (&mut space as &mut dyn MutSpace)
+                .init(
+                    urids.atom.sequence,
+                    TimeStampURID::Frames(urids.units.frame),
+                )
+                .unwrap();
+            writer
+                .write_get(&urids.patch, TimeStamp::Frames(0), None, None)
+                .unwrap();
+            assert_eq!(
+                Err(AtomError::OutOfSpace),
+                writer.forward_set(
+                    &urids.patch,
+                    TimeStamp::Frames(1),
+                    None,
+                    urids.name,
+                    string_atom,
+                )
+            );
+            assert_eq!(
+                Err(AtomError::Malformed),
+                writer.write_put(
+                    &urids.patch,
+                    TimeStamp::Frames(2),
+                    None,
+                    ObjectHeader {
+                        id: None,
+                        otype: urids.patch.put.into_general(),
+                    },
+                    |body| {
+                        body.init(urids.gain, None, urids.atom.float, 0.25)?;
+                        Err(AtomError::Malformed)
+                    },
+                )
+            );
+            // The failed messages didn't advance the time stamp.
+            writer
+                .write_get(&urids.patch, TimeStamp::Frames(1), None, None)
+                .unwrap();
+        }
+
+        // Only the complete messages are in the sequence.
+        let space = Space::from_slice(raw_space.as_ref());
+        let (body, _) = space.split_atom_body(urids.atom.sequence).unwrap();
+        let sequence = Sequence::read(body, urids.units.beat).unwrap();
+        let stamps: Vec<i64> = sequence
+            .map(|(stamp, atom)| {
+                assert!(PatchMessage::parse(&urids.patch, atom).is_ok());
+                stamp.as_frames().unwrap()
+            })
+            .collect();
+        assert_eq!(vec![0, 1], stamps);
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn test_parse_variants() {
+        let mut mapper = Box::pin(HashURIDMapper::new());
+        let interface = mapper.as_mut().make_map_interface();
+        let map = Map::new(&interface);
+        let urids: TestURIDCollection = map.populate_collection().unwrap();
+        let blank = map.map_type::<atom::object::Blank>().unwrap();
+        let header = |otype: URID| ObjectHeader { id: None, otype };
+
+        let mut raw_space = AlignedBuffer::new(1024);
+        {
+            let mut space = RootMutSpace::new(raw_space.as_mut());
+            let space = &mut space as &mut dyn MutSpace;
+
+            // A blank set message with a duplicated value.
+            {
+                let mut object = space
+                    .init(blank, header(urids.patch.set.into_general()))
+                    .unwrap();
+                object
+                    .init(
+                        urids.patch.property,
+                        None,
+                        urids.atom.urid,
+                        urids.gain.into_general(),
+                    )
+                    .unwrap();
+                object
+                    .init(urids.patch.value, None, urids.atom.float, 0.5)
+                    .unwrap();
+                object
+                    .init(urids.patch.value, None, urids.atom.float, 0.75)
+                    .unwrap();
+            }
+
+            // A put message with a blank body.
+            {
+                let mut object = space
+                    .init(urids.atom.object, header(urids.patch.put.into_general()))
+                    .unwrap();
+                let mut body = object
+                    .init(
+                        urids.patch.body,
+                        None,
+                        blank,
+                        header(urids.gain.into_general()),
+                    )
+                    .unwrap();
+                body.init(urids.gain, None, urids.atom.float, 0.25).unwrap();
+            }
+
+            // A set message without a value.
+            space
+                .init(urids.atom.object, header(urids.patch.set.into_general()))
+                .unwrap()
+                .init(
+                    urids.patch.property,
+                    None,
+                    urids.atom.urid,
+                    urids.gain.into_general(),
+                )
+                .unwrap();
+
+            // An object of another class, with a subject that isn't a URID.
+            space
+                .init(urids.atom.object, header(urids.gain.into_general()))
+                .unwrap()
+                .init(urids.patch.subject, None, urids.atom.int, 1)
+                .unwrap();
+        }
+
+        let mut space = Space::from_slice(raw_space.as_ref());
+        let mut next = || {
+            let (atom, rest) = space.split_atom().unwrap();
+            space = rest;
+            PatchMessage::parse(&urids.patch, UnidentifiedAtom::new(atom))
+        };
+
+        match next().unwrap() {
+            PatchMessage::Set {
+                subject,
+                property,
+                value,
+            } => {
+                assert!(subject.is_none());
+                assert_eq!(urids.gain, property);
+                assert_eq!(0.5, value.read(urids.atom.float, ()).unwrap());
+            }
+            _ => panic!("Invalid message"),
+        }
+
+        match next().unwrap() {
+            PatchMessage::Put {
+                header, mut body, ..
+            } => {
+                assert_eq!(urids.gain, header.otype);
+                let (property_header, value) = body.next().unwrap();
+                assert_eq!(urids.gain, property_header.key);
+                assert_eq!(0.25, value.read(urids.atom.float, ()).unwrap());
+            }
+            _ => panic!("Invalid message"),
+        }
+
+        assert_eq!(
+            Err(AtomError::MissingProperty(urids.patch.value.into_general())),
+            next().map(|_| ())
+        );
+        assert_eq!(Err(AtomError::TypeMismatch), next().map(|_| ()));
+    }
+}
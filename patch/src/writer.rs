@@ -0,0 +1,151 @@
+use crate::PatchURIDCollection;
+use atom::object::ObjectWriter;
+use atom::prelude::*;
+use atom::scalar::ScalarAtom;
+use atom::sequence::SequenceWriter;
+use urid::URID;
+
+/// Writing methods for patch messages.
+///
+/// This trait is implemented by `SequenceWriter`: Every message is written as a single event with the given time stamp. Like the other writing methods of the sequence, these methods return an error if the time stamp is invalid or space is insufficient. In this case, the message is removed again and the sequence stays valid.
+///
+/// If no subject is given, the message refers to the plugin instance or the receiver of the message.
+pub trait PatchWriter<'a> {
+    /// Write a `patch:Set` message with a scalar value.
+    fn write_set<K: ?Sized, S: ScalarAtom>(
+        &mut self,
+        urids: &PatchURIDCollection,
+        stamp: TimeStamp,
+        subject: Option<URID>,
+        key: URID<K>,
+        value_urid: URID<S>,
+        value: S::InternalType,
+    ) -> Result<(), AtomError>;
+
+    /// Write a `patch:Set` message with an arbitrary value.
+    ///
+    /// The value atom is copied to the message, which allows values that aren't scalars, like strings or vectors.
+    fn forward_set<K: ?Sized>(
+        &mut self,
+        urids: &PatchURIDCollection,
+        stamp: TimeStamp,
+        subject: Option<URID>,
+        key: URID<K>,
+        value: UnidentifiedAtom,
+    ) -> Result<(), AtomError>;
+
+    /// Write a `patch:Get` message.
+    ///
+    /// If no property is given, the message requests all properties of the subject.
+    fn write_get(
+        &mut self,
+        urids: &PatchURIDCollection,
+        stamp: TimeStamp,
+        subject: Option<URID>,
+        property: Option<URID>,
+    ) -> Result<(), AtomError>;
+
+    /// Write a `patch:Put` message.
+    ///
+    /// The body of the message is an object with the given header. Its properties are written by the `body` function, which receives the writer of the body object. If `body` returns an error, the whole message is removed again.
+    fn write_put<F>(
+        &mut self,
+        urids: &PatchURIDCollection,
+        stamp: TimeStamp,
+        subject: Option<URID>,
+        body_header: ObjectHeader,
+        body: F,
+    ) -> Result<(), AtomError>
+    where
+        F: for<'x, 'c> FnOnce(&mut ObjectWriter<'x, 'c>) -> Result<(), AtomError>;
+}
+
+/// Write the header of a message and its subject, if there is one.
+fn init_message<'a, 'c, T: ?Sized>(
+    writer: &'c mut SequenceWriter<'a, '_>,
+    urids: &PatchURIDCollection,
+    stamp: TimeStamp,
+    class: URID<T>,
+    subject: Option<URID>,
+) -> Result<ObjectWriter<'a, 'c>, AtomError> {
+    let mut object = writer.init(
+        stamp,
+        urids.atom.object,
+        ObjectHeader {
+            id: None,
+            otype: class.into_general(),
+        },
+    )?;
+    if let Some(subject) = subject {
+        object.init(urids.subject, None, urids.atom.urid, subject)?;
+    }
+    Ok(object)
+}
+
+impl<'a, 'b> PatchWriter<'a> for SequenceWriter<'a, 'b> {
+    fn write_set<K: ?Sized, S: ScalarAtom>(
+        &mut self,
+        urids: &PatchURIDCollection,
+        stamp: TimeStamp,
+        subject: Option<URID>,
+        key: URID<K>,
+        value_urid: URID<S>,
+        value: S::InternalType,
+    ) -> Result<(), AtomError> {
+        self.transaction(|writer| {
+            let mut object = init_message(writer, urids, stamp, urids.set, subject)?;
+            object.init(urids.property, None, urids.atom.urid, key.into_general())?;
+            object.init(urids.value, None, value_urid, value)?;
+            Ok(())
+        })
+    }
+
+    fn forward_set<K: ?Sized>(
+        &mut self,
+        urids: &PatchURIDCollection,
+        stamp: TimeStamp,
+        subject: Option<URID>,
+        key: URID<K>,
+        value: UnidentifiedAtom,
+    ) -> Result<(), AtomError> {
+        self.transaction(|writer| {
+            let mut object = init_message(writer, urids, stamp, urids.set, subject)?;
+            object.init(urids.property, None, urids.atom.urid, key.into_general())?;
+            object.forward(urids.value, None, value)
+        })
+    }
+
+    fn write_get(
+        &mut self,
+        urids: &PatchURIDCollection,
+        stamp: TimeStamp,
+        subject: Option<URID>,
+        property: Option<URID>,
+    ) -> Result<(), AtomError> {
+        self.transaction(|writer| {
+            let mut object = init_message(writer, urids, stamp, urids.get, subject)?;
+            if let Some(property) = property {
+                object.init(urids.property, None, urids.atom.urid, property)?;
+            }
+            Ok(())
+        })
+    }
+
+    fn write_put<F>(
+        &mut self,
+        urids: &PatchURIDCollection,
+        stamp: TimeStamp,
+        subject: Option<URID>,
+        body_header: ObjectHeader,
+        body: F,
+    ) -> Result<(), AtomError>
+    where
+        F: for<'x, 'c> FnOnce(&mut ObjectWriter<'x, 'c>) -> Result<(), AtomError>,
+    {
+        self.transaction(|writer| {
+            let mut object = init_message(writer, urids, stamp, urids.put, subject)?;
+            let mut body_writer = object.init(urids.body, None, urids.atom.object, body_header)?;
+            body(&mut body_writer)
+        })
+    }
+}
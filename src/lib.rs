@@ -15,10 +15,10 @@
 //! additional features, including:
 //!
 //! * MIDI processing
-//! * Custom Graphical User Interfaces, both in a toolkit-agnostic and in a platform-agnostic way **(Not yet implemented)**
+//! * Custom Graphical User Interfaces, both in a toolkit-agnostic and in a platform-agnostic way
 //! * Serialization of custom data structures, and plugin-plugin or plugin-GUI communication and property manipulation
 //! * Presets handling and State management **(Not yet implemented)**
-//! * Asynchronous work processing **(Not yet implemented)**
+//! * Asynchronous work processing
 //! * … and more! (Not yet implemented either)
 //!
 //! Note that this library will only provide Rust bindings for the official LV2 specifications, however it is compatible
@@ -39,8 +39,13 @@
 
 pub extern crate lv2_atom as atom;
 pub extern crate lv2_core as core;
+pub extern crate lv2_log as log;
 pub extern crate lv2_midi as midi;
+pub extern crate lv2_options as options;
+pub extern crate lv2_patch as patch;
 pub extern crate lv2_state as state;
 pub extern crate lv2_time as time;
+pub extern crate lv2_ui as ui;
 pub extern crate lv2_units as units;
 pub extern crate lv2_urid as urid;
+pub extern crate lv2_worker as worker;
@@ -1,8 +1,10 @@
 use crate::raw::*;
 use crate::StateErr;
 use core::extension::ExtensionDescriptor;
+use core::plugin::PluginInstance;
 use core::prelude::*;
 use std::marker::PhantomData;
+use std::panic::{catch_unwind, AssertUnwindSafe};
 
 /// A plugin extension that lets a plugins save and restore it's state.
 ///
@@ -42,7 +44,7 @@ unsafe impl<P: State> UriBound for StateDescriptor<P> {
 impl<P: State> StateDescriptor<P> {
     /// Handle a save request by the host.
     ///
-    /// This involves creating the plugin reference, constructing the store handle and discovering the required host features.
+    /// This involves creating the plugin reference, constructing the store handle and discovering the required host features. Panics of the plugin are caught and handled according to the plugin's panic policy, and a faulted instance isn't saved.
     ///
     /// # Safety
     ///
@@ -58,11 +60,10 @@ impl<P: State> StateDescriptor<P> {
             return sys::LV2_State_Status_LV2_STATE_ERR_BAD_FLAGS;
         }
 
-        let plugin: &P = if let Some(plugin) = (instance as *const P).as_ref() {
-            plugin
-        } else {
+        if PluginInstance::<P>::is_faulted(instance) {
             return sys::LV2_State_Status_LV2_STATE_ERR_UNKNOWN;
-        };
+        }
+        let plugin = &*(instance as *const P);
 
         let store = StoreHandle::new(store, handle);
 
@@ -73,12 +74,18 @@ impl<P: State> StateDescriptor<P> {
             return sys::LV2_State_Status_LV2_STATE_ERR_NO_FEATURE;
         };
 
-        StateErr::into(plugin.save(store, features))
+        match catch_unwind(AssertUnwindSafe(|| plugin.save(store, features))) {
+            Ok(result) => StateErr::into(result),
+            Err(payload) => {
+                PluginInstance::<P>::handle_panic(instance, "save", payload);
+                sys::LV2_State_Status_LV2_STATE_ERR_UNKNOWN
+            }
+        }
     }
 
     /// Handle a restore request by the host.
     ///
-    /// This involves creating the plugin reference, constructing the retrieve handle and discovering the required host features.
+    /// This involves creating the plugin reference, constructing the retrieve handle and discovering the required host features. Panics of the plugin are caught and handled according to the plugin's panic policy, and a faulted instance isn't restored.
     ///
     /// # Safety
     ///
@@ -94,11 +101,10 @@ impl<P: State> StateDescriptor<P> {
             return sys::LV2_State_Status_LV2_STATE_ERR_BAD_FLAGS;
         }
 
-        let plugin: &mut P = if let Some(plugin) = (instance as *mut P).as_mut() {
-            plugin
-        } else {
+        if PluginInstance::<P>::is_faulted(instance) {
             return sys::LV2_State_Status_LV2_STATE_ERR_UNKNOWN;
-        };
+        }
+        let plugin = &mut *(instance as *mut P);
 
         let store = RetrieveHandle::new(retrieve, handle);
 
@@ -109,7 +115,13 @@ impl<P: State> StateDescriptor<P> {
             return sys::LV2_State_Status_LV2_STATE_ERR_NO_FEATURE;
         };
 
-        StateErr::into(plugin.restore(store, features))
+        match catch_unwind(AssertUnwindSafe(|| plugin.restore(store, features))) {
+            Ok(result) => StateErr::into(result),
+            Err(payload) => {
+                PluginInstance::<P>::handle_panic(instance, "restore", payload);
+                sys::LV2_State_Status_LV2_STATE_ERR_UNKNOWN
+            }
+        }
     }
 }
 
@@ -123,9 +135,12 @@ impl<P: State> ExtensionDescriptor for StateDescriptor<P> {
 }
 
 #[cfg(test)]
+#[cfg(feature = "host")]
 mod tests {
     use crate::*;
+    use lv2_core::plugin::PanicReport;
     use lv2_core::prelude::*;
+    use lv2_core::test::Harness;
     use lv2_urid::prelude::*;
 
     struct Stateful;
@@ -166,10 +181,45 @@ mod tests {
         }
     }
 
+    /// A plugin that panics in `run`.
+    struct Faulty;
+
+    unsafe impl UriBound for Faulty {
+        const URI: &'static [u8] = b"urn:faulty\0";
+    }
+
+    impl Plugin for Faulty {
+        type Features = ();
+        type Ports = ();
+
+        fn new(_: &PluginInfo, _: ()) -> Option<Self> {
+            Some(Self)
+        }
+
+        fn run(&mut self, _: &mut ()) {
+            panic!("Faulty run");
+        }
+
+        fn report_panic(_: &PanicReport) {}
+    }
+
+    impl State for Faulty {
+        type StateFeatures = ();
+
+        fn save(&self, _: StoreHandle, _: ()) -> Result<(), StateErr> {
+            Ok(())
+        }
+
+        fn restore(&mut self, _: RetrieveHandle, _: ()) -> Result<(), StateErr> {
+            Ok(())
+        }
+    }
+
     #[test]
     fn test_illegal_paths() {
         type Descriptor = StateDescriptor<Stateful>;
-        let mut plugin = Stateful;
+        let mut harness = Harness::<Stateful>::new(44100.0, 0);
+        assert!(harness.instantiate());
 
         assert_eq!(sys::LV2_State_Status_LV2_STATE_ERR_BAD_FLAGS, unsafe {
             Descriptor::extern_save(
@@ -213,7 +263,7 @@ mod tests {
 
         assert_eq!(sys::LV2_State_Status_LV2_STATE_ERR_NO_FEATURE, unsafe {
             Descriptor::extern_save(
-                &mut plugin as *mut Stateful as sys::LV2_Handle,
+                harness.handle(),
                 None,
                 std::ptr::null_mut(),
                 sys::LV2_State_Flags_LV2_STATE_IS_POD,
@@ -223,7 +273,7 @@ mod tests {
 
         assert_eq!(sys::LV2_State_Status_LV2_STATE_ERR_NO_FEATURE, unsafe {
             Descriptor::extern_restore(
-                &mut plugin as *mut Stateful as sys::LV2_Handle,
+                harness.handle(),
                 None,
                 std::ptr::null_mut(),
                 sys::LV2_State_Flags_LV2_STATE_IS_POD,
@@ -231,4 +281,50 @@ mod tests {
             )
         });
     }
+    #[test]
+    fn test_faulted_instance() {
+        type Descriptor = StateDescriptor<Faulty>;
+        let mut harness = Harness::<Faulty>::new(44100.0, 0);
+        assert!(harness.instantiate());
+        harness.activate();
+
+        let save = |handle| unsafe {
+            Descriptor::extern_save(
+                handle,
+                None,
+                std::ptr::null_mut(),
+                sys::LV2_State_Flags_LV2_STATE_IS_POD,
+                std::ptr::null_mut(),
+            )
+        };
+        let restore = |handle| unsafe {
+            Descriptor::extern_restore(
+                handle,
+                None,
+                std::ptr::null_mut(),
+                sys::LV2_State_Flags_LV2_STATE_IS_POD,
+                std::ptr::null_mut(),
+            )
+        };
+        assert_eq!(
+            sys::LV2_State_Status_LV2_STATE_SUCCESS,
+            save(harness.handle())
+        );
+        assert_eq!(
+            sys::LV2_State_Status_LV2_STATE_SUCCESS,
+            restore(harness.handle())
+        );
+
+        // After the panic in `run`, the instance isn't saved or restored anymore.
+        harness.run(0);
+        assert!(harness.is_faulted());
+        assert_eq!(
+            sys::LV2_State_Status_LV2_STATE_ERR_UNKNOWN,
+            save(harness.handle())
+        );
+        assert_eq!(
+            sys::LV2_State_Status_LV2_STATE_ERR_UNKNOWN,
+            restore(harness.handle())
+        );
+    }
 }
@@ -1,6 +1,7 @@
 use crate::StateErr;
 use atom::prelude::*;
 use atom::space::*;
+use std::cell::RefCell;
 use std::collections::HashMap;
 use std::ffi::c_void;
 use std::marker::PhantomData;
@@ -12,7 +13,7 @@ use urid::prelude::*;
 ///
 /// The written properties a buffered and flushed when requested. Create new properties by calling [`draft`](#method.draft) and write them like any other atom. Once you are done, you can commit your properties by calling [`commit_all`](#method.commit_all) or [`commit`](#method.commit). You have to commit manually: Uncommitted properties will be discarded when the handle is dropped.
 pub struct StoreHandle<'a> {
-    properties: HashMap<URID, SpaceElement>,
+    properties: HashMap<URID, AtomVec>,
     store_fn: sys::LV2_State_Store_Function,
     handle: sys::LV2_State_Handle,
     lifetime: PhantomData<&'a mut c_void>,
@@ -35,11 +36,8 @@ impl<'a> StoreHandle<'a> {
     ///
     /// If you began to write a property and don't want the written things to be stored, you can discard it with [`discard`](#method.discard) or [`discard_all`](#method.discard_all).
     pub fn draft(&mut self, property_key: URID) -> StatePropertyWriter {
-        self.properties
-            .insert(property_key, SpaceElement::default());
-        StatePropertyWriter::new(SpaceHead::new(
-            self.properties.get_mut(&property_key).unwrap(),
-        ))
+        self.properties.insert(property_key, AtomVec::new());
+        StatePropertyWriter::new(self.properties.get_mut(&property_key).unwrap().head())
     }
 
     /// Internal helper function to store a property.
@@ -47,18 +45,17 @@ impl<'a> StoreHandle<'a> {
         store_fn: sys::LV2_State_Store_Function,
         handle: sys::LV2_State_Handle,
         key: URID,
-        space: SpaceElement,
+        mut space: AtomVec,
     ) -> Result<(), StateErr> {
         let store_fn = store_fn.ok_or(StateErr::BadCallback)?;
-        let space: Vec<u8> = space.to_vec();
-        let space = Space::from_slice(space.as_ref());
+        let space = Space::from_slice(space.make_contiguous());
         let (header, data) = space
             .split_type::<sys::LV2_Atom>()
-            .ok_or(StateErr::BadData)?;
+            .map_err(|_| StateErr::BadData)?;
         let data = data
             .split_raw(header.size as usize)
             .map(|(data, _)| data)
-            .ok_or(StateErr::BadData)?;
+            .map_err(|_| StateErr::BadData)?;
 
         let key = key.get();
         let data_ptr = data as *const _ as *const c_void;
@@ -102,13 +99,13 @@ impl<'a> StoreHandle<'a> {
 
 /// Writing handle for properties.
 pub struct StatePropertyWriter<'a> {
-    head: SpaceHead<'a>,
+    head: AtomVecHead<'a>,
     initialized: bool,
 }
 
 impl<'a> StatePropertyWriter<'a> {
     /// Create a new property writer that uses the given space head.
-    pub fn new(head: SpaceHead<'a>) -> Self {
+    pub fn new(head: AtomVecHead<'a>) -> Self {
         Self {
             head,
             initialized: false,
@@ -127,7 +124,7 @@ impl<'a> StatePropertyWriter<'a> {
             self.initialized = true;
             (&mut self.head as &mut dyn MutSpace)
                 .init(urid, parameter)
-                .ok_or(StateErr::Unknown)
+                .map_err(|_| StateErr::Unknown)
         } else {
             Err(StateErr::Unknown)
         }
@@ -135,9 +132,12 @@ impl<'a> StatePropertyWriter<'a> {
 }
 
 /// Property retrieval handle.
+///
+/// Hosts don't have to align the values of properties. Since atoms can only be read from 64-bit-aligned memory, misaligned values are copied into buffers that live as long as the handle.
 pub struct RetrieveHandle<'a> {
     retrieve_fn: sys::LV2_State_Retrieve_Function,
     handle: sys::LV2_State_Handle,
+    buffers: RefCell<Vec<AlignedBuffer>>,
     lifetime: PhantomData<&'a mut c_void>,
 }
 
@@ -150,6 +150,7 @@ impl<'a> RetrieveHandle<'a> {
         RetrieveHandle {
             retrieve_fn,
             handle,
+            buffers: RefCell::new(Vec::new()),
             lifetime: PhantomData,
         }
     }
@@ -171,13 +172,26 @@ impl<'a> RetrieveHandle<'a> {
         };
 
         let type_ = URID::new(type_).ok_or(StateErr::Unknown)?;
-        let space = if !property_ptr.is_null() {
+        let data = if !property_ptr.is_null() {
             unsafe { std::slice::from_raw_parts(property_ptr as *const u8, size) }
         } else {
             return Err(StateErr::NoProperty);
         };
 
-        Ok(StatePropertyReader::new(type_, Space::from_slice(space)))
+        let space = match Space::try_from_slice(data) {
+            Ok(space) => space,
+            Err(_) => {
+                let mut buffers = self.buffers.borrow_mut();
+                buffers.push(AlignedBuffer::from_bytes(data));
+                let buffer = buffers.last().unwrap();
+                // The contents of a buffer stay in place when the vector grows, and the buffers are only dropped together with the handle.
+                Space::from_slice(unsafe {
+                    std::slice::from_raw_parts(buffer.as_ptr(), buffer.len())
+                })
+            }
+        };
+
+        Ok(StatePropertyReader::new(type_, space))
     }
 }
 
@@ -216,7 +230,7 @@ impl<'a> StatePropertyReader<'a> {
         parameter: A::ReadParameter,
     ) -> Result<A::ReadHandle, StateErr> {
         if urid == self.type_ {
-            A::read(self.body, parameter).ok_or(StateErr::Unknown)
+            A::read(self.body, parameter).map_err(|_| StateErr::Unknown)
         } else {
             Err(StateErr::BadType)
         }
@@ -227,7 +241,6 @@ impl<'a> StatePropertyReader<'a> {
 mod tests {
     use crate::raw::*;
     use crate::storage::Storage;
-    use atom::space::Space;
     use urid::mapper::*;
 
     fn store(storage: &mut Storage, urids: &AtomURIDCollection) {
@@ -311,8 +324,8 @@ mod tests {
                 }
                 3 => {
                     assert_eq!(urids.vector::<Int>(), *type_);
-                    let space = Space::from_slice(value.as_slice());
-                    let data = Vector::read(space, urids.int).unwrap();
+                    let value = AlignedBuffer::from_bytes(value);
+                    let data = Vector::read(value.space(), urids.int).unwrap();
                     assert_eq!([1, 2, 3, 4], data);
                 }
                 _ => panic!("Invalid key!"),
@@ -321,4 +334,42 @@ mod tests {
 
         retrieve(&mut storage, &urids);
     }
+
+    /// Retrieve a property from a storage, but skip the first byte of the value.
+    unsafe extern "C" fn retrieve_shifted(
+        handle: sys::LV2_State_Handle,
+        key: u32,
+        size: *mut usize,
+        type_: *mut u32,
+        flags: *mut u32,
+    ) -> *const c_void {
+        let value = Storage::extern_retrieve(handle, key, size, type_, flags) as *const u8;
+        *size -= 1;
+        value.add(1) as *const c_void
+    }
+
+    #[test]
+    fn test_misaligned_retrieve() {
+        let mut mapper = Box::pin(HashURIDMapper::new());
+        let interface = mapper.as_mut().make_map_interface();
+        let map = Map::new(&interface);
+        let urids = AtomURIDCollection::from_map(&map).unwrap();
+
+        let mut storage = Storage::default();
+        let mut value = vec![0u8];
+        value.extend_from_slice(&17i32.to_ne_bytes());
+        storage.store(URID::new(1).unwrap(), urids.int.into_general(), &value);
+        let mut value = vec![0u8];
+        value.extend_from_slice(&42i64.to_ne_bytes());
+        storage.store(URID::new(2).unwrap(), urids.long.into_general(), &value);
+
+        let retrieve_handle = RetrieveHandle::new(
+            Some(retrieve_shifted),
+            &mut storage as *mut Storage as *mut c_void,
+        );
+        let int = retrieve_handle.retrieve(URID::new(1).unwrap()).unwrap();
+        let long = retrieve_handle.retrieve(URID::new(2).unwrap()).unwrap();
+        assert_eq!(17, int.read(urids.int, ()).unwrap());
+        assert_eq!(42, long.read(urids.long, ()).unwrap());
+    }
 }
@@ -4,8 +4,8 @@ use lv2_core::prelude::*;
 use lv2_state::*;
 use lv2_urid::mapper::*;
 use lv2_urid::prelude::*;
-use std::path::Path;
-use std::pin::Pin;
+use std::ffi::c_void;
+use std::os::raw::c_char;
 
 struct Stateful {
     internal: f32,
@@ -77,35 +77,42 @@ lv2_descriptors! {
     Stateful
 }
 
-fn create_plugin(mapper: Pin<&mut HashURIDMapper>) -> Stateful {
-    let plugin = {
-        // Faking the map's lifetime.
-        let interface = mapper.make_map_interface();
-        let interface = &interface as *const lv2_sys::LV2_URID_Map;
-        let interface = unsafe { interface.as_ref().unwrap() };
-        let map = Map::new(interface);
-
-        // Constructing the plugin.
-        Stateful::new(
-            &PluginInfo::new(Stateful::uri(), Path::new("./"), 44100.0),
-            Features { map: map },
-        )
-        .unwrap()
+/// Instantiate the plugin with its C interface, like a host does.
+unsafe fn create_plugin(
+    descriptor: &lv2_sys::LV2_Descriptor,
+    map: &lv2_sys::LV2_URID_Map,
+) -> lv2_sys::LV2_Handle {
+    let map_feature = lv2_sys::LV2_Feature {
+        URI: lv2_sys::LV2_URID__map.as_ptr() as *const c_char,
+        data: map as *const lv2_sys::LV2_URID_Map as *mut c_void,
     };
-
+    let features = [
+        &map_feature as *const lv2_sys::LV2_Feature,
+        std::ptr::null(),
+    ];
+    let handle = (descriptor.instantiate.unwrap())(
+        descriptor,
+        44100.0,
+        "./\0".as_ptr() as *const c_char,
+        features.as_ptr(),
+    );
+
+    let plugin = (handle as *const Stateful).as_ref().unwrap();
     assert_eq!(42.0, plugin.internal);
     assert_eq!(0, plugin.audio.len());
 
-    plugin
+    handle
 }
 
 #[test]
 fn test_save_n_restore() {
     let mut mapper = Box::pin(HashURIDMapper::new());
+    let map = mapper.as_mut().make_map_interface();
     let mut storage = lv2_state::Storage::default();
 
+    let descriptor = unsafe { lv2_descriptor(0).as_ref().unwrap() };
     let (store_fn, restore_fn) = unsafe {
-        let extension_data_fn = lv2_descriptor(0).as_ref().unwrap().extension_data;
+        let extension_data_fn = descriptor.extension_data;
         let uri = lv2_sys::LV2_STATE__interface.as_ptr() as *const i8;
         let extension = ((extension_data_fn.unwrap())(uri) as *const lv2_sys::LV2_State_Interface)
             .as_ref()
@@ -115,16 +122,17 @@ fn test_save_n_restore() {
     assert!(store_fn == StateDescriptor::<Stateful>::extern_save);
     assert!(restore_fn == StateDescriptor::<Stateful>::extern_restore);
 
-    let mut first_plugin = create_plugin(mapper.as_mut());
+    let first_plugin = unsafe { create_plugin(descriptor, &map) };
 
-    first_plugin.run(&mut ());
+    unsafe { (descriptor.run.unwrap())(first_plugin, 0) };
 
-    assert_eq!(17.0, first_plugin.internal);
-    assert_eq!(32, first_plugin.audio.len());
+    let first = unsafe { &*(first_plugin as *const Stateful) };
+    assert_eq!(17.0, first.internal);
+    assert_eq!(32, first.audio.len());
 
     unsafe {
         (store_fn)(
-            &mut first_plugin as *mut Stateful as lv2_sys::LV2_Handle,
+            first_plugin,
             Some(lv2_state::Storage::extern_store),
             &mut storage as *mut lv2_state::Storage as lv2_sys::LV2_State_Handle,
             lv2_sys::LV2_State_Flags_LV2_STATE_IS_POD,
@@ -132,11 +140,11 @@ fn test_save_n_restore() {
         )
     };
 
-    let mut second_plugin = create_plugin(mapper.as_mut());
+    let second_plugin = unsafe { create_plugin(descriptor, &map) };
 
     unsafe {
         (restore_fn)(
-            &mut second_plugin as *mut Stateful as lv2_sys::LV2_Handle,
+            second_plugin,
             Some(lv2_state::Storage::extern_retrieve),
             &mut storage as *mut lv2_state::Storage as lv2_sys::LV2_State_Handle,
             lv2_sys::LV2_State_Flags_LV2_STATE_IS_POD,
@@ -144,6 +152,12 @@ fn test_save_n_restore() {
         )
     };
 
-    assert_eq!(17.0, second_plugin.internal);
-    assert_eq!(32, second_plugin.audio.len());
+    let second = unsafe { &*(second_plugin as *const Stateful) };
+    assert_eq!(17.0, second.internal);
+    assert_eq!(32, second.audio.len());
+
+    unsafe {
+        (descriptor.cleanup.unwrap())(first_plugin);
+        (descriptor.cleanup.unwrap())(second_plugin);
+    }
 }
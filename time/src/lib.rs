@@ -4,10 +4,13 @@
 //! The original [specification](https://lv2plug.in/ns/ext/time/time.html) contains means to
 //! describe time for LV2 values in RDF files. This implementation is focused on the stock time
 //! descriptions defined by the specification by binding them to marker types.
+extern crate lv2_atom as atom;
 extern crate lv2_core as core;
 extern crate lv2_sys as sys;
 extern crate lv2_urid as urid;
 
+pub mod position;
+
 use urid::prelude::*;
 
 /// All time URI bounds
@@ -131,6 +134,7 @@ pub struct TimeURIDCollection {
 
 /// Prelude of `lv2_time` for wildcard usage.
 pub mod prelude {
+    pub use crate::position::TransportPosition;
     pub use crate::time::*;
     pub use crate::TimeURIDCollection;
 }
@@ -0,0 +1,226 @@
+//! Tracking the transport position of the host.
+//!
+//! Hosts send the position of the transport as `time:Position` objects to plugins that support
+//! it, usually in an atom sequence and only when it changes. A
+//! [`TransportPosition`](struct.TransportPosition.html) is updated with these objects and
+//! provides the [`Tempo`](../../lv2_atom/sequence/struct.Tempo.html) to convert time stamps
+//! between frames and beats.
+//!
+//! # Example
+//!
+//! ```
+//! use lv2_atom::prelude::*;
+//! use lv2_atom::sequence::*;
+//! use lv2_time::prelude::*;
+//! use lv2_units::prelude::*;
+//!
+//! fn run(
+//!     input: UnidentifiedAtom,
+//!     position: &mut TransportPosition,
+//!     atom_urids: &AtomURIDCollection,
+//!     time_urids: &TimeURIDCollection,
+//!     unit_urids: &UnitURIDCollection,
+//! ) {
+//!     let events = input.read(atom_urids.sequence, unit_urids.beat).unwrap();
+//!     for (_, atom) in events.clone() {
+//!         // Other objects are ignored.
+//!         let _ = position.update(atom, atom_urids, time_urids);
+//!     }
+//!
+//!     // Read all time stamps as frames, even if the host sends beats.
+//!     for (stamp, atom) in events.convert(TimeStampUnit::Frames, position.tempo()) {
+//!         let frame: i64 = stamp.as_frames().unwrap();
+//!     }
+//! }
+//! ```
+use crate::TimeURIDCollection;
+use atom::prelude::*;
+use atom::sequence::Tempo;
+
+/// The position and tempo of the transport.
+///
+/// All fields have the value of the last `time:Position` object that contained them, or their
+/// default value if no object contained them yet.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TransportPosition {
+    /// The sample rate of the plugin, which is needed to convert beats to frames.
+    pub sample_rate: f64,
+    /// The frame of the transport, `time:frame`. The default is 0.
+    pub frame: i64,
+    /// The speed of the transport, `time:speed`. The default is 0.0, which means stopped.
+    pub speed: f64,
+    /// The bar number, `time:bar`. The default is 0.
+    pub bar: i64,
+    /// The beat within the bar, `time:barBeat`. The default is 0.0.
+    pub bar_beat: f64,
+    /// The global beat number, `time:beat`. The default is 0.0.
+    pub beat: f64,
+    /// The note value of a beat, `time:beatUnit`. The default is 4.
+    pub beat_unit: i64,
+    /// The number of beats per bar, `time:beatsPerBar`. The default is 4.0.
+    pub beats_per_bar: f64,
+    /// The tempo, `time:beatsPerMinute`. The default is 120.0.
+    pub beats_per_minute: f64,
+}
+
+impl TransportPosition {
+    /// Create a new position at the start of a stopped transport.
+    pub fn new(sample_rate: f64) -> Self {
+        Self {
+            sample_rate,
+            frame: 0,
+            speed: 0.0,
+            bar: 0,
+            bar_beat: 0.0,
+            beat: 0.0,
+            beat_unit: 4,
+            beats_per_bar: 4.0,
+            beats_per_minute: 120.0,
+        }
+    }
+
+    /// Update the position with the properties of a `time:Position` object.
+    ///
+    /// Properties with any numeric type are accepted and properties that are not contained in the
+    /// object keep their previous value. If the atom is not an object of the `time:Position`
+    /// class, `AtomError::TypeMismatch` is returned and the position is left unchanged.
+    pub fn update(
+        &mut self,
+        atom: UnidentifiedAtom,
+        atom_urids: &AtomURIDCollection,
+        time_urids: &TimeURIDCollection,
+    ) -> Result<(), AtomError> {
+        let (header, reader) = atom.read(atom_urids.object, ())?;
+        if header.otype != time_urids.position_class {
+            return Err(AtomError::TypeMismatch);
+        }
+
+        for (property, value) in reader {
+            let value = match NumberValue::read(value, atom_urids) {
+                Ok(value) => value,
+                Err(_) => continue,
+            };
+            let key = property.key;
+            if key == time_urids.frame {
+                self.frame = value.as_i64();
+            } else if key == time_urids.speed {
+                self.speed = value.as_f64();
+            } else if key == time_urids.bar {
+                self.bar = value.as_i64();
+            } else if key == time_urids.bar_beat {
+                self.bar_beat = value.as_f64();
+            } else if key == time_urids.beat {
+                self.beat = value.as_f64();
+            } else if key == time_urids.beat_unit {
+                self.beat_unit = value.as_i64();
+            } else if key == time_urids.beats_per_bar {
+                self.beats_per_bar = value.as_f64();
+            } else if key == time_urids.beats_per_minute {
+                self.beats_per_minute = value.as_f64();
+            }
+        }
+        Ok(())
+    }
+
+    /// Return the tempo of the transport.
+    ///
+    /// The tempo describes the transport at normal speed and can be used to convert time stamps
+    /// between frames and beats, even if the transport is stopped.
+    pub fn tempo(&self) -> Tempo {
+        Tempo {
+            beats_per_minute: self.beats_per_minute,
+            sample_rate: self.sample_rate,
+        }
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "host")]
+mod tests {
+    use crate::position::*;
+    use atom::sequence::*;
+    use atom::space::*;
+    use urid::mapper::*;
+    use urid::prelude::*;
+
+    #[test]
+    fn test_position() {
+        let mut mapper = Box::pin(HashURIDMapper::new());
+        let interface = mapper.as_mut().make_map_interface();
+        let map = Map::new(&interface);
+        let atom_urids = AtomURIDCollection::from_map(&map).unwrap();
+        let time_urids = TimeURIDCollection::from_map(&map).unwrap();
+
+        let mut raw_space = AlignedBuffer::new(256);
+        {
+            let mut space = raw_space.mut_space();
+            let mut writer = (&mut space as &mut dyn MutSpace)
+                .init(
+                    atom_urids.object,
+                    ObjectHeader {
+                        id: None,
+                        otype: time_urids.position_class.into_general(),
+                    },
+                )
+                .unwrap();
+            writer
+                .init(time_urids.frame, None, atom_urids.long, 4800)
+                .unwrap();
+            writer
+                .init(time_urids.speed, None, atom_urids.float, 1.0)
+                .unwrap();
+            writer
+                .init(time_urids.beats_per_minute, None, atom_urids.double, 150.0)
+                .unwrap();
+            writer
+                .init(time_urids.bar, None, atom_urids.string, ())
+                .unwrap()
+                .append("ignored")
+                .unwrap();
+        }
+
+        let mut position = TransportPosition::new(48000.0);
+        position
+            .update(
+                UnidentifiedAtom::new(raw_space.space()),
+                &atom_urids,
+                &time_urids,
+            )
+            .unwrap();
+        assert_eq!(position.frame, 4800);
+        assert_eq!(position.speed, 1.0);
+        assert_eq!(position.beats_per_minute, 150.0);
+        assert_eq!(position.bar, 0);
+        assert_eq!(position.beats_per_bar, 4.0);
+
+        let tempo = position.tempo();
+        assert_eq!(tempo.frames_per_beat(), 19200.0);
+        assert_eq!(
+            tempo
+                .convert(TimeStamp::BeatsPerMinute(0.5), TimeStampUnit::Frames)
+                .as_frames(),
+            Some(9600)
+        );
+
+        // other objects are rejected
+        let mut raw_space = AlignedBuffer::new(64);
+        (&mut raw_space.mut_space() as &mut dyn MutSpace)
+            .init(
+                atom_urids.object,
+                ObjectHeader {
+                    id: None,
+                    otype: time_urids.rate_class.into_general(),
+                },
+            )
+            .unwrap();
+        assert_eq!(
+            position.update(
+                UnidentifiedAtom::new(raw_space.space()),
+                &atom_urids,
+                &time_urids,
+            ),
+            Err(AtomError::TypeMismatch)
+        );
+        assert_eq!(position.frame, 4800);
+    }
+}
@@ -0,0 +1,128 @@
+use crate::{AtomProtocol, UIError};
+use atom::UnidentifiedAtom;
+use std::ffi::c_void;
+use urid::URID;
+
+/// Handle to send new port values to the plugin.
+///
+/// This is a wrapper of the write function and the controller the host passes when the user interface is created. The host forwards the written data to the plugin, possibly with a delay.
+pub struct UIController {
+    write_function: sys::LV2UI_Write_Function,
+    controller: sys::LV2UI_Controller,
+}
+
+impl UIController {
+    /// Create a new controller from the raw write function and controller.
+    pub fn new(
+        write_function: sys::LV2UI_Write_Function,
+        controller: sys::LV2UI_Controller,
+    ) -> Self {
+        Self {
+            write_function,
+            controller,
+        }
+    }
+
+    /// Write raw data with the given protocol to a port.
+    ///
+    /// The protocol has to be `0` for control values or the URID of a protocol supported by the port.
+    pub fn write_raw(&self, port_index: u32, protocol: u32, data: &[u8]) -> Result<(), UIError> {
+        let write_function = self.write_function.ok_or(UIError::NoCallback)?;
+        unsafe {
+            (write_function)(
+                self.controller,
+                port_index,
+                data.len() as u32,
+                protocol,
+                data.as_ptr() as *const c_void,
+            )
+        };
+        Ok(())
+    }
+
+    /// Write a new value to a control port.
+    pub fn write_control(&self, port_index: u32, value: f32) -> Result<(), UIError> {
+        self.write_raw(port_index, 0, &value.to_ne_bytes())
+    }
+
+    /// Write an atom to an atom port.
+    ///
+    /// The atom is sent with the given protocol, which is usually [`EventTransfer`](struct.EventTransfer.html) for atom sequence ports. If the atom is malformed, `Err(UIError::MalformedAtom)` is returned.
+    pub fn write_atom<P: AtomProtocol>(
+        &self,
+        port_index: u32,
+        protocol: URID<P>,
+        atom: UnidentifiedAtom,
+    ) -> Result<(), UIError> {
+        let (atom, _) = atom
+            .space()
+            .split_atom()
+            .map_err(|_| UIError::MalformedAtom)?;
+        let data = atom.data().ok_or(UIError::MalformedAtom)?;
+        self.write_raw(port_index, protocol.get(), data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use atom::prelude::*;
+    use atom::space::AlignedBuffer;
+    use std::ffi::c_void;
+    use urid::URID;
+
+    struct Written {
+        port_index: u32,
+        protocol: u32,
+        data: Vec<u8>,
+    }
+
+    unsafe extern "C" fn write(
+        controller: sys::LV2UI_Controller,
+        port_index: u32,
+        buffer_size: u32,
+        port_protocol: u32,
+        buffer: *const c_void,
+    ) {
+        let written = &mut *(controller as *mut Vec<Written>);
+        written.push(Written {
+            port_index,
+            protocol: port_protocol,
+            data: std::slice::from_raw_parts(buffer as *const u8, buffer_size as usize).to_vec(),
+        });
+    }
+
+    #[test]
+    fn test_write() {
+        let mut written: Vec<Written> = Vec::new();
+        let controller = UIController::new(
+            Some(write),
+            &mut written as *mut Vec<Written> as sys::LV2UI_Controller,
+        );
+        let int: URID<Int> = unsafe { URID::new_unchecked(1) };
+        let event_transfer: URID<EventTransfer> = unsafe { URID::new_unchecked(2) };
+
+        controller.write_control(3, 0.25).unwrap();
+
+        let mut buffer = AlignedBuffer::new(256);
+        (&mut buffer.mut_space() as &mut dyn MutSpace)
+            .init(int, 42)
+            .unwrap();
+        let atom = UnidentifiedAtom::new(buffer.space());
+        controller.write_atom(1, event_transfer, atom).unwrap();
+
+        assert_eq!(2, written.len());
+        assert_eq!(3, written[0].port_index);
+        assert_eq!(0, written[0].protocol);
+        assert_eq!(0.25f32.to_ne_bytes().to_vec(), written[0].data);
+
+        assert_eq!(1, written[1].port_index);
+        assert_eq!(2, written[1].protocol);
+        let buffer = AlignedBuffer::from_bytes(&written[1].data);
+        let atom = UnidentifiedAtom::new(buffer.space());
+        assert_eq!(42, atom.read(int, ()).unwrap());
+
+        let controller = UIController::new(None, std::ptr::null_mut());
+        assert_eq!(Err(UIError::NoCallback), controller.write_control(0, 1.0));
+    }
+}
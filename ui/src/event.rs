@@ -0,0 +1,210 @@
+use crate::UIURIDCollection;
+use atom::prelude::*;
+use atom::space::AlignedBuffer;
+use std::mem::size_of;
+
+/// A peak measurement of an audio port.
+///
+/// This is sent by the host if the user interface subscribed to an audio port with the peak protocol.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PeakData {
+    /// The start of the measurement period, in frames since some arbitrary point in time.
+    pub period_start: u32,
+    /// The length of the measurement period, in frames.
+    pub period_size: u32,
+    /// The peak amplitude of the signal in the period.
+    pub peak: f32,
+}
+
+/// A decoded notification about a port of the plugin.
+#[derive(Clone, Copy)]
+pub enum PortEvent<'a> {
+    /// The new value of a control port.
+    Control(f32),
+    /// A peak measurement of an audio port.
+    Peak(PeakData),
+    /// An atom, sent with the event or the atom transfer protocol.
+    Atom(UnidentifiedAtom<'a>),
+    /// Data with an unknown protocol, or data that couldn't be decoded.
+    Unknown { protocol: u32, data: &'a [u8] },
+}
+
+impl<'a> PortEvent<'a> {
+    /// Decode the data of a port notification.
+    ///
+    /// The protocol `0` is always decoded as a control value. Peak data and atoms can only be decoded if the URIDs of the protocols are given. Malformed data and data with other protocols are returned as [`Unknown`](#variant.Unknown).
+    ///
+    /// Atoms have to be read from 64-bit-aligned memory, which the host doesn't guarantee. If the data of an atom is misaligned, it is copied into `buffer` and the atom is read from there.
+    pub fn new(
+        protocol: u32,
+        data: &'a [u8],
+        urids: Option<&UIURIDCollection>,
+        buffer: &'a mut AlignedBuffer,
+    ) -> Self {
+        let (is_float, is_peak, is_atom) = match urids {
+            Some(urids) => (
+                urids.float_protocol.get() == protocol,
+                urids.peak_protocol.get() == protocol,
+                urids.event_transfer.get() == protocol || urids.atom_transfer.get() == protocol,
+            ),
+            None => (false, false, false),
+        };
+        let is_control = protocol == 0 || is_float;
+
+        let event = if is_control && data.len() == size_of::<f32>() {
+            let mut bytes = [0u8; 4];
+            bytes.copy_from_slice(data);
+            Some(PortEvent::Control(f32::from_ne_bytes(bytes)))
+        } else if is_peak && data.len() == size_of::<sys::LV2UI_Peak_Data>() {
+            let raw =
+                unsafe { std::ptr::read_unaligned(data.as_ptr() as *const sys::LV2UI_Peak_Data) };
+            Some(PortEvent::Peak(PeakData {
+                period_start: raw.period_start,
+                period_size: raw.period_size,
+                peak: raw.peak,
+            }))
+        } else if is_atom {
+            let space = match Space::try_from_slice(data) {
+                Ok(space) => space,
+                Err(_) => {
+                    *buffer = AlignedBuffer::from_bytes(data);
+                    let buffer: &'a AlignedBuffer = buffer;
+                    buffer.space()
+                }
+            };
+            space
+                .split_atom()
+                .ok()
+                .map(|(atom, _)| PortEvent::Atom(UnidentifiedAtom::new(atom)))
+        } else {
+            None
+        };
+
+        event.unwrap_or(PortEvent::Unknown { protocol, data })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use atom::prelude::*;
+    use atom::space::AlignedBuffer;
+    use std::mem::size_of;
+    use urid::URID;
+
+    fn urids() -> UIURIDCollection {
+        unsafe {
+            UIURIDCollection {
+                float_protocol: URID::new_unchecked(20),
+                peak_protocol: URID::new_unchecked(21),
+                event_transfer: URID::new_unchecked(22),
+                atom_transfer: URID::new_unchecked(23),
+            }
+        }
+    }
+
+    #[test]
+    fn test_control_event() {
+        let urids = urids();
+        let mut buffer = AlignedBuffer::new(0);
+        let value: f32 = 0.5;
+        let data = value.to_ne_bytes();
+
+        match PortEvent::new(0, &data, None, &mut buffer) {
+            PortEvent::Control(value) => assert_eq!(0.5, value),
+            _ => panic!("Control value not decoded"),
+        }
+        match PortEvent::new(20, &data, Some(&urids), &mut buffer) {
+            PortEvent::Control(value) => assert_eq!(0.5, value),
+            _ => panic!("Control value not decoded"),
+        }
+        match PortEvent::new(0, &data[..2], Some(&urids), &mut buffer) {
+            PortEvent::Unknown { protocol, data } => {
+                assert_eq!(0, protocol);
+                assert_eq!(2, data.len());
+            }
+            _ => panic!("Malformed control value decoded"),
+        }
+    }
+
+    #[test]
+    fn test_peak_event() {
+        let urids = urids();
+        let mut buffer = AlignedBuffer::new(0);
+        let raw = sys::LV2UI_Peak_Data {
+            period_start: 256,
+            period_size: 128,
+            peak: 0.75,
+        };
+        let data = unsafe {
+            std::slice::from_raw_parts(
+                &raw as *const sys::LV2UI_Peak_Data as *const u8,
+                size_of::<sys::LV2UI_Peak_Data>(),
+            )
+        };
+
+        match PortEvent::new(21, data, Some(&urids), &mut buffer) {
+            PortEvent::Peak(peak) => assert_eq!(
+                PeakData {
+                    period_start: 256,
+                    period_size: 128,
+                    peak: 0.75
+                },
+                peak
+            ),
+            _ => panic!("Peak data not decoded"),
+        }
+        match PortEvent::new(21, data, None, &mut buffer) {
+            PortEvent::Unknown { protocol, .. } => assert_eq!(21, protocol),
+            _ => panic!("Peak data decoded without URIDs"),
+        }
+    }
+
+    #[test]
+    fn test_atom_event() {
+        let urids = urids();
+        let mut buffer = AlignedBuffer::new(0);
+        let int: URID<Int> = unsafe { URID::new_unchecked(6) };
+        let mut raw = sys::LV2_Atom_Int {
+            atom: sys::LV2_Atom {
+                size: size_of::<i32>() as u32,
+                type_: int.get(),
+            },
+            body: 42,
+        };
+        let data = unsafe {
+            std::slice::from_raw_parts(
+                &raw as *const sys::LV2_Atom_Int as *const u8,
+                size_of::<sys::LV2_Atom_Int>(),
+            )
+        };
+
+        for protocol in [22, 23].iter() {
+            match PortEvent::new(*protocol, data, Some(&urids), &mut buffer) {
+                PortEvent::Atom(atom) => assert_eq!(42, atom.read(int, ()).unwrap()),
+                _ => panic!("Atom not decoded"),
+            }
+        }
+
+        // The same atom, one byte after an aligned address.
+        let mut shifted = AlignedBuffer::new(data.len() + 1);
+        shifted[1..].copy_from_slice(data);
+        match PortEvent::new(22, &shifted[1..], Some(&urids), &mut buffer) {
+            PortEvent::Atom(atom) => assert_eq!(42, atom.read(int, ()).unwrap()),
+            _ => panic!("Misaligned atom not decoded"),
+        }
+
+        // An atom that is larger than the buffer.
+        raw.atom.size = 64;
+        let data = unsafe {
+            std::slice::from_raw_parts(
+                &raw as *const sys::LV2_Atom_Int as *const u8,
+                size_of::<sys::LV2_Atom_Int>(),
+            )
+        };
+        match PortEvent::new(22, data, Some(&urids), &mut buffer) {
+            PortEvent::Unknown { protocol, .. } => assert_eq!(22, protocol),
+            _ => panic!("Malformed atom decoded"),
+        }
+    }
+}
@@ -0,0 +1,282 @@
+use crate::PluginUI;
+use core::extension::ExtensionDescriptor;
+use core::feature::Feature;
+use core::UriBound;
+use std::ffi::c_void;
+use std::marker::PhantomData;
+use std::os::raw::c_int;
+
+/// A user interface extension to receive regular calls from the host.
+///
+/// The host calls [`idle`](#tymethod.idle) regularly in the UI thread. User interfaces without a host-managed widget use it to drive their own event loop.
+pub trait IdleInterface: PluginUI {
+    /// Run a single iteration of the user interface's event loop.
+    ///
+    /// Returns `false` if the user interface has been closed, which means that the host should stop calling this method.
+    fn idle(&mut self) -> bool;
+}
+
+/// Raw wrapper of the [`IdleInterface`](trait.IdleInterface.html) extension.
+///
+/// This is a marker type that has the required external methods for the extension.
+pub struct IdleDescriptor<U: IdleInterface> {
+    ui: PhantomData<U>,
+}
+
+unsafe impl<U: IdleInterface> UriBound for IdleDescriptor<U> {
+    const URI: &'static [u8] = sys::LV2_UI__idleInterface;
+}
+
+impl<U: IdleInterface> IdleDescriptor<U> {
+    /// Call `idle`.
+    ///
+    /// # Safety
+    ///
+    /// This method is unsafe since it is an interface for hosts written in C and since it dereferences raw pointers.
+    pub unsafe extern "C" fn extern_idle(handle: sys::LV2UI_Handle) -> c_int {
+        match (handle as *mut U).as_mut().map(|ui| ui.idle()) {
+            Some(true) => 0,
+            _ => 1,
+        }
+    }
+}
+
+impl<U: IdleInterface> ExtensionDescriptor for IdleDescriptor<U> {
+    type ExtensionInterface = sys::LV2UI_Idle_Interface;
+
+    const INTERFACE: &'static sys::LV2UI_Idle_Interface = &sys::LV2UI_Idle_Interface {
+        idle: Some(Self::extern_idle),
+    };
+}
+
+/// A user interface extension to show and hide a window that is managed by the user interface.
+///
+/// This is used by user interfaces that create their own window instead of a widget that is embedded by the host. These user interfaces should also implement the [`IdleInterface`](trait.IdleInterface.html) to run their event loop.
+pub trait ShowInterface: PluginUI {
+    /// Show the window of the user interface.
+    ///
+    /// Returns `true` if the window was shown.
+    fn show(&mut self) -> bool;
+
+    /// Hide the window of the user interface.
+    ///
+    /// Returns `true` if the window was hidden.
+    fn hide(&mut self) -> bool;
+}
+
+/// Raw wrapper of the [`ShowInterface`](trait.ShowInterface.html) extension.
+///
+/// This is a marker type that has the required external methods for the extension.
+pub struct ShowDescriptor<U: ShowInterface> {
+    ui: PhantomData<U>,
+}
+
+unsafe impl<U: ShowInterface> UriBound for ShowDescriptor<U> {
+    const URI: &'static [u8] = sys::LV2_UI__showInterface;
+}
+
+impl<U: ShowInterface> ShowDescriptor<U> {
+    /// Call `show`.
+    ///
+    /// # Safety
+    ///
+    /// This method is unsafe since it is an interface for hosts written in C and since it dereferences raw pointers.
+    pub unsafe extern "C" fn extern_show(handle: sys::LV2UI_Handle) -> c_int {
+        match (handle as *mut U).as_mut().map(|ui| ui.show()) {
+            Some(true) => 0,
+            _ => 1,
+        }
+    }
+
+    /// Call `hide`.
+    ///
+    /// # Safety
+    ///
+    /// This method is unsafe since it is an interface for hosts written in C and since it dereferences raw pointers.
+    pub unsafe extern "C" fn extern_hide(handle: sys::LV2UI_Handle) -> c_int {
+        match (handle as *mut U).as_mut().map(|ui| ui.hide()) {
+            Some(true) => 0,
+            _ => 1,
+        }
+    }
+}
+
+impl<U: ShowInterface> ExtensionDescriptor for ShowDescriptor<U> {
+    type ExtensionInterface = sys::LV2UI_Show_Interface;
+
+    const INTERFACE: &'static sys::LV2UI_Show_Interface = &sys::LV2UI_Show_Interface {
+        show: Some(Self::extern_show),
+        hide: Some(Self::extern_hide),
+    };
+}
+
+/// A user interface extension to let the host resize the widget.
+pub trait ResizeInterface: PluginUI {
+    /// Resize the widget to the given size in pixels.
+    ///
+    /// Returns `true` if the widget was resized.
+    fn resize(&mut self, width: i32, height: i32) -> bool;
+}
+
+/// Raw wrapper of the [`ResizeInterface`](trait.ResizeInterface.html) extension.
+///
+/// This is a marker type that has the required external methods for the extension.
+pub struct ResizeDescriptor<U: ResizeInterface> {
+    ui: PhantomData<U>,
+}
+
+unsafe impl<U: ResizeInterface> UriBound for ResizeDescriptor<U> {
+    const URI: &'static [u8] = sys::LV2_UI__resize;
+}
+
+impl<U: ResizeInterface> ResizeDescriptor<U> {
+    /// Call `resize`.
+    ///
+    /// # Safety
+    ///
+    /// This method is unsafe since it is an interface for hosts written in C and since it dereferences raw pointers.
+    pub unsafe extern "C" fn extern_resize(
+        handle: sys::LV2UI_Feature_Handle,
+        width: c_int,
+        height: c_int,
+    ) -> c_int {
+        match (handle as *mut U)
+            .as_mut()
+            .map(|ui| ui.resize(width, height))
+        {
+            Some(true) => 0,
+            _ => 1,
+        }
+    }
+}
+
+impl<U: ResizeInterface> ExtensionDescriptor for ResizeDescriptor<U> {
+    type ExtensionInterface = sys::LV2UI_Resize;
+
+    // The handle is ignored: The host passes the handle of the user interface instead.
+    const INTERFACE: &'static sys::LV2UI_Resize = &sys::LV2UI_Resize {
+        handle: std::ptr::null_mut(),
+        ui_resize: Some(Self::extern_resize),
+    };
+}
+
+/// Host feature to request a new size for the widget of the user interface.
+pub struct Resize<'a> {
+    internal: &'a sys::LV2UI_Resize,
+}
+
+unsafe impl<'a> UriBound for Resize<'a> {
+    const URI: &'static [u8] = sys::LV2_UI__resize;
+}
+
+unsafe impl<'a> Feature for Resize<'a> {
+    unsafe fn from_feature_ptr(feature: *const c_void) -> Option<Self> {
+        (feature as *const sys::LV2UI_Resize)
+            .as_ref()
+            .map(|internal| Self { internal })
+    }
+}
+
+impl<'a> Resize<'a> {
+    #[cfg(feature = "host")]
+    pub fn new(internal: &'a sys::LV2UI_Resize) -> Self {
+        Self { internal }
+    }
+
+    /// Request the host to resize the widget to the given size in pixels.
+    ///
+    /// Returns `true` if the host accepted the request.
+    pub fn resize(&self, width: i32, height: i32) -> bool {
+        match self.internal.ui_resize {
+            Some(ui_resize) => unsafe { (ui_resize)(self.internal.handle, width, height) == 0 },
+            None => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use core::prelude::*;
+
+    struct Window {
+        visible: bool,
+        size: (i32, i32),
+    }
+
+    unsafe impl UriBound for Window {
+        const URI: &'static [u8] = b"urn:lv2_ui:window\0";
+    }
+
+    impl PluginUI for Window {
+        type Features = ();
+
+        #[cfg_attr(tarpaulin, skip)]
+        fn new(_: &UIInfo, _: UIController, _: ()) -> Option<Self> {
+            None
+        }
+
+        #[cfg_attr(tarpaulin, skip)]
+        fn port_event(&mut self, _: u32, _: PortEvent) {}
+    }
+
+    impl IdleInterface for Window {
+        fn idle(&mut self) -> bool {
+            self.visible
+        }
+    }
+
+    impl ShowInterface for Window {
+        fn show(&mut self) -> bool {
+            self.visible = true;
+            true
+        }
+
+        fn hide(&mut self) -> bool {
+            self.visible = false;
+            true
+        }
+    }
+
+    impl ResizeInterface for Window {
+        fn resize(&mut self, width: i32, height: i32) -> bool {
+            if width < 0 || height < 0 {
+                return false;
+            }
+            self.size = (width, height);
+            true
+        }
+    }
+
+    #[test]
+    fn test_extensions() {
+        let mut window = Window {
+            visible: false,
+            size: (0, 0),
+        };
+        let handle = &mut window as *mut Window as sys::LV2UI_Handle;
+
+        unsafe {
+            assert_eq!(1, IdleDescriptor::<Window>::extern_idle(handle));
+            assert_eq!(0, ShowDescriptor::<Window>::extern_show(handle));
+            assert_eq!(0, IdleDescriptor::<Window>::extern_idle(handle));
+            assert_eq!(0, ShowDescriptor::<Window>::extern_hide(handle));
+            assert_eq!(1, IdleDescriptor::<Window>::extern_idle(handle));
+
+            assert_eq!(
+                0,
+                ResizeDescriptor::<Window>::extern_resize(handle, 640, 480)
+            );
+            assert_eq!(
+                1,
+                ResizeDescriptor::<Window>::extern_resize(handle, -1, 480)
+            );
+
+            assert_eq!(
+                1,
+                IdleDescriptor::<Window>::extern_idle(std::ptr::null_mut())
+            );
+        }
+        assert_eq!((640, 480), window.size);
+    }
+}
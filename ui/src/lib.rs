@@ -0,0 +1,171 @@
+//! Custom user interfaces for LV2 plugins.
+//!
+//! The [LV2 UI specification](http://lv2plug.in/ns/extensions/ui/ui.html) lets plugins ship their own user interfaces. A user interface lives in a separate library and never talks to the plugin instance directly: The host notifies it about changes of the plugin's ports and the user interface sends new port values back to the host.
+//!
+//! This crate only handles this communication and does not depend on any GUI toolkit. A user interface implements the [`PluginUI`](trait.PluginUI.html) trait, creates its widget with the toolkit of its choice and returns a pointer to it from [`widget`](trait.PluginUI.html#method.widget). Port notifications are decoded and passed to [`port_event`](trait.PluginUI.html#tymethod.port_event) as [`PortEvent`s](enum.PortEvent.html) and new values are sent with the [`UIController`](struct.UIController.html). The user interfaces of a library are exported with the [`lv2_ui_descriptors`](macro.lv2_ui_descriptors.html) macro.
+//!
+//! # Example
+//!
+//! ```
+//! use lv2_atom::prelude::*;
+//! use lv2_core::prelude::*;
+//! use lv2_ui::prelude::*;
+//! use lv2_urid::prelude::*;
+//!
+//! #[derive(FeatureCollection)]
+//! pub struct Features<'a> {
+//!     map: Map<'a>,
+//! }
+//!
+//! /// A user interface without a widget that mirrors the gain control of a plugin.
+//! pub struct GainUI {
+//!     controller: UIController,
+//!     urids: AtomURIDCollection,
+//!     gain: f32,
+//! }
+//!
+//! unsafe impl UriBound for GainUI {
+//!     const URI: &'static [u8] = b"urn:lv2_ui:gain-ui\0";
+//! }
+//!
+//! impl PluginUI for GainUI {
+//!     type Features = Features<'static>;
+//!
+//!     fn new(_: &UIInfo, controller: UIController, features: Features<'static>) -> Option<Self> {
+//!         Some(Self {
+//!             controller,
+//!             urids: features.map.populate_collection()?,
+//!             gain: 0.0,
+//!         })
+//!     }
+//!
+//!     fn port_event(&mut self, port_index: u32, event: PortEvent) {
+//!         match event {
+//!             PortEvent::Control(value) if port_index == 0 => self.gain = value,
+//!             PortEvent::Atom(atom) => {
+//!                 // The plugin may also send a new gain as a `Float` atom.
+//!                 if let Ok(gain) = atom.read(self.urids.float, ()) {
+//!                     self.gain = gain;
+//!                     let _ = self.controller.write_control(0, self.gain);
+//!                 }
+//!             }
+//!             _ => (),
+//!         }
+//!     }
+//!
+//!     fn extension_data(uri: &Uri) -> Option<&'static dyn std::any::Any> {
+//!         match_extensions!(uri, IdleDescriptor<Self>)
+//!     }
+//! }
+//!
+//! impl IdleInterface for GainUI {
+//!     fn idle(&mut self) -> bool {
+//!         // Process the events of the toolkit here.
+//!         true
+//!     }
+//! }
+//!
+//! lv2_ui_descriptors!(GainUI);
+//! ```
+extern crate lv2_atom as atom;
+extern crate lv2_core as core;
+extern crate lv2_sys as sys;
+extern crate lv2_urid as urid;
+
+mod controller;
+pub use controller::*;
+
+mod event;
+pub use event::*;
+
+mod extensions;
+pub use extensions::*;
+
+mod plugin_ui;
+pub use plugin_ui::*;
+
+use core::UriBound;
+use urid::{URIDCollection, URID};
+
+/// Prelude for wildcard use, containing many important types.
+pub mod prelude {
+    pub use crate::{
+        lv2_ui_descriptors, AtomTransfer, EventTransfer, IdleDescriptor, IdleInterface, PeakData,
+        PluginUI, PortEvent, Resize, ResizeDescriptor, ResizeInterface, ShowDescriptor,
+        ShowInterface, UIController, UIError, UIInfo, UIURIDCollection,
+    };
+    pub use sys::LV2UI_Descriptor;
+}
+
+/// Port protocol for the values of control ports, transmitted as a single `f32`.
+///
+/// Control values use the reserved protocol number `0`, which is why there is no need to map this URI.
+pub struct FloatProtocol;
+
+unsafe impl UriBound for FloatProtocol {
+    const URI: &'static [u8] = sys::LV2_UI__floatProtocol;
+}
+
+/// Port protocol for peak measurements of audio ports, transmitted as [`PeakData`](struct.PeakData.html).
+pub struct PeakProtocol;
+
+unsafe impl UriBound for PeakProtocol {
+    const URI: &'static [u8] = sys::LV2_UI__peakProtocol;
+}
+
+/// Port protocol for single events of atom sequence ports, transmitted as a complete atom.
+pub struct EventTransfer;
+
+unsafe impl UriBound for EventTransfer {
+    const URI: &'static [u8] = sys::LV2_ATOM__eventTransfer;
+}
+
+/// Port protocol for the complete contents of atom ports, transmitted as a complete atom.
+pub struct AtomTransfer;
+
+unsafe impl UriBound for AtomTransfer {
+    const URI: &'static [u8] = sys::LV2_ATOM__atomTransfer;
+}
+
+/// Marker trait for port protocols that transmit atoms.
+pub trait AtomProtocol: UriBound {}
+
+impl AtomProtocol for EventTransfer {}
+impl AtomProtocol for AtomTransfer {}
+
+#[derive(Clone, URIDCollection)]
+/// Collection with the URIDs of all port protocols in this crate.
+pub struct UIURIDCollection {
+    pub float_protocol: URID<FloatProtocol>,
+    pub peak_protocol: URID<PeakProtocol>,
+    pub event_transfer: URID<EventTransfer>,
+    pub atom_transfer: URID<AtomTransfer>,
+}
+
+/// Kinds of errors that may occur in the crate.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UIError {
+    /// The host didn't provide a write function.
+    NoCallback,
+    /// The atom that should be written is malformed.
+    MalformedAtom,
+}
+
+/// Export the user interfaces of the library to the host.
+///
+/// This macro works like `lv2_descriptors`, but for user interfaces: It creates the `lv2ui_descriptor` function the host uses to discover the user interfaces of a library. Just list all exported user interface types, separated by commas.
+#[macro_export]
+macro_rules! lv2_ui_descriptors {
+    ($($ui:ty),* $(,)?) => {
+        #[no_mangle]
+        pub unsafe extern "C" fn lv2ui_descriptor(index: u32) -> *const $crate::prelude::LV2UI_Descriptor {
+            let descriptors: &[&$crate::prelude::LV2UI_Descriptor] = &[
+                $(&<$ui as $crate::PluginUIInstanceDescriptor>::DESCRIPTOR),*
+            ];
+            match descriptors.get(index as usize) {
+                Some(descriptor) => *descriptor,
+                None => ::std::ptr::null(),
+            }
+        }
+    };
+}
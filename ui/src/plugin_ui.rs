@@ -0,0 +1,310 @@
+use crate::{PortEvent, UIController, UIURIDCollection};
+use atom::space::AlignedBuffer;
+use core::feature::*;
+use core::{Uri, UriBound};
+use std::any::Any;
+use std::ffi::c_void;
+use std::os::raw::c_char;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::path::Path;
+use std::str::Utf8Error;
+use urid::Map;
+
+/// The central trait to describe plugin user interfaces.
+///
+/// A user interface is created by the host and receives notifications about the ports of the plugin via [`port_event`](#tymethod.port_event). It may send new port values to the plugin using the [`UIController`](struct.UIController.html) it receives when it is created.
+///
+/// Like plugins, user interfaces don't talk to the host directly. Instead, the host talks to the [`PluginUIInstance`](struct.PluginUIInstance.html), which guarantees that a valid `sys::LV2UI_Handle` is always a valid `*mut MyUI`, where `MyUI` is your user interface's name.
+pub trait PluginUI: UriBound + Sized + 'static {
+    /// The host features used by this user interface.
+    type Features: FeatureCollection<'static>;
+
+    /// Create a new user interface instance.
+    ///
+    /// The user interface should create its widget here, if it has one.
+    fn new(ui_info: &UIInfo, controller: UIController, features: Self::Features) -> Option<Self>;
+
+    /// Return the widget of the user interface.
+    ///
+    /// The type of the widget depends on the toolkit of the user interface, for example a `GtkWidget*` or the handle of a X11 window. This method is called once, right after the user interface was created and moved to its final location. The default implementation returns a null pointer, which is fine for user interfaces without widgets.
+    fn widget(&self) -> *mut c_void {
+        std::ptr::null_mut()
+    }
+
+    /// Handle a notification about a port of the plugin.
+    ///
+    /// The host calls this method when the value of a control port changes or when an atom port receives an event, if the user interface subscribed to it.
+    fn port_event(&mut self, port_index: u32, event: PortEvent);
+
+    /// Return additional, extension-specific data.
+    ///
+    /// This works like the method of the same name in the `Plugin` trait and the [`match_extensions`](../lv2_core/macro.match_extensions.html) macro may be used here too.
+    fn extension_data(_uri: &Uri) -> Option<&'static dyn Any> {
+        None
+    }
+}
+
+/// Errors that may occur when the user interface info is created from the raw information of the host.
+#[derive(Debug)]
+pub enum UIInfoError {
+    /// The bundle path isn't valid UTF-8.
+    InvalidBundlePathUtf8(Utf8Error),
+}
+
+/// Holds various data that is passed from the host when the user interface is created.
+pub struct UIInfo<'a> {
+    plugin_uri: &'a Uri,
+    bundle_path: &'a Path,
+}
+
+impl<'a> UIInfo<'a> {
+    /// Create a new user interface info instance from raw information.
+    ///
+    /// # Safety
+    ///
+    /// This method is unsafe since it dereferences raw pointers. It does not check the pointers for validity.
+    pub unsafe fn from_raw(
+        plugin_uri: *const c_char,
+        bundle_path: *const c_char,
+    ) -> Result<Self, UIInfoError> {
+        let bundle_path = Path::new(
+            Uri::from_ptr(bundle_path)
+                .to_str()
+                .map_err(UIInfoError::InvalidBundlePathUtf8)?,
+        );
+        Ok(Self::new(Uri::from_ptr(plugin_uri), bundle_path))
+    }
+
+    /// Create a new user interface info instance.
+    pub fn new(plugin_uri: &'a Uri, bundle_path: &'a Path) -> Self {
+        Self {
+            plugin_uri,
+            bundle_path,
+        }
+    }
+
+    /// The URI of the plugin this user interface controls.
+    pub fn plugin_uri(&self) -> &Uri {
+        self.plugin_uri
+    }
+
+    /// The path to the LV2 bundle directory which contains the user interface's binary.
+    pub fn bundle_path(&self) -> &Path {
+        self.bundle_path
+    }
+}
+
+/// User interface wrapper which translates between the host and the user interface.
+///
+/// This struct is `repr(C)` and has the user interface as it's first field. Therefore, a valid `*mut PluginUIInstance<T>` is also a valid `*mut T`.
+#[repr(C)]
+pub struct PluginUIInstance<T: PluginUI> {
+    instance: T,
+    urids: Option<UIURIDCollection>,
+    atom_buffer: AlignedBuffer,
+}
+
+impl<T: PluginUI> PluginUIInstance<T> {
+    /// Report a panic that has been caught at the C interface.
+    ///
+    /// Unwinding across the C interface would abort the host or cause undefined behaviour, which is why every method that is called by the host catches panics of the user interface and reports them on the standard error output.
+    fn report_panic(method: &'static str, payload: Box<dyn Any + Send>) {
+        let message = match payload.downcast_ref::<&'static str>() {
+            Some(message) => Some(*message),
+            None => payload.downcast_ref::<String>().map(String::as_str),
+        };
+        eprintln!(
+            "User interface <{}> panicked in `{}`: {}",
+            T::uri().to_string_lossy(),
+            method,
+            message.unwrap_or("unknown cause")
+        );
+    }
+
+    /// Instantiate the user interface.
+    ///
+    /// If the host provides the `Map` feature, the URIDs of the port protocols are mapped too, which is required to decode atoms in [`port_event`](#method.port_event).
+    ///
+    /// This method provides a required method for the C interface of a user interface and is used by the `lv2_ui_descriptors` macro.
+    ///
+    /// # Safety
+    ///
+    /// This method is unsafe since it derefences multiple raw pointers and is part of the C interface.
+    pub unsafe extern "C" fn instantiate(
+        _descriptor: *const sys::LV2UI_Descriptor,
+        plugin_uri: *const c_char,
+        bundle_path: *const c_char,
+        write_function: sys::LV2UI_Write_Function,
+        controller: sys::LV2UI_Controller,
+        widget: *mut sys::LV2UI_Widget,
+        features: *const *const sys::LV2_Feature,
+    ) -> sys::LV2UI_Handle {
+        match catch_unwind(|| {
+            Self::instantiate_unchecked(
+                plugin_uri,
+                bundle_path,
+                write_function,
+                controller,
+                widget,
+                features,
+            )
+        }) {
+            Ok(handle) => handle,
+            Err(payload) => {
+                Self::report_panic("instantiate", payload);
+                std::ptr::null_mut()
+            }
+        }
+    }
+
+    /// Instantiate the user interface without catching panics.
+    unsafe fn instantiate_unchecked(
+        plugin_uri: *const c_char,
+        bundle_path: *const c_char,
+        write_function: sys::LV2UI_Write_Function,
+        controller: sys::LV2UI_Controller,
+        widget: *mut sys::LV2UI_Widget,
+        features: *const *const sys::LV2_Feature,
+    ) -> sys::LV2UI_Handle {
+        if plugin_uri.is_null() || bundle_path.is_null() {
+            eprintln!("Failed to initialize user interface: Null pointer from host");
+            return std::ptr::null_mut();
+        }
+
+        let ui_info = match UIInfo::from_raw(plugin_uri, bundle_path) {
+            Ok(info) => info,
+            Err(e) => {
+                eprintln!(
+                    "Failed to initialize user interface: Illegal info from host: {:?}",
+                    e
+                );
+                return std::ptr::null_mut();
+            }
+        };
+
+        // Collect the supported features.
+        let mut features = FeatureCache::from_raw(features);
+
+        // Map the port protocols with a copy of the cache, so that the user interface can still retrieve the map.
+        let urids = FeatureCache::from_cache(&mut features)
+            .ok()
+            .and_then(|mut cache| cache.retrieve_feature::<Map, Map>().ok())
+            .and_then(|map| map.populate_collection());
+
+        let features = match <T::Features as FeatureCollection>::from_cache(&mut features) {
+            Ok(f) => f,
+            Err(e) => {
+                eprintln!("{}", e);
+                return std::ptr::null_mut();
+            }
+        };
+
+        let controller = UIController::new(write_function, controller);
+
+        // Instantiate the user interface.
+        match T::new(&ui_info, controller, features) {
+            Some(instance) => {
+                let instance = Box::leak(Box::new(Self {
+                    instance,
+                    urids,
+                    atom_buffer: AlignedBuffer::new(0),
+                }));
+                if let Some(widget) = widget.as_mut() {
+                    *widget = instance.instance.widget();
+                }
+                instance as *mut Self as sys::LV2UI_Handle
+            }
+            None => std::ptr::null_mut(),
+        }
+    }
+
+    /// Clean the user interface.
+    ///
+    /// This method provides a required method for the C interface of a user interface and is used by the `lv2_ui_descriptors` macro.
+    ///
+    /// # Safety
+    ///
+    /// This method is unsafe since it derefences multiple raw pointers and is part of the C interface.
+    pub unsafe extern "C" fn cleanup(instance: sys::LV2UI_Handle) {
+        if !instance.is_null() {
+            let instance = Box::from_raw(instance as *mut Self);
+            if let Err(payload) = catch_unwind(AssertUnwindSafe(|| drop(instance))) {
+                Self::report_panic("cleanup", payload);
+            }
+        }
+    }
+
+    /// Decode a port notification and call `port_event`.
+    ///
+    /// Control values and atoms are decoded as described by [`PortEvent::new`](enum.PortEvent.html#method.new).
+    ///
+    /// This method provides a required method for the C interface of a user interface and is used by the `lv2_ui_descriptors` macro.
+    ///
+    /// # Safety
+    ///
+    /// This method is unsafe since it derefences multiple raw pointers and is part of the C interface.
+    pub unsafe extern "C" fn port_event(
+        instance: sys::LV2UI_Handle,
+        port_index: u32,
+        buffer_size: u32,
+        format: u32,
+        buffer: *const c_void,
+    ) {
+        let instance = if let Some(instance) = (instance as *mut Self).as_mut() {
+            instance
+        } else {
+            return;
+        };
+        let data: &[u8] = if buffer.is_null() {
+            &[]
+        } else {
+            std::slice::from_raw_parts(buffer as *const u8, buffer_size as usize)
+        };
+        let result = catch_unwind(AssertUnwindSafe(|| {
+            let event = PortEvent::new(
+                format,
+                data,
+                instance.urids.as_ref(),
+                &mut instance.atom_buffer,
+            );
+            instance.instance.port_event(port_index, event);
+        }));
+        if let Err(payload) = result {
+            Self::report_panic("port_event", payload);
+        }
+    }
+
+    /// Dereference the URI, call the `extension_data` function and return the pointer.
+    ///
+    /// This method provides a required method for the C interface of a user interface and is used by the `lv2_ui_descriptors` macro.
+    ///
+    /// # Safety
+    ///
+    /// This method is unsafe since it derefences multiple raw pointers and is part of the C interface.
+    pub unsafe extern "C" fn extension_data(uri: *const c_char) -> *const c_void {
+        let uri = Uri::from_ptr(uri);
+        match catch_unwind(|| T::extension_data(uri)) {
+            Ok(Some(data)) => data as *const _ as *const c_void,
+            Ok(None) => std::ptr::null(),
+            Err(payload) => {
+                Self::report_panic("extension_data", payload);
+                std::ptr::null()
+            }
+        }
+    }
+}
+
+#[doc(hidden)]
+pub unsafe trait PluginUIInstanceDescriptor: PluginUI {
+    const DESCRIPTOR: sys::LV2UI_Descriptor;
+}
+
+unsafe impl<T: PluginUI> PluginUIInstanceDescriptor for T {
+    const DESCRIPTOR: sys::LV2UI_Descriptor = sys::LV2UI_Descriptor {
+        URI: T::URI.as_ptr() as *const c_char,
+        instantiate: Some(PluginUIInstance::<T>::instantiate),
+        cleanup: Some(PluginUIInstance::<T>::cleanup),
+        port_event: Some(PluginUIInstance::<T>::port_event),
+        extension_data: Some(PluginUIInstance::<T>::extension_data),
+    };
+}
@@ -0,0 +1,199 @@
+#![cfg(feature = "host")]
+use lv2_atom::prelude::*;
+use lv2_atom::space::AlignedBuffer;
+use lv2_core::feature::{FeatureCache, FeatureCollection, MissingFeatureError};
+use lv2_core::prelude::*;
+use lv2_sys::*;
+use lv2_ui::prelude::*;
+use lv2_urid::mapper::*;
+use lv2_urid::prelude::*;
+use std::ffi::c_void;
+use std::os::raw::c_char;
+
+/// A user interface that echoes control values and atoms back to the plugin.
+struct Echo {
+    controller: UIController,
+    atom_urids: AtomURIDCollection,
+    ui_urids: UIURIDCollection,
+    widget: u32,
+}
+
+#[derive(FeatureCollection)]
+struct Features<'a> {
+    map: Map<'a>,
+}
+
+unsafe impl UriBound for Echo {
+    const URI: &'static [u8] = b"urn:lv2_ui:echo\0";
+}
+
+impl PluginUI for Echo {
+    type Features = Features<'static>;
+
+    fn new(
+        ui_info: &UIInfo,
+        controller: UIController,
+        features: Features<'static>,
+    ) -> Option<Self> {
+        assert_eq!(
+            ui_info.plugin_uri().to_str().unwrap(),
+            "urn:lv2_ui:echo-plugin"
+        );
+        assert_eq!(
+            ui_info.bundle_path().to_str().unwrap(),
+            "/home/lv2/echo.lv2/"
+        );
+        Some(Self {
+            controller,
+            atom_urids: features.map.populate_collection()?,
+            ui_urids: features.map.populate_collection()?,
+            widget: 42,
+        })
+    }
+
+    fn widget(&self) -> *mut c_void {
+        &self.widget as *const u32 as *mut c_void
+    }
+
+    fn port_event(&mut self, port_index: u32, event: PortEvent) {
+        match event {
+            PortEvent::Control(value) => {
+                self.controller.write_control(port_index, value).unwrap();
+            }
+            PortEvent::Atom(atom) => {
+                let value = atom.read(self.atom_urids.int, ()).unwrap();
+                let mut buffer = AlignedBuffer::new(64);
+                (&mut buffer.mut_space() as &mut dyn MutSpace)
+                    .init(self.atom_urids.int, value + 1)
+                    .unwrap();
+                let atom = UnidentifiedAtom::new(buffer.space());
+                self.controller
+                    .write_atom(port_index, self.ui_urids.event_transfer, atom)
+                    .unwrap();
+            }
+            _ => panic!("Unexpected event"),
+        }
+    }
+
+    fn extension_data(uri: &Uri) -> Option<&'static dyn std::any::Any> {
+        match_extensions!(uri, IdleDescriptor<Self>)
+    }
+}
+
+impl IdleInterface for Echo {
+    fn idle(&mut self) -> bool {
+        true
+    }
+}
+
+lv2_ui_descriptors!(Echo);
+
+/// The fake controller of the host, which records everything that was written.
+#[derive(Default)]
+struct Controller {
+    written: Vec<(u32, u32, Vec<u8>)>,
+}
+
+unsafe extern "C" fn write(
+    controller: LV2UI_Controller,
+    port_index: u32,
+    buffer_size: u32,
+    port_protocol: u32,
+    buffer: *const c_void,
+) {
+    let controller = &mut *(controller as *mut Controller);
+    let data = std::slice::from_raw_parts(buffer as *const u8, buffer_size as usize);
+    controller
+        .written
+        .push((port_index, port_protocol, data.to_vec()));
+}
+
+#[test]
+fn test_headless_ui() {
+    let mut mapper = Box::pin(HashURIDMapper::new());
+    let map_interface = mapper.as_mut().make_map_interface();
+    let map = Map::new(&map_interface);
+    let atom_urids: AtomURIDCollection = map.populate_collection().unwrap();
+    let ui_urids: UIURIDCollection = map.populate_collection().unwrap();
+
+    let map_feature = LV2_Feature {
+        URI: Map::URI.as_ptr() as *const c_char,
+        data: &map_interface as *const LV2_URID_Map as *mut c_void,
+    };
+    let features: &[*const LV2_Feature] = &[&map_feature, std::ptr::null()];
+
+    let mut controller = Controller::default();
+
+    unsafe {
+        assert!(lv2ui_descriptor(1).is_null());
+        let descriptor: &LV2UI_Descriptor = lv2ui_descriptor(0).as_ref().unwrap();
+
+        // Constructing the user interface.
+        let mut widget: LV2UI_Widget = std::ptr::null_mut();
+        let ui: LV2UI_Handle = (descriptor.instantiate.unwrap())(
+            descriptor,
+            "urn:lv2_ui:echo-plugin\0".as_ptr() as *const c_char,
+            "/home/lv2/echo.lv2/\0".as_ptr() as *const c_char,
+            Some(write),
+            &mut controller as *mut Controller as LV2UI_Controller,
+            &mut widget,
+            features.as_ptr(),
+        );
+        assert!(!ui.is_null());
+        assert_eq!(42, *(widget as *const u32));
+
+        // Sending a control value.
+        let value: f32 = 0.5;
+        (descriptor.port_event.unwrap())(
+            ui,
+            0,
+            std::mem::size_of::<f32>() as u32,
+            0,
+            &value as *const f32 as *const c_void,
+        );
+
+        // Sending an atom.
+        let atom = LV2_Atom_Int {
+            atom: LV2_Atom {
+                size: std::mem::size_of::<i32>() as u32,
+                type_: atom_urids.int.get(),
+            },
+            body: 17,
+        };
+        (descriptor.port_event.unwrap())(
+            ui,
+            1,
+            std::mem::size_of::<LV2_Atom_Int>() as u32,
+            ui_urids.event_transfer.get(),
+            &atom as *const LV2_Atom_Int as *const c_void,
+        );
+
+        // Calling the idle interface.
+        let idle_interface =
+            (descriptor.extension_data.unwrap())(LV2_UI__idleInterface.as_ptr() as *const c_char)
+                as *const LV2UI_Idle_Interface;
+        assert_eq!(0, ((*idle_interface).idle.unwrap())(ui));
+        assert!((descriptor.extension_data.unwrap())(
+            LV2_UI__showInterface.as_ptr() as *const c_char
+        )
+        .is_null());
+
+        // Destroying the user interface.
+        (descriptor.cleanup.unwrap())(ui);
+    }
+
+    // Verifying the written data.
+    assert_eq!(2, controller.written.len());
+
+    let (port_index, protocol, data) = &controller.written[0];
+    assert_eq!(0, *port_index);
+    assert_eq!(0, *protocol);
+    assert_eq!(0.5f32.to_ne_bytes().to_vec(), *data);
+
+    let (port_index, protocol, data) = &controller.written[1];
+    assert_eq!(1, *port_index);
+    assert_eq!(ui_urids.event_transfer.get(), *protocol);
+    let buffer = AlignedBuffer::from_bytes(data);
+    let atom = UnidentifiedAtom::new(buffer.space());
+    assert_eq!(18, atom.read(atom_urids.int, ()).unwrap());
+}
@@ -0,0 +1,80 @@
+#![cfg(feature = "host")]
+use lv2_core::prelude::*;
+use lv2_sys::*;
+use lv2_ui::prelude::*;
+use std::ffi::c_void;
+use std::os::raw::c_char;
+
+/// A user interface that panics in every method.
+struct Panicking;
+
+unsafe impl UriBound for Panicking {
+    const URI: &'static [u8] = b"urn:lv2_ui:panicking\0";
+}
+
+impl PluginUI for Panicking {
+    type Features = ();
+
+    fn new(ui_info: &UIInfo, _: UIController, _: ()) -> Option<Self> {
+        if ui_info.plugin_uri().to_bytes().ends_with(b"panic") {
+            panic!("Invalid plugin");
+        }
+        Some(Self)
+    }
+
+    fn port_event(&mut self, _: u32, _: PortEvent) {
+        panic!("Unexpected event");
+    }
+
+    fn extension_data(_: &Uri) -> Option<&'static dyn std::any::Any> {
+        panic!("Unexpected extension");
+    }
+}
+
+impl Drop for Panicking {
+    fn drop(&mut self) {
+        panic!("Unexpected cleanup");
+    }
+}
+
+lv2_ui_descriptors!(Panicking);
+
+#[test]
+fn test_caught_panics() {
+    unsafe {
+        let descriptor: &LV2UI_Descriptor = lv2ui_descriptor(0).as_ref().unwrap();
+        let features: &[*const LV2_Feature] = &[std::ptr::null()];
+        let instantiate = |plugin_uri: &str| {
+            (descriptor.instantiate.unwrap())(
+                descriptor,
+                plugin_uri.as_ptr() as *const c_char,
+                "/home/lv2/panicking.lv2/\0".as_ptr() as *const c_char,
+                None,
+                std::ptr::null_mut(),
+                std::ptr::null_mut(),
+                features.as_ptr(),
+            )
+        };
+
+        assert!(instantiate("urn:lv2_ui:panic\0").is_null());
+
+        let ui: LV2UI_Handle = instantiate("urn:lv2_ui:plugin\0");
+        assert!(!ui.is_null());
+
+        let value: f32 = 0.5;
+        (descriptor.port_event.unwrap())(
+            ui,
+            0,
+            std::mem::size_of::<f32>() as u32,
+            0,
+            &value as *const f32 as *const c_void,
+        );
+
+        assert!((descriptor.extension_data.unwrap())(
+            LV2_UI__idleInterface.as_ptr() as *const c_char
+        )
+        .is_null());
+
+        (descriptor.cleanup.unwrap())(ui);
+    }
+}
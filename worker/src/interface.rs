@@ -0,0 +1,413 @@
+use crate::{receive, transfer, RespondError, WorkerError};
+use core::extension::ExtensionDescriptor;
+use core::plugin::PluginInstance;
+use core::prelude::*;
+use std::ffi::c_void;
+use std::marker::PhantomData;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+/// A plugin extension that lets a plugin execute non-realtime work.
+///
+/// Work is scheduled with the [`Schedule`](struct.Schedule.html) feature in the audio thread. Later, the host calls [`work`](#tymethod.work) with the scheduled data in a separate, non-realtime thread. The work may respond with the [`ResponseHandler`](struct.ResponseHandler.html), and the response is delivered to [`work_response`](#method.work_response) in the audio thread again, right after the next `run` call. When all responses of a cycle were delivered, [`end_run`](#method.end_run) is called.
+///
+/// Since `work` may be called concurrently with `run`, it doesn't have access to the plugin instance. Everything the work needs has to be sent within the work data.
+pub trait Worker: Plugin {
+    /// The type of the data sent from the audio thread to the worker.
+    type WorkData: 'static + Send;
+
+    /// The type of the data sent from the worker back to the audio thread.
+    type ResponseData: 'static + Send;
+
+    /// Execute scheduled work.
+    ///
+    /// This method is called by the host in a non-realtime thread. Therefore, you may allocate memory or block in it.
+    fn work(
+        response_handler: &ResponseHandler<Self>,
+        data: Self::WorkData,
+    ) -> Result<(), WorkerError>;
+
+    /// Handle a response from the worker.
+    ///
+    /// This method is called by the host in the audio thread, after `run` has been called.
+    fn work_response(&mut self, _data: Self::ResponseData) -> Result<(), WorkerError> {
+        Ok(())
+    }
+
+    /// Signal the end of a run cycle.
+    ///
+    /// This method is called by the host in the audio thread after all responses of the current cycle have been delivered.
+    fn end_run(&mut self) -> Result<(), WorkerError> {
+        Ok(())
+    }
+}
+
+/// Handle to send responses from the worker to the audio thread.
+pub struct ResponseHandler<P: Worker> {
+    respond_fn: sys::LV2_Worker_Respond_Function,
+    handle: sys::LV2_Worker_Respond_Handle,
+    plugin: PhantomData<fn() -> P>,
+}
+
+impl<P: Worker> ResponseHandler<P> {
+    /// Create a new response handler that uses the given callback function and handle.
+    pub fn new(
+        respond_fn: sys::LV2_Worker_Respond_Function,
+        handle: sys::LV2_Worker_Respond_Handle,
+    ) -> Self {
+        Self {
+            respond_fn,
+            handle,
+            plugin: PhantomData,
+        }
+    }
+
+    /// Send a response to the audio thread.
+    ///
+    /// The data is moved to the host and will be passed to [`work_response`](trait.Worker.html#method.work_response). If the host could not accept the response, the data is returned inside the error.
+    pub fn respond(&self, data: P::ResponseData) -> Result<(), RespondError<P::ResponseData>> {
+        let respond_fn = match self.respond_fn {
+            Some(respond_fn) => respond_fn,
+            None => return Err(RespondError::NoCallback(data)),
+        };
+        let handle = self.handle;
+        transfer(data, |size, data| unsafe {
+            (respond_fn)(handle, size, data)
+        })
+        .map_err(|(error, data)| match error {
+            WorkerError::NoSpace => RespondError::NoSpace(data),
+            WorkerError::Unknown => RespondError::Unknown(data),
+        })
+    }
+}
+
+/// Raw wrapper of the [`Worker`](trait.Worker.html) extension.
+///
+/// This is a marker type that has the required external methods for the extension.
+pub struct WorkerDescriptor<P: Worker> {
+    plugin: PhantomData<P>,
+}
+
+unsafe impl<P: Worker> UriBound for WorkerDescriptor<P> {
+    const URI: &'static [u8] = sys::LV2_WORKER__interface;
+}
+
+impl<P: Worker> WorkerDescriptor<P> {
+    /// Handle a work request by the host.
+    ///
+    /// This involves retrieving the work data and constructing the response handler. Panics of the plugin are caught and handled according to the plugin's panic policy, and no work is done for a faulted instance.
+    ///
+    /// # Safety
+    ///
+    /// This method is unsafe since it is an interface for hosts written in C and since it dereferences raw pointers.
+    pub unsafe extern "C" fn extern_work(
+        instance: sys::LV2_Handle,
+        respond: sys::LV2_Worker_Respond_Function,
+        handle: sys::LV2_Worker_Respond_Handle,
+        size: u32,
+        data: *const c_void,
+    ) -> sys::LV2_Worker_Status {
+        if PluginInstance::<P>::is_faulted(instance) {
+            return sys::LV2_Worker_Status_LV2_WORKER_ERR_UNKNOWN;
+        }
+
+        let data: P::WorkData = if let Some(data) = receive(size, data) {
+            data
+        } else {
+            return sys::LV2_Worker_Status_LV2_WORKER_ERR_UNKNOWN;
+        };
+
+        let response_handler = ResponseHandler::new(respond, handle);
+        match catch_unwind(AssertUnwindSafe(|| P::work(&response_handler, data))) {
+            Ok(result) => WorkerError::into(result),
+            Err(payload) => {
+                PluginInstance::<P>::handle_panic(instance, "work", payload);
+                sys::LV2_Worker_Status_LV2_WORKER_ERR_UNKNOWN
+            }
+        }
+    }
+
+    /// Handle a work response by the host.
+    ///
+    /// This involves creating the plugin reference and retrieving the response data. Panics of the plugin are caught and handled according to the plugin's panic policy, and responses aren't delivered to a faulted instance.
+    ///
+    /// # Safety
+    ///
+    /// This method is unsafe since it is an interface for hosts written in C and since it dereferences raw pointers.
+    pub unsafe extern "C" fn extern_work_response(
+        instance: sys::LV2_Handle,
+        size: u32,
+        body: *const c_void,
+    ) -> sys::LV2_Worker_Status {
+        if PluginInstance::<P>::is_faulted(instance) {
+            return sys::LV2_Worker_Status_LV2_WORKER_ERR_UNKNOWN;
+        }
+        let plugin = &mut *(instance as *mut P);
+
+        let data: P::ResponseData = if let Some(data) = receive(size, body) {
+            data
+        } else {
+            return sys::LV2_Worker_Status_LV2_WORKER_ERR_UNKNOWN;
+        };
+
+        match catch_unwind(AssertUnwindSafe(|| plugin.work_response(data))) {
+            Ok(result) => WorkerError::into(result),
+            Err(payload) => {
+                PluginInstance::<P>::handle_panic(instance, "work_response", payload);
+                sys::LV2_Worker_Status_LV2_WORKER_ERR_UNKNOWN
+            }
+        }
+    }
+
+    /// Handle the end of a run cycle.
+    ///
+    /// Panics of the plugin are caught and handled according to the plugin's panic policy, and `end_run` isn't called for a faulted instance.
+    ///
+    /// # Safety
+    ///
+    /// This method is unsafe since it is an interface for hosts written in C and since it dereferences raw pointers.
+    pub unsafe extern "C" fn extern_end_run(instance: sys::LV2_Handle) -> sys::LV2_Worker_Status {
+        if PluginInstance::<P>::is_faulted(instance) {
+            return sys::LV2_Worker_Status_LV2_WORKER_ERR_UNKNOWN;
+        }
+        let plugin = &mut *(instance as *mut P);
+
+        match catch_unwind(AssertUnwindSafe(|| plugin.end_run())) {
+            Ok(result) => WorkerError::into(result),
+            Err(payload) => {
+                PluginInstance::<P>::handle_panic(instance, "end_run", payload);
+                sys::LV2_Worker_Status_LV2_WORKER_ERR_UNKNOWN
+            }
+        }
+    }
+}
+
+impl<P: Worker> ExtensionDescriptor for WorkerDescriptor<P> {
+    type ExtensionInterface = sys::LV2_Worker_Interface;
+
+    const INTERFACE: &'static sys::LV2_Worker_Interface = &sys::LV2_Worker_Interface {
+        work: Some(Self::extern_work),
+        work_response: Some(Self::extern_work_response),
+        end_run: Some(Self::extern_end_run),
+    };
+}
+
+#[cfg(test)]
+#[cfg(feature = "host")]
+mod tests {
+    use crate::*;
+    use lv2_core::plugin::PanicReport;
+    use lv2_core::prelude::*;
+    use lv2_core::test::Harness;
+    use std::ffi::c_void;
+
+    struct Squarer {
+        squares: Vec<u64>,
+        runs: u32,
+    }
+
+    unsafe impl UriBound for Squarer {
+        const URI: &'static [u8] = b"urn:lv2_worker:squarer\0";
+    }
+
+    impl Plugin for Squarer {
+        type Features = ();
+        type Ports = ();
+
+        #[cfg_attr(tarpaulin, skip)]
+        fn new(_: &PluginInfo, _: ()) -> Option<Self> {
+            Some(Self {
+                squares: Vec::new(),
+                runs: 0,
+            })
+        }
+
+        #[cfg_attr(tarpaulin, skip)]
+        fn run(&mut self, _: &mut ()) {}
+
+        fn report_panic(_: &PanicReport) {}
+    }
+
+    impl Worker for Squarer {
+        type WorkData = u32;
+        type ResponseData = u64;
+
+        fn work(response_handler: &ResponseHandler<Self>, data: u32) -> Result<(), WorkerError> {
+            if data == 0 {
+                panic!("Nothing to square");
+            }
+            let data = u64::from(data);
+            response_handler
+                .respond(data * data)
+                .map_err(|_| WorkerError::NoSpace)
+        }
+
+        fn work_response(&mut self, data: u64) -> Result<(), WorkerError> {
+            self.squares.push(data);
+            Ok(())
+        }
+
+        fn end_run(&mut self) -> Result<(), WorkerError> {
+            self.runs += 1;
+            Ok(())
+        }
+    }
+
+    /// Fake host queue that stores the transferred bytes.
+    #[derive(Default)]
+    struct Queue {
+        items: Vec<Vec<u8>>,
+        capacity: usize,
+    }
+
+    unsafe extern "C" fn extern_push(
+        handle: *mut c_void,
+        size: u32,
+        data: *const c_void,
+    ) -> sys::LV2_Worker_Status {
+        let queue = (handle as *mut Queue).as_mut().unwrap();
+        if queue.items.len() >= queue.capacity {
+            return sys::LV2_Worker_Status_LV2_WORKER_ERR_NO_SPACE;
+        }
+        let data = std::slice::from_raw_parts(data as *const u8, size as usize);
+        queue.items.push(data.to_owned());
+        sys::LV2_Worker_Status_LV2_WORKER_SUCCESS
+    }
+
+    /// Instantiate the squarer with a harness.
+    fn instantiate() -> Harness<Squarer> {
+        let mut harness = Harness::new(44100.0, 0);
+        assert!(harness.instantiate());
+        harness
+    }
+
+    #[test]
+    fn test_work_cycle() {
+        type Descriptor = WorkerDescriptor<Squarer>;
+        let harness = instantiate();
+        let plugin_handle = harness.handle();
+
+        // Scheduling the work in the audio thread.
+        let mut work_queue = Queue {
+            items: Vec::new(),
+            capacity: 2,
+        };
+        let raw_schedule = sys::LV2_Worker_Schedule {
+            handle: &mut work_queue as *mut Queue as *mut c_void,
+            schedule_work: Some(extern_push),
+        };
+        let schedule = unsafe {
+            <Schedule<Squarer> as lv2_core::feature::Feature>::from_feature_ptr(
+                &raw_schedule as *const _ as *const c_void,
+            )
+        }
+        .unwrap();
+        schedule.schedule_work(3).unwrap();
+        schedule.schedule_work(4).unwrap();
+        assert_eq!(Err(ScheduleError::NoSpace(5)), schedule.schedule_work(5));
+
+        // Executing the work in the worker thread.
+        let mut response_queue = Queue {
+            items: Vec::new(),
+            capacity: 2,
+        };
+        for work in work_queue.items.iter() {
+            assert_eq!(sys::LV2_Worker_Status_LV2_WORKER_SUCCESS, unsafe {
+                Descriptor::extern_work(
+                    plugin_handle,
+                    Some(extern_push),
+                    &mut response_queue as *mut Queue as *mut c_void,
+                    work.len() as u32,
+                    work.as_ptr() as *const c_void,
+                )
+            });
+        }
+
+        // Delivering the responses in the audio thread.
+        for response in response_queue.items.iter() {
+            assert_eq!(sys::LV2_Worker_Status_LV2_WORKER_SUCCESS, unsafe {
+                Descriptor::extern_work_response(
+                    plugin_handle,
+                    response.len() as u32,
+                    response.as_ptr() as *const c_void,
+                )
+            });
+        }
+        assert_eq!(sys::LV2_Worker_Status_LV2_WORKER_SUCCESS, unsafe {
+            Descriptor::extern_end_run(plugin_handle)
+        });
+
+        let plugin = harness.plugin().unwrap();
+        assert_eq!(vec![9, 16], plugin.squares);
+        assert_eq!(1, plugin.runs);
+    }
+
+    #[test]
+    fn test_illegal_paths() {
+        type Descriptor = WorkerDescriptor<Squarer>;
+        let harness = instantiate();
+        let data: u8 = 0;
+
+        // Data with the wrong size.
+        assert_eq!(sys::LV2_Worker_Status_LV2_WORKER_ERR_UNKNOWN, unsafe {
+            Descriptor::extern_work(
+                harness.handle(),
+                None,
+                std::ptr::null_mut(),
+                1,
+                &data as *const u8 as *const c_void,
+            )
+        });
+        assert_eq!(sys::LV2_Worker_Status_LV2_WORKER_ERR_UNKNOWN, unsafe {
+            Descriptor::extern_work_response(
+                harness.handle(),
+                1,
+                &data as *const u8 as *const c_void,
+            )
+        });
+
+        // Null instances.
+        assert_eq!(sys::LV2_Worker_Status_LV2_WORKER_ERR_UNKNOWN, unsafe {
+            Descriptor::extern_work_response(std::ptr::null_mut(), 0, std::ptr::null())
+        });
+        assert_eq!(sys::LV2_Worker_Status_LV2_WORKER_ERR_UNKNOWN, unsafe {
+            Descriptor::extern_end_run(std::ptr::null_mut())
+        });
+
+        // Missing respond function.
+        let work: u32 = 2;
+        assert_eq!(sys::LV2_Worker_Status_LV2_WORKER_ERR_NO_SPACE, unsafe {
+            Descriptor::extern_work(
+                harness.handle(),
+                None,
+                std::ptr::null_mut(),
+                std::mem::size_of::<u32>() as u32,
+                &work as *const u32 as *const c_void,
+            )
+        });
+    }
+
+    #[test]
+    fn test_panicking_work() {
+        type Descriptor = WorkerDescriptor<Squarer>;
+        let harness = instantiate();
+        let work = |data: u32| unsafe {
+            Descriptor::extern_work(
+                harness.handle(),
+                None,
+                std::ptr::null_mut(),
+                std::mem::size_of::<u32>() as u32,
+                &data as *const u32 as *const c_void,
+            )
+        };
+
+        assert_eq!(sys::LV2_Worker_Status_LV2_WORKER_ERR_UNKNOWN, work(0));
+        assert!(harness.is_faulted());
+
+        // A faulted instance doesn't work anymore.
+        assert_eq!(sys::LV2_Worker_Status_LV2_WORKER_ERR_UNKNOWN, work(2));
+        assert_eq!(sys::LV2_Worker_Status_LV2_WORKER_ERR_UNKNOWN, unsafe {
+            Descriptor::extern_end_run(harness.handle())
+        });
+        assert_eq!(0, harness.plugin().unwrap().runs);
+    }
+}
@@ -0,0 +1,228 @@
+//! Extension for LV2 plugins to schedule non-realtime work.
+//!
+//! Some tasks, like loading a sample from disk or allocating big buffers, can not be done in the realtime context of the [`run`](../lv2_core/plugin/trait.Plugin.html#tymethod.run) method. The worker extension provides a way to offload these tasks to a separate, non-realtime thread provided by the host: The plugin schedules work with the [`Schedule`](struct.Schedule.html) feature, the host calls the plugin's [`work`](trait.Worker.html#tymethod.work) method in the worker thread, and the work may send a response back to the plugin, which is delivered to [`work_response`](trait.Worker.html#method.work_response) in the audio thread.
+//!
+//! The data that is passed between these threads is copied by the host bytewise. Therefore, the [`WorkData`](trait.Worker.html#associatedtype.WorkData) and [`ResponseData`](trait.Worker.html#associatedtype.ResponseData) types are moved to the other thread without being dropped on the sending side. This also means that they should be small: The host only has a limited amount of space for them.
+//!
+//! ## Example usage
+//!
+//! ```
+//! use lv2_core::prelude::*;
+//! use lv2_worker::*;
+//!
+//! /// A plugin that asks the worker to square numbers.
+//! struct Squarer {
+//!     schedule: Schedule<'static, Squarer>,
+//!     counter: u32,
+//!     last_square: u32,
+//! }
+//!
+//! #[derive(FeatureCollection)]
+//! pub struct Features<'a> {
+//!     schedule: Schedule<'a, Squarer>,
+//! }
+//!
+//! unsafe impl UriBound for Squarer {
+//!     const URI: &'static [u8] = b"urn:lv2_worker:squarer\0";
+//! }
+//!
+//! impl Plugin for Squarer {
+//!     type Ports = ();
+//!     type Features = Features<'static>;
+//!
+//!     fn new(_: &PluginInfo, features: Features<'static>) -> Option<Self> {
+//!         Some(Self {
+//!             schedule: features.schedule,
+//!             counter: 0,
+//!             last_square: 0,
+//!         })
+//!     }
+//!
+//!     fn run(&mut self, _: &mut ()) {
+//!         self.counter += 1;
+//!         // Schedule the work. If the host has no space left, we simply try again later.
+//!         let _ = self.schedule.schedule_work(self.counter);
+//!     }
+//!
+//!     fn extension_data(uri: &Uri) -> Option<&'static dyn std::any::Any> {
+//!         // Export the worker extension. Otherwise, the host won't use it.
+//!         match_extensions!(uri, WorkerDescriptor<Self>)
+//!     }
+//! }
+//!
+//! impl Worker for Squarer {
+//!     type WorkData = u32;
+//!     type ResponseData = u32;
+//!
+//!     fn work(response_handler: &ResponseHandler<Self>, data: u32) -> Result<(), WorkerError> {
+//!         // This is executed in the worker thread, where we may block or allocate.
+//!         response_handler
+//!             .respond(data * data)
+//!             .map_err(|_| WorkerError::Unknown)
+//!     }
+//!
+//!     fn work_response(&mut self, data: u32) -> Result<(), WorkerError> {
+//!         // This is executed in the audio thread again.
+//!         self.last_square = data;
+//!         Ok(())
+//!     }
+//! }
+//! ```
+extern crate lv2_core as core;
+extern crate lv2_sys as sys;
+
+mod interface;
+pub use interface::*;
+
+mod schedule;
+pub use schedule::*;
+
+use std::ffi::c_void;
+use std::mem::{size_of, ManuallyDrop};
+use std::ptr::NonNull;
+
+/// Kinds of errors that may occur in the worker methods of a plugin.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WorkerError {
+    /// The kind of the error is unknown or doesn't have a representation.
+    Unknown,
+    /// There isn't enough space available to execute the task.
+    NoSpace,
+}
+
+impl WorkerError {
+    /// Convert a raw status flag to a result or possible error value.
+    pub fn from(value: u32) -> Result<(), WorkerError> {
+        match value {
+            sys::LV2_Worker_Status_LV2_WORKER_SUCCESS => Ok(()),
+            sys::LV2_Worker_Status_LV2_WORKER_ERR_NO_SPACE => Err(WorkerError::NoSpace),
+            _ => Err(WorkerError::Unknown),
+        }
+    }
+
+    /// Convert a result to a raw status flag.
+    pub fn into(result: Result<(), WorkerError>) -> u32 {
+        match result {
+            Ok(()) => sys::LV2_Worker_Status_LV2_WORKER_SUCCESS,
+            Err(WorkerError::NoSpace) => sys::LV2_Worker_Status_LV2_WORKER_ERR_NO_SPACE,
+            Err(WorkerError::Unknown) => sys::LV2_Worker_Status_LV2_WORKER_ERR_UNKNOWN,
+        }
+    }
+}
+
+/// Errors that may occur when work is scheduled.
+///
+/// Since the work data is moved into the scheduling method, it is returned with the error, which allows you to retry or to drop it outside of the realtime context.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ScheduleError<T> {
+    /// The kind of the error is unknown or doesn't have a representation.
+    Unknown(T),
+    /// The host has no space left to store the work data.
+    NoSpace(T),
+    /// The host did not provide a scheduling function.
+    NoCallback(T),
+}
+
+/// Errors that may occur when a response is sent.
+///
+/// Since the response data is moved into the responding method, it is returned with the error.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RespondError<T> {
+    /// The kind of the error is unknown or doesn't have a representation.
+    Unknown(T),
+    /// The host has no space left to store the response data.
+    NoSpace(T),
+    /// The host did not provide a responding function.
+    NoCallback(T),
+}
+
+/// Move a value to the host by passing it's bytes to the given function.
+///
+/// The host copies the bytes of the value, which means that the value itself is moved to the host. It is therefore not dropped if the transfer succeeded. If it failed, the value is returned together with the error.
+pub(crate) fn transfer<T, F>(data: T, function: F) -> Result<(), (WorkerError, T)>
+where
+    F: FnOnce(u32, *const c_void) -> u32,
+{
+    let data = ManuallyDrop::new(data);
+    let status = function(size_of::<T>() as u32, &*data as *const T as *const c_void);
+    WorkerError::from(status).map_err(|error| (error, ManuallyDrop::into_inner(data)))
+}
+
+/// Retrieve a value that was moved to the host with [`transfer`](fn.transfer.html).
+///
+/// If the size of the data does not match the size of `T`, `None` is returned.
+///
+/// # Safety
+///
+/// The data has to point to `size` readable bytes which were written by `transfer` with the same type. Since this moves the value out of the host's buffer, it must not be received twice.
+pub(crate) unsafe fn receive<T>(size: u32, data: *const c_void) -> Option<T> {
+    if size as usize != size_of::<T>() {
+        return None;
+    }
+    if size_of::<T>() == 0 {
+        return Some(std::ptr::read(NonNull::<T>::dangling().as_ptr()));
+    }
+    if data.is_null() {
+        return None;
+    }
+    // The host's buffer is not required to be aligned.
+    Some(std::ptr::read_unaligned(data as *const T))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+
+    #[test]
+    fn test_worker_error_conversion() {
+        assert_eq!(
+            Ok(()),
+            WorkerError::from(sys::LV2_Worker_Status_LV2_WORKER_SUCCESS)
+        );
+        assert_eq!(
+            Err(WorkerError::NoSpace),
+            WorkerError::from(sys::LV2_Worker_Status_LV2_WORKER_ERR_NO_SPACE)
+        );
+        assert_eq!(
+            Err(WorkerError::Unknown),
+            WorkerError::from(sys::LV2_Worker_Status_LV2_WORKER_ERR_UNKNOWN)
+        );
+        assert_eq!(Err(WorkerError::Unknown), WorkerError::from(u32::MAX));
+
+        assert_eq!(
+            sys::LV2_Worker_Status_LV2_WORKER_SUCCESS,
+            WorkerError::into(Ok(()))
+        );
+        assert_eq!(
+            sys::LV2_Worker_Status_LV2_WORKER_ERR_NO_SPACE,
+            WorkerError::into(Err(WorkerError::NoSpace))
+        );
+        assert_eq!(
+            sys::LV2_Worker_Status_LV2_WORKER_ERR_UNKNOWN,
+            WorkerError::into(Err(WorkerError::Unknown))
+        );
+    }
+
+    #[test]
+    fn test_transfer_n_receive() {
+        let mut buffer: Vec<u8> = Vec::new();
+        transfer(String::from("Hello World!"), |size, data| {
+            buffer.extend_from_slice(unsafe {
+                std::slice::from_raw_parts(data as *const u8, size as usize)
+            });
+            sys::LV2_Worker_Status_LV2_WORKER_SUCCESS
+        })
+        .unwrap();
+
+        assert!(
+            unsafe { receive::<u8>(buffer.len() as u32, buffer.as_ptr() as *const c_void) }
+                .is_none()
+        );
+        let string: String =
+            unsafe { receive(buffer.len() as u32, buffer.as_ptr() as *const c_void) }.unwrap();
+        assert_eq!("Hello World!", string);
+
+        let result = transfer(42u32, |_, _| sys::LV2_Worker_Status_LV2_WORKER_ERR_NO_SPACE);
+        assert_eq!(Err((WorkerError::NoSpace, 42)), result);
+    }
+}
@@ -0,0 +1,54 @@
+use crate::{transfer, ScheduleError, Worker, WorkerError};
+use core::feature::Feature;
+use core::UriBound;
+use std::ffi::c_void;
+use std::marker::PhantomData;
+
+/// Host feature to schedule work.
+///
+/// This feature is provided by the host and lets a plugin schedule work for it's [`Worker`](trait.Worker.html) implementation. It may only be used in the audio threading context, which means that it should only be used in the `run` method of the plugin. Therefore, you usually store it in your plugin struct when the plugin is created.
+pub struct Schedule<'a, P: Worker> {
+    internal: &'a sys::LV2_Worker_Schedule,
+    plugin: PhantomData<fn() -> P>,
+}
+
+// The host guarantees that the scheduling function can be called from the audio thread, regardless of which thread instantiated the plugin, and a plugin's `run` method is never called concurrently.
+unsafe impl<'a, P: Worker> Send for Schedule<'a, P> {}
+unsafe impl<'a, P: Worker> Sync for Schedule<'a, P> {}
+
+unsafe impl<'a, P: Worker> UriBound for Schedule<'a, P> {
+    const URI: &'static [u8] = sys::LV2_WORKER__schedule;
+}
+
+unsafe impl<'a, P: Worker> Feature for Schedule<'a, P> {
+    unsafe fn from_feature_ptr(feature: *const c_void) -> Option<Self> {
+        (feature as *const sys::LV2_Worker_Schedule)
+            .as_ref()
+            .map(|internal| Self {
+                internal,
+                plugin: PhantomData,
+            })
+    }
+}
+
+impl<'a, P: Worker> Schedule<'a, P> {
+    /// Request the host to call the worker with the given data.
+    ///
+    /// The data is moved to the host and the [`work`](trait.Worker.html#tymethod.work) method of the plugin will be called with it in a non-realtime thread. This method is realtime-safe: It does not allocate memory and only calls the host's scheduling function.
+    ///
+    /// If the host could not accept the work, the data is returned inside the error.
+    pub fn schedule_work(&self, data: P::WorkData) -> Result<(), ScheduleError<P::WorkData>> {
+        let schedule_fn = match self.internal.schedule_work {
+            Some(schedule_fn) => schedule_fn,
+            None => return Err(ScheduleError::NoCallback(data)),
+        };
+        let handle = self.internal.handle;
+        transfer(data, |size, data| unsafe {
+            (schedule_fn)(handle, size, data)
+        })
+        .map_err(|(error, data)| match error {
+            WorkerError::NoSpace => ScheduleError::NoSpace(data),
+            WorkerError::Unknown => ScheduleError::Unknown(data),
+        })
+    }
+}